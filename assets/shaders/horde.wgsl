@@ -0,0 +1,49 @@
+// Every beet in the horde drawn in one go. Each instance carries where it is, how big, which way
+// it's facing and what colour it is. The lighting's a simple fixed sun, which is plenty at the
+// distances a horde gets to.
+#import bevy_pbr::mesh_types
+#import bevy_pbr::mesh_view_bindings
+
+@group(1) @binding(0)
+var<uniform> mesh: Mesh;
+
+// Bindings have to come before the functions that use them
+#import bevy_pbr::mesh_functions
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+
+    @location(3) i_position_scale: vec4<f32>,
+    @location(4) i_rotation: vec4<f32>,
+    @location(5) i_color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+fn rotate(rotation: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    return v + 2.0 * cross(rotation.xyz, cross(rotation.xyz, v) + rotation.w * v);
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let position = rotate(vertex.i_rotation, vertex.position * vertex.i_position_scale.w)
+        + vertex.i_position_scale.xyz;
+    var out: VertexOutput;
+    out.clip_position = mesh_position_local_to_clip(mesh.model, vec4<f32>(position, 1.0));
+    out.normal = rotate(vertex.i_rotation, vertex.normal);
+    out.color = vertex.i_color;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sun = normalize(vec3<f32>(0.3, 1.0, 0.5));
+    let light = 0.35 + 0.65 * max(dot(normalize(in.normal), sun), 0.0);
+    return vec4<f32>(in.color.rgb * light, in.color.a);
+}
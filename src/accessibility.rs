@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::GameConfig, settings::Settings};
+
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 1.5;
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Palette>()
+            .add_system(apply_accessibility);
+    }
+}
+
+// For anyone who has trouble telling the usual colours apart. Each one keeps the target, damage
+// and the HUD bars clear of the pairs that kind of colour blindness mixes up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColourPalette {
+    #[default]
+    Standard,
+    // Greens and reds look alike
+    Deuteranopia,
+    // Much the same, but reds look darker too
+    Protanopia,
+    // Blues and yellows look alike
+    Tritanopia,
+}
+
+impl ColourPalette {
+    pub const ALL: [ColourPalette; 4] = [
+        ColourPalette::Standard,
+        ColourPalette::Deuteranopia,
+        ColourPalette::Protanopia,
+        ColourPalette::Tritanopia,
+    ];
+
+    // The standard palette's target is whatever config.ron asks for
+    pub fn colours(self, target_highlight: Color) -> Palette {
+        match self {
+            ColourPalette::Standard => Palette {
+                target: target_highlight,
+                falling_off: Color::rgb(1., 0.5, 0.2),
+                damage: Color::WHITE,
+                critical: Color::ORANGE_RED,
+                stamina: Color::rgb(0.4, 0.9, 0.3),
+                grenade: Color::rgb(1., 0.6, 0.1),
+                ultimate: Color::rgb(0.75, 0.35, 1.),
+                painted: Color::rgb(1., 0.3, 0.2),
+                player: Color::rgb(0.4, 0.9, 0.3),
+                enemy: Color::rgb(0.9, 0.2, 0.2),
+                spawner: Color::rgb(0.85, 0.55, 0.15),
+            },
+            // Blues against oranges and yellows
+            ColourPalette::Deuteranopia => Palette {
+                target: Color::rgb(0.94, 0.89, 0.26),
+                falling_off: Color::rgb(0.8, 0.47, 0.65),
+                damage: Color::WHITE,
+                critical: Color::rgb(0.9, 0.6, 0.),
+                stamina: Color::rgb(0.34, 0.71, 0.91),
+                grenade: Color::rgb(0.9, 0.6, 0.),
+                ultimate: Color::rgb(0.8, 0.47, 0.65),
+                painted: Color::rgb(0.84, 0.37, 0.),
+                player: Color::rgb(0.34, 0.71, 0.91),
+                enemy: Color::rgb(0.9, 0.6, 0.),
+                spawner: Color::rgb(0.8, 0.47, 0.65),
+            },
+            // The same, with the reds brightened up into oranges
+            ColourPalette::Protanopia => Palette {
+                target: Color::rgb(0.94, 0.89, 0.26),
+                falling_off: Color::rgb(0.6, 0.5, 0.95),
+                damage: Color::WHITE,
+                critical: Color::rgb(1., 0.7, 0.1),
+                stamina: Color::rgb(0.34, 0.71, 0.91),
+                grenade: Color::rgb(1., 0.7, 0.1),
+                ultimate: Color::rgb(0.6, 0.5, 0.95),
+                painted: Color::rgb(1., 0.7, 0.1),
+                player: Color::rgb(0.34, 0.71, 0.91),
+                enemy: Color::rgb(1., 0.7, 0.1),
+                spawner: Color::rgb(0.9, 0.9, 0.9),
+            },
+            // Reds and pinks against cyans
+            ColourPalette::Tritanopia => Palette {
+                target: Color::rgb(1., 0.4, 0.7),
+                falling_off: Color::rgb(0.3, 0.85, 0.85),
+                damage: Color::WHITE,
+                critical: Color::rgb(0.95, 0.25, 0.25),
+                stamina: Color::rgb(0.3, 0.85, 0.85),
+                grenade: Color::rgb(0.95, 0.35, 0.35),
+                ultimate: Color::rgb(1., 0.4, 0.7),
+                painted: Color::rgb(0.95, 0.25, 0.25),
+                player: Color::rgb(0.3, 0.85, 0.85),
+                enemy: Color::rgb(0.95, 0.25, 0.25),
+                spawner: Color::rgb(0.9, 0.9, 0.9),
+            },
+        }
+    }
+}
+
+// The colours in use, from whichever palette's picked in the settings
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    // Whatever's locked on: its highlight, its blip on the radar and the crosshair
+    pub target: Color,
+    // Still locked on, but past the point the damage starts dropping off
+    pub falling_off: Color,
+    pub damage: Color,
+    pub critical: Color,
+    // The stamina, grenade and ultimate bars once they're full, and the barrage's lock pips
+    pub stamina: Color,
+    pub grenade: Color,
+    pub ultimate: Color,
+    pub painted: Color,
+    // On the radar
+    pub player: Color,
+    pub enemy: Color,
+    pub spawner: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        ColourPalette::Standard.colours(GameConfig::default().target_highlight)
+    }
+}
+
+fn apply_accessibility(
+    settings: Res<Settings>,
+    config: Res<GameConfig>,
+    mut palette: ResMut<Palette>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if !settings.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    let colours = settings.palette.colours(config.target_highlight);
+    if *palette != colours {
+        *palette = colours;
+    }
+    let scale = settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE) as f64;
+    if ui_scale.scale != scale {
+        ui_scale.scale = scale;
+    }
+}
+
+// From `Camera::world_to_viewport`, which knows nothing of the UI scale, to where a `Val::Px`
+// has to be for the UI to put it in the same spot
+pub fn viewport_to_ui(ui_scale: &UiScale, position: Vec2) -> Vec2 {
+    position / ui_scale.scale as f32
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{campaign::Boss, damage::Health, settings::Settings, state::GameState, Enemy, Player};
+
+const CROSSFADE_SECONDS: f32 = 3.;
+const AMBIENCE_VOLUME: f32 = 0.6;
+const MUSIC_VOLUME: f32 = 0.4;
+// How many beets on screen it takes for the crowd alone to count for everything it can
+const CROWDED: f32 = 12.;
+// How much each thing adds to the threat, which tops out at 1
+const CROWD_THREAT: f32 = 0.6;
+const BOSS_THREAT: f32 = 0.5;
+const LOW_HEALTH_THREAT: f32 = 0.3;
+// Below this much of their health, someone's in trouble
+const LOW_HEALTH: f32 = 0.35;
+// Per second. The music picks up quickly and takes its time calming down again.
+const INTENSITY_RISE: f32 = 1.;
+const INTENSITY_FALL: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum Biome {
+    #[default]
+    Garden,
+    Fields,
+    Greenhouse,
+}
+
+impl Biome {
+    fn ambience(&self) -> &'static str {
+        match self {
+            Biome::Garden => "audio/ambience/garden.ogg",
+            Biome::Fields => "audio/ambience/fields.ogg",
+            Biome::Greenhouse => "audio/ambience/greenhouse.ogg",
+        }
+    }
+
+    // Each biome's music comes in stems that all loop together, e.g. garden.ogg,
+    // garden_percussion.ogg and garden_lead.ogg
+    fn music(&self, layer: MusicLayer) -> String {
+        let biome = match self {
+            Biome::Garden => "garden",
+            Biome::Fields => "fields",
+            Biome::Greenhouse => "greenhouse",
+        };
+        match layer {
+            MusicLayer::Base => format!("audio/music/{biome}.ogg"),
+            MusicLayer::Percussion => format!("audio/music/{biome}_percussion.ogg"),
+            MusicLayer::Lead => format!("audio/music/{biome}_lead.ogg"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MusicLayer {
+    // Always playing
+    Base,
+    // Comes in as things get busy
+    Percussion,
+    // Only when it's all kicking off
+    Lead,
+}
+
+impl MusicLayer {
+    const ALL: [MusicLayer; 3] = [MusicLayer::Base, MusicLayer::Percussion, MusicLayer::Lead];
+
+    // How loud this layer plays at a given intensity, from 0 to 1
+    fn mix(&self, intensity: f32) -> f32 {
+        let (from, to) = match self {
+            MusicLayer::Base => return 1.,
+            MusicLayer::Percussion => (0.2, 0.5),
+            MusicLayer::Lead => (0.6, 0.9),
+        };
+        let blend = ((intensity - from) / (to - from)).clamp(0., 1.);
+        // Eased, so a layer doesn't lurch in as soon as it starts
+        blend * blend * (3. - 2. * blend)
+    }
+}
+
+// How much is going on, from 0 for a quiet stroll to 1 for a boss fight with everyone on their
+// last legs. Worked out from the game every frame and eased towards, so the music doesn't flap.
+#[derive(Resource, Default)]
+pub struct MusicIntensity {
+    pub level: f32,
+    // What `level` is heading for
+    pub threat: f32,
+}
+
+// Whatever decides where the player is (the level streaming) sets this, and the soundscape
+// follows along.
+#[derive(Resource, Default)]
+pub struct CurrentBiome(pub Biome);
+
+struct FadingTrack {
+    sink: Handle<AudioSink>,
+    gain: f32,
+    // Music follows the music volume setting and the intensity for its layer, everything else
+    // the effects volume
+    layer: Option<MusicLayer>,
+    volume: f32,
+    fading_in: bool,
+}
+
+#[derive(Resource, Default)]
+struct Ambience {
+    biome: Option<Biome>,
+    tracks: Vec<FadingTrack>,
+}
+
+pub struct AmbiencePlugin;
+
+impl Plugin for AmbiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentBiome>()
+            .init_resource::<Ambience>()
+            .init_resource::<MusicIntensity>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(switch_biome)
+                    .with_system(measure_intensity)
+                    .with_system(crossfade.after(switch_biome).after(measure_intensity)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(calm_music));
+    }
+}
+
+fn switch_biome(
+    current: Res<CurrentBiome>,
+    mut ambience: ResMut<Ambience>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if ambience.biome == Some(current.0) {
+        return;
+    }
+    ambience.biome = Some(current.0);
+
+    for track in &mut ambience.tracks {
+        track.fading_in = false;
+    }
+
+    let stems = MusicLayer::ALL.map(|layer| (current.0.music(layer), MUSIC_VOLUME, Some(layer)));
+    let ambience_track = (current.0.ambience().to_string(), AMBIENCE_VOLUME, None);
+    // Started together, so the stems stay in time with each other
+    for (path, gain, layer) in [ambience_track].into_iter().chain(stems) {
+        let sink = audio.play_with_settings(
+            asset_server.load(path.as_str()),
+            PlaybackSettings::LOOP.with_volume(0.),
+        );
+        ambience.tracks.push(FadingTrack {
+            sink: audio_sinks.get_handle(sink),
+            gain,
+            layer,
+            volume: 0.,
+            fading_in: true,
+        });
+    }
+}
+
+fn measure_intensity(
+    mut intensity: ResMut<MusicIntensity>,
+    time: Res<Time>,
+    enemies: Query<(), With<Enemy>>,
+    bosses: Query<(), With<Boss>>,
+    players: Query<&Health, With<Player>>,
+) {
+    let crowd = (enemies.iter().count() as f32 / CROWDED).min(1.) * CROWD_THREAT;
+    let boss = if bosses.is_empty() { 0. } else { BOSS_THREAT };
+    let struggling = players
+        .iter()
+        .any(|health| health.max > 0. && health.current / health.max < LOW_HEALTH);
+    let low_health = if struggling { LOW_HEALTH_THREAT } else { 0. };
+    intensity.threat = (crowd + boss + low_health).min(1.);
+
+    let delta = time.delta_seconds();
+    let level = intensity.level;
+    intensity.level = if intensity.threat > level {
+        (level + INTENSITY_RISE * delta).min(intensity.threat)
+    } else {
+        (level - INTENSITY_FALL * delta).max(intensity.threat)
+    };
+}
+
+fn crossfade(
+    mut ambience: ResMut<Ambience>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    settings: Res<Settings>,
+    intensity: Res<MusicIntensity>,
+    time: Res<Time>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_SECONDS;
+
+    ambience.tracks.retain_mut(|track| {
+        track.volume = if track.fading_in {
+            (track.volume + step).min(1.)
+        } else {
+            (track.volume - step).max(0.)
+        };
+
+        let Some(sink) = audio_sinks.get(&track.sink) else { return true };
+        let setting = match track.layer {
+            Some(layer) => settings.music() * layer.mix(intensity.level),
+            None => settings.effects(),
+        };
+        sink.set_volume(track.volume * track.gain * setting);
+
+        let finished = !track.fading_in && track.volume <= 0.;
+        if finished {
+            sink.stop();
+        }
+        !finished
+    });
+}
+
+// Every run starts quiet
+fn calm_music(mut intensity: ResMut<MusicIntensity>) {
+    *intensity = MusicIntensity::default();
+}
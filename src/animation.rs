@@ -0,0 +1,211 @@
+use bevy::{gltf::Gltf, prelude::*, utils::HashMap};
+
+use crate::{
+    melee::Melee, state::GameState, Enemy, Player, Velocity, Weapon, WeaponFired, ENEMY_SPEED,
+};
+
+const FIRE_ANIMATION_SECONDS: f32 = 0.3;
+// How far from its usual pace a beet's walk can be sped up or slowed down
+const MIN_WALK_SPEED: f32 = 0.25;
+const MAX_WALK_SPEED: f32 = 2.5;
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(link_animation_players)
+            .add_system(player_animation_state)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(weapon_animation_state),
+            )
+            .add_system(
+                play_animations
+                    .after(link_animation_players)
+                    .after(player_animation_state)
+                    .after(weapon_animation_state),
+            )
+            .add_system(enemy_walk_speed.after(play_animations));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Fire,
+    // A player's swipe at anything too close to shoot
+    Melee,
+    // Enemies gearing up to hit someone, then hitting them
+    WindUp,
+    Attack,
+}
+
+impl AnimationState {
+    // These are the names the clips need to have in the glTF files
+    fn clip_name(&self) -> &'static str {
+        match self {
+            AnimationState::Idle => "idle",
+            AnimationState::Walk => "walk",
+            AnimationState::Fire => "fire",
+            AnimationState::Melee => "melee",
+            AnimationState::WindUp => "windup",
+            AnimationState::Attack => "attack",
+        }
+    }
+
+    fn repeats(&self) -> bool {
+        !matches!(
+            self,
+            AnimationState::Fire
+                | AnimationState::Melee
+                | AnimationState::WindUp
+                | AnimationState::Attack
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct Animated {
+    pub gltf: Handle<Gltf>,
+    pub state: AnimationState,
+    playing: Option<AnimationState>,
+    player: Option<Entity>,
+}
+
+impl Animated {
+    pub fn new(gltf: Handle<Gltf>, state: AnimationState) -> Self {
+        Self {
+            gltf,
+            state,
+            playing: None,
+            player: None,
+        }
+    }
+
+    // Play the clip for `state` from the start, even if it's already playing
+    pub fn restart(&mut self, state: AnimationState) {
+        self.state = state;
+        self.playing = None;
+    }
+}
+
+// The glTF loader puts the AnimationPlayer somewhere inside the spawned scene, so walk up the
+// hierarchy until we find the entity that owns the animations.
+fn link_animation_players(
+    new_players: Query<Entity, Added<AnimationPlayer>>,
+    parents: Query<&Parent>,
+    mut animated: Query<&mut Animated>,
+) {
+    for player_entity in &new_players {
+        let mut current = player_entity;
+        loop {
+            if let Ok(mut animated) = animated.get_mut(current) {
+                animated.player = Some(player_entity);
+                animated.playing = None;
+                break;
+            }
+            let Ok(parent) = parents.get(current) else { break };
+            current = parent.get();
+        }
+    }
+}
+
+fn player_animation_state(
+    mut players: Query<(Entity, &Transform, &mut Animated, Option<&Melee>), With<Player>>,
+    mut last_translations: Local<HashMap<Entity, Vec3>>,
+) {
+    for (entity, transform, mut animated, melee) in &mut players {
+        let last_translation = last_translations.entry(entity).or_insert(transform.translation);
+        let moved = transform.translation.distance(*last_translation) > f32::EPSILON;
+        *last_translation = transform.translation;
+        // Walking about doesn't cut a swipe short
+        if melee.map_or(false, Melee::is_swinging) {
+            continue;
+        }
+
+        let state = if moved {
+            AnimationState::Walk
+        } else {
+            AnimationState::Idle
+        };
+
+        if animated.state != state {
+            animated.state = state;
+        }
+    }
+}
+
+fn weapon_animation_state(
+    mut fired: EventReader<WeaponFired>,
+    mut weapons: Query<&mut Animated, With<Weapon>>,
+    mut fire_timers: Local<HashMap<Entity, Timer>>,
+    time: Res<Time>,
+) {
+    // Tick before starting any new timers, so a gun gets its whole animation from the frame it fires
+    fire_timers.retain(|weapon, timer| {
+        if !timer.tick(time.delta()).finished() {
+            return true;
+        }
+        if let Ok(mut animated) = weapons.get_mut(*weapon) {
+            animated.state = AnimationState::Idle;
+        }
+        false
+    });
+
+    for event in fired.iter() {
+        let Ok(mut animated) = weapons.get_mut(event.weapon) else { continue };
+        animated.restart(AnimationState::Fire);
+        fire_timers.insert(
+            event.weapon,
+            Timer::from_seconds(FIRE_ANIMATION_SECONDS, TimerMode::Once),
+        );
+    }
+}
+
+fn play_animations(
+    gltfs: Res<Assets<Gltf>>,
+    mut animated: Query<&mut Animated>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    for mut animated in &mut animated {
+        if animated.playing == Some(animated.state) {
+            continue;
+        }
+        let Some(player_entity) = animated.player else { continue };
+        let Some(gltf) = gltfs.get(&animated.gltf) else { continue };
+        let Ok(mut player) = players.get_mut(player_entity) else { continue };
+
+        let state = animated.state;
+        animated.playing = Some(state);
+
+        // Not every model ships every clip - a missing one just leaves the model where it is
+        let Some(clip) = gltf.named_animations.get(state.clip_name()) else { continue };
+        player.play(clip.clone());
+        if state.repeats() {
+            player.repeat();
+        }
+    }
+}
+
+// A beet's legs keep up with how fast it's actually going, so slowed ones trudge and quick ones
+// scurry. Everything else plays at its normal speed.
+fn enemy_walk_speed(
+    time: Res<Time>,
+    enemies: Query<(&Animated, &Velocity), With<Enemy>>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    // Movement's per frame and already slowed along with the game, which the clips are too
+    let usual = (ENEMY_SPEED * time.relative_speed()).max(f32::EPSILON);
+    for (animated, velocity) in &enemies {
+        let Some(player_entity) = animated.player else { continue };
+        let Ok(mut player) = players.get_mut(player_entity) else { continue };
+        let speed = if animated.state == AnimationState::Walk {
+            (velocity.per_frame.length() / usual).clamp(MIN_WALK_SPEED, MAX_WALK_SPEED)
+        } else {
+            1.
+        };
+        if (player.speed() - speed).abs() > 0.05 {
+            player.set_speed(speed);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::GameConfig,
+    input::{ActiveDevice, InputAction, PlayerInput},
+    intermission::WaveModifier,
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    settings::Settings,
+    state::GameState,
+    shop::Upgrades,
+    weapons::{Loadout, WeaponEquipped},
+    Game,
+};
+
+const TURNTABLE_SPEED: f32 = 0.8;
+// Where the weapon sits relative to the camera, off to the left of the stats panel
+const TURNTABLE_OFFSET: Vec3 = Vec3::new(-0.35, -0.1, -1.2);
+const TURNTABLE_SCALE: f32 = 2.;
+
+pub struct ArmoryPlugin;
+
+impl Plugin for ArmoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArmorySelection>()
+            .add_system(open_armory)
+            .add_system_set(SystemSet::on_enter(GameState::Armory).with_system(spawn_armory))
+            .add_system_set(
+                SystemSet::on_update(GameState::Armory)
+                    .with_system(select_weapon)
+                    .with_system(show_turntable.after(select_weapon))
+                    .with_system(spin_turntable)
+                    .with_system(update_stats_panel.after(select_weapon)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Armory).with_system(despawn_armory));
+    }
+}
+
+// Index into `Loadout::unlocked`
+#[derive(Resource, Default)]
+struct ArmorySelection {
+    index: usize,
+}
+
+#[derive(Component)]
+struct ArmoryUi;
+
+#[derive(Component)]
+struct StatsText;
+
+#[derive(Component)]
+struct Turntable {
+    weapon: usize,
+}
+
+fn open_armory(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.inspect && *state.current() == GameState::Paused {
+        if let Err(e) = state.push(GameState::Armory) {
+            warn!("Unable to open the armory: {e:?}");
+        }
+    }
+}
+
+fn spawn_armory(
+    mut commands: Commands,
+    mut selection: ResMut<ArmorySelection>,
+    loadout: Res<Loadout>,
+    asset_server: Res<AssetServer>,
+) {
+    // Start on whatever's equipped
+    selection.index = loadout
+        .unlocked
+        .iter()
+        .position(|weapon| *weapon == loadout.equipped)
+        .unwrap_or(0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(0.),
+                        top: Val::Px(0.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(45.), Val::Percent(100.)),
+                    padding: UiRect::all(Val::Px(30.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.75).into(),
+                ..default()
+            },
+            ArmoryUi,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 22.,
+                        color: Color::WHITE,
+                    },
+                ),
+                StatsText,
+            ));
+        });
+}
+
+fn despawn_armory(
+    mut commands: Commands,
+    ui: Query<Entity, With<ArmoryUi>>,
+    turntables: Query<Entity, With<Turntable>>,
+) {
+    for entity in ui.iter().chain(turntables.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Left and right to browse, fire or confirm to equip
+fn select_weapon(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut selection: ResMut<ArmorySelection>,
+    mut loadout: ResMut<Loadout>,
+    mut equipped: EventWriter<WeaponEquipped>,
+) {
+    if navigation.step.x != 0 {
+        let count = loadout.unlocked.len().max(1);
+        selection.index = if navigation.step.x > 0 {
+            (selection.index + 1) % count
+        } else {
+            (selection.index + count - 1) % count
+        };
+    }
+
+    if input.fire || input.confirm {
+        let selected = loadout.unlocked.get(selection.index).copied();
+        if let Some(weapon) = selected.filter(|weapon| *weapon != loadout.equipped) {
+            loadout.equipped = weapon;
+            equipped.send(WeaponEquipped { weapon });
+        }
+    }
+}
+
+fn show_turntable(
+    mut commands: Commands,
+    selection: Res<ArmorySelection>,
+    loadout: Res<Loadout>,
+    config: Res<GameConfig>,
+    game: Res<Game>,
+    asset_server: Res<AssetServer>,
+    turntables: Query<(Entity, &Turntable)>,
+) {
+    let Some(weapon) = loadout.unlocked.get(selection.index).copied() else { return };
+    if turntables.iter().any(|(_, turntable)| turntable.weapon == weapon) {
+        return;
+    }
+    for (entity, _) in &turntables {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(definition) = config.weapons.get(weapon) else { return };
+    let turntable = commands
+        .spawn((
+            SceneBundle {
+                scene: asset_server.load(definition.scene.as_str()),
+                transform: Transform::from_translation(TURNTABLE_OFFSET)
+                    .with_scale(Vec3::splat(TURNTABLE_SCALE)),
+                ..default()
+            },
+            Turntable { weapon },
+        ))
+        .id();
+    commands.entity(game.camera).add_child(turntable);
+}
+
+fn spin_turntable(mut turntables: Query<&mut Transform, With<Turntable>>, time: Res<Time>) {
+    for mut transform in &mut turntables {
+        transform.rotate_y(TURNTABLE_SPEED * time.delta_seconds());
+    }
+}
+
+fn update_stats_panel(
+    selection: Res<ArmorySelection>,
+    loadout: Res<Loadout>,
+    upgrades: Res<Upgrades>,
+    modifier: Res<WaveModifier>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    device: Res<ActiveDevice>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<StatsText>>,
+) {
+    let Some(selected) = loadout.unlocked.get(selection.index).copied() else { return };
+    let name = |weapon: usize| {
+        config
+            .weapons
+            .get(weapon)
+            .map_or("???", |definition| definition.name.as_str())
+    };
+
+    let mut panel = format!("{}\n\n", localization.get("armory-title"));
+    for weapon in &loadout.unlocked {
+        let cursor = if *weapon == selected { ">" } else { " " };
+        let key = if *weapon == loadout.equipped {
+            "armory-weapon-equipped"
+        } else {
+            "armory-weapon"
+        };
+        let line = localization.format(
+            key,
+            &[("name", &name(*weapon)), ("tier", &loadout.tier(*weapon))],
+        );
+        panel += &format!("{cursor} {line}\n");
+    }
+
+    let equipped = modifier.apply(upgrades.apply(loadout.equipped_stats(&config)));
+    let candidate = modifier.apply(upgrades.apply(loadout.stats(&config, selected)));
+    panel += &format!(
+        "\n{:<14}{:>8}{:>8}\n",
+        "",
+        truncate(name(loadout.equipped)),
+        truncate(name(selected))
+    );
+    for (key, equipped, candidate) in [
+        ("armory-damage", equipped.damage, candidate.damage),
+        ("armory-fire-rate", equipped.fire_rate, candidate.fire_rate),
+        ("armory-dps", equipped.dps(), candidate.dps()),
+        ("armory-range", equipped.range, candidate.range),
+        ("armory-max-range", equipped.max_range, candidate.max_range),
+    ] {
+        panel += &compare_row(localization.get(key), equipped, candidate);
+    }
+    let prompt = |action| settings.bindings.prompt(action, *device);
+    let help = localization.format(
+        "armory-help",
+        &[
+            ("fire", &prompt(InputAction::Fire)),
+            ("pause", &prompt(InputAction::Pause)),
+        ],
+    );
+    panel += &format!("\n{help}");
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn truncate(name: &str) -> String {
+    name.chars().take(7).collect()
+}
+
+fn compare_row(label: &str, equipped: f32, candidate: f32) -> String {
+    let difference = candidate - equipped;
+    let change = if difference.abs() < 0.005 {
+        String::new()
+    } else {
+        format!(" ({difference:+.2})")
+    };
+    format!("{label:<14}{equipped:>8.2}{candidate:>8.2}{change}\n")
+}
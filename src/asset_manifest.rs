@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{campaign::Levels, characters::Characters, config::GameConfig, level_gen::PROP_SCENES};
+
+pub const ASSETS_DIR: &str = "assets";
+const MANIFEST_FILE: &str = "manifest.ron";
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+
+// Scenes the game loads by name, besides the level props
+const CORE_SCENES: [&str; 5] = [
+    "carrot.glb#Scene0",
+    "launcher.glb#Scene0",
+    "beet.glb#Scene0",
+    "pumpkinBasic.glb#Scene0",
+    "environment.glb#Scene0",
+];
+
+// Everything `--process-assets` worked out ahead of time, so the game doesn't have to dig through
+// glTF files at startup. The game runs fine without one, it just falls back to hand-tuned numbers.
+// Only collider sizes are cached: beets steer round obstacles as they go rather than following a
+// navmesh, and there's no loading screen to hand anything else to.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AssetManifest {
+    // Keyed by path relative to the assets directory
+    pub scenes: BTreeMap<String, SceneInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneInfo {
+    pub bytes: u64,
+    // How far the meshes reach from the origin on the ground plane - what obstacles collide with
+    pub collider_radius: f32,
+    pub height: f32,
+    pub vertices: u32,
+    pub triangles: u32,
+    pub lod: LodTier,
+}
+
+// A rough budget class so the renderer can decide what's worth drawing far away. Recorded for
+// when there are lower detail models to swap in, but nothing picks one by it yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LodTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl LodTier {
+    fn for_triangles(triangles: u32) -> Self {
+        match triangles {
+            0..=499 => LodTier::Low,
+            500..=4999 => LodTier::Medium,
+            _ => LodTier::High,
+        }
+    }
+}
+
+impl AssetManifest {
+    pub fn path() -> PathBuf {
+        Path::new(ASSETS_DIR).join(MANIFEST_FILE)
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            info!("No asset manifest at {path:?}, run with --process-assets to generate one");
+            return Self::default();
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|e| {
+            error!("{path:?} is invalid, ignoring it: {e}");
+            Self::default()
+        })
+    }
+
+    pub fn scene(&self, scene_path: &str) -> Option<&SceneInfo> {
+        self.scenes.get(strip_label(scene_path))
+    }
+}
+
+pub struct AssetManifestPlugin;
+
+impl Plugin for AssetManifestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AssetManifest::load());
+    }
+}
+
+// "carrot.glb#Scene0" lives in "carrot.glb"
+fn strip_label(asset_path: &str) -> &str {
+    asset_path.split('#').next().unwrap_or(asset_path)
+}
+
+// Checks every scene the game and its data files refer to is actually there, measures every glb
+// in the assets directory and writes the manifest. Returns the problems found, if any.
+pub fn process_assets() -> Result<PathBuf, Vec<String>> {
+    let assets_dir = Path::new(ASSETS_DIR);
+    let mut problems = Vec::new();
+
+    for (source, scene) in referenced_scenes() {
+        let file = strip_label(&scene);
+        if !assets_dir.join(file).is_file() {
+            problems.push(format!(
+                "{source} refers to {scene} but {file} doesn't exist"
+            ));
+        }
+    }
+
+    let mut manifest = AssetManifest::default();
+    let entries = match fs::read_dir(assets_dir) {
+        Ok(entries) => entries,
+        Err(e) => return Err(vec![format!("Unable to read {assets_dir:?}: {e}")]),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("glb") {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        match measure_scene(&path) {
+            Ok(info) => {
+                println!(
+                    "{name}: radius {:.2}, height {:.2}, {} triangles ({:?})",
+                    info.collider_radius, info.height, info.triangles, info.lod
+                );
+                manifest.scenes.insert(name, info);
+            }
+            Err(e) => problems.push(format!("{name}: {e}")),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let path = AssetManifest::path();
+    let contents = ron::ser::to_string_pretty(&manifest, default())
+        .map_err(|e| vec![format!("Unable to serialize the manifest: {e}")])?;
+    fs::write(&path, contents).map_err(|e| vec![format!("Unable to write {path:?}: {e}")])?;
+    Ok(path)
+}
+
+// Every scene that can end up loaded, along with where it's named
+fn referenced_scenes() -> Vec<(&'static str, String)> {
+    let built_in = CORE_SCENES.iter().chain(PROP_SCENES.iter());
+    let mut scenes: Vec<_> = built_in
+        .map(|scene| ("the game", scene.to_string()))
+        .collect();
+    for weapon in GameConfig::load().weapons {
+        scenes.push(("config.ron", weapon.scene));
+    }
+    for character in Characters::load().0 {
+        scenes.push(("characters.ron", character.scene()));
+    }
+    for level in Levels::load().0 {
+        scenes.push(("levels.ron", level.environment));
+    }
+    scenes
+}
+
+// Only the JSON chunk is needed - the accessors already carry the bounds and counts of every mesh
+pub fn measure_scene(path: &Path) -> Result<SceneInfo, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    if bytes.get(0..4) != Some(GLB_MAGIC.as_slice()) {
+        return Err("not a binary glTF file".into());
+    }
+    let (Some(json_length), Some(GLB_JSON_CHUNK)) = (read_u32(12), read_u32(16)) else {
+        return Err("missing JSON chunk".into());
+    };
+    let json = bytes
+        .get(20..20 + json_length as usize)
+        .ok_or("truncated JSON chunk")?;
+    let gltf: serde_json::Value = serde_json::from_slice(json).map_err(|e| e.to_string())?;
+
+    let accessors = gltf["accessors"].as_array().cloned().unwrap_or_default();
+    let accessor = |index: &serde_json::Value| index.as_u64().and_then(|i| accessors.get(i as usize));
+    let component = |value: &serde_json::Value, i: usize| {
+        value.get(i).and_then(|v| v.as_f64()).unwrap_or(0.) as f32
+    };
+
+    let mut collider_radius = 0f32;
+    let mut height = 0f32;
+    let mut vertices = 0;
+    let mut triangles = 0;
+    let primitives = gltf["meshes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|mesh| mesh["primitives"].as_array().cloned().unwrap_or_default());
+    for primitive in primitives {
+        let Some(position) = accessor(&primitive["attributes"]["POSITION"]) else { continue };
+        let count = position["count"].as_u64().unwrap_or(0) as u32;
+        vertices += count;
+        triangles += match accessor(&primitive["indices"]) {
+            Some(indices) => indices["count"].as_u64().unwrap_or(0) as u32 / 3,
+            None => count / 3,
+        };
+
+        let (min, max) = (&position["min"], &position["max"]);
+        for corner in [min, max] {
+            collider_radius = collider_radius
+                .max(component(corner, 0).abs())
+                .max(component(corner, 2).abs());
+        }
+        height = height.max(component(max, 1));
+    }
+
+    Ok(SceneInfo {
+        bytes: bytes.len() as u64,
+        collider_radius,
+        height,
+        vertices,
+        triangles,
+        lod: LodTier::for_triangles(triangles),
+    })
+}
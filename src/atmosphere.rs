@@ -0,0 +1,97 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use crate::{
+    config::GameConfig,
+    lighting::{sample_color, TimeOfDay},
+};
+
+const SKY_COLORS: [(f32, Color); 5] = [
+    (0.0, Color::rgb(0.95, 0.7, 0.55)),
+    (0.3, Color::rgb(0.55, 0.75, 0.95)),
+    (0.65, Color::rgb(0.95, 0.6, 0.4)),
+    (0.8, Color::rgb(0.35, 0.25, 0.45)),
+    (1.0, Color::rgb(0.05, 0.07, 0.15)),
+];
+
+pub struct AtmospherePlugin;
+
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_fog_material)
+            .add_system(attach_fog_layers)
+            .add_system(update_sky_color);
+    }
+}
+
+#[derive(Resource)]
+struct FogMaterial(Handle<StandardMaterial>);
+
+// There's no fog in the renderer, so instead we hang translucent, sky-coloured layers in front of
+// the camera. Anything further away sits behind more of them and fades into the sky.
+#[derive(Component)]
+struct FogLayer;
+
+fn setup_fog_material(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::NONE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    commands.insert_resource(FogMaterial(material));
+}
+
+fn attach_fog_layers(
+    mut commands: Commands,
+    cameras: Query<Entity, Added<Camera3d>>,
+    config: Res<GameConfig>,
+    fog_material: Res<FogMaterial>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let fog = &config.fog;
+    let layers = fog.layers.max(1);
+
+    for camera in &cameras {
+        commands.entity(camera).with_children(|parent| {
+            for layer in 0..layers {
+                let distance = fog.start + (fog.end - fog.start) * layer as f32 / layers as f32;
+                // Generously oversized so the layer covers the view at any sensible FOV
+                let size = Vec2::new(distance * 4., distance * 3.);
+                parent.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Quad::new(size))),
+                        material: fog_material.0.clone(),
+                        transform: Transform::from_xyz(0., 0., -distance),
+                        ..default()
+                    },
+                    FogLayer,
+                    NotShadowCaster,
+                ));
+            }
+        });
+    }
+}
+
+fn update_sky_color(
+    time_of_day: Res<TimeOfDay>,
+    config: Res<GameConfig>,
+    fog_material: Res<FogMaterial>,
+    mut clear_color: ResMut<ClearColor>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    let sky = sample_color(&SKY_COLORS, time_of_day.progress);
+    clear_color.0 = sky;
+
+    // Each layer lets through a bit less, so that all of them together hit `max_opacity`
+    let fog = &config.fog;
+    let transmittance = 1. - fog.max_opacity.clamp(0., 0.99);
+    let layer_alpha = 1. - transmittance.powf(1. / fog.layers.max(1) as f32);
+    if let Some(material) = materials.get_mut(&fog_material.0) {
+        material.base_color = sky;
+        material.base_color.set_a(layer_alpha);
+    }
+}
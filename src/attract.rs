@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    campaign::ActiveLevel,
+    config::read_data_file,
+    input::{InputSource, PlayerInput},
+    level_gen::LevelSeed,
+    localization::Localization,
+    reset::DespawnOnReset,
+    reset_world,
+    state::GameState,
+    suspend::Suspending,
+    GameRng,
+};
+
+const DEMO_PATH: &str = "assets/demo.ron";
+// Of nobody touching anything on the main menu
+const IDLE_SECONDS: f32 = 30.;
+
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Demo::load())
+            .init_resource::<Attract>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(wake_menu))
+            .add_system_set(SystemSet::on_resume(GameState::MainMenu).with_system(wake_menu))
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(start_demo))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(stop_demo))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(finish_demo.before(reset_world)),
+            );
+    }
+}
+
+// A recorded run, and the seed it was recorded on so it plays out the same again
+#[derive(Resource, Clone, Debug, Default, Deserialize)]
+struct Demo {
+    seed: u64,
+    frames: Vec<PlayerInput>,
+}
+
+impl Demo {
+    fn load() -> Self {
+        let path = Path::new(DEMO_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/demo.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, the main menu won't play a demo: {e}");
+                return Self::default();
+            }
+        };
+
+        match ron::from_str::<Demo>(&contents) {
+            Ok(demo) => demo,
+            Err(e) => {
+                error!("{path:?} is invalid, the main menu won't play a demo: {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+// How long the menu's sat there untouched, and while the demo's playing, the seed to put back
+// once it's done
+#[derive(Resource, Default)]
+struct Attract {
+    idle: f32,
+    playing: Option<u64>,
+}
+
+// Anything at all, rather than just what's bound to something
+fn touched(
+    keys: &Input<KeyCode>,
+    buttons: &Input<GamepadButton>,
+    mouse: &Input<MouseButton>,
+    touches: &Touches,
+) -> bool {
+    keys.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some()
+}
+
+fn wake_menu(mut attract: ResMut<Attract>) {
+    attract.idle = 0.;
+}
+
+// Straight into the endless lane as the first hero, the same as any other recording. It leaves the
+// way a suspended run does, so there's nothing to look back over and nothing goes on the
+// leaderboard.
+fn start_demo(
+    mut commands: Commands,
+    mut attract: ResMut<Attract>,
+    demo: Res<Demo>,
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+    mut seed: ResMut<LevelSeed>,
+    mut rng: ResMut<GameRng>,
+    mut level: ResMut<ActiveLevel>,
+    mut suspending: ResMut<Suspending>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if touched(&keys, &buttons, &mouse, &touches) {
+        attract.idle = 0.;
+        return;
+    }
+    attract.idle += time.raw_delta_seconds();
+    if attract.idle < IDLE_SECONDS || demo.frames.is_empty() {
+        return;
+    }
+
+    attract.idle = 0.;
+    attract.playing = Some(seed.0);
+    seed.0 = demo.seed;
+    *rng = GameRng::from_seed(demo.seed);
+    level.0 = None;
+    suspending.0 = true;
+    commands.insert_resource(InputSource::Playback {
+        frames: demo.frames.clone(),
+        cursor: 0,
+    });
+    spawn_attract_ui(&mut commands, &localization, &asset_server);
+    if let Err(e) = state.set(GameState::Playing) {
+        warn!("Unable to start the demo: {e:?}");
+    }
+}
+
+// The title stays up over the top, so it still looks like the menu with the demo going on behind
+fn spawn_attract_ui(commands: &mut Commands, localization: &Localization, assets: &AssetServer) {
+    let font = assets.load("FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(40.)),
+                    ..default()
+                },
+                ..default()
+            },
+            DespawnOnReset,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.get("menu-title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 80.,
+                    color: Color::WHITE,
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                localization.get("attract-prompt"),
+                TextStyle {
+                    font,
+                    font_size: 36.,
+                    color: Color::YELLOW,
+                },
+            ));
+        });
+}
+
+// Anyone touching anything, or the recording running out, and it's back to the menu
+fn stop_demo(
+    attract: Res<Attract>,
+    source: Res<InputSource>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if attract.playing.is_none() {
+        return;
+    }
+    if !source.is_finished() && !touched(&keys, &buttons, &mouse, &touches) {
+        return;
+    }
+    if let Err(e) = state.set(GameState::GameOver) {
+        warn!("Unable to stop the demo: {e:?}");
+    }
+}
+
+// However it ended, the next run's played by whoever's there, on the seed it would have had. This
+// goes in before the world's reset, which seeds the next run's randomness from it.
+fn finish_demo(mut commands: Commands, mut attract: ResMut<Attract>, mut seed: ResMut<LevelSeed>) {
+    let Some(previous) = attract.playing.take() else { return };
+    seed.0 = previous;
+    commands.insert_resource(InputSource::Live);
+}
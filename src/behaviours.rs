@@ -0,0 +1,471 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    campaign::Boss,
+    config::GameConfig,
+    damage::{resolve_damage, DamageDealt, DamageEvent, Health},
+    difficulty::Difficulty,
+    enemy_bundle, enemy_movement,
+    localization::Localization,
+    obstacles::Collider,
+    popups::PopupEvent,
+    shield::Shield,
+    stagger::Staggered,
+    state::GameState,
+    status::Stunned,
+    waves::{Formation, WaveCleared, WaveManager, WaveStarted},
+    AttackState, CameraRig, Enemy, EnemyKind, Game, GameRng, Player, ENEMY_SPAWN_DISTANCE,
+};
+
+// Under assets/, and loaded like the models so `hot_reload` picks up changes to it
+const BEHAVIOURS_PATH: &str = "beets.behaviours.ron";
+// Taunts start above a beet's head
+const TAUNT_HEIGHT: f32 = 1.;
+const TAUNT_COLOR: Color = Color::rgb(0.9, 0.8, 0.3);
+// How far from where their parent fell split beets land
+const SPLIT_SPREAD: f32 = 0.4;
+// Wave announcements go up over player one's head
+const ANNOUNCE_HEIGHT: f32 = 1.5;
+const ANNOUNCE_COLOR: Color = Color::rgb(1., 0.5, 0.2);
+
+pub struct BehaviourPlugin;
+
+impl Plugin for BehaviourPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BehaviourSet>()
+            .add_asset_loader(BehaviourLoader)
+            .init_resource::<Behaviours>()
+            .add_startup_system(load_behaviours)
+            .add_system(apply_behaviours)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(assign_behaviours.after(apply_behaviours))
+                    .with_system(weave.after(enemy_movement))
+                    .with_system(run_wave_events.after(apply_behaviours)),
+            )
+            // Straight after the damage is done, while anything it killed is still around to be
+            // found
+            .add_system_to_stage(CoreStage::PostUpdate, last_words.after(resolve_damage));
+    }
+}
+
+// Something extra a beet can do, as written in beets.behaviours.ron. Waves pick one by name, so
+// new kinds of beet can be made up without touching the code.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BehaviourDefinition {
+    pub name: String,
+    pub movement: Movement,
+    pub on_death: Vec<DeathEffect>,
+    // Localization keys. One of them is said when the beet turns up, some of the time.
+    pub taunts: Vec<String>,
+    pub taunt_chance: f32,
+    // Degrees either side of straight ahead that a shield covers, for beets that carry one
+    pub shield: Option<f32>,
+    // Compared to an ordinary beet's. Bigger beets are that much wider to bump into, too.
+    pub size: f32,
+    pub speed: f32,
+    pub health: f32,
+}
+
+impl Default for BehaviourDefinition {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            movement: Movement::default(),
+            on_death: Vec::new(),
+            taunts: Vec::new(),
+            taunt_chance: 0.,
+            shield: None,
+            size: 1.,
+            speed: 1.,
+            health: 1.,
+        }
+    }
+}
+
+// On top of heading for the nearest player
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum Movement {
+    // Straight at them, like any other beet
+    #[default]
+    Chase,
+    // Swaying from side to side across the lane, `width` either way, once every `seconds`
+    Weave { width: f32, seconds: f32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum DeathEffect {
+    // Hurts every player within `radius`
+    Burst { radius: f32, damage: f32 },
+    // Leaves `count` beets behind, ordinary ones unless `into` names a behaviour for them
+    Split {
+        count: u32,
+        #[serde(default)]
+        into: Option<String>,
+    },
+}
+
+// Something that happens when a wave starts or is cleared, as written in the same file
+#[derive(Clone, Debug, Deserialize)]
+pub struct WaveEventDefinition {
+    pub on: WaveTrigger,
+    // Every wave if it's left out
+    #[serde(default)]
+    pub wave: Option<u32>,
+    pub actions: Vec<WaveAction>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum WaveTrigger {
+    Started,
+    Cleared,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum WaveAction {
+    // A localization key, popped up over player one
+    Announce(String),
+    // `count` more beets down the lane on top of the wave's own, following `behaviour` if it
+    // names one
+    Spawn {
+        count: u32,
+        #[serde(default)]
+        formation: Formation,
+        #[serde(default)]
+        behaviour: Option<String>,
+    },
+}
+
+// The whole file, as the asset server hands it over
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[uuid = "5f0c3b9e-8a41-4d2f-9c6e-2b7a1d4e8f13"]
+#[serde(default)]
+pub struct BehaviourSet {
+    pub behaviours: Vec<BehaviourDefinition>,
+    pub wave_events: Vec<WaveEventDefinition>,
+}
+
+#[derive(Default)]
+struct BehaviourLoader;
+
+impl AssetLoader for BehaviourLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let set = ron::de::from_bytes::<BehaviourSet>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(set));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["behaviours.ron"]
+    }
+}
+
+// Whatever's been loaded so far. Empty until the file's in, or if it won't parse, so beets only
+// chase and waves go by without any events.
+#[derive(Resource, Default)]
+pub struct Behaviours {
+    handle: Handle<BehaviourSet>,
+    pub definitions: Vec<BehaviourDefinition>,
+    pub wave_events: Vec<WaveEventDefinition>,
+}
+
+impl Behaviours {
+    fn find(&self, name: &str) -> Option<usize> {
+        let index = self
+            .definitions
+            .iter()
+            .position(|behaviour| behaviour.name == name);
+        if index.is_none() {
+            warn!("There's no behaviour called {name} in {BEHAVIOURS_PATH}");
+        }
+        index
+    }
+}
+
+fn load_behaviours(mut behaviours: ResMut<Behaviours>, asset_server: Res<AssetServer>) {
+    behaviours.handle = asset_server.load(BEHAVIOURS_PATH);
+}
+
+// Picks the file up once it's loaded and again every time it changes. Beets already out there
+// keep their behaviour by name, wherever it's moved to in the list.
+fn apply_behaviours(
+    mut events: EventReader<AssetEvent<BehaviourSet>>,
+    mut behaviours: ResMut<Behaviours>,
+    sets: Res<Assets<BehaviourSet>>,
+    mut beets: Query<&mut Behaviour>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != behaviours.handle {
+            continue;
+        }
+        let Some(set) = sets.get(handle) else { continue };
+
+        let old = std::mem::replace(&mut behaviours.definitions, set.behaviours.clone());
+        behaviours.wave_events = set.wave_events.clone();
+        for mut beet in &mut beets {
+            let Some(index) = beet.index else { continue };
+            beet.index = old.get(index).and_then(|old| behaviours.find(&old.name));
+        }
+        info!(
+            "Loaded {} behaviours and {} wave events",
+            behaviours.definitions.len(),
+            behaviours.wave_events.len()
+        );
+    }
+}
+
+// How much quicker than an ordinary beet this one gets about
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Pace(pub f32);
+
+// Which of `Behaviours` a beet follows. Beets without one just chase.
+#[derive(Component)]
+pub struct Behaviour {
+    index: Option<usize>,
+    age: f32,
+}
+
+// Every beet that turns up takes on whatever its wave says, apart from the ones a split leaves
+// behind, which come with their own. The boss is a law unto itself.
+fn assign_behaviours(
+    mut commands: Commands,
+    behaviours: Res<Behaviours>,
+    waves: Res<WaveManager>,
+    localization: Res<Localization>,
+    mut rng: ResMut<GameRng>,
+    mut popups: EventWriter<PopupEvent>,
+    mut enemies: Query<
+        (Entity, &mut Transform, &mut Health, &mut Collider, Option<&Behaviour>),
+        (Added<Enemy>, Without<Boss>),
+    >,
+) {
+    if enemies.is_empty() {
+        return;
+    }
+    let wave = waves.behaviour().and_then(|name| behaviours.find(name));
+
+    for (entity, mut transform, mut health, mut collider, split) in &mut enemies {
+        let index = match split {
+            Some(split) => split.index,
+            None => {
+                commands.entity(entity).insert(Behaviour {
+                    index: wave,
+                    age: 0.,
+                });
+                wave
+            }
+        };
+        let Some(behaviour) = index.and_then(|index| behaviours.definitions.get(index)) else {
+            continue;
+        };
+
+        if behaviour.size != 1. {
+            transform.scale = Vec3::splat(behaviour.size);
+            collider.radius *= behaviour.size;
+        }
+        if behaviour.health != 1. {
+            health.max *= behaviour.health;
+            health.current = health.max;
+        }
+        if behaviour.speed != 1. {
+            commands.entity(entity).insert(Pace(behaviour.speed));
+        }
+        if let Some(arc) = behaviour.shield {
+            commands.entity(entity).insert(Shield::new(arc));
+        }
+        // A whole brood shouting at once would just be noise
+        if split.is_some() {
+            continue;
+        }
+
+        let chance = behaviour.taunt_chance.clamp(0., 1.) as f64;
+        if behaviour.taunts.is_empty() || !rng.0.gen_bool(chance) {
+            continue;
+        }
+        let Some(taunt) = behaviour.taunts.choose(&mut rng.0) else { continue };
+        popups.send(PopupEvent {
+            text: localization.get(taunt).to_string(),
+            position: transform.translation + Vec3::Y * TAUNT_HEIGHT,
+            color: TAUNT_COLOR,
+        });
+    }
+}
+
+// Moves them by however much further along the sway they are since last frame, so the chasing
+// underneath carries on as usual. A stunned or staggered beet stops swaying too.
+fn weave(
+    behaviours: Res<Behaviours>,
+    time: Res<Time>,
+    mut enemies: Query<
+        (&mut Transform, &mut Behaviour, Option<&AttackState>),
+        (With<Enemy>, Without<Stunned>, Without<Staggered>),
+    >,
+) {
+    let delta = time.delta_seconds();
+    for (mut transform, mut behaviour, attack) in &mut enemies {
+        let before = behaviour.age;
+        behaviour.age += delta;
+        let Some(index) = behaviour.index else { continue };
+        let Some(definition) = behaviours.definitions.get(index) else { continue };
+        let Movement::Weave { width, seconds } = definition.movement else { continue };
+        // Standing still to swing at someone
+        if !matches!(attack, None | Some(AttackState::Chasing)) || seconds <= 0. {
+            continue;
+        }
+        let sway = |age: f32| width * (age / seconds * TAU).sin();
+        transform.translation.x += sway(behaviour.age) - sway(before);
+    }
+}
+
+fn last_words(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    mut damage: EventWriter<DamageEvent>,
+    behaviours: Res<Behaviours>,
+    game: Res<Game>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+    enemies: Query<(&GlobalTransform, &Behaviour), With<Enemy>>,
+    players: Query<(Entity, &GlobalTransform), With<Player>>,
+) {
+    for killed in dealt.iter().filter(|damage| damage.killed) {
+        let Ok((transform, behaviour)) = enemies.get(killed.target) else { continue };
+        let Some(index) = behaviour.index else { continue };
+        let Some(definition) = behaviours.definitions.get(index) else { continue };
+        let centre = transform.translation();
+
+        for effect in &definition.on_death {
+            match effect {
+                &DeathEffect::Burst { radius, damage: amount } => {
+                    for (player, player_transform) in &players {
+                        let offset = player_transform.translation() - centre;
+                        let offset = Vec3::new(offset.x, 0., offset.z);
+                        if offset.length() > radius {
+                            continue;
+                        }
+                        damage.send(DamageEvent {
+                            source: Some(killed.target),
+                            target: player,
+                            amount,
+                            direction: offset.normalize_or_zero(),
+                            critical: false,
+                            knockback: 0.,
+                        });
+                    }
+                }
+                DeathEffect::Split { count, into } => {
+                    let index = into.as_deref().and_then(|name| behaviours.find(name));
+                    for _ in 0..*count {
+                        let offset = Vec3::new(
+                            rng.0.gen_range(-SPLIT_SPREAD..SPLIT_SPREAD),
+                            0.,
+                            rng.0.gen_range(-SPLIT_SPREAD..SPLIT_SPREAD),
+                        );
+                        let translation = Vec3::new(centre.x, 0., centre.z) + offset;
+                        commands.spawn((
+                            enemy_bundle(&game, *difficulty, EnemyKind::Beet, translation),
+                            Behaviour { index, age: 0. },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The same beets as any other, but they come in straight away rather than waiting on the spawn
+// timer, and don't count towards the wave's own
+fn run_wave_events(
+    mut commands: Commands,
+    mut started: EventReader<WaveStarted>,
+    mut cleared: EventReader<WaveCleared>,
+    mut popups: EventWriter<PopupEvent>,
+    behaviours: Res<Behaviours>,
+    localization: Res<Localization>,
+    config: Res<GameConfig>,
+    rig: Res<CameraRig>,
+    game: Res<Game>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let triggered = started
+        .iter()
+        .map(|event| (WaveTrigger::Started, event.wave))
+        .chain(
+            cleared
+                .iter()
+                .map(|event| (WaveTrigger::Cleared, event.wave)),
+        )
+        .collect::<Vec<_>>();
+    if triggered.is_empty() {
+        return;
+    }
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let spawn_z = camera.translation().z - ENEMY_SPAWN_DISTANCE;
+    let half_width = config
+        .spawning
+        .pressure_at(rig.travelled())
+        .half_width
+        .max(0.1);
+
+    for (trigger, wave) in triggered {
+        let events = behaviours
+            .wave_events
+            .iter()
+            .filter(|event| event.on == trigger && event.wave.map_or(true, |only| only == wave));
+        for action in events.flat_map(|event| &event.actions) {
+            match action {
+                WaveAction::Announce(key) => {
+                    let Ok(player) = transforms.get(game.player) else { continue };
+                    popups.send(PopupEvent {
+                        text: localization.get(key).to_string(),
+                        position: player.translation() + Vec3::Y * ANNOUNCE_HEIGHT,
+                        color: ANNOUNCE_COLOR,
+                    });
+                }
+                WaveAction::Spawn {
+                    count,
+                    formation,
+                    behaviour,
+                } => {
+                    let index = behaviour.as_deref().and_then(|name| behaviours.find(name));
+                    for spot in formation.layout(*count, half_width) {
+                        // Scattered leaves where across the lane to the spawner
+                        let spot = match formation {
+                            Formation::Scattered => {
+                                Vec2::new(rng.0.gen_range(-half_width..half_width), 0.)
+                            }
+                            _ => spot,
+                        };
+                        let translation = Vec3::new(spot.x, 0., spawn_z - spot.y);
+                        commands.spawn((
+                            enemy_bundle(&game, *difficulty, EnemyKind::Beet, translation),
+                            Behaviour { index, age: 0. },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
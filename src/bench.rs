@@ -0,0 +1,161 @@
+use bevy::{app::AppExit, prelude::*};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{
+    damage::Health, difficulty::Difficulty, enemy_bundle, video::VideoSettings, EnemyKind,
+    EnemySpawnTimer, Game, GameRng, Projectile,
+};
+
+pub const DEFAULT_BENCH_ENEMIES: u32 = 200;
+pub const DEFAULT_BENCH_PROJECTILES: u32 = 200;
+// The crowd's laid out in rows across the lane, starting this far in front of the player
+const CROWD_START: f32 = 3.;
+const CROWD_SPACING: f32 = 0.4;
+const CROWD_HALF_WIDTH: f32 = 2.;
+// Pumpkins are scattered over the stretch between the player and the crowd, heading up the lane
+const VOLLEY_DEPTH: f32 = 3.;
+const VOLLEY_SPREAD: f32 = 0.3;
+// Nothing the crowd does should end the run early
+const BENCH_PLAYER_HEALTH: f32 = 1e9;
+
+// Throws a crowd of beets and a volley of pumpkins at the game in one go, with nothing else
+// spawning, then times every frame for a while and prints how long they took. The seed decides
+// where everything goes, so runs on the same seed can be compared.
+pub struct BenchPlugin {
+    pub frames: u32,
+    pub enemies: u32,
+    pub projectiles: u32,
+}
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bench {
+            frames: self.frames.max(1),
+            enemies: self.enemies,
+            projectiles: self.projectiles,
+            started: false,
+            frame_times: Vec::with_capacity(self.frames as usize),
+        })
+        .add_startup_system(uncap_frame_rate)
+        .add_system(start_bench)
+        .add_system_to_stage(CoreStage::Last, time_frames);
+    }
+}
+
+#[derive(Resource)]
+struct Bench {
+    frames: u32,
+    enemies: u32,
+    projectiles: u32,
+    started: bool,
+    // In real seconds, from the frame after everything was spawned
+    frame_times: Vec<f32>,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    frames: u32,
+    enemies: u32,
+    projectiles: u32,
+    // In milliseconds
+    mean: f32,
+    p50: f32,
+    p90: f32,
+    p99: f32,
+    max: f32,
+}
+
+// Vsync would just measure the monitor
+fn uncap_frame_rate(mut video: ResMut<VideoSettings>) {
+    video.vsync = false;
+}
+
+// As soon as there's a pumpkin to throw
+fn start_bench(
+    mut commands: Commands,
+    mut bench: ResMut<Bench>,
+    mut spawn_timer: ResMut<EnemySpawnTimer>,
+    mut rng: ResMut<GameRng>,
+    game: Res<Game>,
+    difficulty: Res<Difficulty>,
+    mut health: Query<&mut Health>,
+) {
+    if bench.started {
+        return;
+    }
+    let Some(projectile_asset) = &game.projectile else { return };
+    bench.started = true;
+    spawn_timer.0.pause();
+    if let Ok(mut health) = health.get_mut(game.player) {
+        *health = Health::new(BENCH_PLAYER_HEALTH);
+    }
+
+    let per_row = (CROWD_HALF_WIDTH * 2. / CROWD_SPACING) as u32 + 1;
+    for index in 0..bench.enemies {
+        let (row, column) = (index / per_row, index % per_row);
+        let translation = Vec3::new(
+            -CROWD_HALF_WIDTH + column as f32 * CROWD_SPACING,
+            0.,
+            -CROWD_START - row as f32 * CROWD_SPACING,
+        );
+        commands.spawn(enemy_bundle(&game, *difficulty, EnemyKind::Beet, translation));
+    }
+
+    for _ in 0..bench.projectiles {
+        let translation = Vec3::new(
+            rng.0.gen_range(-CROWD_HALF_WIDTH..CROWD_HALF_WIDTH),
+            0.25,
+            -rng.0.gen_range(0. ..VOLLEY_DEPTH),
+        );
+        let heading = Vec3::new(rng.0.gen_range(-VOLLEY_SPREAD..VOLLEY_SPREAD), 0., -1.);
+        commands.spawn((
+            SceneBundle {
+                scene: projectile_asset.clone(),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            Projectile {
+                heading: heading.normalize(),
+                damage: 1.,
+                critical: false,
+                blast_radius: 0.,
+                last_hit: None,
+            },
+        ));
+    }
+}
+
+fn time_frames(mut bench: ResMut<Bench>, time: Res<Time>, mut exit: EventWriter<AppExit>) {
+    if !bench.started {
+        return;
+    }
+    bench.frame_times.push(time.raw_delta_seconds());
+    if bench.frame_times.len() < bench.frames as usize {
+        return;
+    }
+
+    let mut sorted = bench.frame_times.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let milliseconds = |seconds: f32| seconds * 1000.;
+    let percentile = |fraction: f32| {
+        let index = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+        milliseconds(sorted[index])
+    };
+    let report = BenchReport {
+        frames: bench.frames,
+        enemies: bench.enemies,
+        projectiles: bench.projectiles,
+        mean: milliseconds(sorted.iter().sum::<f32>() / sorted.len() as f32),
+        p50: percentile(0.5),
+        p90: percentile(0.9),
+        p99: percentile(0.99),
+        max: milliseconds(*sorted.last().unwrap()),
+    };
+
+    match ron::ser::to_string_pretty(&report, default()) {
+        Ok(contents) => println!("{contents}"),
+        Err(e) => error!("Unable to serialize the bench report: {e}"),
+    }
+    exit.send(AppExit);
+}
@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{GamepadAssignments, PlayerInput},
+    localization::Localization,
+    navigation,
+    settings::{Settings, StickSettings},
+    state::GameState,
+};
+
+// Each stick is drawn as a circle of characters this many cells from the middle to the edge
+const GRID_RADIUS: i32 = 8;
+const DEAD_ZONE_STEP: f32 = 0.01;
+const MAX_DEAD_ZONE: f32 = 0.5;
+
+pub struct CalibrationPlugin;
+
+impl Plugin for CalibrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CalibrationMenu>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Calibration).with_system(spawn_calibration),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Calibration)
+                    .with_system(navigate_calibration)
+                    .with_system(update_calibration_text.after(navigate_calibration)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Calibration).with_system(close_calibration),
+            );
+    }
+}
+
+// One line under the sticks. The right stick only ever aims sideways, so there's no flipping its Y.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CalibrationRow {
+    LeftDeadZone,
+    LeftInvertX,
+    LeftInvertY,
+    RightDeadZone,
+    RightInvertX,
+}
+
+const ROWS: [CalibrationRow; 5] = [
+    CalibrationRow::LeftDeadZone,
+    CalibrationRow::LeftInvertX,
+    CalibrationRow::LeftInvertY,
+    CalibrationRow::RightDeadZone,
+    CalibrationRow::RightInvertX,
+];
+
+impl CalibrationRow {
+    // Into the localization
+    fn label(&self) -> &'static str {
+        match self {
+            CalibrationRow::LeftDeadZone => "sticks-left-dead-zone",
+            CalibrationRow::LeftInvertX => "sticks-left-invert-x",
+            CalibrationRow::LeftInvertY => "sticks-left-invert-y",
+            CalibrationRow::RightDeadZone => "sticks-right-dead-zone",
+            CalibrationRow::RightInvertX => "sticks-right-invert-x",
+        }
+    }
+
+    fn value(&self, settings: &Settings, localization: &Localization) -> String {
+        let on_off = |value: bool| localization.on_off(value).to_string();
+        match self {
+            CalibrationRow::LeftDeadZone => format!("{:.2}", settings.left_stick.dead_zone),
+            CalibrationRow::LeftInvertX => on_off(settings.left_stick.invert_x),
+            CalibrationRow::LeftInvertY => on_off(settings.left_stick.invert_y),
+            CalibrationRow::RightDeadZone => format!("{:.2}", settings.right_stick.dead_zone),
+            CalibrationRow::RightInvertX => on_off(settings.right_stick.invert_x),
+        }
+    }
+
+    // `direction` is -1 or 1
+    fn adjust(&self, settings: &mut Settings, direction: f32) {
+        let step = |value: &mut f32| {
+            *value = ((*value + DEAD_ZONE_STEP * direction) / DEAD_ZONE_STEP).round()
+                * DEAD_ZONE_STEP;
+            *value = value.clamp(0., MAX_DEAD_ZONE);
+        };
+        match self {
+            CalibrationRow::LeftDeadZone => step(&mut settings.left_stick.dead_zone),
+            CalibrationRow::LeftInvertX => {
+                settings.left_stick.invert_x = !settings.left_stick.invert_x
+            }
+            CalibrationRow::LeftInvertY => {
+                settings.left_stick.invert_y = !settings.left_stick.invert_y
+            }
+            CalibrationRow::RightDeadZone => step(&mut settings.right_stick.dead_zone),
+            CalibrationRow::RightInvertX => {
+                settings.right_stick.invert_x = !settings.right_stick.invert_x
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct CalibrationMenu {
+    row: usize,
+}
+
+#[derive(Component)]
+struct CalibrationUi;
+
+#[derive(Component)]
+struct CalibrationText;
+
+fn spawn_calibration(
+    mut commands: Commands,
+    mut menu: ResMut<CalibrationMenu>,
+    asset_server: Res<AssetServer>,
+) {
+    menu.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            CalibrationUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 20.,
+                        color: Color::WHITE,
+                    },
+                ),
+                CalibrationText,
+            ));
+        });
+}
+
+// The sticks are what's being calibrated, so this screen is driven by the d-pad and arrow keys
+// instead of the usual menu stick
+fn navigate_calibration(
+    input: Res<PlayerInput>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    assignments: Res<GamepadAssignments>,
+    mut menu: ResMut<CalibrationMenu>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the stick calibration: {e:?}");
+        }
+        return;
+    }
+
+    let pressed = |key, button| {
+        keys.just_pressed(key)
+            || assignments
+                .gamepad(0)
+                .map_or(false, |gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button)))
+    };
+
+    if pressed(KeyCode::Up, GamepadButtonType::DPadUp) {
+        menu.row = (menu.row + ROWS.len() - 1) % ROWS.len();
+    } else if pressed(KeyCode::Down, GamepadButtonType::DPadDown) {
+        menu.row = (menu.row + 1) % ROWS.len();
+    } else if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+        ROWS[menu.row].adjust(&mut settings, -1.);
+    } else if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+        ROWS[menu.row].adjust(&mut settings, 1.);
+    }
+}
+
+fn update_calibration_text(
+    menu: Res<CalibrationMenu>,
+    settings: Res<Settings>,
+    assignments: Res<GamepadAssignments>,
+    axes: Res<Axis<GamepadAxis>>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<CalibrationText>>,
+) {
+    let stick = |x, y| {
+        let Some(gamepad) = assignments.gamepad(0) else { return Vec2::ZERO };
+        let axis = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+        Vec2::new(axis(x), axis(y))
+    };
+    let left = stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+    let right = stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+
+    let mut panel = format!("{}\n\n", localization.get("sticks-title"));
+    if assignments.gamepad(0).is_none() {
+        panel += &format!("{}\n\n", localization.get("sticks-no-controller"));
+    }
+
+    let left_grid = stick_grid(left, &settings.left_stick);
+    let right_grid = stick_grid(right, &settings.right_stick);
+    for (left_line, right_line) in left_grid.iter().zip(&right_grid) {
+        panel += &format!("{left_line}    {right_line}\n");
+    }
+    panel += &format!(
+        "{:^width$}    {:^width$}\n\n",
+        format!("({:+.2}, {:+.2})", left.x, left.y),
+        format!("({:+.2}, {:+.2})", right.x, right.y),
+        width = (GRID_RADIUS as usize * 2 + 1) * 2 - 1,
+    );
+
+    for (index, row) in ROWS.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        panel += &format!(
+            "{cursor} {:<20}{:>8}\n",
+            localization.get(row.label()),
+            row.value(&settings, &localization)
+        );
+    }
+    panel += &format!("\n{}\n", localization.get("sticks-help"));
+    panel += localization.get("sticks-legend");
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+// The stick's reach as a circle of dots, the dead zone filled in around the middle, and the stick
+// itself wherever it is once the inversion's been applied
+fn stick_grid(raw: Vec2, calibration: &StickSettings) -> Vec<String> {
+    let position = calibration.orient(raw);
+    let in_dead_zone = calibration.apply_dead_zone(position) == Vec2::ZERO;
+    let marker = (position * GRID_RADIUS as f32).round();
+    let cell_size = 1. / GRID_RADIUS as f32;
+
+    (-GRID_RADIUS..=GRID_RADIUS)
+        .rev()
+        .map(|y| {
+            (-GRID_RADIUS..=GRID_RADIUS)
+                .map(|x| {
+                    let cell = Vec2::new(x as f32, y as f32) * cell_size;
+                    if Vec2::new(x as f32, y as f32) == marker {
+                        if in_dead_zone {
+                            'x'
+                        } else {
+                            'O'
+                        }
+                    } else if cell.length() <= calibration.dead_zone.max(cell_size / 2.) {
+                        '#'
+                    } else if cell.length() <= 1. {
+                        '.'
+                    } else {
+                        ' '
+                    }
+                })
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+fn close_calibration(mut commands: Commands, ui: Query<Entity, With<CalibrationUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,360 @@
+use std::path::Path;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::read_data_file,
+    crops::CropDefinition,
+    damage::Health,
+    difficulty::Difficulty,
+    enemy_bundle,
+    garden::Garden,
+    input::PlayerInput,
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    objective::ObjectiveDefinition,
+    results::Rank,
+    spawners::SpawnerDefinition,
+    state::GameState,
+    storage,
+    waves::{WaveDefinition, WaveManager},
+    weather::WeatherSpell,
+    EnemyKind, Game, ENEMY_SPAWN_DISTANCE,
+};
+
+const LEVELS_PATH: &str = "assets/levels.ron";
+const PROGRESS_FILE: &str = "campaign.ron";
+// What the endless lane is made from
+const DEFAULT_ENVIRONMENT: &str = "environment.glb";
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Levels::load())
+            .insert_resource(CampaignProgress::load())
+            .init_resource::<ActiveLevel>()
+            .init_resource::<LevelSelect>()
+            .init_resource::<BossSpawned>()
+            .add_system(open_level_select)
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelSelect).with_system(spawn_level_select),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect)
+                    .with_system(navigate_level_select)
+                    .with_system(update_level_select_text.after(navigate_level_select)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::LevelSelect).with_system(despawn_level_select),
+            )
+            // The character select and the garden go over the top, and backing out of them comes
+            // back here
+            .add_system_set(
+                SystemSet::on_pause(GameState::LevelSelect).with_system(despawn_level_select),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::LevelSelect).with_system(spawn_level_select),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(spawn_boss))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_boss));
+    }
+}
+
+// One stage of the campaign, as written in levels.ron
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct LevelDefinition {
+    pub name: String,
+    // The scene every chunk of the lane is made from
+    pub environment: String,
+    // How many enemies each wave brings, and how they turn up
+    pub waves: Vec<WaveDefinition>,
+    // Clearing every wave if there isn't one
+    pub objective: Option<ObjectiveDefinition>,
+    // Turns up with the last wave
+    pub boss: Option<BossDefinition>,
+    // Round and round for as long as the level lasts. Clear skies throughout if there isn't any.
+    pub weather: Vec<WeatherSpell>,
+    // Planted down the lane for the beets to go after
+    pub crops: Option<CropDefinition>,
+    // Down the lane, sending beets out until they're destroyed
+    pub spawners: Vec<SpawnerDefinition>,
+}
+
+impl Default for LevelDefinition {
+    fn default() -> Self {
+        Self {
+            name: "Untitled".into(),
+            environment: DEFAULT_ENVIRONMENT.into(),
+            waves: [4, 6, 8]
+                .into_iter()
+                .map(|enemies| WaveDefinition {
+                    enemies,
+                    ..default()
+                })
+                .collect(),
+            objective: None,
+            boss: None,
+            weather: Vec::new(),
+            crops: None,
+            spawners: Vec::new(),
+        }
+    }
+}
+
+// A beet, only bigger and harder to put down
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BossDefinition {
+    pub health: f32,
+    pub scale: f32,
+}
+
+impl Default for BossDefinition {
+    fn default() -> Self {
+        Self {
+            health: 20.,
+            scale: 2.5,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Levels(pub Vec<LevelDefinition>);
+
+impl Levels {
+    pub fn load() -> Self {
+        let path = Path::new(LEVELS_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/levels.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, there won't be a campaign: {e}");
+                return Self::default();
+            }
+        };
+
+        // A bare list, rather than wrapped up in a `Levels(...)`
+        ron::from_str(&contents).map(Self).unwrap_or_else(|e| {
+            error!("{path:?} is invalid, there won't be a campaign: {e}");
+            Self::default()
+        })
+    }
+}
+
+// The level being played, or none for the endless lane
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ActiveLevel(pub Option<LevelDefinition>);
+
+impl ActiveLevel {
+    // Waves keep coming and keep growing until everyone's down. Only these runs go on the
+    // leaderboard.
+    pub fn is_endless(&self) -> bool {
+        self.0.is_none()
+    }
+
+    pub fn environment(&self) -> &str {
+        self.0
+            .as_ref()
+            .map_or(DEFAULT_ENVIRONMENT, |level| level.environment.as_str())
+    }
+}
+
+// Levels beaten so far, by name, and the garden built up between them
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CampaignProgress {
+    pub completed: Vec<String>,
+    // The best each level's been won with, also by name
+    pub ranks: HashMap<String, Rank>,
+    pub garden: Garden,
+}
+
+impl CampaignProgress {
+    fn load() -> Self {
+        storage::data_file(PROGRESS_FILE)
+            .map(|path| storage::load(&path))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match storage::data_file(PROGRESS_FILE) {
+            Some(path) => storage::save(&path, self),
+            None => warn!("Nowhere to save campaign progress on this platform"),
+        }
+    }
+
+    pub fn is_complete(&self, level: &LevelDefinition) -> bool {
+        self.completed.contains(&level.name)
+    }
+
+    // Only ever goes up
+    pub fn complete(&mut self, level: &LevelDefinition, rank: Rank) {
+        if !self.is_complete(level) {
+            self.completed.push(level.name.clone());
+        }
+        let best = self.ranks.entry(level.name.clone()).or_insert(rank);
+        *best = rank.max(*best);
+    }
+}
+
+#[derive(Resource, Default)]
+struct LevelSelect {
+    row: usize,
+}
+
+#[derive(Resource, Default)]
+struct BossSpawned(bool);
+
+#[derive(Component)]
+pub struct Boss;
+
+#[derive(Component)]
+struct LevelSelectUi;
+
+#[derive(Component)]
+struct LevelSelectText;
+
+fn open_level_select(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.reload && *state.current() == GameState::MainMenu {
+        if let Err(e) = state.push(GameState::LevelSelect) {
+            warn!("Unable to open the level select: {e:?}");
+        }
+    }
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    mut select: ResMut<LevelSelect>,
+    asset_server: Res<AssetServer>,
+) {
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            LevelSelectUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                LevelSelectText,
+            ));
+        });
+}
+
+// Up and down pick a level, confirm picks a hero to play it with, back goes back to the main menu
+fn navigate_level_select(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    levels: Res<Levels>,
+    mut select: ResMut<LevelSelect>,
+    mut active: ResMut<ActiveLevel>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the level select: {e:?}");
+        }
+        return;
+    }
+
+    if input.confirm {
+        let Some(level) = levels.0.get(select.row) else { return };
+        active.0 = Some(level.clone());
+        if let Err(e) = state.push(GameState::CharacterSelect) {
+            warn!("Unable to open the character select: {e:?}");
+        }
+        return;
+    }
+
+    let count = levels.0.len().max(1);
+    select.row = navigation.row(select.row, count);
+}
+
+fn update_level_select_text(
+    select: Res<LevelSelect>,
+    levels: Res<Levels>,
+    progress: Res<CampaignProgress>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<LevelSelectText>>,
+) {
+    let mut panel = format!("{}\n\n", localization.get("campaign-title"));
+    if levels.0.is_empty() {
+        panel += &format!("  {}\n", localization.get("campaign-no-levels"));
+    }
+    for (index, level) in levels.0.iter().enumerate() {
+        let cursor = if index == select.row { ">" } else { " " };
+        // Levels beaten before ranks were kept are just done
+        let done = match progress.ranks.get(&level.name) {
+            Some(rank) => localization.variant("rank", rank),
+            None if progress.is_complete(level) => localization.get("campaign-done").to_string(),
+            None => String::new(),
+        };
+        panel += &format!("{cursor} {}. {:<28}{done:>8}\n", index + 1, level.name);
+    }
+    panel += &format!("\n{}", localization.get("campaign-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn despawn_level_select(mut commands: Commands, ui: Query<Entity, With<LevelSelectUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Turns up out in front as soon as the last planned wave begins
+fn spawn_boss(
+    mut commands: Commands,
+    mut spawned: ResMut<BossSpawned>,
+    level: Res<ActiveLevel>,
+    waves: Res<WaveManager>,
+    game: Res<Game>,
+    difficulty: Res<Difficulty>,
+    transforms: Query<&GlobalTransform>,
+) {
+    if spawned.0 {
+        return;
+    }
+    let Some(level) = &level.0 else { return };
+    let Some(boss) = &level.boss else { return };
+    if (waves.wave as usize) < level.waves.len() {
+        return;
+    }
+    let Ok(camera) = transforms.get(game.camera) else { return };
+
+    spawned.0 = true;
+    let translation = Vec3::new(0., 0., camera.translation().z - ENEMY_SPAWN_DISTANCE);
+    let health = boss.health * difficulty.scaling().enemy_health;
+    commands
+        .spawn(enemy_bundle(&game, *difficulty, EnemyKind::Beet, translation))
+        .insert((
+            Boss,
+            Health::new(health),
+            Transform::from_translation(translation).with_scale(Vec3::splat(boss.scale)),
+        ));
+}
+
+fn reset_boss(mut spawned: ResMut<BossSpawned>) {
+    spawned.0 = false;
+}
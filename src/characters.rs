@@ -0,0 +1,356 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    animation::{AnimationState, Animated},
+    config::{read_data_file, GameConfig},
+    daily::Daily,
+    damage::Health,
+    input::{InputSource, PlayerInput},
+    localization::Localization,
+    mutators::Mutators,
+    navigation::{self, MenuNavigation},
+    netplay::Netplay,
+    settings::Settings,
+    state::GameState,
+    weapons::{Loadout, WeaponEquipped},
+    Player, PLAYER_HEALTH,
+};
+
+const CHARACTERS_PATH: &str = "assets/characters.ron";
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Characters::load())
+            .init_resource::<CharacterSelect>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::CharacterSelect).with_system(spawn_character_select),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::CharacterSelect)
+                    .with_system(navigate_character_select)
+                    .with_system(update_character_select_text.after(navigate_character_select)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::CharacterSelect)
+                    .with_system(despawn_character_select),
+            )
+            // The mutators go over the top, so it's tucked away while they're up
+            .add_system_set(
+                SystemSet::on_pause(GameState::CharacterSelect).with_system(hide_character_select),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::CharacterSelect).with_system(show_character_select),
+            )
+            // Every run leaves the main menu behind, whether it was through here or carrying on a
+            // saved one
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(dress_players)
+                    .with_system(equip_starting_weapon),
+            );
+    }
+}
+
+// One of the heroes, as written in characters.ron
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CharacterDefinition {
+    pub name: String,
+    // Under assets/. Its first scene is the one that gets spawned.
+    pub model: String,
+    // On top of the usual walking pace, so 1 is a carrot
+    pub speed: f32,
+    pub health: f32,
+    // The name of one of the weapons in config.ron
+    pub weapon: String,
+}
+
+impl Default for CharacterDefinition {
+    fn default() -> Self {
+        Self {
+            name: "Carrot".into(),
+            model: "carrot.glb".into(),
+            speed: 1.,
+            health: PLAYER_HEALTH,
+            weapon: "Spud Launcher".into(),
+        }
+    }
+}
+
+impl CharacterDefinition {
+    pub fn scene(&self) -> String {
+        format!("{}#Scene0", self.model)
+    }
+}
+
+// The heroes, in the order the character select lists them
+#[derive(Resource, Clone, Debug)]
+pub struct Characters(pub Vec<CharacterDefinition>);
+
+impl Default for Characters {
+    fn default() -> Self {
+        Self(vec![CharacterDefinition::default()])
+    }
+}
+
+impl Characters {
+    pub fn load() -> Self {
+        let path = Path::new(CHARACTERS_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/characters.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, everyone's a carrot: {e}");
+                return Self::default();
+            }
+        };
+
+        match ron::from_str::<Vec<CharacterDefinition>>(&contents) {
+            Ok(characters) if !characters.is_empty() => Self(characters),
+            Ok(_) => {
+                warn!("{path:?} is empty, everyone's a carrot");
+                Self::default()
+            }
+            Err(e) => {
+                error!("{path:?} is invalid, everyone's a carrot: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    // Recordings and online games were made with the first hero, so they have to be played back
+    // with it too, whoever's been picked on this machine
+    pub fn playing_as(
+        &self,
+        settings: &Settings,
+        source: &InputSource,
+        online: bool,
+    ) -> &CharacterDefinition {
+        let first = &self.0[0];
+        if online || !matches!(source, InputSource::Live) {
+            return first;
+        }
+        self.0
+            .iter()
+            .find(|character| character.name == settings.character)
+            .unwrap_or(first)
+    }
+}
+
+// How fast this player gets about, from their character
+#[derive(Component)]
+pub struct Character {
+    pub speed: f32,
+}
+
+#[derive(Resource, Default)]
+struct CharacterSelect {
+    row: usize,
+}
+
+#[derive(Component)]
+struct CharacterSelectUi;
+
+#[derive(Component)]
+struct CharacterSelectText;
+
+fn spawn_character_select(
+    mut commands: Commands,
+    mut select: ResMut<CharacterSelect>,
+    characters: Res<Characters>,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+) {
+    // Start on whoever was picked last time
+    select.row = characters
+        .0
+        .iter()
+        .position(|character| character.name == settings.character)
+        .unwrap_or(0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            CharacterSelectUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                CharacterSelectText,
+            ));
+        });
+}
+
+// Up and down pick a hero, confirm starts the run as them, inspect opens the mutators and back goes
+// back to where we came from. The daily challenge comes with its own mutators.
+fn navigate_character_select(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    characters: Res<Characters>,
+    daily: Res<Daily>,
+    mut select: ResMut<CharacterSelect>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the character select: {e:?}");
+        }
+        return;
+    }
+
+    if input.inspect && daily.pending().is_none() {
+        if let Err(e) = state.push(GameState::Mutators) {
+            warn!("Unable to open the mutators: {e:?}");
+        }
+        return;
+    }
+
+    if input.confirm {
+        let Some(character) = characters.0.get(select.row) else { return };
+        if settings.character != character.name {
+            settings.character = character.name.clone();
+            settings.save();
+        }
+        // Leaving the menus underneath behind too
+        if let Err(e) = state.replace(GameState::Playing) {
+            warn!("Unable to start the run: {e:?}");
+        }
+        return;
+    }
+
+    let count = characters.0.len();
+    select.row = navigation.row(select.row, count);
+}
+
+fn update_character_select_text(
+    select: Res<CharacterSelect>,
+    characters: Res<Characters>,
+    mutators: Res<Mutators>,
+    daily: Res<Daily>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<CharacterSelectText>>,
+) {
+    let mut panel = format!(
+        "{}\n\n  {:<12}{:>8}{:>8}   {}\n",
+        localization.get("character-title"),
+        "",
+        localization.get("character-speed"),
+        localization.get("character-health"),
+        localization.get("character-weapon"),
+    );
+    for (index, character) in characters.0.iter().enumerate() {
+        let cursor = if index == select.row { ">" } else { " " };
+        panel += &format!(
+            "{cursor} {:<12}{:>8}{:>8}   {}\n",
+            character.name,
+            format!("{:.0}%", character.speed * 100.),
+            character.health,
+            character.weapon,
+        );
+    }
+    let (multiplier, help) = match daily.pending() {
+        Some(challenge) => (challenge.score_multiplier(), "character-daily-help"),
+        None => (mutators.chosen_multiplier(), "character-help"),
+    };
+    if multiplier != 1. {
+        let multiplier = format!("{multiplier:.2}");
+        let line = localization.format("character-mutators", &[("multiplier", &multiplier)]);
+        panel += &format!("\n{line}\n");
+    }
+    panel += &format!("\n{}", localization.get(help));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn hide_character_select(mut ui: Query<&mut Visibility, With<CharacterSelectUi>>) {
+    for mut visibility in &mut ui {
+        visibility.is_visible = false;
+    }
+}
+
+fn show_character_select(mut ui: Query<&mut Visibility, With<CharacterSelectUi>>) {
+    for mut visibility in &mut ui {
+        visibility.is_visible = true;
+    }
+}
+
+fn despawn_character_select(mut commands: Commands, ui: Query<Entity, With<CharacterSelectUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Everyone's already standing there from startup, so swap them over to whoever's been picked
+pub fn dress_players(
+    mut commands: Commands,
+    characters: Res<Characters>,
+    settings: Res<Settings>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    asset_server: Res<AssetServer>,
+    mut players: Query<(Entity, &mut Health), With<Player>>,
+) {
+    let character = characters.playing_as(&settings, &source, netplay.is_some());
+    for (entity, mut health) in &mut players {
+        health.max = character.health;
+        health.current = character.health;
+        commands.entity(entity).insert((
+            asset_server.load::<Scene, _>(character.scene()),
+            Animated::new(asset_server.load(character.model.as_str()), AnimationState::Idle),
+            Character {
+                speed: character.speed,
+            },
+        ));
+    }
+}
+
+// The hero's own weapon goes in the loadout if it isn't already, and in their hands
+fn equip_starting_weapon(
+    characters: Res<Characters>,
+    settings: Res<Settings>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    config: Res<GameConfig>,
+    mut loadout: ResMut<Loadout>,
+    mut equipped: EventWriter<WeaponEquipped>,
+) {
+    let character = characters.playing_as(&settings, &source, netplay.is_some());
+    let Some(weapon) = config
+        .weapons
+        .iter()
+        .position(|weapon| weapon.name == character.weapon)
+    else {
+        warn!("{} starts with {:?}, which isn't in the config", character.name, character.weapon);
+        return;
+    };
+
+    if !loadout.unlocked.contains(&weapon) {
+        loadout.unlocked.push(weapon);
+    }
+    loadout.equipped = weapon;
+    equipped.send(WeaponEquipped { weapon });
+}
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+
+use crate::{
+    campaign::Boss,
+    damage::{resolve_damage, DamageDealt},
+    input::{InputSource, PlayerInput},
+    netplay::Netplay,
+    state::GameState,
+    Game,
+};
+
+// The camera eases off wherever it was on to the track over this long
+const BLEND_SECONDS: f32 = 0.6;
+// And stays on the last waypoint this long before the game over screen comes up
+const HOLD_SECONDS: f32 = 0.5;
+// Looks at about chest height rather than at the feet
+const LOOK_HEIGHT: f32 = 0.6;
+
+// Round and in on the carrot that went down, slowly
+const DEATH_TRACK: [Waypoint; 3] = [
+    Waypoint {
+        seconds: 0.,
+        angle: 0.,
+        distance: 5.,
+        height: 3.5,
+    },
+    Waypoint {
+        seconds: 1.4,
+        angle: 0.9,
+        distance: 3.8,
+        height: 2.2,
+    },
+    Waypoint {
+        seconds: 2.8,
+        angle: 1.8,
+        distance: 2.6,
+        height: 1.2,
+    },
+];
+
+// Further out and the other way round, with more of the field in shot
+const BOSS_TRACK: [Waypoint; 3] = [
+    Waypoint {
+        seconds: 0.,
+        angle: 0.,
+        distance: 8.,
+        height: 5.,
+    },
+    Waypoint {
+        seconds: 1.8,
+        angle: -1.4,
+        distance: 6.5,
+        height: 3.,
+    },
+    Waypoint {
+        seconds: 3.4,
+        angle: -2.6,
+        distance: 5.5,
+        height: 2.,
+    },
+];
+
+pub struct CinematicPlugin;
+
+impl Plugin for CinematicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Cinematic>()
+            .add_system_to_stage(CoreStage::PostUpdate, watch_boss.after(resolve_damage))
+            .add_system_set(SystemSet::on_enter(GameState::Cinematic).with_system(start_cinematic))
+            .add_system_set(SystemSet::on_update(GameState::Cinematic).with_system(play_cinematic))
+            .add_system_set(SystemSet::on_exit(GameState::Cinematic).with_system(end_cinematic))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_cinematic));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shot {
+    Death,
+    BossDefeated,
+}
+
+impl Shot {
+    fn track(&self) -> CameraTrack {
+        let waypoints = match self {
+            Shot::Death => DEATH_TRACK.to_vec(),
+            Shot::BossDefeated => BOSS_TRACK.to_vec(),
+        };
+        CameraTrack { waypoints }
+    }
+}
+
+// Where the camera is at one point in the shot, relative to what it's of. The angle's round from
+// wherever the camera was when the shot started, so it never has to swing all the way round first.
+#[derive(Clone, Copy, Debug, Default)]
+struct Waypoint {
+    seconds: f32,
+    angle: f32,
+    distance: f32,
+    height: f32,
+}
+
+impl Waypoint {
+    fn lerp(&self, other: &Waypoint, t: f32) -> Waypoint {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Waypoint {
+            seconds: mix(self.seconds, other.seconds),
+            angle: mix(self.angle, other.angle),
+            distance: mix(self.distance, other.distance),
+            height: mix(self.height, other.height),
+        }
+    }
+}
+
+// Keyframed, and eased in and out of every waypoint so it never lurches
+#[derive(Clone, Debug, Default)]
+struct CameraTrack {
+    waypoints: Vec<Waypoint>,
+}
+
+impl CameraTrack {
+    fn duration(&self) -> f32 {
+        self.waypoints
+            .last()
+            .map_or(0., |waypoint| waypoint.seconds)
+    }
+
+    // Holds still before the first waypoint and after the last
+    fn sample(&self, seconds: f32) -> Option<Waypoint> {
+        let first = self.waypoints.first()?;
+        if seconds <= first.seconds {
+            return Some(*first);
+        }
+        for pair in self.waypoints.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            if seconds <= to.seconds {
+                let t = (seconds - from.seconds) / (to.seconds - from.seconds).max(f32::EPSILON);
+                return Some(from.lerp(to, t * t * (3. - 2. * t)));
+            }
+        }
+        self.waypoints.last().copied()
+    }
+}
+
+// The run's held on its last frame underneath while the camera has its look round. Where the boss
+// went down is kept from when it happens, since the level's only won a little while after.
+#[derive(Resource, Default)]
+pub struct Cinematic {
+    boss_fell: Option<Vec3>,
+    queued: Option<(Shot, Vec3)>,
+    track: CameraTrack,
+    focus: Vec3,
+    elapsed: f32,
+    // From the focus round to where the camera was when it started
+    bearing: f32,
+    from: Transform,
+    hidden: Vec<Entity>,
+}
+
+impl Cinematic {
+    pub fn boss_fell(&self) -> Option<Vec3> {
+        self.boss_fell
+    }
+}
+
+// However a run ends, this is the way to the game over screen: by way of a shot of something if
+// there's one to play and anything to play it with, or straight there if not
+pub fn cut_to_game_over(
+    state: &mut State<GameState>,
+    cinematic: Option<&mut Cinematic>,
+    shot: Option<(Shot, Vec3)>,
+) {
+    let result = match (cinematic, shot) {
+        (Some(cinematic), Some(shot)) => {
+            cinematic.queued = Some(shot);
+            state.push(GameState::Cinematic)
+        }
+        _ => state.set(GameState::GameOver),
+    };
+    if let Err(e) = result {
+        warn!("Unable to end the run: {e:?}");
+    }
+}
+
+fn watch_boss(
+    mut cinematic: ResMut<Cinematic>,
+    mut dealt: EventReader<DamageDealt>,
+    bosses: Query<&GlobalTransform, With<Boss>>,
+) {
+    for damage in dealt.iter() {
+        if !damage.killed {
+            continue;
+        }
+        if let Ok(transform) = bosses.get(damage.target) {
+            cinematic.boss_fell = Some(transform.translation());
+        }
+    }
+}
+
+// The HUD's tucked away for it, the same as for photo mode
+fn start_cinematic(
+    mut cinematic: ResMut<Cinematic>,
+    game: Res<Game>,
+    cameras: Query<&Transform>,
+    mut hud: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+) {
+    let Some((shot, focus)) = cinematic.queued.take() else { return };
+    let from = cameras.get(game.camera).copied().unwrap_or_default();
+    let offset = from.translation - focus;
+
+    cinematic.track = shot.track();
+    cinematic.focus = focus;
+    cinematic.elapsed = 0.;
+    cinematic.bearing = offset.x.atan2(offset.z);
+    cinematic.from = from;
+    cinematic.hidden.clear();
+    for (entity, mut visibility) in &mut hud {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+            cinematic.hidden.push(entity);
+        }
+    }
+}
+
+// Confirm or back skips it, once it's got going so a last frantic press doesn't. Recordings and
+// online games don't stop for it at all, so they end the same every time.
+fn play_cinematic(
+    mut cinematic: ResMut<Cinematic>,
+    time: Res<Time>,
+    game: Res<Game>,
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut cameras: Query<&mut Transform>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // The game clock could still be slowed from the kill-cam, but the camera shouldn't be
+    cinematic.elapsed += time.raw_delta_seconds();
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    let pressed = input.confirm || input.back;
+    let skipped = !live || (pressed && cinematic.elapsed > BLEND_SECONDS);
+
+    if let (Ok(mut camera), Some(waypoint)) = (
+        cameras.get_mut(game.camera),
+        cinematic.track.sample(cinematic.elapsed),
+    ) {
+        let focus = cinematic.focus;
+        let around = Quat::from_rotation_y(cinematic.bearing + waypoint.angle);
+        let target = focus + around * Vec3::new(0., waypoint.height, waypoint.distance);
+        let blend = (cinematic.elapsed / BLEND_SECONDS).min(1.);
+        let blend = blend * blend * (3. - 2. * blend);
+
+        camera.translation = cinematic.from.translation.lerp(target, blend);
+        let looking = Transform::from_translation(camera.translation)
+            .looking_at(focus + Vec3::Y * LOOK_HEIGHT, Vec3::Y);
+        camera.rotation = cinematic.from.rotation.slerp(looking.rotation, blend);
+    }
+
+    if !skipped && cinematic.elapsed < cinematic.track.duration() + HOLD_SECONDS {
+        return;
+    }
+    // Leaving the run underneath behind too
+    if let Err(e) = state.replace(GameState::GameOver) {
+        warn!("Unable to end the run: {e:?}");
+    }
+}
+
+// The camera's left where the shot finished for the game over screen, and put back with
+// everything else when the next run starts
+fn end_cinematic(mut cinematic: ResMut<Cinematic>, mut hud: Query<&mut Visibility, With<Node>>) {
+    for entity in cinematic.hidden.drain(..) {
+        if let Ok(mut visibility) = hud.get_mut(entity) {
+            visibility.is_visible = true;
+        }
+    }
+}
+
+fn reset_cinematic(mut cinematic: ResMut<Cinematic>) {
+    *cinematic = Cinematic::default();
+}
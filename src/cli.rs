@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub wave: Option<u32>,
+    // Width by height
+    pub windowed: Option<(u32, u32)>,
+    pub no_shadows: bool,
+    pub mute: bool,
+    pub skip_menu: bool,
+    pub record: Option<PathBuf>,
+    pub playback: Option<PathBuf>,
+    pub golden: Option<PathBuf>,
+    // Write the golden test's references from this run rather than comparing against them
+    pub bless: bool,
+    pub headless: Option<u32>,
+    pub bench: Option<u32>,
+    pub bench_enemies: Option<u32>,
+    pub bench_projectiles: Option<u32>,
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    pub telemetry: Option<PathBuf>,
+    pub process_assets: bool,
+}
+
+impl Options {
+    pub fn from_args() -> Self {
+        let mut options = Options::default();
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .unwrap_or_else(|| panic!("{arg} requires a value"))
+            };
+            match arg.as_str() {
+                "--seed" => {
+                    options.seed = Some(value().parse().expect("--seed must be a number"))
+                }
+                "--wave" => {
+                    options.wave = Some(value().parse().expect("--wave must be a number"))
+                }
+                "--windowed" => {
+                    options.windowed = Some(
+                        parse_resolution(&value())
+                            .expect("--windowed must be a resolution like 1280x720"),
+                    )
+                }
+                "--no-shadows" => options.no_shadows = true,
+                "--mute" => options.mute = true,
+                "--skip-menu" => options.skip_menu = true,
+                "--record" => options.record = Some(value().into()),
+                "--playback" => options.playback = Some(value().into()),
+                "--golden" => options.golden = Some(value().into()),
+                "--bless" => options.bless = true,
+                "--headless" => {
+                    options.headless =
+                        Some(value().parse().expect("--headless must be a number of ticks"))
+                }
+                "--bench" => {
+                    options.bench =
+                        Some(value().parse().expect("--bench must be a number of frames"))
+                }
+                "--bench-enemies" => {
+                    options.bench_enemies =
+                        Some(value().parse().expect("--bench-enemies must be a number"))
+                }
+                "--bench-projectiles" => {
+                    options.bench_projectiles =
+                        Some(value().parse().expect("--bench-projectiles must be a number"))
+                }
+                "--host" => {
+                    options.host = Some(value().parse().expect("--host must be a port number"))
+                }
+                "--join" => options.join = Some(value()),
+                "--telemetry" => options.telemetry = Some(value().into()),
+                "--process-assets" => options.process_assets = true,
+                _ => eprintln!("Ignoring unknown argument {arg}"),
+            }
+        }
+
+        options
+    }
+}
+
+fn parse_resolution(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    ambience::Biome,
+    crops::CropDefinition,
+    objective::ObjectiveDefinition,
+    spawn_rules::SpawnConfig,
+    weapons::WeaponDefinition,
+    weather::{WeatherKind, WeatherSpell},
+};
+
+const CONFIG_PATH: &str = "assets/config.ron";
+
+// Tunables that art and design want to tweak without recompiling. Anything missing from the file
+// falls back to the defaults below.
+#[derive(Resource, Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub lighting: LightingConfig,
+    pub fog: FogConfig,
+    // The glow on the locked target
+    pub target_highlight: Color,
+    pub movement_orientation: MovementOrientation,
+    pub environment: EnvironmentConfig,
+    pub spawning: SpawnConfig,
+    // Everything the player could get their hands on. The first is what they start with.
+    pub weapons: Vec<WeaponDefinition>,
+    // Without one a run just goes on until the player goes down
+    pub objective: Option<ObjectiveDefinition>,
+    // With at least this many beets about they're all drawn together in one go
+    pub instanced_horde_size: u32,
+    // The endless lane's forecast, round and round
+    pub weather: Vec<WeatherSpell>,
+    // Planted down the endless lane for the beets to go after, if there are any
+    pub crops: Option<CropDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentConfig {
+    // How far along Z one copy of environment.glb reaches
+    pub chunk_length: f32,
+    pub chunks_ahead: u32,
+    pub chunks_behind: u32,
+    // The biomes take turns, each lasting this many chunks
+    pub chunks_per_biome: u32,
+    pub biomes: Vec<Biome>,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        Self {
+            chunk_length: 38.,
+            chunks_ahead: 1,
+            chunks_behind: 1,
+            chunks_per_biome: 3,
+            biomes: vec![Biome::Garden, Biome::Fields, Biome::Greenhouse],
+        }
+    }
+}
+
+// What "up" on the stick means
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum MovementOrientation {
+    // Straight up the level, whatever the camera is doing
+    #[default]
+    World,
+    // Away from the camera
+    Camera,
+    // Wherever the player is facing
+    Player,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            lighting: LightingConfig::default(),
+            fog: FogConfig::default(),
+            target_highlight: Color::rgb(1.0, 0.85, 0.2),
+            movement_orientation: MovementOrientation::default(),
+            environment: EnvironmentConfig::default(),
+            spawning: SpawnConfig::default(),
+            weapons: vec![WeaponDefinition::default()],
+            objective: None,
+            instanced_horde_size: 100,
+            weather: [
+                (WeatherKind::Clear, 90.),
+                (WeatherKind::Windy, 45.),
+                (WeatherKind::Rain, 60.),
+                (WeatherKind::Storm, 40.),
+                (WeatherKind::Drizzle, 45.),
+            ]
+            .into_iter()
+            .map(|(kind, seconds)| WeatherSpell { kind, seconds })
+            .collect(),
+            crops: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FogConfig {
+    // Distances from the camera
+    pub start: f32,
+    pub end: f32,
+    pub layers: u32,
+    // How much of the sky colour has been blended in by `end`
+    pub max_opacity: f32,
+}
+
+impl Default for FogConfig {
+    fn default() -> Self {
+        Self {
+            start: 5.,
+            end: 12.,
+            layers: 8,
+            max_opacity: 0.9,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct LightingConfig {
+    pub ambient_color: Color,
+    pub ambient_brightness: f32,
+    pub sun: SunConfig,
+    pub fill_lights: Vec<FillLightConfig>,
+    // How long a run takes to go from dawn to night
+    pub day_length_seconds: f32,
+}
+
+impl Default for LightingConfig {
+    fn default() -> Self {
+        Self {
+            ambient_color: Color::rgb(0.85, 0.9, 1.0),
+            ambient_brightness: 0.3,
+            sun: SunConfig::default(),
+            fill_lights: vec![FillLightConfig::default()],
+            day_length_seconds: 600.,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SunConfig {
+    pub color: Color,
+    pub illuminance: f32,
+    // Euler angles (XYZ, radians)
+    pub rotation: Vec3,
+    pub shadows: bool,
+}
+
+impl Default for SunConfig {
+    fn default() -> Self {
+        Self {
+            color: Color::rgb(1.0, 0.92, 0.8),
+            illuminance: 15_000.,
+            rotation: Vec3::new(-0.8, -0.3, 0.),
+            shadows: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FillLightConfig {
+    pub color: Color,
+    pub illuminance: f32,
+    pub rotation: Vec3,
+}
+
+impl Default for FillLightConfig {
+    fn default() -> Self {
+        Self {
+            color: Color::rgb(0.6, 0.7, 1.0),
+            illuminance: 3_000.,
+            rotation: Vec3::new(-0.5, 2.6, 0.),
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn load() -> Self {
+        let path = Path::new(CONFIG_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/config.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, using the default config: {e}");
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|e| {
+            error!("{path:?} is invalid, using the default config: {e}");
+            Self::default()
+        })
+    }
+}
+
+// For the config and every other data file under `assets/`, with `baked` being the file's
+// `include_str!`. There's no reading files from the browser, and fetching them would mean waiting
+// on the network before the first frame, so web builds use the baked in copy.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_data_file(path: &Path, _baked: &'static str) -> std::io::Result<String> {
+    std::fs::read_to_string(path)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_data_file(_: &Path, baked: &'static str) -> std::io::Result<String> {
+    Ok(baked.to_string())
+}
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameConfig::load());
+    }
+}
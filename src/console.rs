@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+
+use crate::{
+    damage::{DamageAppExt, DamageContext, DamageStage, Health},
+    difficulty::Difficulty,
+    enemy_bundle,
+    netplay::Netplay,
+    state::GameState,
+    time_scale::TimeScale,
+    waves::{WavePhase, WaveManager},
+    Ammo, AmmoChanged, EnemyKind, Game, Player,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+// Lines kept in the scrollback, and how many of them fit on screen
+const MAX_LOG_LINES: usize = 100;
+const VISIBLE_LOG_LINES: usize = 14;
+// Cheat-spawned enemies turn up this far in front of player one
+const SPAWN_DISTANCE: f32 = 5.;
+const SPAWN_SPACING: f32 = 0.5;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<GodMode>()
+            .init_resource::<Cheated>()
+            .add_console_command("spawn", "spawn <kind> [count] - e.g. spawn beet 5", spawn)
+            .add_console_command("give", "give <health|ammo> - tops player one up", give)
+            .add_console_command("godmode", "toggles taking no damage", godmode)
+            .add_console_command("wave", "wave <number> - skips to that wave", wave)
+            .add_console_command(
+                "timescale",
+                "timescale <scale> - e.g. 0.5 for half speed",
+                timescale,
+            )
+            .add_damage_modifier(DamageStage::Clamp, apply_godmode)
+            .add_system(toggle_console)
+            .add_system_set(SystemSet::on_enter(GameState::Console).with_system(spawn_console))
+            .add_system_set(
+                SystemSet::on_update(GameState::Console)
+                    .with_system(type_into_console)
+                    .with_system(run_console_commands.after(type_into_console))
+                    .with_system(update_console_text.after(run_console_commands)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Console).with_system(despawn_console))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_cheated));
+    }
+}
+
+// A command gets the words after its name, and answers with a line for the log
+pub type CommandResult = Result<String, String>;
+pub type ConsoleCommand = Box<dyn Fn(&[&str], &mut World) -> CommandResult + Send + Sync>;
+
+struct RegisteredCommand {
+    help: &'static str,
+    run: ConsoleCommand,
+}
+
+#[derive(Resource, Default)]
+pub struct ConsoleCommands {
+    commands: BTreeMap<&'static str, RegisteredCommand>,
+}
+
+impl ConsoleCommands {
+    pub fn add(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: impl Fn(&[&str], &mut World) -> CommandResult + Send + Sync + 'static,
+    ) {
+        self.commands.insert(
+            name,
+            RegisteredCommand {
+                help,
+                run: Box::new(run),
+            },
+        );
+    }
+
+    fn help(&self) -> String {
+        self.commands
+            .iter()
+            .map(|(name, command)| format!("{name:<10} {}", command.help))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Lets any plugin bring its own cheats
+pub trait ConsoleAppExt {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: impl Fn(&[&str], &mut World) -> CommandResult + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: impl Fn(&[&str], &mut World) -> CommandResult + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .add(name, help, run);
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    line: String,
+    log: Vec<String>,
+    // Entered, but not run yet
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn log(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let overflow = self.log.len().saturating_sub(MAX_LOG_LINES);
+        self.log.drain(..overflow);
+    }
+}
+
+#[derive(Resource, Default)]
+struct GodMode(bool);
+
+// Set by the first cheat of a run, which then doesn't count for the high scores or the daily
+#[derive(Resource, Default)]
+pub struct Cheated(pub bool);
+
+#[derive(Component)]
+struct ConsoleUi;
+
+#[derive(Component)]
+struct ConsoleText;
+
+// One key both ways, so this can't live in either state's systems without the key opening and
+// closing it in the same frame. It won't open online, where a cheat would only happen on one side.
+fn toggle_console(
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    netplay: Option<Res<Netplay>>,
+) {
+    // Cheats are for working on the game, so there's no getting at them in a release build
+    if !cfg!(debug_assertions) || !keys.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+
+    let result = match state.current() {
+        GameState::Playing if netplay.is_some() => return,
+        GameState::Playing => state.push(GameState::Console),
+        GameState::Console => state.pop(),
+        _ => return,
+    };
+    if let Err(e) = result {
+        warn!("Unable to toggle the console: {e:?}");
+    }
+}
+
+fn spawn_console(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(40.)),
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(0.),
+                        left: Val::Px(0.),
+                        ..default()
+                    },
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+            ConsoleUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 18.,
+                        color: Color::rgb(0.8, 1., 0.8),
+                    },
+                ),
+                ConsoleText,
+            ));
+        });
+}
+
+fn despawn_console(mut commands: Commands, ui: Query<Entity, With<ConsoleUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn type_into_console(
+    mut console: ResMut<Console>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+) {
+    for character in characters.iter() {
+        // The toggle key types itself too
+        if character.char.is_control() || character.char == '`' || character.char == '~' {
+            continue;
+        }
+        console.line.push(character.char);
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        console.line.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) && !console.line.trim().is_empty() {
+        let line = std::mem::take(&mut console.line);
+        console.pending.push(line);
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    if pending.is_empty() {
+        return;
+    }
+
+    world.resource_scope(|world, commands: Mut<ConsoleCommands>| {
+        for line in pending {
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else { continue };
+            let arguments = words.collect::<Vec<_>>();
+
+            let result = match commands.commands.get(name) {
+                Some(command) => {
+                    world.resource_mut::<Cheated>().0 = true;
+                    (command.run)(&arguments, world)
+                }
+                // The registry is out of the world while commands run, so this one's built in
+                None if name == "help" => Ok(commands.help()),
+                None => Err(format!("Unknown command '{name}', try 'help'")),
+            };
+
+            let mut console = world.resource_mut::<Console>();
+            console.log(format!("> {line}"));
+            match result {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => console.log(output),
+                Err(error) => console.log(format!("error: {error}")),
+            }
+        }
+    });
+}
+
+fn update_console_text(console: Res<Console>, mut texts: Query<&mut Text, With<ConsoleText>>) {
+    let Ok(mut text) = texts.get_single_mut() else { return };
+
+    let start = console.log.len().saturating_sub(VISIBLE_LOG_LINES);
+    let mut contents = console.log[start..].join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    contents.push_str(&format!("> {}_", console.line));
+    text.sections[0].value = contents;
+}
+
+fn reset_cheated(mut cheated: ResMut<Cheated>) {
+    cheated.0 = false;
+}
+
+fn apply_godmode(context: &mut DamageContext, world: &World) {
+    if world.resource::<GodMode>().0 && world.get::<Player>(context.target).is_some() {
+        context.amount = 0.;
+    }
+}
+
+fn spawn(arguments: &[&str], world: &mut World) -> CommandResult {
+    let kind = match arguments.first().copied() {
+        Some("beet") => EnemyKind::Beet,
+        Some(other) => return Err(format!("No such enemy '{other}'")),
+        None => return Err("Spawn what?".into()),
+    };
+    let count = match arguments.get(1) {
+        Some(count) => count
+            .parse::<u32>()
+            .map_err(|_| format!("'{count}' isn't a number"))?,
+        None => 1,
+    };
+
+    let game = world.resource::<Game>();
+    let difficulty = *world.resource::<Difficulty>();
+    let player = world
+        .get::<GlobalTransform>(game.player)
+        .map(|transform| transform.translation())
+        .unwrap_or_default();
+    let centre = Vec3::new(player.x, 0., player.z - SPAWN_DISTANCE);
+    let bundles = (0..count)
+        .map(|index| {
+            let offset = (index as f32 - (count as f32 - 1.) / 2.) * SPAWN_SPACING;
+            enemy_bundle(game, difficulty, kind, centre + Vec3::X * offset)
+        })
+        .collect::<Vec<_>>();
+    for bundle in bundles {
+        world.spawn(bundle);
+    }
+
+    Ok(format!("Spawned {count} {kind:?}"))
+}
+
+fn give(arguments: &[&str], world: &mut World) -> CommandResult {
+    let game = world.resource::<Game>();
+    let (player, spud_gun) = (game.player, game.spud_gun);
+
+    match arguments.first().copied() {
+        Some("health") => {
+            let mut health = world
+                .get_mut::<Health>(player)
+                .ok_or("Player one has no health")?;
+            health.current = health.max;
+            Ok("Health topped up".into())
+        }
+        Some("ammo") => {
+            let mut ammo = world
+                .get_mut::<Ammo>(spud_gun)
+                .ok_or("Player one has no gun")?;
+            ammo.current = ammo.max;
+            world.send_event(AmmoChanged { weapon: spud_gun });
+            Ok("Ammo topped up".into())
+        }
+        Some(other) => Err(format!("Can't give '{other}'")),
+        None => Err("Give what?".into()),
+    }
+}
+
+fn godmode(_: &[&str], world: &mut World) -> CommandResult {
+    let mut god_mode = world.resource_mut::<GodMode>();
+    god_mode.0 = !god_mode.0;
+    Ok(format!("God mode {}", if god_mode.0 { "on" } else { "off" }))
+}
+
+fn wave(arguments: &[&str], world: &mut World) -> CommandResult {
+    let wave = arguments
+        .first()
+        .ok_or("Which wave?")?
+        .parse::<u32>()
+        .map_err(|_| "The wave has to be a number")?
+        .max(1);
+
+    let mut waves = world.resource_mut::<WaveManager>();
+    waves.wave = wave;
+    waves.remaining_to_spawn = waves.wave_size(wave);
+    waves.phase = WavePhase::Spawning;
+    Ok(format!("Skipped to wave {wave}"))
+}
+
+fn timescale(arguments: &[&str], world: &mut World) -> CommandResult {
+    let scale = arguments
+        .first()
+        .ok_or("What scale?")?
+        .parse::<f32>()
+        .map_err(|_| "The scale has to be a number")?;
+    if scale < 0. {
+        return Err("Time only goes forwards".into());
+    }
+
+    world.resource_mut::<TimeScale>().base = scale;
+    Ok(format!("Time scale is now {scale}"))
+}
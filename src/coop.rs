@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{
+    characters::Characters,
+    input::{GamepadAssignments, InputSource},
+    netplay::Netplay,
+    settings::Settings,
+    spawn_player,
+    state::GameState,
+    Game, Player,
+};
+
+// Player one plus a friend on a second pad
+const MAX_PLAYERS: usize = 2;
+// Where a player who joins mid-run turns up, relative to player one
+const JOIN_OFFSET: Vec3 = Vec3::new(0.5, 0., 0.);
+
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(join_players));
+    }
+}
+
+// Plugging in another pad drops a second hero in next to player one, as whoever was picked.
+// Unplugging it leaves them standing there until the run ends, so nobody's progress vanishes over
+// a loose cable.
+// Online, player two is whoever's on the other machine, so they're in from the start.
+fn join_players(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    characters: Res<Characters>,
+    settings: Res<Settings>,
+    assignments: Res<GamepadAssignments>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    game: Res<Game>,
+    players: Query<&Player>,
+    transforms: Query<&Transform>,
+) {
+    let online = netplay.is_some();
+    // Recordings and golden runs are single player
+    if !online && !matches!(*source, InputSource::Live) {
+        return;
+    }
+
+    for index in 1..MAX_PLAYERS {
+        if !online && assignments.gamepad(index).is_none() {
+            continue;
+        }
+        if players.iter().any(|player| player.index == index) {
+            continue;
+        }
+
+        let Ok(player_one) = transforms.get(game.player) else { return };
+        let transform =
+            Transform::from_translation(player_one.translation + JOIN_OFFSET * index as f32);
+        let character = characters.playing_as(&settings, &source, online);
+        spawn_player(&mut commands, &asset_server, character, index, transform);
+        info!("Player {} joined", index + 1);
+    }
+}
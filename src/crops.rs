@@ -0,0 +1,211 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::{
+    camera_start,
+    campaign::ActiveLevel,
+    config::GameConfig,
+    damage::{DamageDealt, Health},
+    level_gen::LevelSeed,
+    localization::Localization,
+    objective::{Objective, ObjectiveFailed, ObjectiveStatus},
+    reset::DespawnOnReset,
+    state::GameState,
+};
+
+// The first one's planted this far in front of where the camera starts
+const FIRST_CROP_AHEAD: f32 = 5.;
+// Either side of the middle of the lane
+const CROP_HALF_WIDTH: f32 = 1.2;
+const CROP_RADIUS: f32 = 0.12;
+// Under the objective
+const HUD_TOP: f32 = 56.;
+
+pub struct CropPlugin;
+
+impl Plugin for CropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Crops>()
+            .add_startup_system(setup_crop_hud)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(plant_crops))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(lose_crops))
+            .add_system(update_crop_hud)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(clear_crops));
+    }
+}
+
+// A row of crops down the lane, as written in levels.ron or config.ron. Beets that pass close to
+// one stop to go after it, and losing more than `spare` of them ends the run.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CropDefinition {
+    pub count: u32,
+    pub health: f32,
+    // How far apart down the lane
+    pub spacing: f32,
+    pub spare: u32,
+}
+
+impl Default for CropDefinition {
+    fn default() -> Self {
+        Self {
+            count: 6,
+            health: 3.,
+            spacing: 12.,
+            spare: 2,
+        }
+    }
+}
+
+// Beets within `CROP_DIVERT_RANGE` of one go for it rather than the players
+#[derive(Component)]
+pub struct Crop;
+
+#[derive(Resource, Default)]
+pub struct Crops {
+    pub definition: Option<CropDefinition>,
+    pub lost: u32,
+}
+
+#[derive(Component)]
+struct CropText;
+
+// Where they go is down to the seed, like everything else in the level
+fn plant_crops(
+    mut commands: Commands,
+    mut crops: ResMut<Crops>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<GameConfig>,
+    level: Res<ActiveLevel>,
+    seed: Res<LevelSeed>,
+) {
+    let definition = match &level.0 {
+        Some(level) => level.crops.clone(),
+        None => config.crops.clone(),
+    };
+    *crops = Crops {
+        definition: definition.clone(),
+        lost: 0,
+    };
+    let Some(definition) = definition else { return };
+
+    let mesh = meshes.add(Mesh::from(shape::Icosphere {
+        radius: CROP_RADIUS,
+        subdivisions: 2,
+    }));
+    let material = materials.add(Color::rgb(0.45, 0.75, 0.25).into());
+    let mut rng = StdRng::seed_from_u64(seed.0);
+    let start = camera_start().translation.z - FIRST_CROP_AHEAD;
+    for index in 0..definition.count {
+        let position = Vec3::new(
+            rng.gen_range(-CROP_HALF_WIDTH..CROP_HALF_WIDTH),
+            CROP_RADIUS,
+            start - index as f32 * definition.spacing,
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Crop,
+            Health::new(definition.health),
+            NotShadowCaster,
+            DespawnOnReset,
+        ));
+    }
+}
+
+fn lose_crops(
+    mut commands: Commands,
+    mut crops: ResMut<Crops>,
+    mut objective: ResMut<Objective>,
+    mut dealt: EventReader<DamageDealt>,
+    mut failed: EventWriter<ObjectiveFailed>,
+    planted: Query<(), With<Crop>>,
+) {
+    for damage in dealt.iter().filter(|damage| damage.killed) {
+        if !planted.contains(damage.target) {
+            continue;
+        }
+        commands.entity(damage.target).despawn_recursive();
+        crops.lost += 1;
+    }
+
+    let Some(definition) = &crops.definition else { return };
+    if crops.lost > definition.spare && objective.status == ObjectiveStatus::InProgress {
+        objective.status = ObjectiveStatus::Failed;
+        failed.send(ObjectiveFailed);
+    }
+}
+
+fn setup_crop_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect::top(Val::Px(HUD_TOP)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 22.,
+                        color: Color::rgb(0.7, 0.95, 0.5),
+                    },
+                )
+                .with_text_alignment(TextAlignment::CENTER),
+                CropText,
+            ));
+        });
+}
+
+// How many are left, how many more can go, and how the worst off of them is doing
+fn update_crop_hud(
+    crops: Res<Crops>,
+    localization: Res<Localization>,
+    planted: Query<&Health, With<Crop>>,
+    mut texts: Query<&mut Text, With<CropText>>,
+) {
+    let hud = match &crops.definition {
+        None => String::new(),
+        Some(definition) => {
+            let left = definition.count.saturating_sub(crops.lost);
+            let spare = definition.spare.saturating_sub(crops.lost);
+            let weakest = planted
+                .iter()
+                .map(|health| health.current.max(0.))
+                .min_by(f32::total_cmp)
+                .map_or(0., |health| health / definition.health.max(f32::EPSILON));
+            localization.format(
+                "crops-hud",
+                &[
+                    ("left", &left),
+                    ("count", &definition.count),
+                    ("spare", &spare),
+                    ("weakest", &format!("{:.0}%", weakest * 100.)),
+                ],
+            )
+        }
+    };
+
+    for mut text in &mut texts {
+        if text.sections[0].value != hud {
+            text.sections[0].value = hud.clone();
+        }
+    }
+}
+
+fn clear_crops(mut crops: ResMut<Crops>) {
+    *crops = Crops::default();
+}
@@ -0,0 +1,329 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    campaign::ActiveLevel,
+    console::Cheated,
+    difficulty::Difficulty,
+    high_scores::{DailyResult, HighScores},
+    input::{InputSource, PlayerInput},
+    level_gen::LevelSeed,
+    localization::Localization,
+    mutators::{begin_mutators, Mutator, Mutators},
+    netplay::Netplay,
+    reset_world,
+    score::Score,
+    state::GameState,
+    streaming::{clear_chunks, ChunkMember, EnvironmentChunks},
+    suspend::Suspending,
+    waves::WaveManager,
+    GameRng,
+};
+
+// Days go by UTC, so everyone's on the same challenge at the same time wherever they are
+#[cfg(not(target_arch = "wasm32"))]
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+// Every day has at least one
+const MAX_DAILY_MUTATORS: usize = 2;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Daily>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(spawn_daily_line))
+            .add_system_set(SystemSet::on_resume(GameState::MainMenu).with_system(spawn_daily_line))
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(start_daily))
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(despawn_daily_line)
+                    .with_system(begin_daily.after(begin_mutators)),
+            )
+            .add_system_set(
+                SystemSet::on_pause(GameState::MainMenu).with_system(despawn_daily_line),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(record_daily))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(despawn_daily_result)
+                    .with_system(end_daily.before(reset_world)),
+            );
+    }
+}
+
+// One day's seed and mutators, the same for everyone who plays it
+#[derive(Clone, Debug)]
+pub struct DailyChallenge {
+    pub day: u64,
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+}
+
+impl DailyChallenge {
+    pub fn for_day(day: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(day);
+        let seed = rng.gen();
+        let count = rng.gen_range(1..=MAX_DAILY_MUTATORS);
+        let mut mutators = Mutator::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect::<Vec<_>>();
+        // In the order they're listed in, however they came out
+        mutators.sort_by_key(|mutator| *mutator as usize);
+        Self {
+            day,
+            seed,
+            mutators,
+        }
+    }
+
+    pub fn score_multiplier(&self) -> f32 {
+        self.mutators
+            .iter()
+            .map(Mutator::score_multiplier)
+            .product()
+    }
+
+    fn mutator_names(&self, localization: &Localization) -> String {
+        self.mutators
+            .iter()
+            .map(|mutator| localization.variant("mutator", mutator))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Daily {
+    // Worked out again every time the main menu comes up, in case it's gone past midnight. None
+    // where the date can't be had.
+    pub today: Option<DailyChallenge>,
+    // Chosen from the main menu, on the way through the character select
+    pending: bool,
+    // The one being played, and the seed to go back to once it's over
+    playing: Option<(DailyChallenge, u64)>,
+}
+
+impl Daily {
+    // Picked, but not started yet
+    pub fn pending(&self) -> Option<&DailyChallenge> {
+        self.today.as_ref().filter(|_| self.pending)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+}
+
+#[derive(Component)]
+struct DailyLine;
+
+#[derive(Component)]
+struct DailyResultUi;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn today() -> Option<u64> {
+    let since = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(since.as_secs() / SECONDS_PER_DAY)
+}
+
+// The standard library can't tell the time in a browser, so there's no daily challenge there
+#[cfg(target_arch = "wasm32")]
+fn today() -> Option<u64> {
+    None
+}
+
+// Days since 1970 as a year, month and day
+fn date(day: u64) -> String {
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Counting from March, so the leap day's at the end
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day_of_month:02}")
+}
+
+// Along the bottom of the main menu, with the best go at it so far
+fn spawn_daily_line(
+    mut commands: Commands,
+    mut daily: ResMut<Daily>,
+    high_scores: Res<HighScores>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    daily.pending = false;
+    daily.today = today().map(DailyChallenge::for_day);
+    let Some(challenge) = &daily.today else { return };
+
+    let mut line = localization.format(
+        "daily-menu",
+        &[
+            ("date", &date(challenge.day)),
+            ("mutators", &challenge.mutator_names(&localization)),
+        ],
+    );
+    if let Some(best) = high_scores.best_daily(challenge.day) {
+        let best = localization.format("daily-best", &[("score", &best.score)]);
+        line += &format!("\n{best}");
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            line,
+            TextStyle {
+                font: asset_server.load("FiraSans-Bold.ttf"),
+                font_size: 26.,
+                color: Color::YELLOW,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(30.),
+                left: Val::Px(30.),
+                ..default()
+            },
+            ..default()
+        }),
+        DailyLine,
+    ));
+}
+
+fn despawn_daily_line(mut commands: Commands, ui: Query<Entity, With<DailyLine>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Deploy on the main menu. It's the endless lane, but on the day's seed and with the day's
+// mutators. Online games have their own seed to agree on, and recordings their own to play back.
+fn start_daily(
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut daily: ResMut<Daily>,
+    mut level: ResMut<ActiveLevel>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    if !input.deploy || !live || daily.today.is_none() {
+        return;
+    }
+    daily.pending = true;
+    level.0 = None;
+    if let Err(e) = state.push(GameState::CharacterSelect) {
+        warn!("Unable to open the character select: {e:?}");
+    }
+}
+
+// After the mutators have been settled, so the day's can go over the top of them. The lane's
+// already been streamed in on the usual seed, so it's thrown away to come back in on the day's.
+fn begin_daily(
+    mut commands: Commands,
+    mut daily: ResMut<Daily>,
+    mut seed: ResMut<LevelSeed>,
+    mut rng: ResMut<GameRng>,
+    mut mutators: ResMut<Mutators>,
+    mut chunks: ResMut<EnvironmentChunks>,
+    members: Query<Entity, With<ChunkMember>>,
+) {
+    let Some(challenge) = daily.pending().cloned() else { return };
+    daily.pending = false;
+
+    mutators.play_with(&challenge.mutators);
+    *rng = GameRng::from_seed(challenge.seed);
+    if seed.0 != challenge.seed {
+        clear_chunks(&mut commands, &mut chunks, &members);
+    }
+    daily.playing = Some((challenge.clone(), seed.0));
+    seed.0 = challenge.seed;
+}
+
+// Kept apart from the leaderboard, and shown as a line that can be copied out of the log and
+// passed on
+fn record_daily(
+    mut commands: Commands,
+    daily: Res<Daily>,
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    waves: Res<WaveManager>,
+    difficulty: Res<Difficulty>,
+    suspending: Res<Suspending>,
+    cheated: Res<Cheated>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some((challenge, _)) = &daily.playing else { return };
+    if suspending.0 || cheated.0 {
+        return;
+    }
+
+    let best = high_scores.add_daily(DailyResult {
+        day: challenge.day,
+        score: score.points,
+        wave: waves.wave,
+        kills: score.kills,
+    });
+    if best {
+        high_scores.save();
+    }
+
+    let difficulty = localization.variant("difficulty", &*difficulty);
+    let share = localization.format(
+        "daily-share",
+        &[
+            ("date", &date(challenge.day)),
+            ("score", &score.points),
+            ("wave", &waves.wave),
+            ("kills", &score.kills),
+            ("mutators", &challenge.mutator_names(&localization)),
+            ("difficulty", &difficulty),
+        ],
+    );
+    info!("{share}");
+    commands.spawn((
+        TextBundle::from_section(
+            share,
+            TextStyle {
+                font: asset_server.load("FiraMono-Medium.ttf"),
+                font_size: 20.,
+                color: Color::YELLOW,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(30.),
+                left: Val::Px(30.),
+                ..default()
+            },
+            ..default()
+        }),
+        DailyResultUi,
+    ));
+}
+
+fn despawn_daily_result(mut commands: Commands, ui: Query<Entity, With<DailyResultUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Back on the usual seed for whatever's played next. This goes in before the world's reset, which
+// seeds the next run's randomness from it.
+fn end_daily(mut daily: ResMut<Daily>, mut seed: ResMut<LevelSeed>) {
+    let Some((_, previous)) = daily.playing.take() else { return };
+    seed.0 = previous;
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    cinematic::{cut_to_game_over, Cinematic, Shot},
+    revive::Downed,
+    state::GameState,
+    Aim, Enemy, EnemyKilled, EnemyKind, Player,
+};
+
+// Each frame a shove keeps this much of its speed
+const KNOCKBACK_DECAY: f32 = 0.85;
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamagePipeline>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageDealt>()
+            .add_damage_modifier(DamageStage::Resistance, apply_resistance)
+            .add_damage_modifier(DamageStage::Clamp, clamp_damage)
+            .add_system_to_stage(CoreStage::PostUpdate, resolve_damage)
+            .add_system_to_stage(CoreStage::PostUpdate, kill_enemies.after(resolve_damage))
+            .add_system_to_stage(CoreStage::PostUpdate, apply_knockback.after(resolve_damage))
+            .add_system_to_stage(CoreStage::PostUpdate, kill_player.after(resolve_damage));
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.
+    }
+}
+
+// A shove that dies away by itself. Whatever moves the entity around applies it, a frame at a time.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Knockback {
+    pub velocity: Vec3,
+}
+
+impl Knockback {
+    // How far to move this frame
+    pub fn step(&mut self) -> Vec3 {
+        let step = self.velocity;
+        self.velocity *= KNOCKBACK_DECAY;
+        if self.velocity.length_squared() < f32::EPSILON {
+            self.velocity = Vec3::ZERO;
+        }
+        step
+    }
+}
+
+// Fraction of incoming damage that's shrugged off
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Resistance(pub f32);
+
+// Someone wants to hurt something. The pipeline decides how much it actually hurts.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    // Which way the blow was travelling
+    pub direction: Vec3,
+    // Decided by whatever dealt it, e.g. when the weapon fired. Modifiers can still change their mind.
+    pub critical: bool,
+    // How hard it shoves whatever it hits along `direction`, if it survives and can be shoved
+    pub knockback: f32,
+}
+
+// What actually happened after the pipeline was done with a `DamageEvent`
+#[derive(Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    pub critical: bool,
+    pub direction: Vec3,
+    pub knockback: f32,
+    pub killed: bool,
+}
+
+// Damage is worked out in this order, each stage getting the result of the one before
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DamageStage {
+    Base,
+    Weapon,
+    Relic,
+    Resistance,
+    Critical,
+    Clamp,
+}
+
+#[derive(Clone, Debug)]
+pub struct DamageContext {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    pub critical: bool,
+    pub direction: Vec3,
+    pub knockback: f32,
+}
+
+pub type DamageModifier = Box<dyn Fn(&mut DamageContext, &World) + Send + Sync>;
+
+#[derive(Resource, Default)]
+pub struct DamagePipeline {
+    stages: BTreeMap<DamageStage, Vec<DamageModifier>>,
+}
+
+impl DamagePipeline {
+    pub fn add_modifier(
+        &mut self,
+        stage: DamageStage,
+        modifier: impl Fn(&mut DamageContext, &World) + Send + Sync + 'static,
+    ) {
+        self.stages.entry(stage).or_default().push(Box::new(modifier));
+    }
+
+    pub fn run(&self, context: &mut DamageContext, world: &World) {
+        for modifiers in self.stages.values() {
+            for modifier in modifiers {
+                modifier(context, world);
+            }
+        }
+    }
+}
+
+// Lets any plugin hook into damage without knowing about the others
+pub trait DamageAppExt {
+    fn add_damage_modifier(
+        &mut self,
+        stage: DamageStage,
+        modifier: impl Fn(&mut DamageContext, &World) + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl DamageAppExt for App {
+    fn add_damage_modifier(
+        &mut self,
+        stage: DamageStage,
+        modifier: impl Fn(&mut DamageContext, &World) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(DamagePipeline::default)
+            .add_modifier(stage, modifier);
+        self
+    }
+}
+
+fn apply_resistance(context: &mut DamageContext, world: &World) {
+    if let Some(resistance) = world.get::<Resistance>(context.target) {
+        context.amount *= 1. - resistance.0.clamp(0., 1.);
+    }
+}
+
+fn clamp_damage(context: &mut DamageContext, _: &World) {
+    context.amount = context.amount.max(0.);
+}
+
+pub fn resolve_damage(world: &mut World) {
+    let events = world
+        .resource_mut::<Events<DamageEvent>>()
+        .drain()
+        .collect::<Vec<_>>();
+    if events.is_empty() {
+        return;
+    }
+
+    world.resource_scope(|world, pipeline: Mut<DamagePipeline>| {
+        for event in events {
+            // Anything that's already dead (say, hit by two pumpkins in one frame) stays dead
+            match world.get::<Health>(event.target) {
+                Some(health) if !health.is_dead() => {}
+                _ => continue,
+            }
+
+            let mut context = DamageContext {
+                source: event.source,
+                target: event.target,
+                amount: event.amount,
+                critical: event.critical,
+                direction: event.direction,
+                knockback: event.knockback,
+            };
+            pipeline.run(&mut context, world);
+
+            let mut health = world.get_mut::<Health>(event.target).unwrap();
+            health.current -= context.amount;
+            let killed = health.is_dead();
+
+            world.resource_mut::<Events<DamageDealt>>().send(DamageDealt {
+                source: context.source,
+                target: context.target,
+                amount: context.amount,
+                critical: context.critical,
+                direction: context.direction,
+                knockback: context.knockback,
+                killed,
+            });
+        }
+    });
+}
+
+fn kill_enemies(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    mut killed: EventWriter<EnemyKilled>,
+    mut aims: Query<&mut Aim>,
+    enemies: Query<(&GlobalTransform, &EnemyKind), With<Enemy>>,
+) {
+    for damage in dealt.iter().filter(|damage| damage.killed) {
+        let Ok((transform, kind)) = enemies.get(damage.target) else { continue };
+
+        for mut aim in &mut aims {
+            if aim.target == Some(damage.target) {
+                aim.target = None;
+            }
+        }
+        commands.entity(damage.target).despawn_recursive();
+        killed.send(EnemyKilled {
+            entity: damage.target,
+            kind: *kind,
+            position: transform.translation(),
+        });
+    }
+}
+
+// Only along the ground - nothing gets knocked into the air
+fn apply_knockback(mut dealt: EventReader<DamageDealt>, mut knockbacks: Query<&mut Knockback>) {
+    for damage in dealt.iter() {
+        if damage.killed || damage.knockback <= 0. {
+            continue;
+        }
+        let Ok(mut knockback) = knockbacks.get_mut(damage.target) else { continue };
+        let direction = Vec3::new(damage.direction.x, 0., damage.direction.z).normalize_or_zero();
+        knockback.velocity += direction * damage.knockback;
+    }
+}
+
+// On your own the run's over as soon as you go down. In co-op you're only downed while someone's
+// still standing to help, and it's over once nobody is, with the camera having a last look round
+// whoever went down last.
+fn kill_player(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    mut state: ResMut<State<GameState>>,
+    mut cinematic: Option<ResMut<Cinematic>>,
+    players: Query<(Entity, &GlobalTransform, Option<&Downed>), With<Player>>,
+) {
+    let fallen = dealt
+        .iter()
+        .filter(|damage| damage.killed)
+        .filter_map(|damage| players.get(damage.target).ok())
+        .map(|(entity, transform, _)| (entity, transform.translation()))
+        .collect::<Vec<_>>();
+    let Some(&(_, last)) = fallen.last() else { return };
+    if *state.current() != GameState::Playing {
+        return;
+    }
+
+    let standing = players
+        .iter()
+        .filter(|(entity, _, downed)| {
+            downed.is_none() && !fallen.iter().any(|(fell, _)| fell == entity)
+        })
+        .count();
+    if standing > 0 {
+        for (entity, _) in fallen {
+            commands.entity(entity).insert(Downed::default());
+        }
+        return;
+    }
+    let shot = Some((Shot::Death, last));
+    cut_to_game_over(&mut state, cinematic.as_deref_mut(), shot);
+}
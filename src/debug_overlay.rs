@@ -0,0 +1,128 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::{pickups::Pickup, waves::WaveManager, Aim, Enemy, Game, Player, Projectile};
+
+// Keyboard only, as clicking the left stick sprints
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_startup_system(setup_debug_overlay)
+            .add_system(toggle_debug_overlay)
+            .add_system(update_debug_overlay.after(toggle_debug_overlay));
+    }
+}
+
+#[derive(Component)]
+struct DebugOverlay;
+
+#[derive(Component)]
+struct DebugOverlayText;
+
+fn setup_debug_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(10.),
+                        left: Val::Px(10.),
+                        ..default()
+                    },
+                    padding: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+            DebugOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 16.,
+                        color: Color::YELLOW,
+                    },
+                ),
+                DebugOverlayText,
+            ));
+        });
+}
+
+fn toggle_debug_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlays: Query<&mut Visibility, With<DebugOverlay>>,
+) {
+    if !keys.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+
+    for mut visibility in &mut overlays {
+        visibility.is_visible = !visibility.is_visible;
+    }
+}
+
+fn update_debug_overlay(
+    diagnostics: Res<Diagnostics>,
+    game: Res<Game>,
+    waves: Res<WaveManager>,
+    enemies: Query<(), With<Enemy>>,
+    projectiles: Query<(), With<Projectile>>,
+    pickups: Query<(), With<Pickup>>,
+    players: Query<&Aim, With<Player>>,
+    transforms: Query<&GlobalTransform>,
+    overlays: Query<&Visibility, With<DebugOverlay>>,
+    mut texts: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    let Ok(visibility) = overlays.get_single() else { return };
+    if !visibility.is_visible {
+        return;
+    }
+    let Ok(mut text) = texts.get_single_mut() else { return };
+
+    let smoothed = |name| {
+        diagnostics
+            .get(name)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+    let camera_z = transforms
+        .get(game.camera)
+        .map(|camera| camera.translation().z)
+        .unwrap_or_default();
+    let target = players
+        .get(game.player)
+        .ok()
+        .and_then(|aim| aim.target)
+        .map(|target| format!("{target:?}"))
+        .unwrap_or_else(|| "none".into());
+
+    text.sections[0].value = format!(
+        "fps         {:.0}\n\
+         frame time  {:.2}ms\n\
+         enemies     {}\n\
+         projectiles {}\n\
+         pickups     {}\n\
+         wave        {} ({:?})\n\
+         camera z    {camera_z:.2}\n\
+         target      {target}",
+        smoothed(FrameTimeDiagnosticsPlugin::FPS),
+        smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME) * 1000.,
+        enemies.iter().count(),
+        projectiles.iter().count(),
+        pickups.iter().count(),
+        waves.wave,
+        waves.phase,
+    );
+}
@@ -0,0 +1,173 @@
+use bevy::{
+    pbr::{
+        wireframe::{WireframeConfig, WireframePlugin},
+        NotShadowCaster,
+    },
+    prelude::*,
+};
+
+use crate::{Aim, Enemy, Game, Player, HIT_THRESHOLD};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+const TOGGLE_BUTTON: GamepadButtonType = GamepadButtonType::Select;
+
+pub struct DebugRenderPlugin;
+
+impl Plugin for DebugRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(WireframePlugin)
+            .init_resource::<DebugRender>()
+            .add_startup_system(setup_debug_assets)
+            .add_system(toggle_debug_render)
+            .add_system(attach_hit_radius_gizmos)
+            .add_system(show_gizmos.after(toggle_debug_render))
+            .add_system(update_aim_ray.after(toggle_debug_render));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugRender {
+    pub enabled: bool,
+}
+
+#[derive(Resource)]
+struct DebugAssets {
+    sphere: Handle<Mesh>,
+    cube: Handle<Mesh>,
+    hit_radius: Handle<StandardMaterial>,
+    aim_ray: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct Gizmo;
+
+#[derive(Component)]
+struct AimRay;
+
+fn setup_debug_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let assets = DebugAssets {
+        sphere: meshes.add(Mesh::from(shape::UVSphere {
+            radius: HIT_THRESHOLD,
+            ..default()
+        })),
+        cube: meshes.add(Mesh::from(shape::Cube { size: 1. })),
+        hit_radius: materials.add(StandardMaterial {
+            base_color: Color::rgba(1., 0.2, 0.2, 0.4),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        aim_ray: materials.add(StandardMaterial {
+            base_color: Color::YELLOW,
+            unlit: true,
+            ..default()
+        }),
+    };
+
+    commands.spawn((
+        PbrBundle {
+            mesh: assets.cube.clone(),
+            material: assets.aim_ray.clone(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        AimRay,
+        Gizmo,
+        NotShadowCaster,
+    ));
+    commands.insert_resource(assets);
+}
+
+fn toggle_debug_render(
+    mut debug_render: ResMut<DebugRender>,
+    mut wireframe: ResMut<WireframeConfig>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let pressed = keys.just_pressed(TOGGLE_KEY)
+        || gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, TOGGLE_BUTTON)));
+    if !pressed {
+        return;
+    }
+
+    debug_render.enabled = !debug_render.enabled;
+    wireframe.global = debug_render.enabled;
+}
+
+fn attach_hit_radius_gizmos(
+    mut commands: Commands,
+    enemies: Query<Entity, Added<Enemy>>,
+    debug_render: Res<DebugRender>,
+    assets: Res<DebugAssets>,
+) {
+    for enemy in &enemies {
+        commands.entity(enemy).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: assets.sphere.clone(),
+                    material: assets.hit_radius.clone(),
+                    visibility: Visibility {
+                        is_visible: debug_render.enabled,
+                    },
+                    ..default()
+                },
+                Gizmo,
+                NotShadowCaster,
+            ));
+        });
+    }
+}
+
+fn show_gizmos(
+    debug_render: Res<DebugRender>,
+    mut gizmos: Query<&mut Visibility, (With<Gizmo>, Without<AimRay>)>,
+) {
+    if !debug_render.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut gizmos {
+        visibility.is_visible = debug_render.enabled;
+    }
+}
+
+// A thin bar stretched from player one's spud gun to whatever it's locked on to
+fn update_aim_ray(
+    debug_render: Res<DebugRender>,
+    game: Res<Game>,
+    players: Query<(&Player, &Aim)>,
+    global_transforms: Query<&GlobalTransform, Without<AimRay>>,
+    mut rays: Query<(&mut Transform, &mut Visibility), With<AimRay>>,
+) {
+    let Ok((mut transform, mut visibility)) = rays.get_single_mut() else { return };
+
+    let ends = players.get(game.player).ok().and_then(|(player, aim)| {
+        let origin = global_transforms.get(player.weapon).ok()?.translation();
+        let target = aim.target?;
+        let target = global_transforms.get(target).ok()?.translation();
+        Some((origin, target))
+    });
+
+    let Some((origin, target)) = ends.filter(|_| debug_render.enabled) else {
+        visibility.is_visible = false;
+        return;
+    };
+
+    let length = origin.distance(target);
+    if length <= f32::EPSILON {
+        visibility.is_visible = false;
+        return;
+    }
+
+    visibility.is_visible = true;
+    *transform = Transform::from_translation((origin + target) / 2.)
+        .looking_at(target, Vec3::Y)
+        .with_scale(Vec3::new(0.01, 0.01, length));
+}
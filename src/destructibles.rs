@@ -0,0 +1,125 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+
+use crate::{
+    damage::DamageDealt,
+    pickups::{spawn_pickup, PickupAssets, PickupKind},
+    reset::DespawnOnReset,
+    state::GameState,
+    GameRng,
+};
+
+const DEBRIS_PIECES: usize = 6;
+const DEBRIS_SECONDS: f32 = 1.2;
+const DEBRIS_SPEED: f32 = 2.;
+const GRAVITY: f32 = 9.8;
+// Something for the trouble of shooting the scenery
+const COIN_DROP_CHANCE: f64 = 0.3;
+
+pub struct DestructiblePlugin;
+
+impl Plugin for DestructiblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_debris_assets).add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(shatter_breakables)
+                .with_system(fly_debris),
+        );
+    }
+}
+
+// A prop that goes to pieces once its `Health` runs out
+#[derive(Component)]
+pub struct Breakable;
+
+#[derive(Component)]
+struct Debris {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+struct DebrisAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_debris_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DebrisAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 0.06 })),
+        material: materials.add(Color::rgb(0.45, 0.6, 0.2).into()),
+    });
+}
+
+fn shatter_breakables(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    mut rng: ResMut<GameRng>,
+    breakables: Query<&GlobalTransform, With<Breakable>>,
+    debris_assets: Res<DebrisAssets>,
+    pickup_assets: Res<PickupAssets>,
+) {
+    for damage in dealt.iter().filter(|damage| damage.killed) {
+        let Ok(transform) = breakables.get(damage.target) else { continue };
+        let position = transform.translation();
+        commands.entity(damage.target).despawn_recursive();
+
+        for _ in 0..DEBRIS_PIECES {
+            // Mostly up and away from the shot
+            let scatter = Vec3::new(
+                rng.0.gen_range(-1.0..1.0),
+                rng.0.gen_range(0.5..1.5),
+                rng.0.gen_range(-1.0..1.0),
+            );
+            let velocity = (scatter + damage.direction).normalize_or_zero() * DEBRIS_SPEED;
+            commands.spawn((
+                PbrBundle {
+                    mesh: debris_assets.mesh.clone(),
+                    material: debris_assets.material.clone(),
+                    transform: Transform::from_translation(position + Vec3::Y * 0.2),
+                    ..default()
+                },
+                Debris {
+                    velocity,
+                    lifetime: Timer::from_seconds(DEBRIS_SECONDS, TimerMode::Once),
+                },
+                NotShadowCaster,
+                DespawnOnReset,
+            ));
+        }
+
+        if rng.0.gen_bool(COIN_DROP_CHANCE) {
+            spawn_pickup(&mut commands, &pickup_assets, PickupKind::Coin, position);
+        }
+    }
+}
+
+fn fly_debris(
+    mut commands: Commands,
+    mut debris: Query<(Entity, &mut Transform, &mut Debris)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut transform, mut piece) in &mut debris {
+        if piece.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        piece.velocity.y -= GRAVITY * dt;
+        transform.translation += piece.velocity * dt;
+        // Bounce along the ground rather than sinking through it
+        if transform.translation.y < 0. {
+            transform.translation.y = 0.;
+            piece.velocity.y = -piece.velocity.y * 0.4;
+            piece.velocity.x *= 0.6;
+            piece.velocity.z *= 0.6;
+        }
+        transform.rotate_x(5. * dt);
+        transform.scale = Vec3::splat(piece.lifetime.percent_left());
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    damage::{DamageAppExt, DamageContext, DamageStage},
+    Player,
+};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_damage_modifier(DamageStage::Base, scale_damage_taken);
+    }
+}
+
+// Picked on the main menu. Normal is the balance the constants were tuned for, the others scale
+// it rather than needing a second set of numbers.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+// Multipliers on the Normal values
+#[derive(Clone, Copy, Debug)]
+pub struct DifficultyScaling {
+    // Time between enemy spawns
+    pub spawn_interval: f32,
+    // The breather between waves
+    pub intermission: f32,
+    pub enemy_speed: f32,
+    pub enemy_health: f32,
+    // Anything hurting a player
+    pub damage_taken: f32,
+    // How far assisted play reaches for something to lock on to
+    pub assist_range: f32,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn scaling(self) -> DifficultyScaling {
+        match self {
+            Difficulty::Easy => DifficultyScaling {
+                spawn_interval: 1.5,
+                intermission: 1.5,
+                enemy_speed: 0.75,
+                enemy_health: 0.75,
+                damage_taken: 0.5,
+                assist_range: 1.3,
+            },
+            Difficulty::Normal => DifficultyScaling {
+                spawn_interval: 1.,
+                intermission: 1.,
+                enemy_speed: 1.,
+                enemy_health: 1.,
+                damage_taken: 1.,
+                assist_range: 1.,
+            },
+            Difficulty::Hard => DifficultyScaling {
+                spawn_interval: 0.7,
+                intermission: 0.6,
+                enemy_speed: 1.3,
+                enemy_health: 1.5,
+                damage_taken: 1.5,
+                assist_range: 0.8,
+            },
+        }
+    }
+
+    // `direction` is -1 or 1, and it stops at either end
+    pub fn step(self, direction: i32) -> Self {
+        let current = Self::ALL.iter().position(|d| *d == self).unwrap_or_default() as i32;
+        let next = (current + direction).clamp(0, Self::ALL.len() as i32 - 1);
+        Self::ALL[next as usize]
+    }
+}
+
+fn scale_damage_taken(context: &mut DamageContext, world: &World) {
+    if world.get::<Player>(context.target).is_some() {
+        context.amount *= world.resource::<Difficulty>().scaling().damage_taken;
+    }
+}
@@ -0,0 +1,115 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    damage::{DamageAppExt, DamageContext, DamageStage},
+    input::PlayerInput,
+    state::GameState,
+};
+
+const DODGE_DISTANCE: f32 = 1.2;
+const DODGE_SECONDS: f32 = 0.25;
+// Counted from the start of the roll, so it runs a touch past the end of it
+const INVULNERABLE_SECONDS: f32 = 0.35;
+const DODGE_COOLDOWN_SECONDS: f32 = 1.;
+// How far the carrot leans into the roll at its peak, in radians
+const DODGE_TILT: f32 = 0.7;
+
+pub struct DodgePlugin;
+
+impl Plugin for DodgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_damage_modifier(DamageStage::Clamp, ignore_damage_while_dodging)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(start_dodges)
+                    .with_system(roll.after(start_dodges)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_dodges));
+    }
+}
+
+// Every player has one. The timers count down to zero and stay there.
+#[derive(Component, Default)]
+pub struct Dodge {
+    direction: Vec3,
+    rolling: f32,
+    invulnerable: f32,
+    cooldown: f32,
+    // What the carrot goes back to once it's done leaning
+    upright: Quat,
+}
+
+impl Dodge {
+    pub fn is_rolling(&self) -> bool {
+        self.rolling > 0.
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.
+    }
+}
+
+// Dodges go whichever way the player's moving, or straight ahead if they're standing still
+fn start_dodges(mut players: Query<(&PlayerInput, &Transform, &mut Dodge)>) {
+    for (input, transform, mut dodge) in &mut players {
+        if !input.dodge || dodge.is_rolling() || dodge.cooldown > 0. {
+            continue;
+        }
+
+        let direction = Vec3::new(input.movement.x, 0., -input.movement.y);
+        dodge.direction = if direction.length_squared() > 0. {
+            direction.normalize()
+        } else {
+            Vec3::NEG_Z
+        };
+        dodge.rolling = DODGE_SECONDS;
+        dodge.invulnerable = INVULNERABLE_SECONDS;
+        dodge.cooldown = DODGE_COOLDOWN_SECONDS;
+        dodge.upright = transform.rotation;
+    }
+}
+
+fn roll(time: Res<Time>, mut players: Query<(&mut Transform, &mut Dodge)>) {
+    let delta = time.delta_seconds();
+    for (mut transform, mut dodge) in &mut players {
+        dodge.invulnerable = (dodge.invulnerable - delta).max(0.);
+        dodge.cooldown = (dodge.cooldown - delta).max(0.);
+        if !dodge.is_rolling() {
+            continue;
+        }
+
+        let step = delta.min(dodge.rolling);
+        dodge.rolling -= step;
+        transform.translation += dodge.direction * DODGE_DISTANCE * step / DODGE_SECONDS;
+
+        // Lean into the roll and back out of it
+        let progress = 1. - dodge.rolling / DODGE_SECONDS;
+        let axis = Vec3::Y.cross(dodge.direction).normalize_or_zero();
+        let lean = Quat::from_axis_angle(axis, (progress * PI).sin() * DODGE_TILT);
+        transform.rotation = if dodge.is_rolling() {
+            lean * dodge.upright
+        } else {
+            dodge.upright
+        };
+    }
+}
+
+fn ignore_damage_while_dodging(context: &mut DamageContext, world: &World) {
+    if world
+        .get::<Dodge>(context.target)
+        .map_or(false, Dodge::is_invulnerable)
+    {
+        context.amount = 0.;
+    }
+}
+
+fn reset_dodges(mut players: Query<(&mut Transform, &mut Dodge)>) {
+    for (mut transform, mut dodge) in &mut players {
+        if dodge.is_rolling() {
+            transform.rotation = dodge.upright;
+        }
+        *dodge = Dodge::default();
+    }
+}
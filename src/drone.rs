@@ -0,0 +1,237 @@
+use bevy::{pbr::NotShadowCaster, prelude::*, utils::HashSet};
+
+use crate::{
+    damage::Health,
+    input::PlayerInput,
+    player_aim,
+    reset::DespawnOnReset,
+    settings::{DroneMode, Settings},
+    state::GameState,
+    Aim, Enemy, Player,
+};
+
+// Where it hangs about, from whoever it belongs to
+const HOVER_OFFSET: Vec3 = Vec3::new(0.35, 0.6, 0.15);
+const BOB_HEIGHT: f32 = 0.05;
+// Radians per second
+const BOB_SPEED: f32 = 3.;
+// How quickly it catches up with its owner, per second
+const FOLLOW_RATE: f32 = 6.;
+const SCAN_SECONDS: f32 = 0.5;
+// It only bothers with beets about this close to its owner
+const SCAN_RANGE: f32 = 6.;
+// Over the marked beet's head
+const MARK_HEIGHT: f32 = 0.7;
+const MARK_SPIN: f32 = 2.;
+
+pub struct DronePlugin;
+
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_drone_assets).add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(launch_drones)
+                .with_system(follow_owners.after(launch_drones))
+                .with_system(scan_for_targets.after(launch_drones))
+                .with_system(show_marks.after(scan_for_targets))
+                .with_system(snap_to_mark.after(scan_for_targets).after(player_aim)),
+        );
+    }
+}
+
+// Hovers by its owner and picks out the beet it reckons is the biggest threat, for them to lock on
+// to with a press of a button rather than flicking along the line to get there
+#[derive(Component)]
+struct Drone {
+    owner: Entity,
+    marked: Option<Entity>,
+    // Floats over whatever's marked
+    mark: Entity,
+    scan: Timer,
+}
+
+#[derive(Component)]
+struct DroneMark;
+
+#[derive(Resource)]
+struct DroneAssets {
+    drone_mesh: Handle<Mesh>,
+    drone_material: Handle<StandardMaterial>,
+    mark_mesh: Handle<Mesh>,
+    mark_material: Handle<StandardMaterial>,
+}
+
+fn setup_drone_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DroneAssets {
+        drone_mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.06,
+            ..default()
+        })),
+        drone_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.3, 0.35, 0.4),
+            emissive: Color::rgb(0.2, 0.6, 1.0),
+            ..default()
+        }),
+        mark_mesh: meshes.add(Mesh::from(shape::Torus {
+            radius: 0.12,
+            ring_radius: 0.02,
+            ..default()
+        })),
+        mark_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.2, 0.6, 1.0),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+// One for every player while the setting's on, and none for anyone when it's off. They all go
+// when the run ends, and are launched again for whoever's playing the next one.
+fn launch_drones(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    drone_assets: Res<DroneAssets>,
+    players: Query<(Entity, &GlobalTransform), With<Player>>,
+    drones: Query<(Entity, &Drone)>,
+) {
+    let enabled = settings.drone != DroneMode::Off;
+    let mut owners = HashSet::new();
+    for (entity, drone) in &drones {
+        if enabled && players.contains(drone.owner) {
+            owners.insert(drone.owner);
+            continue;
+        }
+        commands.entity(drone.mark).despawn_recursive();
+        commands.entity(entity).despawn_recursive();
+    }
+    if !enabled {
+        return;
+    }
+
+    for (owner, transform) in &players {
+        if owners.contains(&owner) {
+            continue;
+        }
+        let mark = commands
+            .spawn((
+                PbrBundle {
+                    mesh: drone_assets.mark_mesh.clone(),
+                    material: drone_assets.mark_material.clone(),
+                    visibility: Visibility::INVISIBLE,
+                    ..default()
+                },
+                DroneMark,
+                NotShadowCaster,
+                DespawnOnReset,
+            ))
+            .id();
+        commands.spawn((
+            PbrBundle {
+                mesh: drone_assets.drone_mesh.clone(),
+                material: drone_assets.drone_material.clone(),
+                transform: Transform::from_translation(transform.translation() + HOVER_OFFSET),
+                ..default()
+            },
+            Drone {
+                owner,
+                marked: None,
+                mark,
+                scan: Timer::from_seconds(SCAN_SECONDS, TimerMode::Repeating),
+            },
+            DespawnOnReset,
+        ));
+    }
+}
+
+fn follow_owners(
+    time: Res<Time>,
+    owners: Query<&GlobalTransform, With<Player>>,
+    mut drones: Query<(&Drone, &mut Transform)>,
+) {
+    let bob = (time.elapsed_seconds() * BOB_SPEED).sin() * BOB_HEIGHT;
+    let catch_up = (time.delta_seconds() * FOLLOW_RATE).min(1.);
+    for (drone, mut transform) in &mut drones {
+        let Ok(owner) = owners.get(drone.owner) else { continue };
+        let hover = owner.translation() + HOVER_OFFSET + Vec3::Y * bob;
+        transform.translation = transform.translation.lerp(hover, catch_up);
+    }
+}
+
+// Every so often rather than every frame, so the mark doesn't flicker between two beets that are
+// neck and neck
+fn scan_for_targets(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    owners: Query<&GlobalTransform, With<Player>>,
+    enemies: Query<(Entity, &GlobalTransform, &Health), With<Enemy>>,
+    mut drones: Query<&mut Drone>,
+) {
+    for mut drone in &mut drones {
+        // Whatever it had marked might not be around any more
+        if drone.marked.map_or(false, |marked| !enemies.contains(marked)) {
+            drone.marked = None;
+        }
+        if !drone.scan.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let Ok(owner) = owners.get(drone.owner) else { continue };
+        let origin = owner.translation();
+
+        let in_range = enemies.iter().filter_map(|(entity, transform, health)| {
+            let distance = transform.translation().distance(origin);
+            (distance <= SCAN_RANGE).then_some((entity, distance, health.current))
+        });
+        let marked = match settings.drone {
+            DroneMode::Off => None,
+            DroneMode::Nearest => in_range
+                .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+                .map(|(entity, ..)| entity),
+            // The closer of two that are as tough as each other
+            DroneMode::Toughest => in_range
+                .max_by(|(_, a_distance, a), (_, b_distance, b)| {
+                    a.total_cmp(b).then(b_distance.total_cmp(a_distance))
+                })
+                .map(|(entity, ..)| entity),
+        };
+        if drone.marked != marked {
+            drone.marked = marked;
+        }
+    }
+}
+
+fn show_marks(
+    time: Res<Time>,
+    drones: Query<&Drone>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    mut marks: Query<(&mut Transform, &mut Visibility), With<DroneMark>>,
+) {
+    let spin = Quat::from_rotation_y(time.elapsed_seconds() * MARK_SPIN);
+    for drone in &drones {
+        let Ok((mut transform, mut visibility)) = marks.get_mut(drone.mark) else { continue };
+        let target = drone.marked.and_then(|marked| enemies.get(marked).ok());
+        visibility.is_visible = target.is_some();
+        if let Some(target) = target {
+            transform.translation = target.translation() + Vec3::Y * MARK_HEIGHT;
+            transform.rotation = spin;
+        }
+    }
+}
+
+// After the usual aiming, so the snap isn't undone the same frame. Aiming at the nearest beet
+// picks its own target every frame regardless.
+fn snap_to_mark(
+    drones: Query<&Drone>,
+    mut players: Query<(&PlayerInput, &mut Aim), With<Player>>,
+) {
+    for drone in &drones {
+        let Some(marked) = drone.marked else { continue };
+        let Ok((input, mut aim)) = players.get_mut(drone.owner) else { continue };
+        if input.snap && aim.target != Some(marked) {
+            aim.target = Some(marked);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::{
+    campaign::ActiveLevel,
+    input::InputSource,
+    netplay::Netplay,
+    score::Score,
+    shop::Upgrades,
+    state::GameState,
+};
+
+// A free upgrade for every this many kills
+const MILESTONE_KILLS: u32 = 25;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Milestones>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(reward_milestones),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_milestones));
+    }
+}
+
+#[derive(Resource)]
+pub struct Milestones {
+    // The kill count the next reward's waiting on
+    pub next: u32,
+}
+
+impl Default for Milestones {
+    fn default() -> Self {
+        Self {
+            next: MILESTONE_KILLS,
+        }
+    }
+}
+
+// Each milestone owes a free pick, spent in the shop. It opens straight away if there's someone at
+// the keyboard to choose. Recordings and online games never open the shop, so theirs go unspent.
+fn reward_milestones(
+    mut milestones: ResMut<Milestones>,
+    mut upgrades: ResMut<Upgrades>,
+    score: Res<Score>,
+    level: Res<ActiveLevel>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !level.is_endless() || score.kills < milestones.next {
+        return;
+    }
+
+    while score.kills >= milestones.next {
+        milestones.next += MILESTONE_KILLS;
+        upgrades.free_picks += 1;
+    }
+    info!("{} kills, that's a free upgrade", score.kills);
+
+    if !matches!(*source, InputSource::Live) || netplay.is_some() {
+        return;
+    }
+    if let Err(e) = state.push(GameState::Shop) {
+        warn!("Unable to open the shop for a free pick: {e:?}");
+    }
+}
+
+fn reset_milestones(mut milestones: ResMut<Milestones>) {
+    *milestones = Milestones::default();
+}
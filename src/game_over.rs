@@ -0,0 +1,341 @@
+use bevy::prelude::*;
+
+use crate::{
+    campaign::ActiveLevel,
+    input::PlayerInput,
+    level_gen::LevelSeed,
+    localization::Localization,
+    navigation::{Focused, MenuNavigation, BUTTON_COLOR},
+    objective::{Objective, ObjectiveStatus},
+    results::LevelResults,
+    score::Score,
+    state::GameState,
+    stats::RunStats,
+    suspend::Suspending,
+    waves::WaveManager,
+    Enemy,
+};
+
+// This many beets at once and the carrot is considered overrun
+const OVERRUN_ENEMIES: usize = 25;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(check_overrun))
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver).with_system(spawn_game_over_ui),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(game_over_buttons)
+                    .with_system(game_over_bindings),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(despawn_game_over_ui),
+            );
+    }
+}
+
+#[derive(Component)]
+struct GameOverUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum GameOverButton {
+    // Only after winning a campaign level that isn't the last
+    NextLevel,
+    Retry,
+    MainMenu,
+}
+
+impl GameOverButton {
+    // Top to bottom
+    const ALL: [GameOverButton; 3] = [
+        GameOverButton::NextLevel,
+        GameOverButton::Retry,
+        GameOverButton::MainMenu,
+    ];
+
+    // Into the localization
+    fn label(&self) -> &'static str {
+        match self {
+            GameOverButton::NextLevel => "results-next-level",
+            GameOverButton::Retry => "game-over-retry",
+            GameOverButton::MainMenu => "game-over-main-menu",
+        }
+    }
+
+    fn target(&self) -> GameState {
+        match self {
+            GameOverButton::NextLevel | GameOverButton::Retry => GameState::Playing,
+            GameOverButton::MainMenu => GameState::MainMenu,
+        }
+    }
+}
+
+fn check_overrun(enemies: Query<(), With<Enemy>>, mut state: ResMut<State<GameState>>) {
+    if enemies.iter().count() < OVERRUN_ENEMIES {
+        return;
+    }
+    if let Err(e) = state.set(GameState::GameOver) {
+        warn!("Unable to end the run: {e:?}");
+    }
+}
+
+fn spawn_game_over_ui(
+    mut commands: Commands,
+    score: Res<Score>,
+    waves: Res<WaveManager>,
+    seed: Res<LevelSeed>,
+    objective: Res<Objective>,
+    stats: Res<RunStats>,
+    results: Res<LevelResults>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+    suspending: Res<Suspending>,
+) {
+    // Straight back to the main menu, with nothing to show for it yet
+    if suspending.0 {
+        return;
+    }
+
+    let font = asset_server.load("FiraSans-Bold.ttf");
+    let text_style = |font_size| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    let panel_style = TextStyle {
+        font: asset_server.load("FiraMono-Medium.ttf"),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+    let next_level = results
+        .0
+        .as_ref()
+        .map_or(false, |result| result.next_level.is_some());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.4).into(),
+                ..default()
+            },
+            GameOverUi,
+        ))
+        .with_children(|parent| {
+            // A campaign level that's been won gets its results instead of the usual summary
+            if let Some(result) = &results.0 {
+                parent.spawn(TextBundle::from_section(
+                    localization.get("results-title"),
+                    text_style(70.),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    result.panel(&localization),
+                    panel_style,
+                ));
+            } else {
+                let title = match objective.status {
+                    ObjectiveStatus::Complete => "game-over-objective-complete",
+                    ObjectiveStatus::Failed => "game-over-objective-failed",
+                    ObjectiveStatus::InProgress => "game-over-title",
+                };
+                parent.spawn(TextBundle::from_section(
+                    localization.get(title),
+                    text_style(70.),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    localization.format(
+                        "game-over-summary",
+                        &[
+                            ("score", &score.points),
+                            ("kills", &score.kills),
+                            ("wave", &waves.wave),
+                        ],
+                    ),
+                    text_style(30.),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    stats_panel(&stats, &localization),
+                    panel_style,
+                ));
+            }
+            // Enough to play the same run again with --seed
+            parent.spawn(TextBundle::from_section(
+                localization.format("game-over-seed", &[("seed", &seed.0)]),
+                text_style(20.),
+            ));
+
+            // Confirm goes on to the next level if there is one, and retries otherwise, unless the
+            // focus is moved
+            let first = if next_level {
+                GameOverButton::NextLevel
+            } else {
+                GameOverButton::Retry
+            };
+            let shown = GameOverButton::ALL
+                .into_iter()
+                .filter(|button| *button != GameOverButton::NextLevel || next_level);
+            for button in shown {
+                let mut entity = parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(320.), Val::Px(50.)),
+                            margin: UiRect::all(Val::Px(8.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    button,
+                ));
+                if button == first {
+                    entity.insert(Focused);
+                }
+                // The usual label has confirm retrying, which it doesn't here
+                let label = match button {
+                    GameOverButton::Retry if next_level => "results-retry",
+                    _ => button.label(),
+                };
+                entity.with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        localization.get(label),
+                        text_style(24.),
+                    ));
+                });
+            }
+        });
+}
+
+fn stats_panel(stats: &RunStats, localization: &Localization) -> String {
+    let seconds = stats.time_survived as u32;
+    let mut rows = vec![
+        ("stats-time", format!("{}:{:02}", seconds / 60, seconds % 60)),
+        ("stats-shots", stats.shots_fired.to_string()),
+        ("stats-hits", stats.hits.to_string()),
+        ("stats-accuracy", format!("{:.0}%", stats.accuracy() * 100.)),
+        ("stats-damage-dealt", format!("{:.1}", stats.damage_dealt)),
+        ("stats-damage-taken", format!("{:.1}", stats.damage_taken)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (localization.get(key).to_string(), value))
+    .collect::<Vec<_>>();
+
+    let mut kills = stats.kills.iter().collect::<Vec<_>>();
+    kills.sort_by_key(|(kind, _)| format!("{kind:?}"));
+    for (kind, count) in kills {
+        let enemy = localization.variant("enemy", kind);
+        rows.push((
+            localization.format("stats-kills", &[("enemy", &enemy)]),
+            count.to_string(),
+        ));
+    }
+
+    rows.iter()
+        .map(|(label, value)| format!("{label:<20}{value:>8}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn despawn_game_over_ui(mut commands: Commands, ui: Query<Entity, With<GameOverUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn game_over_buttons(
+    buttons: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    results: Res<LevelResults>,
+    mut active: ResMut<ActiveLevel>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Clicked {
+            press(*button, &results, &mut active, &mut state);
+        }
+    }
+}
+
+// Up and down move between the buttons, confirm presses the one with focus and back always goes
+// to the main menu
+fn game_over_bindings(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    results: Res<LevelResults>,
+    mut active: ResMut<ActiveLevel>,
+    mut state: ResMut<State<GameState>>,
+    buttons: Query<(Entity, &GameOverButton, Option<&Focused>)>,
+) {
+    let focused = buttons
+        .iter()
+        .find(|(_, _, focused)| focused.is_some())
+        .map_or(GameOverButton::Retry, |(_, button, _)| *button);
+    if input.confirm {
+        press(focused, &results, &mut active, &mut state);
+        return;
+    }
+    if input.back {
+        leave_game_over(&mut state, GameOverButton::MainMenu.target());
+        return;
+    }
+
+    if navigation.step.y == 0 {
+        return;
+    }
+    let shown = GameOverButton::ALL
+        .into_iter()
+        .filter(|button| buttons.iter().any(|(_, shown, _)| shown == button))
+        .collect::<Vec<_>>();
+    // Nothing's shown on the way out to save and quit
+    if shown.is_empty() {
+        return;
+    }
+    let row = shown
+        .iter()
+        .position(|button| *button == focused)
+        .unwrap_or(0);
+    let next = shown[navigation.row(row, shown.len())];
+    for (entity, button, _) in &buttons {
+        if *button == next {
+            commands.entity(entity).insert(Focused);
+        } else {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+}
+
+fn press(
+    button: GameOverButton,
+    results: &LevelResults,
+    active: &mut ActiveLevel,
+    state: &mut State<GameState>,
+) {
+    if button == GameOverButton::NextLevel {
+        let next_level = results
+            .0
+            .as_ref()
+            .and_then(|result| result.next_level.clone());
+        let Some(next_level) = next_level else { return };
+        active.0 = Some(next_level);
+    }
+    leave_game_over(state, button.target());
+}
+
+// Everything that belongs to the run cleans up after itself on the way out of this state
+fn leave_game_over(state: &mut State<GameState>, target: GameState) {
+    if let Err(e) = state.set(target) {
+        warn!("Unable to leave the game over screen: {e:?}");
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{ActiveLevel, CampaignProgress},
+    characters::dress_players,
+    damage::Health,
+    input::{InputSource, PlayerInput},
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    netplay::Netplay,
+    pickups::Currency,
+    state::GameState,
+    suspend::Suspending,
+    turret::TurretCharges,
+    Player,
+};
+
+// On top of whatever the hero starts with
+const GREENHOUSE_HEALTH: f32 = 1.;
+
+pub struct GardenPlugin;
+
+impl Plugin for GardenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GardenMenu>()
+            .add_system(open_garden)
+            .add_system_set(SystemSet::on_enter(GameState::Garden).with_system(spawn_garden))
+            .add_system_set(
+                SystemSet::on_update(GameState::Garden)
+                    .with_system(navigate_garden)
+                    .with_system(update_garden_text.after(navigate_garden)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Garden).with_system(despawn_garden))
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(bank_coins))
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(tend_garden.after(dress_players)),
+            );
+    }
+}
+
+// Built once, with coins saved up from the campaign, and there for every level after
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Structure {
+    // An extra turret to plant, on top of the usual
+    Scarecrow,
+    // More health for everyone
+    Greenhouse,
+}
+
+impl Structure {
+    pub const ALL: [Structure; 2] = [Structure::Scarecrow, Structure::Greenhouse];
+
+    // In coins
+    pub fn cost(&self) -> u32 {
+        match self {
+            Structure::Scarecrow => 30,
+            Structure::Greenhouse => 50,
+        }
+    }
+}
+
+// What's left in the kitty at the end of every campaign level goes in here, win or lose, and
+// what's been built with it. Kept with the rest of the campaign progress.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Garden {
+    pub savings: u32,
+    pub built: Vec<Structure>,
+}
+
+impl Garden {
+    pub fn has(&self, structure: Structure) -> bool {
+        self.built.contains(&structure)
+    }
+}
+
+#[derive(Resource, Default)]
+struct GardenMenu {
+    row: usize,
+}
+
+#[derive(Component)]
+struct GardenUi;
+
+#[derive(Component)]
+struct GardenText;
+
+// Inspect on the level select
+fn open_garden(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.inspect && *state.current() == GameState::LevelSelect {
+        if let Err(e) = state.push(GameState::Garden) {
+            warn!("Unable to open the garden: {e:?}");
+        }
+    }
+}
+
+fn spawn_garden(
+    mut commands: Commands,
+    mut menu: ResMut<GardenMenu>,
+    asset_server: Res<AssetServer>,
+) {
+    menu.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            GardenUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                GardenText,
+            ));
+        });
+}
+
+// Up and down pick a structure, confirm builds it if there's enough saved, back goes back to the
+// level select
+fn navigate_garden(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut menu: ResMut<GardenMenu>,
+    mut progress: ResMut<CampaignProgress>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the garden: {e:?}");
+        }
+        return;
+    }
+
+    if input.confirm {
+        let structure = Structure::ALL[menu.row];
+        let garden = &mut progress.garden;
+        if garden.has(structure) || garden.savings < structure.cost() {
+            return;
+        }
+        garden.savings -= structure.cost();
+        garden.built.push(structure);
+        progress.save();
+        return;
+    }
+
+    let count = Structure::ALL.len();
+    menu.row = navigation.row(menu.row, count);
+}
+
+fn update_garden_text(
+    menu: Res<GardenMenu>,
+    progress: Res<CampaignProgress>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<GardenText>>,
+) {
+    let garden = &progress.garden;
+    let savings = localization.format("garden-savings", &[("coins", &garden.savings)]);
+    let mut panel = format!("{}{savings:>40}\n\n", localization.get("garden-title"));
+    for (index, structure) in Structure::ALL.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        let cost = if garden.has(*structure) {
+            localization.get("garden-built").to_string()
+        } else {
+            structure.cost().to_string()
+        };
+        panel += &format!(
+            "{cursor} {:<18}{cost:>8}   {}\n",
+            localization.variant("structure", structure),
+            localization.variant("structure-about", structure),
+        );
+    }
+    panel += &format!("\n{}", localization.get("garden-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn despawn_garden(mut commands: Commands, ui: Query<Entity, With<GardenUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Only campaign levels played here, on this machine, and actually finished rather than put down
+// to carry on later
+fn bank_coins(
+    level: Res<ActiveLevel>,
+    currency: Res<Currency>,
+    suspending: Res<Suspending>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut progress: ResMut<CampaignProgress>,
+) {
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    if level.is_endless() || suspending.0 || !live || currency.0 == 0 {
+        return;
+    }
+    progress.garden.savings += currency.0;
+    progress.save();
+}
+
+// Every campaign level starts with whatever's been built. The endless lane doesn't, so the
+// leaderboard stays fair.
+fn tend_garden(
+    level: Res<ActiveLevel>,
+    progress: Res<CampaignProgress>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut players: Query<(&mut Health, &mut TurretCharges), With<Player>>,
+) {
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    if level.is_endless() || !live {
+        return;
+    }
+    let garden = &progress.garden;
+    for (mut health, mut charges) in &mut players {
+        if garden.has(Structure::Scarecrow) {
+            charges.0 += 1;
+        }
+        if garden.has(Structure::Greenhouse) {
+            health.max += GREENHOUSE_HEALTH;
+            health.current += GREENHOUSE_HEALTH;
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::RenderAssets,
+        renderer::{RenderDevice, RenderQueue},
+        RenderApp, RenderStage,
+    },
+    time::TimeSystem,
+    utils::Instant,
+};
+use serde::Deserialize;
+
+use crate::{
+    input::InputSource,
+    level_gen::LevelSeed,
+    screenshot::{capture_target, read_back},
+    settings::Settings,
+    GameRng,
+};
+
+const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// A golden test lives in its own directory:
+//  - scenario.ron describes the run (see `GoldenScenario`)
+//  - the input recording it names is played back instead of the gamepad
+//  - frame_<tick>.png are the reference images. A missing one fails the run, so a checkout that's
+//    lost them can't pass by comparing against itself. `--bless` writes them all out from the
+//    current run instead, for new scenarios and deliberate changes to how things look.
+#[derive(Deserialize)]
+pub struct GoldenScenario {
+    pub seed: u64,
+    pub input: PathBuf,
+    pub captures: Vec<u32>,
+    pub tolerance: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl GoldenScenario {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join("scenario.ron");
+        let contents = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Unable to read golden scenario {path:?}: {e}"));
+        ron::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Golden scenario {path:?} is invalid: {e}"))
+    }
+}
+
+pub struct GoldenTestPlugin {
+    pub dir: PathBuf,
+    pub bless: bool,
+}
+
+impl Plugin for GoldenTestPlugin {
+    fn build(&self, app: &mut App) {
+        let scenario = GoldenScenario::load(&self.dir);
+        let report = GoldenReport::default();
+
+        let image = capture_target("golden_target", scenario.width, scenario.height);
+        let image = app
+            .world
+            .resource_mut::<Assets<Image>>()
+            .add(image);
+
+        app.insert_resource(InputSource::playback(&self.dir.join(&scenario.input)))
+            .insert_resource(LevelSeed(scenario.seed))
+            .insert_resource(GameRng::from_seed(scenario.seed))
+            // Whatever the person running the test has set up locally shouldn't change the result
+            .insert_resource(Settings::default())
+            .insert_resource(GoldenCapture {
+                image,
+                dir: self.dir.clone(),
+                tolerance: scenario.tolerance,
+                bless: self.bless,
+                pending: None,
+            })
+            .insert_resource(GoldenRun {
+                tick: 0,
+                captures: scenario.captures,
+            })
+            .insert_resource(report.clone())
+            .add_plugin(ExtractResourcePlugin::<GoldenCapture>::default())
+            .add_system_to_stage(CoreStage::First, fixed_frame_time.after(TimeSystem))
+            .add_system(render_to_golden_target)
+            .add_system_to_stage(CoreStage::Last, golden_tick);
+
+        app.sub_app_mut(RenderApp)
+            .insert_resource(report)
+            .add_system_to_stage(RenderStage::Cleanup, capture_frame);
+    }
+}
+
+#[derive(Resource, Clone)]
+struct GoldenCapture {
+    image: Handle<Image>,
+    dir: PathBuf,
+    tolerance: f32,
+    bless: bool,
+    pending: Option<u32>,
+}
+
+impl ExtractResource for GoldenCapture {
+    type Source = Self;
+
+    fn extract_resource(source: &Self) -> Self {
+        source.clone()
+    }
+}
+
+#[derive(Resource)]
+struct GoldenRun {
+    tick: u32,
+    captures: Vec<u32>,
+}
+
+struct FrameResult {
+    tick: u32,
+    difference: f32,
+    passed: bool,
+}
+
+// Shared between the main and render worlds - the render world does the comparison as soon as the
+// pixels are back from the GPU, and the main world reports once every capture is in.
+#[derive(Resource, Clone, Default)]
+struct GoldenReport(Arc<Mutex<Vec<FrameResult>>>);
+
+// Every frame advances the clock by exactly 1/60th of a second, however long it really took, so
+// timers fire on the same frame every run.
+pub fn fixed_frame_time(mut time: ResMut<Time>, mut fixed: Local<Option<(Time, Instant)>>) {
+    let (fixed_time, instant) = fixed.get_or_insert_with(|| (Time::default(), Instant::now()));
+    *instant += FRAME_TIME;
+    fixed_time.update_with_instant(*instant);
+    *time = fixed_time.clone();
+}
+
+fn render_to_golden_target(
+    mut cameras: Query<&mut Camera, Added<Camera>>,
+    capture: Res<GoldenCapture>,
+) {
+    for mut camera in &mut cameras {
+        camera.target = RenderTarget::Image(capture.image.clone());
+    }
+}
+
+fn golden_tick(
+    mut run: ResMut<GoldenRun>,
+    mut capture: ResMut<GoldenCapture>,
+    report: Res<GoldenReport>,
+) {
+    run.tick += 1;
+    let tick = run.tick;
+    capture.pending = run.captures.contains(&tick).then_some(tick);
+
+    let results = report.0.lock().unwrap();
+    if results.len() < run.captures.len() {
+        return;
+    }
+
+    let mut failures = 0;
+    for result in results.iter() {
+        let verdict = if result.passed { "ok" } else { "FAILED" };
+        println!(
+            "frame {}: difference {:.4} (tolerance {:.4}) {verdict}",
+            result.tick, result.difference, capture.tolerance
+        );
+        if !result.passed {
+            failures += 1;
+        }
+    }
+
+    println!("{} frames compared, {failures} failed", results.len());
+    std::process::exit(if failures == 0 { 0 } else { 1 });
+}
+
+fn capture_frame(
+    capture: Res<GoldenCapture>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    report: Res<GoldenReport>,
+) {
+    let Some(tick) = capture.pending else { return };
+    let Some(gpu_image) = images.get(&capture.image) else { return };
+
+    let (width, height, pixels) = read_back(gpu_image, &render_device, &render_queue);
+    let result = compare_with_reference(&capture, tick, width, height, &pixels);
+    report.0.lock().unwrap().push(result);
+}
+
+fn compare_with_reference(
+    capture: &GoldenCapture,
+    tick: u32,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> FrameResult {
+    let reference_path = capture.dir.join(format!("frame_{tick}.png"));
+    let save = |path: &Path| {
+        if let Err(e) = image::save_buffer(path, pixels, width, height, image::ColorType::Rgba8) {
+            error!("Unable to write {path:?}: {e}");
+        }
+    };
+
+    if capture.bless {
+        info!("Blessing frame {tick} as {reference_path:?}");
+        save(&reference_path);
+        return FrameResult { tick, difference: 0., passed: true };
+    }
+    let Ok(reference) = image::open(&reference_path) else {
+        error!("No reference for frame {tick}, --bless writes one to {reference_path:?}");
+        save(&capture.dir.join(format!("frame_{tick}.actual.png")));
+        return FrameResult { tick, difference: 1., passed: false };
+    };
+
+    let reference = reference.to_rgba8();
+    let difference = if reference.dimensions() != (width, height) {
+        1.
+    } else {
+        let total: u64 = reference
+            .as_raw()
+            .iter()
+            .zip(pixels)
+            .map(|(a, b)| a.abs_diff(*b) as u64)
+            .sum();
+        total as f32 / (pixels.len() as f32 * 255.)
+    };
+
+    let passed = difference <= capture.tolerance;
+    if !passed {
+        save(&capture.dir.join(format!("frame_{tick}.actual.png")));
+    }
+
+    FrameResult {
+        tick,
+        difference,
+        passed,
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::{
+    grenade::GrenadeCooldown, localization::Localization, popups::PopupEvent, settings::Settings,
+    state::GameState,
+};
+
+// Seconds knocked off the wait for the next grenade
+const GRAZE_GRENADE_SECONDS: f32 = 0.75;
+// Above the carrot's head, out of the way of the damage numbers
+const GRAZE_POPUP_HEIGHT: f32 = 0.6;
+const GRAZE_COLOR: Color = Color::rgb(0.6, 0.9, 1.);
+
+pub struct GrazePlugin;
+
+impl Plugin for GrazePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Grazed>()
+            .add_startup_system(setup_graze_assets)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(reward_grazes));
+    }
+}
+
+// A beet's swing went off with a player only just out of its reach, or rolling straight through
+// it. Worth a few points and a little off the next grenade.
+pub struct Grazed {
+    pub player: Entity,
+    pub enemy: Entity,
+    pub position: Vec3,
+}
+
+#[derive(Resource)]
+struct GrazeAssets {
+    whoosh: Handle<AudioSource>,
+}
+
+fn setup_graze_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GrazeAssets {
+        whoosh: asset_server.load("audio/whoosh.ogg"),
+    });
+}
+
+fn reward_grazes(
+    mut grazed: EventReader<Grazed>,
+    mut cooldowns: Query<&mut GrenadeCooldown>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    assets: Res<GrazeAssets>,
+    audio: Res<Audio>,
+    mut popups: EventWriter<PopupEvent>,
+) {
+    for graze in grazed.iter() {
+        if let Ok(mut cooldown) = cooldowns.get_mut(graze.player) {
+            cooldown.hurry(GRAZE_GRENADE_SECONDS);
+        }
+        popups.send(PopupEvent {
+            text: localization.get("graze").to_string(),
+            position: graze.position + Vec3::Y * GRAZE_POPUP_HEIGHT,
+            color: GRAZE_COLOR,
+        });
+        audio.play_with_settings(
+            assets.whoosh.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.effects()),
+        );
+    }
+}
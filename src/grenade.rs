@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::Palette,
+    damage::DamageEvent,
+    ground::Ground,
+    input::PlayerInput,
+    lighting::{spawn_flash, FlashKind},
+    reset::DespawnOnReset,
+    state::GameState,
+    Aim, Enemy, Game, Player,
+};
+
+// How long a player waits between grenades
+const GRENADE_COOLDOWN_SECONDS: f32 = 6.;
+// Thrown without a target, a grenade lands this far in front of the gun
+const GRENADE_RANGE: f32 = 2.5;
+const GRENADE_FLIGHT_SECONDS: f32 = 0.8;
+const GRAVITY: f32 = 9.8;
+const GRENADE_DAMAGE: f32 = 2.;
+const GRENADE_BLAST_RADIUS: f32 = 0.8;
+const GRENADE_KNOCKBACK: f32 = 0.1;
+const GRENADE_SCALE: f32 = 0.6;
+// Tumbling end over end, in radians a second
+const GRENADE_SPIN: f32 = 6.;
+// Sits just to the right of the stamina bar
+const BAR_WIDTH: f32 = 60.;
+const BAR_HEIGHT: f32 = 10.;
+const BAR_LEFT: f32 = 190.;
+const BAR_MARGIN: f32 = 20.;
+const CHARGING_COLOUR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+pub struct GrenadePlugin;
+
+impl Plugin for GrenadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GrenadeThrown>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(throw_grenades)
+                    .with_system(fly_grenades)
+                    .with_system(recharge_grenades),
+            )
+            .add_system(spawn_grenade_bars)
+            .add_system(update_grenade_bars.after(spawn_grenade_bars))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_grenades));
+    }
+}
+
+// Every player has one. Counts down to zero, and there's a grenade ready once it gets there.
+#[derive(Component, Default)]
+pub struct GrenadeCooldown {
+    remaining: f32,
+}
+
+impl GrenadeCooldown {
+    // From 0 just thrown to 1 ready to go
+    pub fn charge(&self) -> f32 {
+        1. - self.remaining / GRENADE_COOLDOWN_SECONDS
+    }
+
+    pub fn hurry(&mut self, seconds: f32) {
+        self.remaining = (self.remaining - seconds).max(0.);
+    }
+}
+
+pub struct GrenadeThrown {
+    pub player: Entity,
+}
+
+// Lobbed, so it flies on an arc rather than straight at anything
+#[derive(Component)]
+struct Grenade {
+    velocity: Vec3,
+}
+
+#[derive(Component)]
+struct GrenadeBar {
+    player: Entity,
+}
+
+#[derive(Component)]
+struct GrenadeFill;
+
+// At whatever's locked on, or else a fixed distance out in front. Either way it'll come down
+// where it was aimed after the same time in the air.
+fn throw_grenades(
+    mut commands: Commands,
+    game: Res<Game>,
+    mut players: Query<(Entity, &Player, &Aim, &PlayerInput, &mut GrenadeCooldown)>,
+    transforms: Query<&GlobalTransform>,
+    mut thrown: EventWriter<GrenadeThrown>,
+) {
+    let Some(projectile_asset) = &game.projectile else { return };
+
+    for (entity, player, aim, input, mut cooldown) in &mut players {
+        if !input.grenade || cooldown.remaining > 0. {
+            continue;
+        }
+        let Ok(weapon) = transforms.get(player.weapon) else { continue };
+
+        let origin = weapon.translation();
+        let landing = aim
+            .target
+            .and_then(|target| transforms.get(target).ok())
+            .map(GlobalTransform::translation)
+            .unwrap_or_else(|| {
+                let forward = weapon.forward();
+                let flat = Vec3::new(forward.x, 0., forward.z).normalize_or_zero();
+                Vec3::new(origin.x, 0., origin.z) + flat * GRENADE_RANGE
+            });
+
+        let flight = GRENADE_FLIGHT_SECONDS;
+        let mut velocity = (landing - origin) / flight;
+        velocity.y += 0.5 * GRAVITY * flight;
+
+        cooldown.remaining = GRENADE_COOLDOWN_SECONDS;
+        thrown.send(GrenadeThrown { player: entity });
+        commands.spawn((
+            SceneBundle {
+                scene: projectile_asset.clone(),
+                transform: Transform::from_translation(origin)
+                    .with_scale(Vec3::splat(GRENADE_SCALE)),
+                ..default()
+            },
+            Grenade { velocity },
+            DespawnOnReset,
+        ));
+    }
+}
+
+// Goes off when it hits the ground, catching every beet nearby
+fn fly_grenades(
+    mut commands: Commands,
+    time: Res<Time>,
+    ground: Res<Ground>,
+    mut grenades: Query<(Entity, &mut Transform, &mut Grenade)>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut grenade) in &mut grenades {
+        grenade.velocity.y -= GRAVITY * delta;
+        transform.translation += grenade.velocity * delta;
+        transform.rotate_x(GRENADE_SPIN * delta);
+        let height = ground.height_at(transform.translation);
+        if transform.translation.y > height || grenade.velocity.y > 0. {
+            continue;
+        }
+
+        let centre = transform.translation;
+        for (enemy, enemy_transform) in &enemies {
+            let offset = enemy_transform.translation() - centre;
+            if Vec3::new(offset.x, 0., offset.z).length() > GRENADE_BLAST_RADIUS {
+                continue;
+            }
+            damage.send(DamageEvent {
+                source: Some(entity),
+                target: enemy,
+                amount: GRENADE_DAMAGE,
+                direction: Vec3::new(offset.x, 0., offset.z).normalize_or_zero(),
+                critical: false,
+                knockback: GRENADE_KNOCKBACK,
+            });
+        }
+        spawn_flash(&mut commands, FlashKind::Blast, centre + Vec3::Y * 0.2);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn recharge_grenades(time: Res<Time>, mut cooldowns: Query<&mut GrenadeCooldown>) {
+    let delta = time.delta_seconds();
+    for mut cooldown in &mut cooldowns {
+        cooldown.remaining = (cooldown.remaining - delta).max(0.);
+    }
+}
+
+// A short bar next to each player's stamina bar
+fn spawn_grenade_bars(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    players: Query<(Entity, &Player), Added<GrenadeCooldown>>,
+) {
+    for (entity, player) in &players {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(BAR_LEFT),
+                            bottom: Val::Px(BAR_MARGIN + player.index as f32 * BAR_HEIGHT * 2.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                },
+                GrenadeBar { player: entity },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                            ..default()
+                        },
+                        background_color: palette.grenade.into(),
+                        ..default()
+                    },
+                    GrenadeFill,
+                ));
+            });
+    }
+}
+
+fn update_grenade_bars(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    bars: Query<(Entity, &GrenadeBar, &Children)>,
+    cooldowns: Query<&GrenadeCooldown>,
+    mut fills: Query<(&mut Style, &mut BackgroundColor), With<GrenadeFill>>,
+) {
+    for (entity, bar, children) in &bars {
+        // Players who've dropped out take their bar with them
+        let Ok(cooldown) = cooldowns.get(bar.player) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        for child in children {
+            let Ok((mut style, mut color)) = fills.get_mut(*child) else { continue };
+            style.size.width = Val::Percent(cooldown.charge() * 100.);
+            *color = if cooldown.remaining > 0. {
+                CHARGING_COLOUR.into()
+            } else {
+                palette.grenade.into()
+            };
+        }
+    }
+}
+
+fn reset_grenades(mut cooldowns: Query<&mut GrenadeCooldown>) {
+    for mut cooldown in &mut cooldowns {
+        *cooldown = GrenadeCooldown::default();
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+
+use crate::{
+    enemy_movement, leash_enemies, player_movement, projectile_hit, projectile_movement,
+    reset::DespawnOnReset, state::GameState, telegraph::Emerging, track_velocity, Enemy, GameRng,
+    Player, Projectile,
+};
+
+const CLODS_PER_SPLASH: usize = 6;
+const CLOD_SPEED: f32 = 1.5;
+const CLOD_GRAVITY: f32 = 6.;
+const CLOD_SECONDS: f32 = 0.5;
+
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ground>()
+            .add_event::<ProjectileMissed>()
+            .add_startup_system(setup_ground_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(
+                        stand_on_ground
+                            .after(player_movement)
+                            .after(enemy_movement)
+                            .after(leash_enemies)
+                            .before(track_velocity),
+                    )
+                    // A pumpkin that reaches a beet the same frame it reaches the ground hits it
+                    .with_system(splash_down.after(projectile_movement).after(projectile_hit))
+                    .with_system(splash_dirt.after(splash_down))
+                    .with_system(fly_clods),
+            );
+    }
+}
+
+// How high the ground is anywhere in the level. It's flat for now, but everything that cares asks
+// here, so a heightmap only has to go in the one place.
+#[derive(Resource, Default)]
+pub struct Ground {
+    pub level: f32,
+}
+
+impl Ground {
+    pub fn height_at(&self, _position: Vec3) -> f32 {
+        self.level
+    }
+}
+
+// A pumpkin that went into the ground rather than a beet
+pub struct ProjectileMissed {
+    pub position: Vec3,
+}
+
+#[derive(Component)]
+struct Clod {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+struct GroundAssets {
+    clod_mesh: Handle<Mesh>,
+    clod_material: Handle<StandardMaterial>,
+}
+
+fn setup_ground_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(GroundAssets {
+        clod_mesh: meshes.add(Mesh::from(shape::Cube { size: 0.04 })),
+        clod_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.35, 0.24, 0.15),
+            perceptual_roughness: 1.,
+            ..default()
+        }),
+    });
+}
+
+// Players and beets walk on the ground, whatever they've been chasing or shoved by. Beets still
+// climbing out of it are left where they are.
+fn stand_on_ground(
+    ground: Res<Ground>,
+    mut actors: Query<&mut Transform, (Or<(With<Player>, With<Enemy>)>, Without<Emerging>)>,
+) {
+    for mut transform in &mut actors {
+        let height = ground.height_at(transform.translation);
+        if transform.translation.y != height {
+            transform.translation.y = height;
+        }
+    }
+}
+
+fn splash_down(
+    mut commands: Commands,
+    ground: Res<Ground>,
+    projectiles: Query<(Entity, &GlobalTransform), With<Projectile>>,
+    mut missed: EventWriter<ProjectileMissed>,
+) {
+    for (entity, transform) in &projectiles {
+        let position = transform.translation();
+        let height = ground.height_at(position);
+        if position.y > height {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        missed.send(ProjectileMissed {
+            position: Vec3::new(position.x, height, position.z),
+        });
+    }
+}
+
+fn splash_dirt(
+    mut commands: Commands,
+    ground_assets: Res<GroundAssets>,
+    mut rng: ResMut<GameRng>,
+    mut missed: EventReader<ProjectileMissed>,
+) {
+    for miss in missed.iter() {
+        for _ in 0..CLODS_PER_SPLASH {
+            let velocity = Vec3::new(
+                rng.0.gen_range(-0.5..0.5),
+                rng.0.gen_range(1.0..2.0),
+                rng.0.gen_range(-0.5..0.5),
+            ) * CLOD_SPEED;
+            commands.spawn((
+                PbrBundle {
+                    mesh: ground_assets.clod_mesh.clone(),
+                    material: ground_assets.clod_material.clone(),
+                    transform: Transform::from_translation(miss.position),
+                    ..default()
+                },
+                Clod {
+                    velocity,
+                    lifetime: Timer::from_seconds(CLOD_SECONDS, TimerMode::Once),
+                },
+                NotShadowCaster,
+                DespawnOnReset,
+            ));
+        }
+    }
+}
+
+fn fly_clods(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut clods: Query<(Entity, &mut Transform, &mut Clod)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut transform, mut clod) in &mut clods {
+        if clod.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        clod.velocity.y -= CLOD_GRAVITY * dt;
+        transform.translation += clod.velocity * dt;
+        transform.scale = Vec3::splat(clod.lifetime.percent_left());
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    damage::{DamageEvent, Health},
+    obstacles::Collider,
+    state::GameState,
+    status::{ApplyStatus, StatusEffect, StatusKind},
+};
+
+// Hazards hurt in pulses rather than every frame, so brushing past one isn't a death sentence
+const HAZARD_TICK_SECONDS: f32 = 0.5;
+const SPRINKLER_TURN_SPEED: f32 = 1.5;
+// How wide the jet of water is either side of the arm
+const SPRINKLER_SPRAY_WIDTH: f32 = 0.2;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_hazard_assets).add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(spin_sprinklers)
+                .with_system(hazard_contact_damage.after(spin_sprinklers)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum HazardKind {
+    // Sits there being unpleasant
+    CompostPit,
+    // Sweeps a jet of water round in a circle
+    Sprinkler,
+}
+
+impl HazardKind {
+    pub fn radius(&self) -> f32 {
+        match self {
+            HazardKind::CompostPit => 0.5,
+            HazardKind::Sprinkler => 1.2,
+        }
+    }
+
+    fn damage_per_tick(&self) -> f32 {
+        match self {
+            HazardKind::CompostPit => 0.5,
+            HazardKind::Sprinkler => 1.,
+        }
+    }
+
+    // Compost's hot enough to keep burning after stepping out of it, and a soaking slows
+    // anyone down
+    fn status(&self) -> StatusEffect {
+        let (kind, seconds, strength) = match self {
+            HazardKind::CompostPit => (StatusKind::Burn, 1.5, 0.4),
+            HazardKind::Sprinkler => (StatusKind::Slow, 2., 0.4),
+        };
+        StatusEffect {
+            kind,
+            seconds,
+            strength,
+        }
+    }
+}
+
+// Anything with `Health` and a `Collider` gets hurt while it's touching one of these - player and
+// enemies alike
+#[derive(Component)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    tick: Timer,
+}
+
+impl Hazard {
+    pub fn new(kind: HazardKind) -> Self {
+        Self {
+            kind,
+            tick: Timer::from_seconds(HAZARD_TICK_SECONDS, TimerMode::Repeating),
+        }
+    }
+
+    // `offset` is from the hazard to the victim, `arm` is the way the hazard is facing
+    fn touches(&self, offset: Vec2, arm: Vec2, victim_radius: f32) -> bool {
+        let reach = self.kind.radius() + victim_radius;
+        if offset.length() > reach {
+            return false;
+        }
+
+        match self.kind {
+            HazardKind::CompostPit => true,
+            // The arm sprays from both ends, so only the distance from its line matters
+            HazardKind::Sprinkler => {
+                arm.perp_dot(offset).abs() < SPRINKLER_SPRAY_WIDTH + victim_radius
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct HazardAssets {
+    pit_mesh: Handle<Mesh>,
+    pit_material: Handle<StandardMaterial>,
+    sprinkler_mesh: Handle<Mesh>,
+    sprinkler_material: Handle<StandardMaterial>,
+    spray_material: Handle<StandardMaterial>,
+}
+
+fn setup_hazard_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HazardAssets {
+        pit_mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: HazardKind::CompostPit.radius(),
+            subdivisions: 3,
+        })),
+        pit_material: materials.add(Color::rgb(0.3, 0.2, 0.1).into()),
+        sprinkler_mesh: meshes.add(Mesh::from(shape::Cube { size: 1. })),
+        sprinkler_material: materials.add(Color::rgb(0.5, 0.5, 0.55).into()),
+        spray_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.8, 1., 0.5),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+}
+
+// `position` is relative to whatever `parent` is - a level chunk, usually
+pub fn spawn_hazard(
+    parent: &mut ChildBuilder,
+    assets: &HazardAssets,
+    kind: HazardKind,
+    position: Vec3,
+) {
+    let mut hazard = parent.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        Hazard::new(kind),
+    ));
+
+    hazard.with_children(|parent| match kind {
+        // A squashed dome of compost, mostly below ground
+        HazardKind::CompostPit => {
+            parent.spawn((
+                PbrBundle {
+                    mesh: assets.pit_mesh.clone(),
+                    material: assets.pit_material.clone(),
+                    transform: Transform::from_scale(Vec3::new(1., 0.1, 1.)),
+                    ..default()
+                },
+                NotShadowCaster,
+            ));
+        }
+        HazardKind::Sprinkler => {
+            let reach = kind.radius() * 2.;
+            parent.spawn(PbrBundle {
+                mesh: assets.sprinkler_mesh.clone(),
+                material: assets.sprinkler_material.clone(),
+                transform: Transform::from_xyz(0., 0.1, 0.)
+                    .with_scale(Vec3::new(0.08, 0.2, 0.08)),
+                ..default()
+            });
+            parent.spawn((
+                PbrBundle {
+                    mesh: assets.sprinkler_mesh.clone(),
+                    material: assets.spray_material.clone(),
+                    transform: Transform::from_xyz(0., 0.15, 0.)
+                        .with_scale(Vec3::new(reach, 0.03, SPRINKLER_SPRAY_WIDTH * 2.)),
+                    ..default()
+                },
+                NotShadowCaster,
+            ));
+        }
+    });
+}
+
+fn spin_sprinklers(mut hazards: Query<(&mut Transform, &Hazard)>, time: Res<Time>) {
+    for (mut transform, hazard) in &mut hazards {
+        if hazard.kind == HazardKind::Sprinkler {
+            transform.rotate_y(SPRINKLER_TURN_SPEED * time.delta_seconds());
+        }
+    }
+}
+
+fn hazard_contact_damage(
+    mut hazards: Query<(Entity, &GlobalTransform, &mut Hazard)>,
+    victims: Query<(Entity, &GlobalTransform, &Collider), With<Health>>,
+    mut damage: EventWriter<DamageEvent>,
+    mut statuses: EventWriter<ApplyStatus>,
+    time: Res<Time>,
+) {
+    for (hazard_entity, hazard_transform, mut hazard) in &mut hazards {
+        if !hazard.tick.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let centre = hazard_transform.translation();
+        let right = hazard_transform.right();
+        let arm = Vec2::new(right.x, right.z);
+        for (victim, victim_transform, collider) in &victims {
+            let offset = victim_transform.translation() - centre;
+            if !hazard.touches(Vec2::new(offset.x, offset.z), arm, collider.radius) {
+                continue;
+            }
+
+            damage.send(DamageEvent {
+                source: Some(hazard_entity),
+                target: victim,
+                amount: hazard.kind.damage_per_tick(),
+                direction: Vec3::new(offset.x, 0., offset.z).normalize_or_zero(),
+                critical: false,
+                knockback: 0.,
+            });
+            statuses.send(ApplyStatus {
+                target: victim,
+                effect: hazard.kind.status(),
+            });
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    app::{AppExit, ScheduleRunnerPlugin},
+    prelude::*,
+    render::settings::WgpuSettings,
+    time::TimeSystem,
+    winit::WinitPlugin,
+};
+use serde::Serialize;
+
+use crate::{
+    damage::{DamageDealt, Health},
+    golden,
+    score::Score,
+    settings::Settings,
+    state::GameState,
+    waves::WaveManager,
+    Enemy, EnemyKilled, Game, Player, WeaponFired,
+};
+
+// Everything bevy usually brings, minus the window and the GPU. The gameplay plugins still lean on
+// assets, scenes and UI nodes existing, so `MinimalPlugins` isn't enough to run them.
+pub fn add_headless_plugins(app: &mut App) {
+    app.insert_resource(WgpuSettings {
+        backends: None,
+        ..default()
+    })
+    .add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                add_primary_window: false,
+                exit_on_all_closed: false,
+                ..default()
+            })
+            .disable::<WinitPlugin>(),
+    )
+    .add_plugin(ScheduleRunnerPlugin::default());
+}
+
+// Runs the game for a set number of ticks as fast as it'll go, then prints what happened and
+// exits. Inputs come from `--playback` if there is one, otherwise nobody touches the controls.
+pub struct HeadlessPlugin {
+    pub ticks: u32,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::default())
+            .insert_resource(HeadlessRun {
+                ticks: self.ticks,
+                report: HeadlessReport::default(),
+            })
+            .add_system_to_stage(CoreStage::First, golden::fixed_frame_time.after(TimeSystem))
+            .add_system_to_stage(CoreStage::Last, collect_stats);
+    }
+}
+
+#[derive(Resource)]
+struct HeadlessRun {
+    ticks: u32,
+    report: HeadlessReport,
+}
+
+#[derive(Default, Debug, Serialize)]
+pub struct HeadlessReport {
+    pub ticks: u32,
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub enemies_killed: u32,
+    pub enemies_alive: u32,
+    pub wave: u32,
+    pub score: u32,
+    pub player_health: f32,
+    pub game_over: bool,
+}
+
+fn collect_stats(
+    mut run: ResMut<HeadlessRun>,
+    mut fired: EventReader<WeaponFired>,
+    mut dealt: EventReader<DamageDealt>,
+    mut killed: EventReader<EnemyKilled>,
+    mut exit: EventWriter<AppExit>,
+    game: Res<Game>,
+    score: Res<Score>,
+    waves: Res<WaveManager>,
+    state: Res<State<GameState>>,
+    enemies: Query<(), With<Enemy>>,
+    players: Query<(), With<Player>>,
+    health: Query<&Health>,
+) {
+    let ticks = run.ticks;
+    let report = &mut run.report;
+    report.ticks += 1;
+    report.shots_fired += fired.iter().count() as u32;
+    report.enemies_killed += killed.iter().count() as u32;
+    for damage in dealt.iter() {
+        if players.contains(damage.target) {
+            report.damage_taken += damage.amount;
+        } else {
+            // Whatever it hit may well be gone by now, so anything that isn't a player counts
+            report.hits += 1;
+            report.damage_dealt += damage.amount;
+        }
+    }
+
+    if report.ticks < ticks {
+        return;
+    }
+
+    report.enemies_alive = enemies.iter().count() as u32;
+    report.wave = waves.wave;
+    report.score = score.points;
+    report.player_health = health.get(game.player).map_or(0., |health| health.current);
+    report.game_over = *state.current() == GameState::GameOver;
+
+    match ron::ser::to_string_pretty(&*report, default()) {
+        Ok(contents) => println!("{contents}"),
+        Err(e) => error!("Unable to serialize the headless report: {e}"),
+    }
+    exit.send(AppExit);
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::{viewport_to_ui, Palette},
+    campaign::Boss,
+    damage::{resolve_damage, DamageDealt, Health},
+    reset::DespawnOnReset,
+    state::GameState,
+    Enemy, Game,
+};
+
+// How long a bar stays up after the last hit, and how long it then takes to fade away
+const HOLD_SECONDS: f32 = 2.5;
+const FADE_SECONDS: f32 = 0.5;
+// In pixels, before the UI scale. A boss gets a longer one.
+const BAR_SIZE: Vec2 = Vec2::new(40., 5.);
+const BOSS_BAR_SIZE: Vec2 = Vec2::new(90., 8.);
+// Above the top of a beet, scaled up along with bigger ones
+const BAR_HEIGHT: f32 = 0.9;
+const BACKGROUND_COLOUR: Color = Color::rgba(0., 0., 0., 0.6);
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        // Straight after the damage is done, the same as the damage numbers
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            show_health_bars.after(resolve_damage),
+        )
+        .add_system_set(SystemSet::on_update(GameState::Playing).with_system(update_health_bars));
+    }
+}
+
+// Drawn as UI and moved over the beet it's for each frame, like a popup, so it's the same size
+// however far away the beet is
+#[derive(Component)]
+struct HealthBar {
+    target: Entity,
+    fill: Entity,
+    since_hit: f32,
+}
+
+// Only for a beet that's been hurt and lived. One that's never been touched doesn't need one.
+fn show_health_bars(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    palette: Res<Palette>,
+    enemies: Query<Option<&Boss>, With<Enemy>>,
+    mut bars: Query<&mut HealthBar>,
+) {
+    for damage in dealt.iter() {
+        if damage.killed || damage.amount <= 0. {
+            continue;
+        }
+        let Ok(boss) = enemies.get(damage.target) else { continue };
+
+        if let Some(mut bar) = bars.iter_mut().find(|bar| bar.target == damage.target) {
+            bar.since_hit = 0.;
+            continue;
+        }
+        let size = if boss.is_some() {
+            BOSS_BAR_SIZE
+        } else {
+            BAR_SIZE
+        };
+        let fill = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    ..default()
+                },
+                background_color: palette.enemy.into(),
+                ..default()
+            })
+            .id();
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                        ..default()
+                    },
+                    background_color: BACKGROUND_COLOUR.into(),
+                    // Hidden until it's been put in the right place
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                },
+                HealthBar {
+                    target: damage.target,
+                    fill,
+                    since_hit: 0.,
+                },
+                DespawnOnReset,
+            ))
+            .add_child(fill);
+    }
+}
+
+fn update_health_bars(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    enemies: Query<(&GlobalTransform, &Health), With<Enemy>>,
+    mut bars: Query<(Entity, &mut HealthBar, &Node, &mut Style, &mut Visibility)>,
+    mut colours: Query<&mut BackgroundColor>,
+    mut fills: Query<&mut Style, Without<HealthBar>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get(game.camera) else { return };
+
+    for (entity, mut bar, node, mut style, mut visibility) in &mut bars {
+        bar.since_hit += time.delta_seconds();
+        let expired = bar.since_hit >= HOLD_SECONDS + FADE_SECONDS;
+        // Gone already, whether it died or was cleared away
+        let target = enemies
+            .get(bar.target)
+            .ok()
+            .filter(|(_, health)| !health.is_dead());
+        let Some((transform, health)) = target.filter(|_| !expired) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        let anchor = translation + Vec3::Y * BAR_HEIGHT * scale.y;
+        // Behind the camera
+        let Some(screen) = camera.world_to_viewport(camera_transform, anchor) else {
+            visibility.is_visible = false;
+            continue;
+        };
+
+        // Centred on the spot, which is bottom-up just like the viewport
+        let screen = viewport_to_ui(&ui_scale, screen);
+        let size = node.size();
+        style.position = UiRect {
+            left: Val::Px(screen.x - size.x / 2.),
+            bottom: Val::Px(screen.y - size.y / 2.),
+            ..default()
+        };
+        visibility.is_visible = true;
+
+        let left = (health.current / health.max.max(f32::EPSILON)).clamp(0., 1.);
+        if let Ok(mut fill) = fills.get_mut(bar.fill) {
+            fill.size.width = Val::Percent(left * 100.);
+        }
+        let alpha = ((HOLD_SECONDS + FADE_SECONDS - bar.since_hit) / FADE_SECONDS).min(1.);
+        if let Ok(mut colour) = colours.get_mut(entity) {
+            colour.0.set_a(BACKGROUND_COLOUR.a() * alpha);
+        }
+        if let Ok(mut colour) = colours.get_mut(bar.fill) {
+            colour.0.set_a(alpha);
+        }
+    }
+}
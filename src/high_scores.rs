@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::ActiveLevel, console::Cheated, daily::Daily, input::PlayerInput,
+    level_gen::LevelSeed, localization::Localization, score::Score, state::GameState, storage,
+    suspend::Suspending, waves::WaveManager, CameraRig,
+};
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+const MAX_ENTRIES: usize = 10;
+// A month's worth of daily challenges
+const MAX_DAILY_RESULTS: usize = 30;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .add_system(open_leaderboard)
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(record_run))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Leaderboard).with_system(spawn_leaderboard),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Leaderboard).with_system(close_leaderboard),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Leaderboard).with_system(despawn_leaderboard),
+            );
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub wave: u32,
+    pub kills: u32,
+    // Down the lane, in metres. Older tables don't have it.
+    #[serde(default)]
+    pub distance: f32,
+    // So a good run can be played again
+    pub seed: u64,
+}
+
+// The best go at one day's challenge
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DailyResult {
+    // Since 1970, the same for everyone on the same date
+    pub day: u64,
+    pub score: u32,
+    pub wave: u32,
+    pub kills: u32,
+}
+
+// Best first. Daily challenges are kept apart, one per day with the latest first, since they're
+// only up against other goes on the same day.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    // Older tables don't have them
+    #[serde(default)]
+    pub daily: Vec<DailyResult>,
+}
+
+impl HighScores {
+    fn load() -> Self {
+        storage::data_file(HIGH_SCORES_FILE)
+            .map(|path| storage::load(&path))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match storage::data_file(HIGH_SCORES_FILE) {
+            Some(path) => storage::save(&path, self),
+            None => warn!("Nowhere to save high scores on this platform"),
+        }
+    }
+
+    // Returns where the run placed, if it made the table at all
+    pub fn add(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    pub fn best_daily(&self, day: u64) -> Option<&DailyResult> {
+        self.daily.iter().find(|result| result.day == day)
+    }
+
+    // Returns whether it beat that day's best so far
+    pub fn add_daily(&mut self, result: DailyResult) -> bool {
+        match self.daily.iter_mut().find(|best| best.day == result.day) {
+            Some(best) if best.score >= result.score => return false,
+            Some(best) => *best = result,
+            None => self.daily.push(result),
+        }
+        self.daily.sort_by(|a, b| b.day.cmp(&a.day));
+        self.daily.truncate(MAX_DAILY_RESULTS);
+        true
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardUi;
+
+// Campaign levels are over after a set number of waves, so they'd never measure up. Daily
+// challenges keep their own.
+fn record_run(
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    waves: Res<WaveManager>,
+    rig: Res<CameraRig>,
+    level: Res<ActiveLevel>,
+    seed: Res<LevelSeed>,
+    suspending: Res<Suspending>,
+    daily: Res<Daily>,
+    cheated: Res<Cheated>,
+) {
+    // Not over yet
+    if !level.is_endless() || suspending.0 || daily.is_playing() || cheated.0 {
+        return;
+    }
+
+    let entry = HighScore {
+        score: score.points,
+        wave: waves.wave,
+        kills: score.kills,
+        distance: rig.travelled().max(0.),
+        seed: seed.0,
+    };
+    if let Some(rank) = high_scores.add(entry) {
+        info!("New high score, ranked #{}", rank + 1);
+        high_scores.save();
+    }
+}
+
+fn open_leaderboard(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.inspect && *state.current() == GameState::MainMenu {
+        if let Err(e) = state.push(GameState::Leaderboard) {
+            warn!("Unable to show the leaderboard: {e:?}");
+        }
+    }
+}
+
+fn close_leaderboard(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the leaderboard: {e:?}");
+        }
+    }
+}
+
+fn spawn_leaderboard(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut table = format!(
+        "{:<4}{:>8}{:>8}{:>8}{:>8}\n",
+        "",
+        localization.get("high-scores-score"),
+        localization.get("high-scores-wave"),
+        localization.get("high-scores-kills"),
+        localization.get("high-scores-distance")
+    );
+    for (rank, entry) in high_scores.entries.iter().enumerate() {
+        table += &format!(
+            "{:<4}{:>8}{:>8}{:>8}{:>8}\n",
+            format!("{}.", rank + 1),
+            entry.score,
+            entry.wave,
+            entry.kills,
+            format!("{:.0}m", entry.distance)
+        );
+    }
+    if high_scores.entries.is_empty() {
+        table += &format!("\n{}\n", localization.get("high-scores-empty"));
+    }
+    table += &format!("\n{}", localization.get("back-help"));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            LeaderboardUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.get("high-scores-title"),
+                TextStyle {
+                    font: asset_server.load("FiraSans-Bold.ttf"),
+                    font_size: 60.,
+                    color: Color::WHITE,
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                table,
+                TextStyle {
+                    font: asset_server.load("FiraMono-Medium.ttf"),
+                    font_size: 26.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn despawn_leaderboard(mut commands: Commands, ui: Query<Entity, With<LeaderboardUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
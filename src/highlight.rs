@@ -0,0 +1,90 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{accessibility::Palette, Aim, Barrage};
+
+pub struct TargetHighlightPlugin;
+
+impl Plugin for TargetHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighlightMaterials>()
+            .add_system(highlight_target);
+    }
+}
+
+// Scenes share their materials between every instance, so the locked targets get their own glowing
+// copy of each material while they're locked.
+#[derive(Resource, Default)]
+struct HighlightMaterials {
+    color: Color,
+    glowing: HashMap<Handle<StandardMaterial>, Handle<StandardMaterial>>,
+}
+
+#[derive(Component)]
+struct Highlighted {
+    original: Handle<StandardMaterial>,
+}
+
+fn highlight_target(
+    mut commands: Commands,
+    aims: Query<&Aim>,
+    barrages: Query<&Barrage>,
+    palette: Res<Palette>,
+    mut highlight_materials: ResMut<HighlightMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    children: Query<&Children>,
+    mut mesh_materials: Query<&mut Handle<StandardMaterial>>,
+    highlighted: Query<(Entity, &Highlighted)>,
+) {
+    if highlight_materials.color != palette.target {
+        highlight_materials.color = palette.target;
+        highlight_materials.glowing.clear();
+    }
+
+    let mut target_meshes = Vec::new();
+    // Along with everything a barrage weapon's painted so far
+    let painted = barrages.iter().flat_map(|barrage| barrage.painted.iter().copied());
+    for target in aims.iter().filter_map(|aim| aim.target).chain(painted) {
+        collect_descendants(target, &children, &mut target_meshes);
+    }
+
+    // Put back anything that isn't part of a current target any more
+    for (entity, highlighted) in &highlighted {
+        if target_meshes.contains(&entity) {
+            continue;
+        }
+        if let Ok(mut material) = mesh_materials.get_mut(entity) {
+            *material = highlighted.original.clone();
+        }
+        commands.entity(entity).remove::<Highlighted>();
+    }
+
+    let color = highlight_materials.color;
+    for entity in target_meshes {
+        if highlighted.contains(entity) {
+            continue;
+        }
+        let Ok(mut material) = mesh_materials.get_mut(entity) else { continue };
+
+        let original = material.clone();
+        let glowing = highlight_materials
+            .glowing
+            .entry(original.clone())
+            .or_insert_with(|| {
+                let mut glowing = materials.get(&original).cloned().unwrap_or_default();
+                glowing.emissive = color;
+                materials.add(glowing)
+            })
+            .clone();
+
+        *material = glowing;
+        commands.entity(entity).insert(Highlighted { original });
+    }
+}
+
+fn collect_descendants(entity: Entity, children: &Query<&Children>, out: &mut Vec<Entity>) {
+    out.push(entity);
+    let Ok(entity_children) = children.get(entity) else { return };
+    for child in entity_children.iter() {
+        collect_descendants(*child, children, out);
+    }
+}
@@ -0,0 +1,395 @@
+use bevy::{
+    core_pipeline::core_3d::Opaque3d,
+    ecs::{
+        query::QueryItem,
+        system::{lifetimeless::*, SystemParamItem},
+    },
+    gltf::{Gltf, GltfMesh, GltfNode},
+    pbr::{MeshPipeline, MeshPipelineKey, MeshUniform, SetMeshBindGroup, SetMeshViewBindGroup},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{GpuBufferInfo, MeshVertexBufferLayout, VertexAttributeValues},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, EntityRenderCommand, RenderCommandResult,
+            RenderPhase, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        view::{ExtractedView, NoFrustumCulling},
+        RenderApp, RenderStage,
+    },
+};
+
+use crate::{config::GameConfig, state::GameState, Enemy, Game};
+
+// Where it is and how big, which way it's facing, and what colour
+type InstanceData = [f32; 12];
+
+pub struct HordeRenderingPlugin;
+
+impl Plugin for HordeRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Horde>()
+            .add_plugin(ExtractComponentPlugin::<HordeInstances>::default())
+            .add_system(build_horde)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(update_horde))
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(disband_horde));
+
+        // Nothing to draw with when there's no GPU, like in headless runs
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app
+            .add_render_command::<Opaque3d, DrawHorde>()
+            .init_resource::<HordePipeline>()
+            .init_resource::<SpecializedMeshPipelines<HordePipeline>>()
+            .add_system_to_stage(RenderStage::Prepare, prepare_horde_buffers)
+            .add_system_to_stage(RenderStage::Queue, queue_horde);
+    }
+}
+
+// Every beet is its own scene, which is its own handful of draw calls. Past
+// `GameConfig::instanced_horde_size` of them they're hidden and drawn all at once instead, one
+// instanced draw for each part of the beet mesh. They stand still in their bind pose while they're
+// drawn that way, which is hard to spot in a crowd that size.
+#[derive(Resource, Default)]
+struct Horde {
+    parts: Vec<Entity>,
+    active: bool,
+}
+
+// One primitive of the beet mesh, baked into where it sits in the model
+#[derive(Component)]
+struct HordePart {
+    color: Color,
+}
+
+#[derive(Component, Clone, Default)]
+struct HordeInstances(Vec<InstanceData>);
+
+impl ExtractComponent for HordeInstances {
+    type Query = &'static HordeInstances;
+    type Filter = ();
+
+    fn extract_component(item: QueryItem<'_, Self::Query>) -> Self {
+        item.clone()
+    }
+}
+
+// Once the beet's loaded. Like the environment's spawn markers, the mesh is expected to sit at the
+// top level of the model.
+fn build_horde(
+    mut commands: Commands,
+    mut horde: ResMut<Horde>,
+    game: Res<Game>,
+    gltfs: Res<Assets<Gltf>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    nodes: Res<Assets<GltfNode>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !horde.parts.is_empty() {
+        return;
+    }
+    let Some(gltf) = game.enemy_animations.first().and_then(|beet| gltfs.get(beet)) else { return };
+    let Some((node, gltf_mesh)) = gltf
+        .nodes
+        .iter()
+        .filter_map(|node| nodes.get(node))
+        .find_map(|node| Some((node, gltf_meshes.get(node.mesh.as_ref()?)?)))
+    else {
+        return;
+    };
+
+    let mut parts = Vec::new();
+    for primitive in &gltf_mesh.primitives {
+        let Some(mesh) = meshes.get(&primitive.mesh) else { return };
+        let color = primitive
+            .material
+            .as_ref()
+            .and_then(|material| materials.get(material))
+            .map_or(Color::WHITE, |material| material.base_color);
+        parts.push((static_copy(mesh, node.transform), color));
+    }
+
+    for (mesh, color) in parts {
+        let part = commands
+            .spawn((
+                meshes.add(mesh),
+                SpatialBundle::VISIBILITY_IN_VIEW,
+                // The instances are all over the place, so the mesh's own bounds mean nothing
+                NoFrustumCulling,
+                HordePart { color },
+                HordeInstances::default(),
+            ))
+            .id();
+        horde.parts.push(part);
+    }
+}
+
+// Just the position, normal and UVs the horde shader reads, with the node's transform baked in.
+// Skinning and tangents would want bind groups and vertex slots the shader doesn't have.
+fn static_copy(mesh: &Mesh, transform: Transform) -> Mesh {
+    let matrix = transform.compute_matrix();
+    let mut copy = Mesh::new(mesh.primitive_topology());
+
+    let mut count = 0;
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    {
+        count = positions.len();
+        let positions = positions
+            .iter()
+            .map(|position| matrix.transform_point3((*position).into()).to_array())
+            .collect::<Vec<_>>();
+        copy.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    }
+    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => normals
+            .iter()
+            .map(|normal| {
+                let normal = matrix.transform_vector3((*normal).into());
+                normal.normalize_or_zero().to_array()
+            })
+            .collect(),
+        _ => vec![[0., 1., 0.]; count],
+    };
+    copy.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => uvs.clone(),
+        _ => vec![[0., 0.]; count],
+    };
+    copy.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    copy.set_indices(mesh.indices().cloned());
+    copy
+}
+
+fn update_horde(
+    mut horde: ResMut<Horde>,
+    config: Res<GameConfig>,
+    mut enemies: Query<(&GlobalTransform, &mut Visibility), With<Enemy>>,
+    mut parts: Query<(&HordePart, &mut HordeInstances)>,
+) {
+    let active =
+        !horde.parts.is_empty() && enemies.iter().count() >= config.instanced_horde_size as usize;
+    // New arrivals get hidden as they join, and everyone's shown again as soon as it's over
+    if active || horde.active {
+        for (_, mut visibility) in &mut enemies {
+            if visibility.is_visible == active {
+                visibility.is_visible = !active;
+            }
+        }
+    }
+    horde.active = active;
+
+    for (part, mut instances) in &mut parts {
+        instances.0.clear();
+        if !active {
+            continue;
+        }
+        let [red, green, blue, alpha] = part.color.as_linear_rgba_f32();
+        instances.0.extend(enemies.iter().map(|(transform, _)| {
+            let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+            [
+                translation.x,
+                translation.y,
+                translation.z,
+                scale.x,
+                rotation.x,
+                rotation.y,
+                rotation.z,
+                rotation.w,
+                red,
+                green,
+                blue,
+                alpha,
+            ]
+        }));
+    }
+}
+
+// Whatever's left of the horde goes back to being drawn the usual way for the game over screen
+fn disband_horde(
+    mut horde: ResMut<Horde>,
+    mut enemies: Query<&mut Visibility, With<Enemy>>,
+    mut parts: Query<&mut HordeInstances>,
+) {
+    if !horde.active {
+        return;
+    }
+    horde.active = false;
+    for mut visibility in &mut enemies {
+        visibility.is_visible = true;
+    }
+    for mut instances in &mut parts {
+        instances.0.clear();
+    }
+}
+
+#[derive(Component)]
+struct HordeBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+fn prepare_horde_buffers(
+    mut commands: Commands,
+    parts: Query<(Entity, &HordeInstances)>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, instances) in &parts {
+        if instances.0.is_empty() {
+            continue;
+        }
+        let contents = instances
+            .0
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect::<Vec<_>>();
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("horde instance buffer"),
+            contents: &contents,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+        commands.entity(entity).insert(HordeBuffer {
+            buffer,
+            length: instances.0.len(),
+        });
+    }
+}
+
+fn queue_horde(
+    draw_functions: Res<DrawFunctions<Opaque3d>>,
+    horde_pipeline: Res<HordePipeline>,
+    msaa: Res<Msaa>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<HordePipeline>>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+    meshes: Res<RenderAssets<Mesh>>,
+    parts: Query<(Entity, &MeshUniform, &Handle<Mesh>), With<HordeBuffer>>,
+    mut views: Query<(&ExtractedView, &mut RenderPhase<Opaque3d>)>,
+) {
+    let draw_horde = draw_functions.read().get_id::<DrawHorde>().unwrap();
+    let msaa_key = MeshPipelineKey::from_msaa_samples(msaa.samples);
+
+    for (view, mut opaque_phase) in &mut views {
+        let rangefinder = view.rangefinder3d();
+        for (entity, mesh_uniform, mesh_handle) in &parts {
+            let Some(mesh) = meshes.get(mesh_handle) else { continue };
+            let key = msaa_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology);
+            let specialized =
+                pipelines.specialize(&mut pipeline_cache, &horde_pipeline, key, &mesh.layout);
+            let pipeline = match specialized {
+                Ok(pipeline) => pipeline,
+                Err(e) => {
+                    error!("Unable to draw the horde: {e}");
+                    continue;
+                }
+            };
+            opaque_phase.add(Opaque3d {
+                entity,
+                pipeline,
+                draw_function: draw_horde,
+                distance: rangefinder.distance(&mesh_uniform.transform),
+            });
+        }
+    }
+}
+
+// The usual mesh pipeline with the horde shader and a second, per-instance vertex buffer
+#[derive(Resource)]
+struct HordePipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+}
+
+impl FromWorld for HordePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let shader = world.resource::<AssetServer>().load("shaders/horde.wgsl");
+        let mesh_pipeline = world.resource::<MeshPipeline>().clone();
+        Self {
+            shader,
+            mesh_pipeline,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for HordePipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayout,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceData>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: (0..3)
+                .map(|index| VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size() * index,
+                    shader_location: 3 + index as u32,
+                })
+                .collect(),
+        });
+        descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
+        descriptor.layout = Some(vec![
+            self.mesh_pipeline.view_layout.clone(),
+            self.mesh_pipeline.mesh_layout.clone(),
+        ]);
+        Ok(descriptor)
+    }
+}
+
+type DrawHorde = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawMeshInstanced,
+);
+
+struct DrawMeshInstanced;
+
+impl EntityRenderCommand for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SQuery<Read<Handle<Mesh>>>,
+        SQuery<Read<HordeBuffer>>,
+    );
+
+    fn render<'w>(
+        _view: Entity,
+        item: Entity,
+        (meshes, mesh_query, buffer_query): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let (Ok(mesh_handle), Ok(instances)) = (mesh_query.get(item), buffer_query.get_inner(item))
+        else {
+            return RenderCommandResult::Failure;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_handle) else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, instances.buffer.slice(..));
+        let instance_range = 0..instances.length as u32;
+        match &gpu_mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, instance_range);
+            }
+            GpuBufferInfo::NonIndexed { vertex_count } => {
+                pass.draw(0..*vertex_count, instance_range);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use bevy::{gltf::Gltf, prelude::*};
+
+use crate::{
+    asset_manifest::{measure_scene, AssetManifest, ASSETS_DIR},
+    obstacles::Obstacle,
+    streaming::EnvironmentChunks,
+    Player,
+};
+
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(respawn_modified_scenes)
+            .add_system(reattach_weapons.after(respawn_modified_scenes))
+            .add_system(remeasure_modified_models);
+    }
+}
+
+// The asset server swaps in the new scene when a glb changes on disk, but anything already spawned
+// from the old one stays as it was. Marking the handle as changed gets the scene spawner to throw
+// the old instance away and spawn the new one in its place, the same as swapping characters does.
+fn respawn_modified_scenes(
+    mut events: EventReader<AssetEvent<Scene>>,
+    mut scenes: Query<&mut Handle<Scene>>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else { continue };
+        for mut scene in &mut scenes {
+            if *scene == *handle {
+                scene.set_changed();
+            }
+        }
+    }
+}
+
+// The spud gun hangs off the player rather than anything in their scene, so it should come
+// through a respawn untouched. Just in case, anyone who's lost theirs gets it back.
+fn reattach_weapons(
+    mut commands: Commands,
+    players: Query<(Entity, &Player), Changed<Handle<Scene>>>,
+    parents: Query<&Parent>,
+) {
+    for (entity, player) in &players {
+        if parents.get(player.weapon).map(Parent::get).ok() != Some(entity) {
+            commands.entity(entity).add_child(player.weapon);
+        }
+    }
+}
+
+// Measures the new file the way `--process-assets` does, so props made from it collide at its new
+// size. Players and beets keep their hand-tuned colliders. If it's the environment, its spawn
+// markers are read again too.
+fn remeasure_modified_models(
+    mut events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    mut manifest: ResMut<AssetManifest>,
+    mut chunks: ResMut<EnvironmentChunks>,
+    mut obstacles: Query<(&Handle<Scene>, &mut Obstacle)>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else { continue };
+        let Some(asset_path) = asset_server.get_handle_path(handle) else { continue };
+        let name = asset_path.path().to_string_lossy().into_owned();
+
+        if *handle == chunks.gltf {
+            chunks.authored_spawn_points = None;
+        }
+
+        let info = match measure_scene(&Path::new(ASSETS_DIR).join(asset_path.path())) {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("Unable to measure {name} after it changed: {e}");
+                continue;
+            }
+        };
+        info!("{name} changed, collider radius is now {:.2}", info.collider_radius);
+        let radius = info.collider_radius;
+        manifest.scenes.insert(name.clone(), info);
+
+        for (scene, mut obstacle) in &mut obstacles {
+            let from_this_model = asset_server
+                .get_handle_path(scene)
+                .map_or(false, |scene_path| scene_path.path() == asset_path.path());
+            if from_this_model {
+                obstacle.radius = radius;
+            }
+        }
+    }
+}
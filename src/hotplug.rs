@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{ControllerConnected, ControllerDisconnected, InputSource},
+    localization::Localization,
+    netplay::Netplay,
+    state::GameState,
+    Player,
+};
+
+pub struct HotplugPlugin;
+
+impl Plugin for HotplugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MissingControllers>()
+            .add_startup_system(setup_disconnected_overlay)
+            .add_system(pause_on_disconnect)
+            .add_system(resume_on_reconnect.after(pause_on_disconnect))
+            .add_system(show_disconnected_overlay.after(resume_on_reconnect))
+            // Unpausing by hand (from the keyboard, say) means they've decided to carry on
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(forget_missing));
+    }
+}
+
+// Players whose pad went away mid-run, and whether we paused the game because of it
+#[derive(Resource, Default)]
+struct MissingControllers {
+    players: Vec<usize>,
+    paused_for_them: bool,
+}
+
+#[derive(Component)]
+struct DisconnectedOverlay;
+
+#[derive(Component)]
+struct DisconnectedText;
+
+fn pause_on_disconnect(
+    mut disconnected: EventReader<ControllerDisconnected>,
+    mut missing: ResMut<MissingControllers>,
+    mut state: ResMut<State<GameState>>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    players: Query<&Player>,
+) {
+    for event in disconnected.iter() {
+        // Recordings don't need a pad, and an online game can't stop at just one end
+        if !matches!(*source, InputSource::Live) || netplay.is_some() {
+            continue;
+        }
+        if !players.iter().any(|player| player.index == event.player) {
+            continue;
+        }
+        info!("Player {}'s controller disconnected", event.player + 1);
+        if !missing.players.contains(&event.player) {
+            missing.players.push(event.player);
+        }
+
+        if *state.current() == GameState::Playing {
+            match state.push(GameState::Paused) {
+                Ok(()) => missing.paused_for_them = true,
+                Err(e) => warn!("Unable to pause for a missing controller: {e:?}"),
+            }
+        }
+    }
+}
+
+// Whatever pad turns up takes the missing player's slot, so it doesn't have to be the same one
+fn resume_on_reconnect(
+    mut connected: EventReader<ControllerConnected>,
+    mut missing: ResMut<MissingControllers>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for event in connected.iter() {
+        missing.players.retain(|player| *player != event.player);
+    }
+    if !missing.players.is_empty() || !missing.paused_for_them {
+        return;
+    }
+
+    missing.paused_for_them = false;
+    // Leave them be if they've wandered off into the settings or the armory
+    if *state.current() == GameState::Paused {
+        if let Err(e) = state.pop() {
+            warn!("Unable to resume after a controller reconnected: {e:?}");
+        }
+    }
+}
+
+fn forget_missing(mut missing: ResMut<MissingControllers>) {
+    missing.players.clear();
+    missing.paused_for_them = false;
+}
+
+fn setup_disconnected_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::bottom(Val::Px(80.)),
+                    ..default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+            DisconnectedOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 36.,
+                        color: Color::ORANGE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::CENTER),
+                DisconnectedText,
+            ));
+        });
+}
+
+fn show_disconnected_overlay(
+    missing: Res<MissingControllers>,
+    localization: Res<Localization>,
+    mut overlays: Query<&mut Visibility, With<DisconnectedOverlay>>,
+    mut texts: Query<&mut Text, With<DisconnectedText>>,
+) {
+    if !missing.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let players = missing
+        .players
+        .iter()
+        .map(|player| localization.format("player", &[("number", &(player + 1))]))
+        .collect::<Vec<_>>()
+        .join(localization.get("list-and"));
+    for mut text in &mut texts {
+        text.sections[0].value =
+            localization.format("controller-disconnected", &[("players", &players)]);
+    }
+
+    let visible = !missing.players.is_empty();
+    for mut visibility in &mut overlays {
+        visibility.is_visible = visible;
+    }
+}
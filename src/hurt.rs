@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::{resolve_damage, DamageAppExt, DamageContext, DamageDealt, DamageStage},
+    reset::DespawnOnReset,
+    state::GameState,
+    Game, Player,
+};
+
+// How long a player can't be hurt again after taking a hit
+const INVULNERABLE_SECONDS: f32 = 1.;
+// The carrot flickers on and off this often while it lasts
+const BLINK_SECONDS: f32 = 0.08;
+const INDICATOR_SECONDS: f32 = 1.;
+// Along the edge of the screen and in from it, in pixels
+const INDICATOR_LENGTH: f32 = 120.;
+const INDICATOR_THICKNESS: f32 = 12.;
+const INDICATOR_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+
+pub struct HurtPlugin;
+
+impl Plugin for HurtPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_damage_modifier(DamageStage::Clamp, ignore_damage_while_invulnerable)
+            // Straight after the damage is done, while whoever dealt it is still around to be found
+            .add_system_to_stage(CoreStage::PostUpdate, take_hits.after(resolve_damage))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(blink))
+            .add_system(update_indicators)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_hurt));
+    }
+}
+
+// Every player has one. Counts down to zero and stays there.
+#[derive(Component, Default)]
+pub struct Invulnerable {
+    remaining: f32,
+}
+
+impl Invulnerable {
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.
+    }
+}
+
+// Points from the middle of the screen towards whatever landed the hit, stuck to the edge
+#[derive(Component)]
+struct DamageIndicator {
+    // Where the attacker was, for when it's gone before the indicator is
+    attacker: Vec3,
+    source: Option<Entity>,
+    age: f32,
+}
+
+fn ignore_damage_while_invulnerable(context: &mut DamageContext, world: &World) {
+    if world
+        .get::<Invulnerable>(context.target)
+        .map_or(false, Invulnerable::is_active)
+    {
+        context.amount = 0.;
+    }
+}
+
+fn take_hits(
+    mut commands: Commands,
+    mut dealt: EventReader<DamageDealt>,
+    mut players: Query<(&GlobalTransform, &mut Invulnerable), With<Player>>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for damage in dealt.iter() {
+        if damage.amount <= 0. || damage.killed {
+            continue;
+        }
+        let Ok((transform, mut invulnerable)) = players.get_mut(damage.target) else { continue };
+        invulnerable.remaining = INVULNERABLE_SECONDS;
+
+        // Back along the way the blow was travelling if the attacker can't be found
+        let attacker = damage
+            .source
+            .and_then(|source| transforms.get(source).ok())
+            .map_or(transform.translation() - damage.direction, |source| {
+                source.translation()
+            });
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: INDICATOR_COLOR.into(),
+                // Hidden until it's been put in the right place
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+            DamageIndicator {
+                attacker,
+                source: damage.source,
+                age: 0.,
+            },
+            DespawnOnReset,
+        ));
+    }
+}
+
+// Nothing else hides players, so they're simply shown again once it's over
+fn blink(time: Res<Time>, mut players: Query<(&mut Invulnerable, &mut Visibility), With<Player>>) {
+    for (mut invulnerable, mut visibility) in &mut players {
+        invulnerable.remaining = (invulnerable.remaining - time.delta_seconds()).max(0.);
+        let shown = !invulnerable.is_active()
+            || (invulnerable.remaining / BLINK_SECONDS) as u32 % 2 == 0;
+        if visibility.is_visible != shown {
+            visibility.is_visible = shown;
+        }
+    }
+}
+
+fn update_indicators(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    transforms: Query<&GlobalTransform>,
+    mut indicators: Query<(
+        Entity,
+        &mut DamageIndicator,
+        &mut Style,
+        &mut BackgroundColor,
+        &mut Visibility,
+    )>,
+) {
+    // Without a window to draw on they still come and go on time. Worked out in the UI's own
+    // units, which the UI scale stretches.
+    let viewport = cameras.get(game.camera).ok().and_then(|(camera, transform)| {
+        let screen = viewport_to_ui(&ui_scale, camera.logical_viewport_size()?);
+        Some((camera, transform, screen))
+    });
+
+    for (entity, mut indicator, mut style, mut background, mut visibility) in &mut indicators {
+        // Real seconds, so it doesn't hang about through a slowdown
+        indicator.age += time.raw_delta_seconds();
+        if indicator.age >= INDICATOR_SECONDS {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(source) = indicator.source.and_then(|source| transforms.get(source).ok()) {
+            indicator.attacker = source.translation();
+        }
+        let Some((camera, camera_transform, screen)) = viewport else { continue };
+        let centre = screen / 2.;
+
+        // Behind the camera it's worked out as if from above, the way the radar shows things: up
+        // the screen is up the lane
+        let direction = match camera.world_to_viewport(camera_transform, indicator.attacker) {
+            Some(position) => viewport_to_ui(&ui_scale, position) - centre,
+            None => {
+                let offset = indicator.attacker - camera_transform.translation();
+                Vec2::new(offset.x, -offset.z)
+            }
+        };
+        let direction = direction.try_normalize().unwrap_or(Vec2::NEG_Y);
+
+        // Out from the middle until it meets an edge, lying along that edge. The viewport is
+        // bottom-up just like `bottom`.
+        let reach = (centre.x / direction.x.abs()).min(centre.y / direction.y.abs());
+        let edge = centre + direction * reach;
+        let size = if direction.x.abs() * centre.y > direction.y.abs() * centre.x {
+            Vec2::new(INDICATOR_THICKNESS, INDICATOR_LENGTH)
+        } else {
+            Vec2::new(INDICATOR_LENGTH, INDICATOR_THICKNESS)
+        };
+        let corner = (edge - size / 2.).clamp(Vec2::ZERO, (screen - size).max(Vec2::ZERO));
+        style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+        style.position = UiRect {
+            left: Val::Px(corner.x),
+            bottom: Val::Px(corner.y),
+            ..default()
+        };
+        visibility.is_visible = true;
+
+        // Solid for the first half, then fading out
+        let alpha = (2. * (1. - indicator.age / INDICATOR_SECONDS)).min(1.);
+        *background = INDICATOR_COLOR.with_a(alpha).into();
+    }
+}
+
+fn reset_hurt(mut players: Query<(&mut Invulnerable, &mut Visibility)>) {
+    for (mut invulnerable, mut visibility) in &mut players {
+        *invulnerable = Invulnerable::default();
+        visibility.is_visible = true;
+    }
+}
@@ -0,0 +1,746 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use bevy::{app::AppExit, input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{GameConfig, MovementOrientation},
+    localization::Localization,
+    settings::Settings,
+    Player,
+};
+
+// How far a finger has to drag to count as the stick being pushed all the way
+const TOUCH_STICK_RADIUS: f32 = 60.;
+// A stick has to be pushed this far before the prompts switch over to the pad
+const STICK_SWITCH_THRESHOLD: f32 = 0.5;
+
+// Everything the gameplay systems need to know about what the player is doing this frame. Reading
+// the devices in one place means a recording can stand in for a real gamepad.
+//
+// The resource is player one, who also drives the menus. Each player entity carries its own copy
+// as a component, which is what the gameplay systems read.
+#[derive(Resource, Component, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerInput {
+    pub movement: Vec2,
+    pub aim: f32,
+    pub fire: bool,
+    // Held, for charging a shot up
+    pub fire_held: bool,
+    pub pause: bool,
+    pub inspect: bool,
+    pub reload: bool,
+    pub dodge: bool,
+    // Held, rather than pressed
+    pub sprint: bool,
+    pub grenade: bool,
+    pub melee: bool,
+    pub deploy: bool,
+    // Lock on to whatever the drone's marked
+    pub snap: bool,
+    pub ultimate: bool,
+    // Held, next to someone who's down
+    pub revive: bool,
+    // Menu navigation
+    pub confirm: bool,
+    pub back: bool,
+}
+
+// Everything a player can rebind. Movement and the right stick stay on their axes, and confirm and
+// back stay put so there's always a way out of the remapping screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputAction {
+    Fire,
+    AimLeft,
+    AimRight,
+    Pause,
+    Inspect,
+    Reload,
+    Dodge,
+    Sprint,
+    Grenade,
+    Melee,
+    DeployTurret,
+    SnapToMark,
+    Ultimate,
+    Revive,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 14] = [
+        InputAction::Fire,
+        InputAction::AimLeft,
+        InputAction::AimRight,
+        InputAction::Pause,
+        InputAction::Inspect,
+        InputAction::Reload,
+        InputAction::Dodge,
+        InputAction::Sprint,
+        InputAction::Grenade,
+        InputAction::Melee,
+        InputAction::DeployTurret,
+        InputAction::SnapToMark,
+        InputAction::Ultimate,
+        InputAction::Revive,
+    ];
+
+    // Into the localization
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::Fire => "action-fire",
+            InputAction::AimLeft => "action-aim-left",
+            InputAction::AimRight => "action-aim-right",
+            InputAction::Pause => "action-pause",
+            InputAction::Inspect => "action-armory",
+            InputAction::Reload => "action-reload",
+            InputAction::Dodge => "action-dodge",
+            InputAction::Sprint => "action-sprint",
+            InputAction::Grenade => "action-grenade",
+            InputAction::Melee => "action-melee",
+            InputAction::DeployTurret => "action-deploy-turret",
+            InputAction::SnapToMark => "action-snap-to-mark",
+            InputAction::Ultimate => "action-ultimate",
+            InputAction::Revive => "action-revive",
+        }
+    }
+
+    fn default_binding(self) -> Binding {
+        let (key, button) = match self {
+            InputAction::Fire => (KeyCode::Space, GamepadButtonType::RightTrigger2),
+            InputAction::AimLeft => (KeyCode::Left, GamepadButtonType::DPadLeft),
+            InputAction::AimRight => (KeyCode::Right, GamepadButtonType::DPadRight),
+            InputAction::Pause => (KeyCode::Escape, GamepadButtonType::Start),
+            InputAction::Inspect => (KeyCode::Tab, GamepadButtonType::North),
+            InputAction::Reload => (KeyCode::R, GamepadButtonType::West),
+            InputAction::Dodge => (KeyCode::LShift, GamepadButtonType::LeftTrigger),
+            InputAction::Sprint => (KeyCode::LControl, GamepadButtonType::LeftThumb),
+            InputAction::Grenade => (KeyCode::G, GamepadButtonType::LeftTrigger2),
+            InputAction::Melee => (KeyCode::F, GamepadButtonType::RightTrigger),
+            InputAction::DeployTurret => (KeyCode::T, GamepadButtonType::DPadUp),
+            InputAction::SnapToMark => (KeyCode::V, GamepadButtonType::DPadDown),
+            InputAction::Ultimate => (KeyCode::Q, GamepadButtonType::RightThumb),
+            InputAction::Revive => (KeyCode::E, GamepadButtonType::South),
+        };
+        Binding {
+            key: Some(key),
+            button: Some(button),
+        }
+    }
+}
+
+// One key and one gamepad button per action, either of which can be left unbound
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    pub key: Option<KeyCode>,
+    pub button: Option<GamepadButtonType>,
+}
+
+// Lives in the settings file. Actions missing from it (say, ones added since it was saved) get
+// their default binding.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings(BTreeMap<InputAction, Binding>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(
+            InputAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_binding()))
+                .collect(),
+        )
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: InputAction) -> Binding {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    // A key only does one thing, so whatever had it before loses it
+    pub fn bind_key(&mut self, action: InputAction, key: KeyCode) {
+        for other in InputAction::ALL {
+            let mut binding = self.get(other);
+            if other == action {
+                binding.key = Some(key);
+            } else if binding.key == Some(key) {
+                binding.key = None;
+            }
+            self.0.insert(other, binding);
+        }
+    }
+
+    // What to press for `action` on `device`, for putting in prompts
+    pub fn prompt(&self, action: InputAction, device: ActiveDevice) -> String {
+        let binding = self.get(action);
+        let glyph = match device {
+            ActiveDevice::Keyboard => binding.key.map(key_glyph),
+            ActiveDevice::Gamepad => binding.button.map(button_glyph),
+        };
+        glyph.unwrap_or_else(|| "-".into())
+    }
+
+    // Sprint's default button has moved twice: off the left trigger when the grenade took it, and
+    // back off B, which also goes back out of the boss cinematic and the choice between waves. A
+    // settings file saved with either still has it there, so it goes to where it is now, or
+    // unbound if that's been taken.
+    pub fn migrate(&mut self) {
+        let sprint = self.get(InputAction::Sprint);
+        let stale = match sprint.button {
+            Some(GamepadButtonType::LeftTrigger2) => !self.0.contains_key(&InputAction::Grenade),
+            Some(GamepadButtonType::East) => true,
+            _ => false,
+        };
+        if !stale {
+            return;
+        }
+        let default = InputAction::Sprint.default_binding().button;
+        let taken = InputAction::ALL
+            .iter()
+            .any(|other| *other != InputAction::Sprint && self.get(*other).button == default);
+        self.0.insert(
+            InputAction::Sprint,
+            Binding {
+                button: if taken { None } else { default },
+                ..sprint
+            },
+        );
+    }
+
+    pub fn bind_button(&mut self, action: InputAction, button: GamepadButtonType) {
+        for other in InputAction::ALL {
+            let mut binding = self.get(other);
+            if other == action {
+                binding.button = Some(button);
+            } else if binding.button == Some(button) {
+                binding.button = None;
+            }
+            self.0.insert(other, binding);
+        }
+    }
+}
+
+// As printed on the keyboard, near enough
+pub fn key_glyph(key: KeyCode) -> String {
+    match key {
+        KeyCode::Return => "Enter".into(),
+        KeyCode::Back => "Backspace".into(),
+        KeyCode::Escape => "Esc".into(),
+        KeyCode::LShift => "Left Shift".into(),
+        KeyCode::RShift => "Right Shift".into(),
+        KeyCode::LControl => "Left Ctrl".into(),
+        KeyCode::RControl => "Right Ctrl".into(),
+        KeyCode::LAlt => "Left Alt".into(),
+        KeyCode::RAlt => "Right Alt".into(),
+        KeyCode::Key0 => "0".into(),
+        KeyCode::Key1 => "1".into(),
+        KeyCode::Key2 => "2".into(),
+        KeyCode::Key3 => "3".into(),
+        KeyCode::Key4 => "4".into(),
+        KeyCode::Key5 => "5".into(),
+        KeyCode::Key6 => "6".into(),
+        KeyCode::Key7 => "7".into(),
+        KeyCode::Key8 => "8".into(),
+        KeyCode::Key9 => "9".into(),
+        _ => format!("{key:?}"),
+    }
+}
+
+// As printed on an Xbox pad, which is what most pads on PC copy
+pub fn button_glyph(button: GamepadButtonType) -> String {
+    match button {
+        GamepadButtonType::South => "A".into(),
+        GamepadButtonType::East => "B".into(),
+        GamepadButtonType::North => "Y".into(),
+        GamepadButtonType::West => "X".into(),
+        GamepadButtonType::LeftTrigger => "LB".into(),
+        GamepadButtonType::RightTrigger => "RB".into(),
+        GamepadButtonType::LeftTrigger2 => "LT".into(),
+        GamepadButtonType::RightTrigger2 => "RT".into(),
+        GamepadButtonType::LeftThumb => "L3".into(),
+        GamepadButtonType::RightThumb => "R3".into(),
+        GamepadButtonType::Select => "Back".into(),
+        GamepadButtonType::Start => "Start".into(),
+        GamepadButtonType::DPadUp => "D-pad up".into(),
+        GamepadButtonType::DPadDown => "D-pad down".into(),
+        GamepadButtonType::DPadLeft => "D-pad left".into(),
+        GamepadButtonType::DPadRight => "D-pad right".into(),
+        _ => format!("{button:?}"),
+    }
+}
+
+// Confirm can't be rebound, so it's always this
+pub fn confirm_glyph(device: ActiveDevice) -> String {
+    match device {
+        ActiveDevice::Keyboard => key_glyph(KeyCode::Return),
+        ActiveDevice::Gamepad => button_glyph(GamepadButtonType::South),
+    }
+}
+
+#[derive(Resource, Default)]
+pub enum InputSource {
+    #[default]
+    Live,
+    Playback {
+        frames: Vec<PlayerInput>,
+        cursor: usize,
+    },
+}
+
+impl InputSource {
+    pub fn playback(path: &PathBuf) -> Self {
+        let contents = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Unable to read input recording {path:?}: {e}"));
+        let frames = ron::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Input recording {path:?} is invalid: {e}"));
+        InputSource::Playback { frames, cursor: 0 }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match self {
+            InputSource::Live => false,
+            InputSource::Playback { frames, cursor } => *cursor >= frames.len(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct InputRecorder {
+    path: PathBuf,
+    frames: Vec<PlayerInput>,
+}
+
+impl InputRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            frames: Vec::new(),
+        }
+    }
+}
+
+// We can't ask the OS whether a keyboard is plugged in, so we only count one once a key is pressed.
+// The mouse counts as the keyboard here too, and a touch screen as soon as it's touched.
+#[derive(Resource, Default)]
+pub struct InputDevices {
+    pub keyboard_seen: bool,
+    pub touch_seen: bool,
+}
+
+// What player one last touched, so prompts can show the keys or buttons for whatever's in their
+// hands. The mouse counts as the keyboard.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ActiveDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+// Which gamepad belongs to which player. A pad keeps its slot until it's unplugged, so player two
+// doesn't end up with player one's carrot when player one's pad reconnects.
+#[derive(Resource, Default)]
+pub struct GamepadAssignments(Vec<Option<Gamepad>>);
+
+impl GamepadAssignments {
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.0.get(player).copied().flatten()
+    }
+}
+
+// A pad took over a player's slot. It needn't be the same pad they had before.
+pub struct ControllerConnected {
+    pub player: usize,
+    pub gamepad: Gamepad,
+}
+
+pub struct ControllerDisconnected {
+    pub player: usize,
+    pub gamepad: Gamepad,
+}
+
+#[derive(Component)]
+struct NoInputOverlay;
+
+#[derive(Component)]
+struct NoInputText;
+
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .init_resource::<InputSource>()
+            .init_resource::<InputDevices>()
+            .init_resource::<ActiveDevice>()
+            .init_resource::<GamepadAssignments>()
+            .add_event::<ControllerConnected>()
+            .add_event::<ControllerDisconnected>()
+            .add_startup_system(setup_no_input_overlay)
+            .add_system_to_stage(CoreStage::PreUpdate, assign_gamepads.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, gather_input.after(assign_gamepads))
+            .add_system_to_stage(CoreStage::PreUpdate, record_input.after(gather_input))
+            .add_system_to_stage(CoreStage::PreUpdate, gather_player_input.after(gather_input))
+            .add_system(detect_input_devices)
+            .add_system(show_no_input_overlay.after(detect_input_devices))
+            .add_system_to_stage(CoreStage::Last, save_recording);
+    }
+}
+
+// Keeps the slots in step with pads coming and going, and tells everyone else about it
+fn assign_gamepads(
+    mut assignments: ResMut<GamepadAssignments>,
+    mut events: EventReader<GamepadEvent>,
+    mut connected: EventWriter<ControllerConnected>,
+    mut disconnected: EventWriter<ControllerDisconnected>,
+) {
+    for event in events.iter() {
+        let gamepad = event.gamepad;
+        match event.event_type {
+            GamepadEventType::Connected(_) => {
+                if assignments.0.contains(&Some(gamepad)) {
+                    continue;
+                }
+                let player = match assignments.0.iter().position(|slot| slot.is_none()) {
+                    Some(player) => player,
+                    None => {
+                        assignments.0.push(None);
+                        assignments.0.len() - 1
+                    }
+                };
+                assignments.0[player] = Some(gamepad);
+                connected.send(ControllerConnected { player, gamepad });
+            }
+            GamepadEventType::Disconnected => {
+                let Some(player) = assignments.0.iter().position(|slot| *slot == Some(gamepad))
+                else {
+                    continue;
+                };
+                assignments.0[player] = None;
+                disconnected.send(ControllerDisconnected { player, gamepad });
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn gather_input(
+    mut input: ResMut<PlayerInput>,
+    mut source: ResMut<InputSource>,
+    assignments: Res<GamepadAssignments>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    keys: Res<Input<KeyCode>>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    players: Query<(&Player, &GlobalTransform)>,
+) {
+    if let InputSource::Playback { frames, cursor } = source.as_mut() {
+        *input = frames.get(*cursor).copied().unwrap_or_default();
+        *cursor += 1;
+        return;
+    }
+
+    let key = |key_code| if keys.pressed(key_code) { 1. } else { 0. };
+    let bound = |action| settings.bindings.get(action).key;
+    let held = |action| bound(action).map_or(0., key);
+    let tapped = |action| bound(action).map_or(false, |key_code| keys.just_pressed(key_code));
+    *input = PlayerInput {
+        movement: Vec2::new(
+            key(KeyCode::D) - key(KeyCode::A),
+            key(KeyCode::W) - key(KeyCode::S),
+        ),
+        aim: held(InputAction::AimRight) - held(InputAction::AimLeft),
+        fire: tapped(InputAction::Fire),
+        fire_held: held(InputAction::Fire) > 0.,
+        pause: tapped(InputAction::Pause),
+        inspect: tapped(InputAction::Inspect),
+        reload: tapped(InputAction::Reload),
+        dodge: tapped(InputAction::Dodge),
+        sprint: held(InputAction::Sprint) > 0.,
+        grenade: tapped(InputAction::Grenade),
+        melee: tapped(InputAction::Melee),
+        deploy: tapped(InputAction::DeployTurret),
+        snap: tapped(InputAction::SnapToMark),
+        ultimate: tapped(InputAction::Ultimate),
+        revive: held(InputAction::Revive) > 0.,
+        confirm: keys.just_pressed(KeyCode::Return),
+        back: keys.just_pressed(KeyCode::Back),
+    };
+
+    if let Some(window) = windows.get_primary() {
+        read_touches(&mut input, &touches, window.width());
+    }
+    if let Some(gamepad) = assignments.gamepad(0) {
+        read_gamepad(&mut input, gamepad, &axes, &buttons, &settings);
+    }
+
+    let reference = match config.movement_orientation {
+        MovementOrientation::World => None,
+        MovementOrientation::Camera => cameras.iter().next(),
+        MovementOrientation::Player => players
+            .iter()
+            .find(|(player, _)| player.index == 0)
+            .map(|(_, transform)| transform),
+    };
+    if let Some(reference) = reference {
+        input.movement = orient_movement(input.movement, reference.forward());
+    }
+}
+
+// Player one gets the keyboard and the first pad, everyone else just their own pad
+pub fn gather_player_input(
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    assignments: Res<GamepadAssignments>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut players: Query<(&Player, &GlobalTransform, &mut PlayerInput)>,
+) {
+    for (player, transform, mut player_input) in &mut players {
+        if player.index == 0 {
+            *player_input = *input;
+            continue;
+        }
+
+        *player_input = PlayerInput::default();
+        // Recordings only have player one in them
+        if !matches!(*source, InputSource::Live) {
+            continue;
+        }
+        let Some(gamepad) = assignments.gamepad(player.index) else { continue };
+        read_gamepad(&mut player_input, gamepad, &axes, &buttons, &settings);
+
+        let reference = match config.movement_orientation {
+            MovementOrientation::World => None,
+            MovementOrientation::Camera => cameras.iter().next(),
+            MovementOrientation::Player => Some(transform),
+        };
+        if let Some(reference) = reference {
+            player_input.movement = orient_movement(player_input.movement, reference.forward());
+        }
+    }
+}
+
+// Adds a gamepad on top of whatever's already in `input`
+fn read_gamepad(
+    input: &mut PlayerInput,
+    gamepad: Gamepad,
+    axes: &Axis<GamepadAxis>,
+    buttons: &Input<GamepadButton>,
+    settings: &Settings,
+) {
+    let axis = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+    let left_stick = settings.left_stick.orient(Vec2::new(
+        axis(GamepadAxisType::LeftStickX),
+        axis(GamepadAxisType::LeftStickY),
+    ));
+    let right_stick = settings.right_stick.orient(Vec2::new(
+        axis(GamepadAxisType::RightStickX),
+        axis(GamepadAxisType::RightStickY),
+    ));
+
+    input.movement = (input.movement + left_stick).clamp(Vec2::NEG_ONE, Vec2::ONE);
+    let bound = |action| {
+        settings
+            .bindings
+            .get(action)
+            .button
+            .map(|button_type| GamepadButton::new(gamepad, button_type))
+    };
+    let held = |action| match bound(action) {
+        Some(button) if buttons.pressed(button) => 1.,
+        _ => 0.,
+    };
+    let tapped = |action| bound(action).map_or(false, |button| buttons.just_pressed(button));
+
+    input.aim = (input.aim
+        + right_stick.x
+        + held(InputAction::AimRight)
+        - held(InputAction::AimLeft))
+    .clamp(-1., 1.);
+    input.fire |= tapped(InputAction::Fire);
+    input.fire_held |= held(InputAction::Fire) > 0.;
+    input.pause |= tapped(InputAction::Pause);
+    input.inspect |= tapped(InputAction::Inspect);
+    input.reload |= tapped(InputAction::Reload);
+    input.dodge |= tapped(InputAction::Dodge);
+    input.sprint |= held(InputAction::Sprint) > 0.;
+    input.grenade |= tapped(InputAction::Grenade);
+    input.melee |= tapped(InputAction::Melee);
+    input.deploy |= tapped(InputAction::DeployTurret);
+    input.snap |= tapped(InputAction::SnapToMark);
+    input.ultimate |= tapped(InputAction::Ultimate);
+    input.revive |= held(InputAction::Revive) > 0.;
+    input.confirm |= buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
+    input.back |= buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East));
+}
+
+// For phones and tablets in the browser. A finger on the left half of the screen is a stick that
+// starts wherever it went down, and on the right half it fires as it lands, charges while it stays
+// down and aims as it's dragged sideways. Any tap confirms in the menus.
+fn read_touches(input: &mut PlayerInput, touches: &Touches, window_width: f32) {
+    for touch in touches.iter() {
+        let offset = touch.position() - touch.start_position();
+        if touch.start_position().x < window_width / 2. {
+            // Touches come in with y running down the screen
+            let stick = Vec2::new(offset.x, -offset.y) / TOUCH_STICK_RADIUS;
+            input.movement = (input.movement + stick.clamp_length_max(1.))
+                .clamp(Vec2::NEG_ONE, Vec2::ONE);
+        } else {
+            input.aim = (input.aim + offset.x / TOUCH_STICK_RADIUS).clamp(-1., 1.);
+            input.fire_held = true;
+        }
+    }
+
+    for touch in touches.iter_just_pressed() {
+        input.confirm = true;
+        if touch.position().x >= window_width / 2. {
+            input.fire = true;
+        }
+    }
+}
+
+// Movement is always handed to gameplay in world terms (x is +X, y is -Z), so stick input relative
+// to something else gets rotated by that thing's yaw here.
+fn orient_movement(movement: Vec2, forward: Vec3) -> Vec2 {
+    let forward = Vec3::new(forward.x, 0., forward.z).normalize_or_zero();
+    if forward == Vec3::ZERO {
+        return movement;
+    }
+
+    let right = forward.cross(Vec3::Y);
+    let world = right * movement.x + forward * movement.y;
+    Vec2::new(world.x, -world.z)
+}
+
+fn detect_input_devices(
+    mut devices: ResMut<InputDevices>,
+    mut active: ResMut<ActiveDevice>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    assignments: Res<GamepadAssignments>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    let typed = keys.get_just_pressed().next().is_some();
+    let clicked = mouse.get_just_pressed().next().is_some();
+    if !devices.keyboard_seen && (typed || clicked) {
+        devices.keyboard_seen = true;
+    }
+    if !devices.touch_seen && touches.iter().next().is_some() {
+        devices.touch_seen = true;
+    }
+
+    let pad = assignments.gamepad(0).map_or(false, |gamepad| {
+        let pushed = |axis_type| {
+            let axis = axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+            axis.abs() > STICK_SWITCH_THRESHOLD
+        };
+        let pressed = buttons
+            .get_just_pressed()
+            .any(|button| button.gamepad == gamepad);
+        pressed
+            || pushed(GamepadAxisType::LeftStickX)
+            || pushed(GamepadAxisType::LeftStickY)
+            || pushed(GamepadAxisType::RightStickX)
+            || pushed(GamepadAxisType::RightStickY)
+    });
+    let device = if pad {
+        ActiveDevice::Gamepad
+    } else if typed || clicked {
+        ActiveDevice::Keyboard
+    } else {
+        return;
+    };
+    if *active != device {
+        *active = device;
+    }
+}
+
+fn setup_no_input_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+            NoInputOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 40.,
+                        color: Color::WHITE,
+                    },
+                ),
+                NoInputText,
+            ));
+        });
+}
+
+fn show_no_input_overlay(
+    devices: Res<InputDevices>,
+    gamepads: Res<Gamepads>,
+    source: Res<InputSource>,
+    localization: Res<Localization>,
+    mut overlays: Query<&mut Visibility, With<NoInputOverlay>>,
+    mut texts: Query<&mut Text, With<NoInputText>>,
+) {
+    if localization.is_changed() {
+        for mut text in &mut texts {
+            text.sections[0].value = localization.get("no-input").to_string();
+        }
+    }
+
+    let no_input = matches!(*source, InputSource::Live)
+        && !devices.keyboard_seen
+        && !devices.touch_seen
+        && gamepads.iter().next().is_none();
+
+    for mut visibility in &mut overlays {
+        if visibility.is_visible != no_input {
+            visibility.is_visible = no_input;
+        }
+    }
+}
+
+fn record_input(input: Res<PlayerInput>, recorder: Option<ResMut<InputRecorder>>) {
+    let Some(mut recorder) = recorder else { return };
+    recorder.frames.push(*input);
+}
+
+fn save_recording(mut exit: EventReader<AppExit>, recorder: Option<Res<InputRecorder>>) {
+    let Some(recorder) = recorder else { return };
+    if exit.iter().next().is_none() {
+        return;
+    }
+
+    match ron::to_string(&recorder.frames) {
+        Ok(contents) => match fs::write(&recorder.path, contents) {
+            Ok(()) => info!("Saved input recording to {:?}", recorder.path),
+            Err(e) => error!("Unable to save input recording to {:?}: {e}", recorder.path),
+        },
+        Err(e) => error!("Unable to serialize input recording: {e}"),
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy_editor_pls::EditorPlugin;
+
+use crate::{damage::Health, weapons::FireCooldown, Aim, Ammo, Game, Projectile};
+
+// The editor can show anything, but it can only pick apart and edit types that have been
+// registered for reflection. Add gameplay types here as they turn out to be worth poking at.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EditorPlugin)
+            .register_type::<Game>()
+            .register_type::<Health>()
+            .register_type::<Projectile>()
+            .register_type::<Aim>()
+            .register_type::<Ammo>()
+            .register_type::<FireCooldown>();
+    }
+}
@@ -0,0 +1,460 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    config::read_data_file,
+    input::{InputSource, PlayerInput},
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    netplay::Netplay,
+    pickups::{spawn_pickup, PickupAssets, PickupKind},
+    popups::PopupEvent,
+    reset::DespawnOnReset,
+    state::GameState,
+    waves::{WaveCleared, WaveManager, WavePhase},
+    weapons::WeaponStats,
+    Game, GameRng, Player, PLAYER_RADIUS,
+};
+
+const INTERMISSIONS_PATH: &str = "assets/intermissions.ron";
+// Before the shop, which waits for this to be dealt with
+const CHOICE_DELAY_SECONDS: f32 = 0.5;
+// Crates land somewhere in front of the player, never right on top of them
+const CRATE_MIN_DISTANCE: f32 = 1.5;
+const CRATE_MAX_DISTANCE: f32 = 3.5;
+const CRATE_SPREAD: f32 = 1.5;
+const CRATE_SIZE: f32 = 0.3;
+const CRATE_DROP_HEIGHT: f32 = 6.;
+// Metres per second
+const CRATE_FALL_SPEED: f32 = 4.;
+// How far from the crate what's inside ends up
+const CONTENTS_SPREAD: f32 = 0.4;
+const SUPPLY_DROP_COLOR: Color = Color::rgb(0.9, 0.75, 0.4);
+
+pub struct IntermissionPlugin;
+
+impl Plugin for IntermissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(IntermissionEvents::load())
+            .init_resource::<WaveModifier>()
+            .init_resource::<ChoiceMenu>()
+            .add_startup_system(setup_crate_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(pick_intermission)
+                    .with_system(drop_crates)
+                    .with_system(open_crates.after(drop_crates))
+                    .with_system(offer_choice.after(pick_intermission)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Choice).with_system(spawn_choice))
+            .add_system_set(
+                SystemSet::on_update(GameState::Choice)
+                    .with_system(navigate_choice)
+                    .with_system(update_choice_text.after(navigate_choice)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Choice).with_system(close_choice))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(reset_intermissions),
+            );
+    }
+}
+
+// Something that makes the players better at one thing for a wave
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Modifier {
+    Damage(f32),
+    FireRate(f32),
+    MoveSpeed(f32),
+    Pierce(u32),
+}
+
+impl Modifier {
+    pub fn describe(&self, localization: &Localization) -> String {
+        let percent = |multiplier: f32| ((multiplier - 1.) * 100.).round();
+        match self {
+            Modifier::Damage(x) => {
+                localization.format("modifier-damage", &[("percent", &percent(*x))])
+            }
+            Modifier::FireRate(x) => {
+                localization.format("modifier-fire-rate", &[("percent", &percent(*x))])
+            }
+            Modifier::MoveSpeed(x) => {
+                localization.format("modifier-move-speed", &[("percent", &percent(*x))])
+            }
+            Modifier::Pierce(n) => localization.format("modifier-pierce", &[("count", n)]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum IntermissionEvent {
+    #[default]
+    Nothing,
+    SupplyDrop {
+        ammo: u32,
+        health: u32,
+    },
+    Choice(Modifier, Modifier),
+}
+
+// One thing that might happen between waves, as written in intermissions.ron
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct IntermissionDefinition {
+    pub event: IntermissionEvent,
+    pub weight: f32,
+    pub from_wave: u32,
+}
+
+impl Default for IntermissionDefinition {
+    fn default() -> Self {
+        Self {
+            event: IntermissionEvent::default(),
+            weight: 1.,
+            from_wave: 1,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct IntermissionEvents(pub Vec<IntermissionDefinition>);
+
+impl IntermissionEvents {
+    fn load() -> Self {
+        let path = Path::new(INTERMISSIONS_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/intermissions.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, nothing will happen between waves: {e}");
+                return Self::default();
+            }
+        };
+
+        match ron::from_str::<Vec<IntermissionDefinition>>(&contents) {
+            Ok(events) => Self(events),
+            Err(e) => {
+                error!("{path:?} is invalid, nothing will happen between waves: {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+// Whatever was taken from the last choice, until the wave it was for is cleared. Goes on top of
+// the upgrades.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct WaveModifier(pub Option<Modifier>);
+
+impl WaveModifier {
+    pub fn apply(&self, stats: WeaponStats) -> WeaponStats {
+        match self.0 {
+            Some(Modifier::Damage(x)) => WeaponStats {
+                damage: stats.damage * x,
+                ..stats
+            },
+            Some(Modifier::FireRate(x)) => WeaponStats {
+                fire_rate: stats.fire_rate * x,
+                ..stats
+            },
+            Some(Modifier::Pierce(n)) => WeaponStats {
+                pierce: stats.pierce + n,
+                ..stats
+            },
+            _ => stats,
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self.0 {
+            Some(Modifier::MoveSpeed(x)) => x,
+            _ => 1.,
+        }
+    }
+}
+
+// The two on offer, and which one's highlighted
+#[derive(Resource, Default)]
+struct ChoiceMenu {
+    offer: Option<(Modifier, Modifier)>,
+    row: usize,
+}
+
+#[derive(Component)]
+struct ChoiceUi;
+
+#[derive(Component)]
+struct ChoiceText;
+
+// On its way down, then waiting to be walked into
+#[derive(Component)]
+struct SupplyCrate {
+    ammo: u32,
+    health: u32,
+    landed: bool,
+}
+
+#[derive(Resource)]
+struct CrateAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_crate_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(CrateAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: CRATE_SIZE })),
+        material: materials.add(Color::rgb(0.55, 0.38, 0.2).into()),
+    });
+}
+
+// Each cleared wave gets one of whatever's allowed by then, the last wave's modifier having run
+// its course. Recordings and online games have nobody to pick from a choice, so they're only
+// ever offered the rest.
+fn pick_intermission(
+    mut commands: Commands,
+    mut cleared: EventReader<WaveCleared>,
+    events: Res<IntermissionEvents>,
+    mut modifier: ResMut<WaveModifier>,
+    mut menu: ResMut<ChoiceMenu>,
+    mut rng: ResMut<GameRng>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    assets: Res<CrateAssets>,
+    game: Res<Game>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Some(wave) = cleared.iter().last().map(|cleared| cleared.wave) else { return };
+    modifier.0 = None;
+
+    let can_choose = matches!(*source, InputSource::Live) && netplay.is_none();
+    let allowed = events
+        .0
+        .iter()
+        .filter(|definition| definition.from_wave <= wave)
+        .filter(|definition| {
+            can_choose || !matches!(definition.event, IntermissionEvent::Choice(..))
+        })
+        .collect::<Vec<_>>();
+    let chosen = allowed.choose_weighted(&mut rng.0, |definition| definition.weight);
+    let Ok(definition) = chosen else { return };
+
+    match definition.event {
+        IntermissionEvent::Nothing => {}
+        IntermissionEvent::SupplyDrop { ammo, health } => {
+            let Ok(player) = transforms.get(game.player) else { return };
+            let player = player.translation();
+            let position = Vec3::new(
+                player.x + rng.0.gen_range(-CRATE_SPREAD..CRATE_SPREAD),
+                CRATE_DROP_HEIGHT,
+                player.z - rng.0.gen_range(CRATE_MIN_DISTANCE..CRATE_MAX_DISTANCE),
+            );
+            commands.spawn((
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    transform: Transform::from_translation(position),
+                    ..default()
+                },
+                SupplyCrate {
+                    ammo,
+                    health,
+                    landed: false,
+                },
+                DespawnOnReset,
+            ));
+        }
+        IntermissionEvent::Choice(first, second) => {
+            menu.offer = Some((first, second));
+        }
+    }
+}
+
+fn drop_crates(
+    mut crates: Query<(&mut Transform, &mut SupplyCrate)>,
+    time: Res<Time>,
+    localization: Res<Localization>,
+    mut popups: EventWriter<PopupEvent>,
+) {
+    let floor = CRATE_SIZE / 2.;
+    for (mut transform, mut supply) in &mut crates {
+        if supply.landed {
+            continue;
+        }
+        transform.translation.y -= CRATE_FALL_SPEED * time.delta_seconds();
+        if transform.translation.y > floor {
+            continue;
+        }
+        transform.translation.y = floor;
+        supply.landed = true;
+        popups.send(PopupEvent {
+            text: localization.get("supply-drop").to_string(),
+            position: transform.translation,
+            color: SUPPLY_DROP_COLOR,
+        });
+    }
+}
+
+// Walking into one spills what's inside round about it
+fn open_crates(
+    mut commands: Commands,
+    crates: Query<(Entity, &GlobalTransform, &SupplyCrate)>,
+    players: Query<&GlobalTransform, With<Player>>,
+    assets: Res<PickupAssets>,
+    mut rng: ResMut<GameRng>,
+) {
+    let reach = PLAYER_RADIUS + CRATE_SIZE / 2.;
+    for (entity, transform, supply) in &crates {
+        if !supply.landed {
+            continue;
+        }
+        let position = transform.translation() * Vec3::new(1., 0., 1.);
+        let opened = players.iter().any(|player| {
+            (player.translation() * Vec3::new(1., 0., 1.)).distance(position) <= reach
+        });
+        if !opened {
+            continue;
+        }
+
+        commands.entity(entity).despawn_recursive();
+        let contents = std::iter::repeat(PickupKind::Ammo)
+            .take(supply.ammo as usize)
+            .chain(std::iter::repeat(PickupKind::Health).take(supply.health as usize));
+        for kind in contents {
+            let offset = Vec3::new(
+                rng.0.gen_range(-CONTENTS_SPREAD..CONTENTS_SPREAD),
+                0.,
+                rng.0.gen_range(-CONTENTS_SPREAD..CONTENTS_SPREAD),
+            );
+            spawn_pickup(&mut commands, &assets, kind, position + offset);
+        }
+    }
+}
+
+// A moment after the wave's cleared, the same as the shop
+fn offer_choice(
+    mut menu: ResMut<ChoiceMenu>,
+    mut countdown: Local<Option<Timer>>,
+    time: Res<Time>,
+    waves: Res<WaveManager>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.offer.is_none() {
+        *countdown = None;
+        return;
+    }
+    let timer =
+        countdown.get_or_insert_with(|| Timer::from_seconds(CHOICE_DELAY_SECONDS, TimerMode::Once));
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    *countdown = None;
+    // The run might have moved on without us
+    if waves.phase != WavePhase::Intermission {
+        menu.offer = None;
+        return;
+    }
+    if let Err(e) = state.push(GameState::Choice) {
+        warn!("Unable to offer a choice: {e:?}");
+    }
+}
+
+fn spawn_choice(
+    mut commands: Commands,
+    mut menu: ResMut<ChoiceMenu>,
+    asset_server: Res<AssetServer>,
+) {
+    menu.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            ChoiceUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                ChoiceText,
+            ));
+        });
+}
+
+// Up and down pick one, confirm takes it, back passes on both
+fn navigate_choice(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut menu: ResMut<ChoiceMenu>,
+    mut modifier: ResMut<WaveModifier>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back || input.confirm {
+        if let Some((first, second)) = menu.offer.take() {
+            if input.confirm {
+                modifier.0 = Some(if menu.row == 0 { first } else { second });
+            }
+        }
+        if let Err(e) = state.pop() {
+            warn!("Unable to leave the choice: {e:?}");
+        }
+        return;
+    }
+
+    menu.row = navigation.row(menu.row, 2);
+}
+
+fn update_choice_text(
+    menu: Res<ChoiceMenu>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<ChoiceText>>,
+) {
+    let Some((first, second)) = menu.offer else { return };
+    let mut panel = format!("{}\n\n", localization.get("choice-title"));
+    for (index, modifier) in [first, second].iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        panel += &format!("{cursor} {}\n", modifier.describe(&localization));
+    }
+    panel += &format!("\n{}", localization.get("choice-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn close_choice(
+    mut commands: Commands,
+    mut menu: ResMut<ChoiceMenu>,
+    ui: Query<Entity, With<ChoiceUi>>,
+) {
+    menu.offer = None;
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn reset_intermissions(mut modifier: ResMut<WaveModifier>, mut menu: ResMut<ChoiceMenu>) {
+    *modifier = WaveModifier::default();
+    menu.offer = None;
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::InputSource, netplay::Netplay, state::GameState, time_scale::TimeScale,
+    waves::WaveCleared, CameraRig, EnemyKilled, Game,
+};
+
+// How slow things get, how long they stay that way and how long they take to come back, in real
+// seconds
+const SLOW_MOTION_SCALE: f32 = 0.2;
+const SLOW_MOTION_HOLD_SECONDS: f32 = 0.6;
+const SLOW_MOTION_RAMP_SECONDS: f32 = 0.8;
+// How far the camera moves in on the last beet at the slowest point. It never gets closer than
+// half the way there.
+const PUSH_IN_DISTANCE: f32 = 0.8;
+
+pub struct KillCamPlugin;
+
+impl Plugin for KillCamPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillCam>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(start_kill_cam)
+                .with_system(push_in.after(start_kill_cam)),
+        );
+    }
+}
+
+// Where the last beet died, so there's something to look at once the wave's cleared
+#[derive(Resource, Default)]
+struct KillCam {
+    last_kill: Option<Vec3>,
+    focus: Option<Vec3>,
+}
+
+// Recordings and online games keep to normal speed, so the run plays out the same every time
+fn start_kill_cam(
+    mut kill_cam: ResMut<KillCam>,
+    mut killed: EventReader<EnemyKilled>,
+    mut cleared: EventReader<WaveCleared>,
+    mut time_scale: ResMut<TimeScale>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+) {
+    if let Some(kill) = killed.iter().last() {
+        kill_cam.last_kill = Some(kill.position);
+    }
+    if cleared.iter().count() == 0 {
+        return;
+    }
+    if !matches!(*source, InputSource::Live) || netplay.is_some() {
+        return;
+    }
+    let Some(focus) = kill_cam.last_kill.take() else { return };
+
+    kill_cam.focus = Some(focus);
+    time_scale.dilate(
+        SLOW_MOTION_SCALE,
+        SLOW_MOTION_HOLD_SECONDS,
+        SLOW_MOTION_RAMP_SECONDS,
+    );
+}
+
+// Follows the slowdown in and back out again
+fn push_in(
+    mut kill_cam: ResMut<KillCam>,
+    mut rig: ResMut<CameraRig>,
+    time_scale: Res<TimeScale>,
+    game: Res<Game>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let weight = time_scale.dilation_weight();
+    let Some(focus) = kill_cam.focus else { return };
+    if weight <= 0. {
+        kill_cam.focus = None;
+        rig.push_in = Vec3::ZERO;
+        return;
+    }
+    let Ok(camera) = transforms.get(game.camera) else { return };
+
+    // From where the camera would be without any push
+    let offset = focus - (camera.translation() - rig.push_in);
+    let distance = PUSH_IN_DISTANCE.min(offset.length() * 0.5);
+    rig.push_in = offset.normalize_or_zero() * distance * weight;
+}
@@ -0,0 +1,120 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{Enemy, Projectile};
+
+const SAMPLE_SECONDS: f32 = 1.;
+// How many samples we look back over, and how much a category has to grow over them before we
+// call it a leak rather than a busy wave.
+const WINDOW: usize = 10;
+const MIN_GROWTH: usize = 5;
+
+pub struct LeakDetectorPlugin;
+
+impl Plugin for LeakDetectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LeakDetector>()
+            .insert_resource(LeakSampleTimer(Timer::from_seconds(
+                SAMPLE_SECONDS,
+                TimerMode::Repeating,
+            )))
+            // Last, so despawns from commands applied at the end of PostUpdate (kills, say) have
+            // landed, and before the removals are forgotten at the end of the frame
+            .add_system_to_stage(CoreStage::Last, track::<Enemy>.before(sample_live_counts))
+            .add_system_to_stage(
+                CoreStage::Last,
+                track::<Projectile>.before(sample_live_counts),
+            )
+            .add_system_to_stage(CoreStage::Last, sample_live_counts);
+    }
+}
+
+#[derive(Default)]
+pub struct CategoryStats {
+    pub spawned: u64,
+    pub despawned: u64,
+    // Counted straight from the world rather than worked out from the other two, so a despawn
+    // that's missed can't look like a leak
+    pub live: u64,
+    pub leaking: bool,
+    samples: VecDeque<u64>,
+}
+
+impl CategoryStats {
+    fn is_growing(&self) -> bool {
+        if self.samples.len() < WINDOW {
+            return false;
+        }
+
+        let monotonic = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .all(|(a, b)| b >= a);
+        let growth = self.samples.back().unwrap() - self.samples.front().unwrap();
+        monotonic && growth >= MIN_GROWTH as u64
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LeakDetector {
+    pub categories: HashMap<&'static str, CategoryStats>,
+}
+
+#[derive(Resource)]
+struct LeakSampleTimer(Timer);
+
+fn category_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn track<T: Component>(
+    mut detector: ResMut<LeakDetector>,
+    alive: Query<(), With<T>>,
+    added: Query<(), Added<T>>,
+    removed: RemovedComponents<T>,
+) {
+    let spawned = added.iter().count() as u64;
+    let despawned = removed.iter().count() as u64;
+    let live = alive.iter().count() as u64;
+    let name = category_name::<T>();
+    if spawned == 0 && despawned == 0 && !detector.categories.contains_key(name) {
+        return;
+    }
+
+    let stats = detector.categories.entry(name).or_default();
+    stats.spawned += spawned;
+    stats.despawned += despawned;
+    stats.live = live;
+}
+
+fn sample_live_counts(
+    mut detector: ResMut<LeakDetector>,
+    mut timer: ResMut<LeakSampleTimer>,
+    time: Res<Time>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for (name, stats) in detector.categories.iter_mut() {
+        let live = stats.live;
+        stats.samples.push_back(live);
+        if stats.samples.len() > WINDOW {
+            stats.samples.pop_front();
+        }
+
+        let leaking = stats.is_growing();
+        if leaking && !stats.leaking {
+            warn!(
+                "Possible {name} leak: {live} alive after growing for {WINDOW} samples ({} spawned, {} despawned)",
+                stats.spawned, stats.despawned
+            );
+        } else if !leaking && stats.leaking {
+            info!("{name} count has stopped growing ({live} alive)");
+        }
+        stats.leaking = leaking;
+    }
+}
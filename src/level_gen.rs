@@ -0,0 +1,199 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{hazards::HazardKind, pickups::PickupKind};
+
+// Halfway along environment.glb, which is what the mirrored variant spins around
+const ENVIRONMENT_CENTER_Z: f32 = -16.85;
+// Props and spawn points stay within the lane the player can actually reach
+const LANE_HALF_WIDTH: f32 = 2.;
+const PROPS_PER_CHUNK: std::ops::RangeInclusive<usize> = 2..=5;
+// Keep the start of the run clear so the player isn't boxed in on the first frame
+const CLEAR_START_Z: f32 = -6.;
+const SPAWN_POINT_SPACING: f32 = 1.5;
+// Roughly how far each prop's footprint reaches from its origin, in the same order as the scenes.
+// Only used when there's no asset manifest to measure them from.
+const PROP_RADII: [f32; 8] = [0.3, 0.3, 0.15, 0.2, 0.15, 0.25, 0.3, 0.25];
+const BREAKABLE_CHANCE: f64 = 0.3;
+const HAZARDS_PER_CHUNK: std::ops::RangeInclusive<usize> = 0..=2;
+const PICKUPS_PER_CHUNK: std::ops::RangeInclusive<usize> = 1..=3;
+// Out of every ten pickups left lying around, how many patch the player up rather than reload
+const HEALTH_PICKUP_CHANCE: f64 = 0.4;
+
+pub const PROP_SCENES: [&str; 8] = [
+    "broccoli.glb#Scene0",
+    "cauliflower.glb#Scene0",
+    "celeryStick.glb#Scene0",
+    "eggplant.glb#Scene0",
+    "leek.glb#Scene0",
+    "onion.glb#Scene0",
+    "salad.glb#Scene0",
+    "tomato.glb#Scene0",
+];
+
+// The seed the whole run is generated from. Share it and someone else gets the same level.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LevelSeed(pub u64);
+
+#[derive(Resource)]
+pub struct LevelAssets {
+    pub props: Vec<Handle<Scene>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileVariant {
+    Normal,
+    // Spun around halfway down the chunk
+    Turned,
+}
+
+impl TileVariant {
+    pub fn transform(&self) -> Transform {
+        match self {
+            TileVariant::Normal => Transform::IDENTITY,
+            TileVariant::Turned => Transform::from_xyz(0., 0., 2. * ENVIRONMENT_CENTER_Z)
+                .with_rotation(Quat::from_rotation_y(PI)),
+        }
+    }
+}
+
+pub struct PropPlacement {
+    pub scene: usize,
+    // Relative to the chunk
+    pub position: Vec3,
+    pub rotation: f32,
+    pub radius: f32,
+    // Can be shot to pieces
+    pub breakable: bool,
+}
+
+pub struct HazardPlacement {
+    pub kind: HazardKind,
+    // Relative to the chunk
+    pub position: Vec3,
+}
+
+pub struct PickupPlacement {
+    pub kind: PickupKind,
+    // World space, since pickups don't belong to a chunk once they start moving
+    pub position: Vec3,
+}
+
+pub struct ChunkLayout {
+    pub variant: TileVariant,
+    pub props: Vec<PropPlacement>,
+    pub hazards: Vec<HazardPlacement>,
+    pub pickups: Vec<PickupPlacement>,
+    // World space, ready for spawning enemies
+    pub spawn_points: Vec<Vec3>,
+}
+
+pub struct LevelGenPlugin;
+
+impl Plugin for LevelGenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_level_assets)
+            .add_startup_system(log_seed);
+    }
+}
+
+fn load_level_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelAssets {
+        props: PROP_SCENES
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    });
+}
+
+fn log_seed(seed: Res<LevelSeed>) {
+    info!("Level seed is {}", seed.0);
+}
+
+// Each chunk gets its own RNG derived from the level seed, so a chunk always comes out the same
+// no matter what order chunks stream in.
+fn chunk_rng(seed: LevelSeed, chunk: i32) -> StdRng {
+    StdRng::seed_from_u64(seed.0 ^ (chunk as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+pub fn generate_chunk(seed: LevelSeed, chunk: i32, chunk_length: f32) -> ChunkLayout {
+    let mut rng = chunk_rng(seed, chunk);
+    let chunk_z = -chunk as f32 * chunk_length;
+
+    let variant = *[TileVariant::Normal, TileVariant::Turned]
+        .choose(&mut rng)
+        .unwrap();
+
+    let prop_count = rng.gen_range(PROPS_PER_CHUNK);
+    let props = (0..prop_count)
+        .filter_map(|_| {
+            let position = Vec3::new(
+                rng.gen_range(-LANE_HALF_WIDTH..LANE_HALF_WIDTH),
+                0.,
+                rng.gen_range(-chunk_length..0.),
+            );
+            let scene = rng.gen_range(0..PROP_SCENES.len());
+            let placement = PropPlacement {
+                scene,
+                position,
+                rotation: rng.gen_range(0. ..(2. * PI)),
+                radius: PROP_RADII[scene],
+                breakable: rng.gen_bool(BREAKABLE_CHANCE),
+            };
+            (chunk_z + position.z < CLEAR_START_Z).then_some(placement)
+        })
+        .collect();
+
+    let spawn_point_count = (chunk_length / SPAWN_POINT_SPACING) as usize;
+    let spawn_points = (0..spawn_point_count)
+        .map(|i| {
+            Vec3::new(
+                rng.gen_range(-LANE_HALF_WIDTH..LANE_HALF_WIDTH),
+                0.,
+                chunk_z - i as f32 * SPAWN_POINT_SPACING,
+            )
+        })
+        .collect();
+
+    let hazard_count = rng.gen_range(HAZARDS_PER_CHUNK);
+    let hazards = (0..hazard_count)
+        .filter_map(|_| {
+            let kind = *[HazardKind::CompostPit, HazardKind::Sprinkler]
+                .choose(&mut rng)
+                .unwrap();
+            let position = Vec3::new(
+                rng.gen_range(-LANE_HALF_WIDTH..LANE_HALF_WIDTH),
+                0.,
+                rng.gen_range(-chunk_length..0.),
+            );
+            (chunk_z + position.z < CLEAR_START_Z).then_some(HazardPlacement { kind, position })
+        })
+        .collect();
+
+    let pickup_count = rng.gen_range(PICKUPS_PER_CHUNK);
+    let pickups = (0..pickup_count)
+        .map(|_| {
+            let kind = if rng.gen_bool(HEALTH_PICKUP_CHANCE) {
+                PickupKind::Health
+            } else {
+                PickupKind::Ammo
+            };
+            let position = Vec3::new(
+                rng.gen_range(-LANE_HALF_WIDTH..LANE_HALF_WIDTH),
+                0.,
+                chunk_z - rng.gen_range(0. ..chunk_length),
+            );
+            PickupPlacement { kind, position }
+        })
+        .collect();
+
+    ChunkLayout {
+        variant,
+        props,
+        hazards,
+        pickups,
+        spawn_points,
+    }
+}
@@ -0,0 +1,2258 @@
+use accessibility::AccessibilityPlugin;
+use ambience::AmbiencePlugin;
+use animation::{AnimationState, Animated, CharacterAnimationPlugin};
+use armory::ArmoryPlugin;
+use asset_manifest::AssetManifestPlugin;
+use atmosphere::AtmospherePlugin;
+use attract::AttractPlugin;
+use behaviours::{BehaviourPlugin, Pace};
+use bench::{BenchPlugin, DEFAULT_BENCH_ENEMIES, DEFAULT_BENCH_PROJECTILES};
+use bevy::{app::PluginGroupBuilder, ecs::query::ReadOnlyWorldQuery, gltf::Gltf, prelude::*};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::render::{render_resource::WgpuFeatures, settings::WgpuSettings};
+use calibration::CalibrationPlugin;
+use campaign::{Boss, CampaignPlugin};
+use characters::{Character, CharacterDefinition, CharacterPlugin, Characters};
+use cinematic::CinematicPlugin;
+use cli::Options;
+use config::{ConfigPlugin, GameConfig};
+use console::ConsolePlugin;
+use coop::CoopPlugin;
+use crops::{Crop, CropPlugin};
+use daily::DailyPlugin;
+use damage::{DamageEvent, DamagePlugin, Health, Knockback};
+#[cfg(debug_assertions)]
+use debug_overlay::DebugOverlayPlugin;
+// Wireframes need POLYGON_MODE_LINE, which WebGL doesn't have
+#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+use debug_render::DebugRenderPlugin;
+use destructibles::DestructiblePlugin;
+use difficulty::{Difficulty, DifficultyPlugin};
+use dodge::{Dodge, DodgePlugin};
+use drone::DronePlugin;
+use endless::EndlessPlugin;
+use game_over::GameOverPlugin;
+use garden::GardenPlugin;
+use golden::GoldenTestPlugin;
+use graze::{GrazePlugin, Grazed};
+use grenade::{GrenadeCooldown, GrenadePlugin};
+use ground::GroundPlugin;
+use hazards::HazardPlugin;
+use headless::HeadlessPlugin;
+use health_bars::HealthBarPlugin;
+use high_scores::HighScorePlugin;
+use highlight::TargetHighlightPlugin;
+use horde::HordeRenderingPlugin;
+#[cfg(feature = "hot_reload")]
+use hot_reload::HotReloadPlugin;
+use hotplug::HotplugPlugin;
+use hurt::{HurtPlugin, Invulnerable};
+use input::{InputRecorder, InputSource, PlayerInput, PlayerInputPlugin};
+#[cfg(feature = "inspector")]
+use inspector::InspectorPlugin;
+use intermission::{IntermissionPlugin, WaveModifier};
+use kill_cam::KillCamPlugin;
+#[cfg(debug_assertions)]
+use leak_detector::LeakDetectorPlugin;
+use level_gen::{LevelGenPlugin, LevelSeed};
+use lighting::LightingPlugin;
+use localization::LocalizationPlugin;
+use lock_on_camera::LockOnCameraPlugin;
+use loot::LootPlugin;
+use low_health::LowHealthPlugin;
+use melee::{Melee, MeleePlugin};
+use menu::MainMenuPlugin;
+use mutators::{Mutator, MutatorPlugin, Mutators};
+use navigation::MenuNavigationPlugin;
+use netplay::{NetRole, Netplay, NetplayPlugin};
+use objective::{Objective, ObjectivePlugin, Protected};
+use obstacles::{Collider, Obstacle, ObstaclePlugin};
+use pause::PausePlugin;
+use photo_mode::PhotoModePlugin;
+use pickups::PickupPlugin;
+use popups::PopupPlugin;
+use radar::RadarPlugin;
+use remap::RemapPlugin;
+use reset::{DespawnOnReset, ResetPlugin};
+use results::ResultsPlugin;
+use revive::{Downed, RevivePlugin};
+use score::ScorePlugin;
+use screen_effects::ScreenEffectsPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+use screenshot::ScreenshotPlugin;
+use settings::{AimAssist, AimMode, Settings, SettingsPlugin};
+use shield::{Shield, ShieldPlugin, ShieldStruck};
+use shop::{ShopPlugin, Upgrades};
+use spawners::{Spawner, SpawnerPlugin};
+use sprint::{SprintPlugin, Stamina};
+use stagger::{StaggerPlugin, Staggered};
+use state::{GameState, GameStatePlugin};
+use stats::RunStatsPlugin;
+use status::{ApplyStatus, Inflicts, Slowed, StatusPlugin, Stunned};
+use streaming::{EnvironmentChunks, EnvironmentStreamingPlugin};
+use suspend::SuspendPlugin;
+use telegraph::{spawn_telegraph, SpawnTelegraph, TelegraphAssets, TelegraphPlugin};
+use telemetry::TelemetryPlugin;
+use threat_camera::ThreatCameraPlugin;
+use time_scale::TimeScalePlugin;
+use timeline::RunTimelinePlugin;
+use turret::{TurretCharges, TurretPlugin};
+use tutorial::TutorialPlugin;
+use ultimate::UltimatePlugin;
+use video::{DisplayMode, ShadowQuality, VideoPlugin, VideoSettings};
+use waves::{WaveManager, WavePlugin};
+use weapon_hud::WeaponHudPlugin;
+use weapons::{FireCooldown, FireMode, Loadout, WeaponPlugin};
+use weather::WeatherPlugin;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+mod accessibility;
+mod ambience;
+mod animation;
+mod armory;
+mod asset_manifest;
+mod atmosphere;
+mod attract;
+mod behaviours;
+mod bench;
+mod calibration;
+mod campaign;
+mod characters;
+mod cinematic;
+mod cli;
+mod config;
+mod console;
+mod coop;
+mod crops;
+mod daily;
+mod damage;
+#[cfg(debug_assertions)]
+mod debug_overlay;
+#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+mod debug_render;
+mod destructibles;
+mod difficulty;
+mod dodge;
+mod drone;
+mod endless;
+mod game_over;
+mod garden;
+mod golden;
+mod graze;
+mod grenade;
+mod ground;
+mod hazards;
+mod headless;
+mod health_bars;
+mod high_scores;
+mod highlight;
+mod horde;
+#[cfg(feature = "hot_reload")]
+mod hot_reload;
+mod hotplug;
+mod hurt;
+mod input;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod intermission;
+mod kill_cam;
+#[cfg(debug_assertions)]
+mod leak_detector;
+mod level_gen;
+mod lighting;
+mod localization;
+mod lock_on_camera;
+mod loot;
+mod low_health;
+mod melee;
+mod menu;
+mod mutators;
+mod navigation;
+mod netplay;
+mod objective;
+mod obstacles;
+mod pause;
+mod photo_mode;
+mod pickups;
+mod popups;
+pub mod prelude;
+mod radar;
+mod remap;
+mod reset;
+mod results;
+mod revive;
+mod score;
+mod screen_effects;
+mod screenshot;
+mod settings;
+mod shield;
+mod shop;
+mod spawn_rules;
+mod spawners;
+mod sprint;
+mod stagger;
+mod state;
+mod stats;
+mod status;
+mod steering;
+mod storage;
+mod streaming;
+mod suspend;
+mod telegraph;
+mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod threat_camera;
+mod time_scale;
+mod timeline;
+mod turret;
+mod tutorial;
+mod ultimate;
+mod video;
+mod waves;
+mod weapon_hud;
+mod weapons;
+mod weather;
+
+const PLAYER_SPEED: f32 = 0.05;
+const ENEMY_SPEED: f32 = 0.01;
+const PROJECTILE_SPEED: f32 = 0.05;
+const HIT_THRESHOLD: f32 = 0.1;
+// How fast a pumpkin shoves a beet back along its flight, per frame. It dies away from there.
+const PROJECTILE_KNOCKBACK: f32 = 0.06;
+// Splash damage is this much of a direct hit's
+const BLAST_DAMAGE_FRACTION: f32 = 0.5;
+// A pumpkin that goes through a beet carries on with this much of its damage
+const PIERCE_DAMAGE_FRACTION: f32 = 0.6;
+// What's left of a shot's damage by the time it's flown its weapon's max range
+const FALLOFF_DAMAGE_FRACTION: f32 = 0.4;
+// How far a ricochet will look for its next beet
+const RICOCHET_RANGE: f32 = 3.;
+// How long fire has to be held before letting go of it looses a charged shot, and what that does
+const CHARGE_SECONDS: f32 = 1.;
+const CHARGED_DAMAGE_MULTIPLIER: f32 = 2.;
+const CHARGED_SCALE: f32 = 1.6;
+// How long a press of fire is held on to while locked on with nothing to shoot at, in case a lock
+// turns up
+const FIRE_BUFFER_SECONDS: f32 = 0.15;
+// Further than any beet walks in a frame, so it must have been leashed or teleported
+const MAX_TRACKED_STEP: f32 = 0.5;
+// A beet that comes this close to a crop goes for it, unless a player's closer still
+const CROP_DIVERT_RANGE: f32 = 2.5;
+// On Normal, how far assisted play looks for something to lock on to
+const ASSISTED_RANGE: f32 = 7.;
+const ENEMY_HEALTH: f32 = 1.;
+const PLAYER_HEALTH: f32 = 5.;
+const STARTING_AMMO: u32 = 30;
+const PLAYER_RADIUS: f32 = 0.15;
+const ENEMY_RADIUS: f32 = 0.15;
+// Enemies stop this close to a player and start winding up a hit. Anyone who's got further away
+// than the reach by the time it lands gets away with it.
+const ENEMY_ATTACK_RANGE: f32 = 0.35;
+const ENEMY_ATTACK_REACH: f32 = 0.5;
+const ENEMY_WINDUP_SECONDS: f32 = 0.5;
+const ENEMY_ATTACK_COOLDOWN_SECONDS: f32 = 1.5;
+const ENEMY_ATTACK_DAMAGE: f32 = 1.;
+// A swing that misses a player by less than this past its reach counts as a graze
+const GRAZE_MARGIN: f32 = 0.2;
+// Enemies closer together than this start shuffling apart
+const ENEMY_SEPARATION: f32 = 0.5;
+const ENEMY_SEPARATION_WEIGHT: f32 = 1.5;
+// How quickly a beet turns to face the way it's going, in radians a second
+const ENEMY_TURN_SPEED: f32 = 8.;
+// Any slower than this a frame and it's shuffling about rather than going anywhere
+const MIN_HEADING_STEP: f32 = 0.001;
+// How fast the camera scrolls down the lane on the first wave. Each wave after adds a bit on top,
+// up to a limit.
+const CAMERA_SPEED: f32 = 0.009;
+const CAMERA_SPEED_PER_WAVE: f32 = 0.05;
+const CAMERA_MAX_WAVE_SPEED: f32 = 1.5;
+// A player who gets this far further up the lane than where they started, relative to the camera,
+// has it speed up to catch them, by this much more for every metre beyond it, up to the limit
+const CAMERA_CATCH_UP_AFTER: f32 = 1.5;
+const CAMERA_CATCH_UP_PER_METRE: f32 = 0.006;
+const CAMERA_MAX_CATCH_UP: f32 = 0.02;
+// How much of the way to its new speed the camera gets each frame
+const CAMERA_PACING_EASE: f32 = 0.02;
+// How far up the lane from the camera enemies arrive
+const ENEMY_SPAWN_DISTANCE: f32 = 10.;
+// ..and how far behind it they can fall before they're sent back there
+const ENEMY_LEASH_DISTANCE: f32 = 3.;
+// On Normal. The difficulty scales it from there.
+const ENEMY_SPAWN_SECONDS: f32 = 3.;
+// Co-op players can get this far apart before the camera starts pulling back to keep them both in
+// shot, and it pulls back at most this far
+const CAMERA_COMFORTABLE_SPREAD: f32 = 1.5;
+const CAMERA_MAX_ZOOM: f32 = 4.;
+// How quickly the camera catches up with where it wants to be, per second
+const CAMERA_FRAMING_RATE: f32 = 3.;
+// Without lock-on, a full push of the right stick swings the gun this far off straight ahead
+const FREE_AIM_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+// Magnetism only pulls towards beets within this angle of where the gun's pointing
+const MAGNETISM_CONE: f32 = std::f32::consts::FRAC_PI_6;
+// The spread from firing starts closing up this long after the last shot, by this many radians a
+// second
+const SPREAD_RECOVERY_DELAY: f32 = 0.25;
+const SPREAD_RECOVERY: f32 = 0.5;
+// How much wider it is on the move, at full tilt
+const MOVING_SPREAD: f32 = 0.1;
+
+// Everything `cargo run` does, from the command line up
+pub fn run() {
+    let options = Options::from_args();
+    if options.process_assets {
+        match asset_manifest::process_assets() {
+            Ok(path) => println!("Wrote {path:?}"),
+            Err(problems) => {
+                for problem in problems {
+                    eprintln!("{problem}");
+                }
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let netplay = match (options.host, options.join) {
+        (Some(port), _) => Some(NetRole::Host { port }),
+        (None, Some(address)) => Some(NetRole::Join { address }),
+        (None, None) => None,
+    };
+    // Replays start straight into the run they recorded, online games into the run both machines
+    // agreed on, and anyone who asks into a fresh one
+    let initial_state = if options.skip_menu
+        || options.playback.is_some()
+        || options.golden.is_some()
+        || options.headless.is_some()
+        || options.bench.is_some()
+        || netplay.is_some()
+    {
+        GameState::Playing
+    } else {
+        GameState::MainMenu
+    };
+
+    let mut app = App::new();
+    if options.headless.is_some() {
+        headless::add_headless_plugins(&mut app);
+    } else {
+        add_window_plugins(&mut app);
+    }
+
+    app.add_plugins(GamePlugins {
+        initial: initial_state,
+        seed,
+    });
+
+    #[cfg(feature = "inspector")]
+    app.add_plugin(InspectorPlugin);
+    #[cfg(feature = "hot_reload")]
+    app.add_plugin(HotReloadPlugin);
+    // Reading frames back off the GPU needs to wait for it, which a browser won't allow
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugin(ScreenshotPlugin);
+
+    #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+    app.add_plugin(DebugRenderPlugin);
+    #[cfg(debug_assertions)]
+    app.add_plugin(LeakDetectorPlugin)
+        .add_plugin(DebugOverlayPlugin);
+
+    // Overrides for this session, on top of whatever's been saved
+    if let Some(wave) = options.wave {
+        app.insert_resource(WaveManager::starting_at(wave));
+    }
+    if let Some(resolution) = options.windowed {
+        let mut video = app.world.resource_mut::<VideoSettings>();
+        video.display_mode = DisplayMode::Windowed;
+        video.resolution = resolution;
+    }
+    if options.no_shadows {
+        app.world.resource_mut::<VideoSettings>().shadows = ShadowQuality::Off;
+    }
+    if options.mute {
+        app.world.resource_mut::<Settings>().muted = true;
+    }
+
+    if let Some(path) = options.playback {
+        app.insert_resource(InputSource::playback(&path));
+    }
+    if let Some(path) = options.record {
+        app.insert_resource(InputRecorder::new(path));
+    }
+    if let Some(ticks) = options.headless {
+        app.add_plugin(HeadlessPlugin { ticks });
+    }
+    if let Some(dir) = options.golden {
+        app.add_plugin(GoldenTestPlugin {
+            dir,
+            bless: options.bless,
+        });
+    }
+    if let Some(frames) = options.bench {
+        app.add_plugin(BenchPlugin {
+            frames,
+            enemies: options.bench_enemies.unwrap_or(DEFAULT_BENCH_ENEMIES),
+            projectiles: options.bench_projectiles.unwrap_or(DEFAULT_BENCH_PROJECTILES),
+        });
+    }
+    if let Some(dir) = options.telemetry {
+        app.add_plugin(TelemetryPlugin { dir });
+    }
+    if let Some(role) = netplay {
+        app.add_plugin(NetplayPlugin { role, seed });
+    }
+
+    app.run();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn add_window_plugins(app: &mut App) {
+    // enable wireframe rendering
+    let mut wgpu_settings = WgpuSettings::default();
+    wgpu_settings.features |= WgpuFeatures::POLYGON_MODE_LINE;
+
+    app.insert_resource(wgpu_settings)
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            // Picks up art changes without a restart
+            watch_for_changes: cfg!(feature = "hot_reload"),
+            ..default()
+        }));
+}
+
+// In the browser the game draws into the page's canvas and grows to fill whatever it's put in, so
+// the page decides how big it is
+#[cfg(target_arch = "wasm32")]
+fn add_window_plugins(app: &mut App) {
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        window: WindowDescriptor {
+            title: "Vegetable Funeral".into(),
+            canvas: Some("#bevy".into()),
+            fit_canvas_to_parent: true,
+            ..default()
+        },
+        ..default()
+    }));
+}
+
+// The carrot, the beets and the pumpkins flying between them. Everything else is layered on top
+// by the other plugins.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Game>()
+            .init_resource::<CameraRig>()
+            .init_resource::<CameraPacing>()
+            .add_event::<WeaponFired>()
+            .add_event::<AmmoChanged>()
+            .add_event::<EnemyKilled>()
+            .add_event::<ProjectileHit>()
+            .insert_resource(EnemySpawnTimer(Timer::from_seconds(
+                ENEMY_SPAWN_SECONDS,
+                TimerMode::Repeating,
+            )))
+            .add_startup_system(setup_camera)
+            .add_startup_system(setup_models)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_movement)
+                    .with_system(spawn_enemy)
+                    .with_system(enemy_attack.before(enemy_movement))
+                    .with_system(enemy_movement)
+                    .with_system(leash_enemies.after(enemy_movement))
+                    .with_system(track_velocity.after(leash_enemies))
+                    .with_system(face_heading.after(track_velocity))
+                    .with_system(weapon_movement)
+                    .with_system(pace_camera)
+                    .with_system(camera_movement.after(pace_camera))
+                    .with_system(projectile_movement)
+                    .with_system(projectile_hit)
+                    .with_system(update_spread.before(weapon_fire))
+                    .with_system(weapon_fire)
+                    .with_system(player_aim),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_world));
+    }
+}
+
+// Every plugin that makes up the game, in the order they go in. A custom `App` can add these after
+// Bevy's own and then add plugins of its own, or leave any of these out and put something else in
+// their place:
+//
+//     App::new()
+//         .add_plugins(DefaultPlugins)
+//         .add_plugins(GamePlugins::default().build().disable::<WeaponPlugin>())
+//         .add_plugin(MyWeaponPlugin)
+//         .run();
+pub struct GamePlugins {
+    pub initial: GameState,
+    // For the level and everything else random in a run
+    pub seed: u64,
+}
+
+impl Default for GamePlugins {
+    fn default() -> Self {
+        Self {
+            initial: GameState::MainMenu,
+            seed: rand::random(),
+        }
+    }
+}
+
+impl PluginGroup for GamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(ConfigPlugin)
+            .add(SettingsPlugin)
+            .add(AccessibilityPlugin)
+            .add(LocalizationPlugin)
+            .add(VideoPlugin)
+            .add(RemapPlugin)
+            .add(CalibrationPlugin)
+            .add(GameStatePlugin {
+                initial: self.initial,
+            })
+            .add(MainMenuPlugin)
+            .add(AttractPlugin)
+            .add(AssetManifestPlugin)
+            .add(PlayerInputPlugin)
+            .add(MenuNavigationPlugin)
+            .add(CoopPlugin)
+            .add(RevivePlugin)
+            .add(PausePlugin)
+            .add(PhotoModePlugin)
+            .add(HotplugPlugin)
+            .add(LightingPlugin)
+            .add(AtmospherePlugin)
+            .add(WeatherPlugin)
+            .add(CharacterAnimationPlugin)
+            .add(TargetHighlightPlugin)
+            .add(AmbiencePlugin)
+            .add(LevelGenPlugin)
+            .add(EnvironmentStreamingPlugin)
+            .add(ObstaclePlugin)
+            .add(GroundPlugin)
+            .add(DamagePlugin)
+            .add(StatusPlugin)
+            .add(StaggerPlugin)
+            .add(ShieldPlugin)
+            .add(PopupPlugin)
+            .add(HealthBarPlugin)
+            .add(HordeRenderingPlugin)
+            .add(DifficultyPlugin)
+            .add(DodgePlugin)
+            .add(HurtPlugin)
+            .add(ScreenEffectsPlugin)
+            .add(LowHealthPlugin)
+            .add(SprintPlugin)
+            .add(GrenadePlugin)
+            .add(GrazePlugin)
+            .add(UltimatePlugin)
+            .add(MeleePlugin)
+            .add(TurretPlugin)
+            .add(DronePlugin)
+            .add(WavePlugin)
+            .add(TelegraphPlugin)
+            .add(BehaviourPlugin)
+            .add(PickupPlugin)
+            .add(LootPlugin)
+            .add(DestructiblePlugin)
+            .add(HazardPlugin)
+            .add(RunTimelinePlugin)
+            .add(WeaponPlugin)
+            .add(WeaponHudPlugin)
+            .add(ArmoryPlugin)
+            .add(ShopPlugin)
+            .add(IntermissionPlugin)
+            .add(EndlessPlugin)
+            .add(ScorePlugin)
+            .add(RunStatsPlugin)
+            .add(TimeScalePlugin)
+            .add(KillCamPlugin)
+            .add(CinematicPlugin)
+            .add(LockOnCameraPlugin)
+            .add(ThreatCameraPlugin)
+            .add(RadarPlugin)
+            .add(ObjectivePlugin)
+            .add(CropPlugin)
+            .add(SpawnerPlugin)
+            .add(TutorialPlugin)
+            .add(CampaignPlugin)
+            .add(ResultsPlugin)
+            .add(GardenPlugin)
+            .add(CharacterPlugin)
+            .add(MutatorPlugin)
+            .add(SuspendPlugin)
+            .add(GameOverPlugin)
+            .add(ResetPlugin)
+            .add(HighScorePlugin)
+            .add(DailyPlugin)
+            .add(ConsolePlugin)
+            .add(GameplayPlugin)
+            .add(SeedPlugin { seed: self.seed })
+    }
+}
+
+struct SeedPlugin {
+    seed: u64,
+}
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelSeed(self.seed))
+            .insert_resource(GameRng::from_seed(self.seed));
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Game {
+    player: Entity,
+    spud_gun: Entity,
+    camera: Entity,
+    #[reflect(ignore)]
+    enemies: Vec<Handle<Scene>>,
+    #[reflect(ignore)]
+    enemy_animations: Vec<Handle<Gltf>>,
+    #[reflect(ignore)]
+    projectile: Option<Handle<Scene>>,
+}
+
+#[derive(Component)]
+pub struct Enemy;
+
+// What an enemy's doing about the player in front of it
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AttackState {
+    #[default]
+    Chasing,
+    // Stood still, about to hit whoever it's closest to
+    WindingUp { remaining: f32 },
+    // Just hit someone (or missed), and can't again until this runs out
+    Recovering { remaining: f32 },
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Beet,
+}
+
+#[derive(Component)]
+pub struct Player {
+    // Player one is 0, and also gets the keyboard
+    pub index: usize,
+    pub weapon: Entity,
+}
+
+// The enemy a player is locked on to, if any
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Aim {
+    pub target: Option<Entity>,
+    is_aiming: bool,
+}
+
+// Beets painted so far with a barrage weapon, in the order they were painted
+#[derive(Component, Default)]
+pub struct Barrage {
+    pub painted: Vec<Entity>,
+}
+
+// How far off where it's aimed a player's next shot can go. Every shot opens it up, and it only
+// closes again once they've let off the trigger for a moment. Being on the move opens it further.
+#[derive(Component, Default)]
+pub struct Spread {
+    // Either side of straight on, in radians
+    pub angle: f32,
+    // From firing, on top of any from moving
+    bloom: f32,
+    since_shot: f32,
+}
+
+#[derive(Component)]
+pub struct Weapon;
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Ammo {
+    pub current: u32,
+    pub max: u32,
+}
+
+#[derive(Resource)]
+struct EnemySpawnTimer(Timer);
+
+// The camera scrolls down the lane on its own, and with more than one player it's also nudged
+// across and back so everyone stays in shot. Keeping the two apart means framing never affects
+// how far the lane has scrolled.
+#[derive(Resource)]
+struct CameraRig {
+    scroll: Vec3,
+    framing: Vec3,
+    // Towards something worth a closer look, like the kill-cam's last beet
+    push_in: Vec3,
+    // Across the lane and round towards whatever's locked on
+    lock_on: Vec3,
+    // Back and up to fit a crowd in
+    threat: Vec3,
+    yaw: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            scroll: camera_start().translation,
+            framing: Vec3::ZERO,
+            push_in: Vec3::ZERO,
+            lock_on: Vec3::ZERO,
+            threat: Vec3::ZERO,
+            yaw: 0.,
+        }
+    }
+}
+
+impl CameraRig {
+    // How far down the lane the run's got
+    fn travelled(&self) -> f32 {
+        camera_start().translation.z - self.scroll.z
+    }
+}
+
+// How fast the camera's scrolling down the lane, per frame like everything else that moves. Picks
+// up a little each wave, stops for a boss and hurries along after anyone who's run on ahead.
+#[derive(Resource)]
+pub struct CameraPacing {
+    pub speed: f32,
+}
+
+impl Default for CameraPacing {
+    fn default() -> Self {
+        Self {
+            speed: CAMERA_SPEED,
+        }
+    }
+}
+
+// All gameplay randomness goes through here so a run can be reproduced from its seed
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+pub struct WeaponFired {
+    pub weapon: Entity,
+}
+
+// Anything but firing that changes how much ammo a weapon has, like a pickup or a fresh run
+pub struct AmmoChanged {
+    pub weapon: Entity,
+}
+
+pub struct EnemyKilled {
+    pub entity: Entity,
+    pub kind: EnemyKind,
+    pub position: Vec3,
+}
+
+// A pumpkin found its beet. Splash damage doesn't count as another one.
+pub struct ProjectileHit {
+    pub target: Entity,
+    // Which way it was heading when it landed
+    pub direction: Vec3,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Projectile {
+    heading: Vec3,
+    // Crits are rolled when it's fired and already counted in here
+    damage: f32,
+    critical: bool,
+    blast_radius: f32,
+    // So one that's carrying on doesn't hit the same beet again on its way out
+    #[reflect(ignore)]
+    last_hit: Option<Entity>,
+}
+
+// Goes through this many more beets before it stops
+#[derive(Component)]
+struct Pierce {
+    remaining: u32,
+}
+
+// Bounces on to the nearest beet this many more times
+#[derive(Component)]
+struct Ricochet {
+    remaining: u32,
+}
+
+// How far a player's shot has flown, against its weapon's range. Turrets don't bother.
+#[derive(Component)]
+struct Falloff {
+    range: f32,
+    max_range: f32,
+    travelled: f32,
+}
+
+impl Falloff {
+    // Full damage out to the range, then down to a fraction of it by the max range
+    fn multiplier(&self) -> f32 {
+        let past = (self.travelled - self.range) / (self.max_range - self.range).max(f32::EPSILON);
+        1. - past.clamp(0., 1.) * (1. - FALLOFF_DAMAGE_FRACTION)
+    }
+}
+
+// How far a beet moved last frame, for leading shots at it, turning it the way it's going and
+// keeping its legs in step. Measured rather than set by whatever moves it, so knockback, weaving
+// and slows all count.
+#[derive(Component, Default)]
+pub struct Velocity {
+    pub per_frame: Vec3,
+    last: Option<Vec3>,
+}
+
+impl Velocity {
+    // Along the ground, if it's actually going anywhere
+    pub fn heading(&self) -> Option<Vec3> {
+        let flat = Vec3::new(self.per_frame.x, 0., self.per_frame.z);
+        (flat.length() > MIN_HEADING_STEP).then(|| flat.normalize())
+    }
+}
+
+// Let go after holding fire. Hits harder, and goes straight through a shield.
+#[derive(Component)]
+struct Charged;
+
+// Seconds fire's been held for, on the player
+#[derive(Component, Default)]
+struct ChargeShot {
+    held: f32,
+}
+
+// A press of fire that couldn't go off straight away, and how long it's been waiting for a lock.
+// Waiting on the gun to be ready again doesn't count, so a press mid-cooldown is never lost.
+#[derive(Component, Default)]
+struct FireBuffer {
+    pressed: Option<f32>,
+}
+
+// Shots still to go in the burst a burst-fire gun's part way through
+#[derive(Component, Default)]
+struct BurstFire {
+    remaining: u32,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            player: Entity::from_bits(0),
+            spud_gun: Entity::from_bits(1),
+            camera: Entity::from_bits(3),
+            enemies: Vec::new(),
+            enemy_animations: Vec::new(),
+            projectile: None,
+        }
+    }
+}
+
+fn camera_start() -> Transform {
+    Transform::from_xyz(0.0, 2.5, 2.0).looking_at(Vec3::NEG_Z * 2., Vec3::Y)
+}
+
+fn setup_camera(mut commands: Commands, mut game: ResMut<Game>) {
+    game.camera = commands.spawn(Camera3dBundle {
+        transform: camera_start(),
+        ..default()
+    }).id();
+}
+
+// Puts the core of the game back how `setup_camera` and `setup_models` left it. The plugins reset
+// their own bits on the way out of the game over screen too, and everything marked
+// `DespawnOnReset`, beets and pumpkins included, goes at the same time.
+fn reset_world(
+    mut commands: Commands,
+    game: Res<Game>,
+    seed: Res<LevelSeed>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_timer: ResMut<EnemySpawnTimer>,
+    mut camera_rig: ResMut<CameraRig>,
+    mut camera_pacing: ResMut<CameraPacing>,
+    mut transforms: Query<&mut Transform>,
+    mut health: Query<&mut Health>,
+    mut weapons: Query<(&mut Ammo, &mut FireCooldown)>,
+    mut players: Query<(Entity, &Player, &mut Aim)>,
+    mut ammo_changed: EventWriter<AmmoChanged>,
+) {
+    // Anyone else drops out, and joins again next run if their pad's still plugged in
+    for (entity, player, mut aim) in &mut players {
+        if player.index == 0 {
+            *aim = Aim::default();
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    // Every run on a seed plays out the same, not just the first
+    *rng = GameRng::from_seed(seed.0);
+    spawn_timer.0.reset();
+    *camera_rig = CameraRig::default();
+    *camera_pacing = CameraPacing::default();
+
+    if let Ok(mut camera) = transforms.get_mut(game.camera) {
+        *camera = camera_start();
+    }
+    if let Ok(mut player) = transforms.get_mut(game.player) {
+        *player = Transform::IDENTITY;
+    }
+    if let Ok(mut health) = health.get_mut(game.player) {
+        health.current = health.max;
+    }
+    if let Ok((mut ammo, mut cooldown)) = weapons.get_mut(game.spud_gun) {
+        ammo.current = ammo.max;
+        *cooldown = FireCooldown::default();
+        ammo_changed.send(AmmoChanged {
+            weapon: game.spud_gun,
+        });
+    }
+}
+
+fn setup_models(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut game: ResMut<Game>,
+    characters: Res<Characters>,
+    settings: Res<Settings>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+) {
+    let character = characters.playing_as(&settings, &source, netplay.is_some());
+    (game.player, game.spud_gun) =
+        spawn_player(&mut commands, &asset_server, character, 0, Transform::IDENTITY);
+
+    game.projectile = Some(asset_server.load("pumpkinBasic.glb#Scene0"));
+
+    game.enemies = vec![asset_server.load("beet.glb#Scene0")];
+    game.enemy_animations = vec![asset_server.load("beet.glb")];
+}
+
+// A vegetable with a spud gun of its own. Player one is spawned at startup, everyone else when
+// their gamepad turns up.
+fn spawn_player(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    character: &CharacterDefinition,
+    index: usize,
+    transform: Transform,
+) -> (Entity, Entity) {
+    let spud_gun = commands
+        .spawn(SceneBundle {
+            scene: asset_server.load("launcher.glb#Scene0"),
+            transform: Transform {
+                translation: [0.07, 0.25, 0.].into(),
+                ..default()
+            },
+            ..default()
+        })
+        .insert((
+            Weapon,
+            Ammo {
+                current: STARTING_AMMO,
+                max: STARTING_AMMO,
+            },
+            FireCooldown::default(),
+            Animated::new(asset_server.load("launcher.glb"), AnimationState::Idle),
+        ))
+        .id();
+
+    let player = commands
+        .spawn(SceneBundle {
+            scene: asset_server.load(character.scene()),
+            transform,
+            ..default()
+        })
+        .add_child(spud_gun)
+        .id();
+    commands.entity(player).insert((
+        Player {
+            index,
+            weapon: spud_gun,
+        },
+        Aim::default(),
+        PlayerInput::default(),
+        Dodge::default(),
+        Invulnerable::default(),
+        Stamina::default(),
+        GrenadeCooldown::default(),
+        Melee::default(),
+        TurretCharges::default(),
+        // Grouped, as a bundle can only be so big
+        (
+            ChargeShot::default(),
+            FireBuffer::default(),
+            BurstFire::default(),
+            Barrage::default(),
+            Spread::default(),
+        ),
+        Health::new(character.health),
+        Collider {
+            radius: PLAYER_RADIUS,
+        },
+        Animated::new(asset_server.load(character.model.as_str()), AnimationState::Idle),
+        Character {
+            speed: character.speed,
+        },
+    ));
+
+    (player, spud_gun)
+}
+
+fn player_movement(
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    modifier: Res<WaveModifier>,
+    time: Res<Time>,
+    mut players: Query<
+        (
+            &mut Transform,
+            &PlayerInput,
+            &Character,
+            Option<&Stamina>,
+            Option<&Slowed>,
+            Option<&Downed>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (mut transform, input, character, stamina, slowed, downed) in &mut players {
+        let speed = PLAYER_SPEED
+            * time.relative_speed()
+            * character.speed
+            * upgrades.speed_multiplier()
+            * modifier.speed_multiplier()
+            * stamina.map_or(1., Stamina::speed_multiplier)
+            * slowed.map_or(1., Slowed::multiplier)
+            * downed.map_or(1., Downed::speed_multiplier);
+        let movement = settings.left_stick.apply_dead_zone(input.movement) * speed;
+        transform.translation.x += movement.x;
+        transform.translation.z -= movement.y;
+    }
+}
+
+fn projectile_movement(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Transform, &Projectile, Option<&mut Falloff>)>,
+    time: Res<Time>,
+) {
+    let step = PROJECTILE_SPEED * time.relative_speed();
+    for (entity, mut transform, projectile, falloff) in projectiles.iter_mut() {
+        transform.translation += projectile.heading * step;
+        transform.rotate_x(step);
+
+        // Out of range, it's spent
+        let Some(mut falloff) = falloff else { continue };
+        falloff.travelled += step;
+        if falloff.travelled >= falloff.max_range {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Eased towards rather than jumped to, so the camera never lurches
+fn pace_camera(
+    mut pacing: ResMut<CameraPacing>,
+    rig: Res<CameraRig>,
+    waves: Res<WaveManager>,
+    bosses: Query<(), With<Boss>>,
+    players: Query<&GlobalTransform, With<Player>>,
+) {
+    let target = if bosses.is_empty() {
+        let ramp = 1. + waves.wave.saturating_sub(1) as f32 * CAMERA_SPEED_PER_WAVE;
+        // Players start this far in front of the camera
+        let start = rig.scroll.z - camera_start().translation.z;
+        let ahead = players
+            .iter()
+            .map(|transform| start - transform.translation().z)
+            .fold(0., f32::max);
+        let catch_up = ((ahead - CAMERA_CATCH_UP_AFTER).max(0.) * CAMERA_CATCH_UP_PER_METRE)
+            .min(CAMERA_MAX_CATCH_UP);
+        CAMERA_SPEED * ramp.min(CAMERA_MAX_WAVE_SPEED) + catch_up
+    } else {
+        0.
+    };
+    pacing.speed += (target - pacing.speed) * CAMERA_PACING_EASE;
+}
+
+fn camera_movement(
+    mut transforms: Query<&mut Transform>,
+    mut rig: ResMut<CameraRig>,
+    pacing: Res<CameraPacing>,
+    game: Res<Game>,
+    objective: Res<Objective>,
+    players: Query<&GlobalTransform, With<Player>>,
+    time: Res<Time>,
+) {
+    if !objective.holds_camera() {
+        rig.scroll.z -= pacing.speed * time.relative_speed();
+    }
+
+    let players = players
+        .iter()
+        .map(|transform| transform.translation())
+        .collect::<Vec<_>>();
+    let mut camera = transforms.get_mut(game.camera).unwrap();
+
+    // On your own the camera just scrolls, like it always has
+    let target = if players.len() > 1 {
+        let centre = players.iter().sum::<Vec3>() / players.len() as f32;
+        let spread = players
+            .iter()
+            .flat_map(|a| players.iter().map(move |b| a.distance(*b)))
+            .fold(0., f32::max);
+        let zoom = (spread - CAMERA_COMFORTABLE_SPREAD).clamp(0., CAMERA_MAX_ZOOM);
+        Vec3::new(centre.x, 0., 0.) + camera.back() * zoom
+    } else {
+        Vec3::ZERO
+    };
+
+    let catch_up = (time.delta_seconds() * CAMERA_FRAMING_RATE).min(1.);
+    rig.framing = rig.framing.lerp(target, catch_up);
+    camera.translation = rig.scroll + rig.framing + rig.push_in + rig.lock_on + rig.threat;
+    camera.rotation = Quat::from_rotation_y(rig.yaw) * camera_start().rotation;
+}
+
+
+// A pumpkin stops in the first beet it hits, unless it pierces on through or ricochets off
+// towards another one. Piercing's used up first.
+fn projectile_hit(
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    mut projectiles: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Projectile,
+            Option<&mut Pierce>,
+            Option<&mut Ricochet>,
+            Option<&Inflicts>,
+            Option<&Charged>,
+            Option<&Falloff>,
+        ),
+        Without<Enemy>,
+    >,
+    mut commands: Commands,
+    mut damage: EventWriter<DamageEvent>,
+    mut hits: EventWriter<ProjectileHit>,
+    mut statuses: EventWriter<ApplyStatus>,
+    shields: Query<&Shield>,
+    mut struck: EventWriter<ShieldStruck>,
+) {
+    // Most frames there's nothing in the air, or nothing to hit
+    if projectiles.is_empty() || enemies.is_empty() {
+        return;
+    }
+    // Every pumpkin is checked against every beet, so how many of each there were is most of the
+    // story when this shows up in a profile
+    let _span = info_span!(
+        "projectile_hit",
+        projectiles = projectiles.iter().count(),
+        enemies = enemies.iter().count()
+    )
+    .entered();
+
+    for (
+        projectile_entity,
+        projectile_transform,
+        mut projectile,
+        pierce,
+        ricochet,
+        inflicts,
+        charged,
+        falloff,
+    ) in projectiles.iter_mut()
+    {
+        let falloff = falloff.map_or(1., Falloff::multiplier);
+        for (enemy_entity, enemy_transform) in enemies.iter() {
+            if projectile.last_hit == Some(enemy_entity) {
+                continue;
+            }
+            if !is_hit(projectile_transform, enemy_transform) {
+                continue;
+            }
+
+            // Straight into a shield glances off it, and might yet find something else. A charged
+            // shot breaks it and hurts the beet all the same.
+            let shield = shields.get(enemy_entity).ok();
+            if let Some(shield) = shield.filter(|shield| shield.blocks(projectile.heading)) {
+                struck.send(ShieldStruck {
+                    target: enemy_entity,
+                    position: projectile_transform.translation(),
+                    broken: charged.is_some(),
+                });
+                if charged.is_none() {
+                    projectile.heading = shield.deflect(projectile.heading);
+                    projectile.last_hit = Some(enemy_entity);
+                    break;
+                }
+            }
+
+            // It's a hit! The damage pipeline decides whether it's a kill.
+            damage.send(DamageEvent {
+                source: Some(projectile_entity),
+                target: enemy_entity,
+                amount: projectile.damage * falloff,
+                direction: projectile.heading,
+                critical: projectile.critical,
+                knockback: PROJECTILE_KNOCKBACK,
+            });
+            hits.send(ProjectileHit {
+                target: enemy_entity,
+                direction: projectile.heading,
+            });
+            if let Some(Inflicts(effect)) = inflicts {
+                statuses.send(ApplyStatus {
+                    target: enemy_entity,
+                    effect: *effect,
+                });
+            }
+            splash(&mut damage, &projectile, falloff, enemy_entity, enemy_transform, &enemies);
+            projectile.last_hit = Some(enemy_entity);
+
+            if let Some(mut pierce) = pierce.filter(|pierce| pierce.remaining > 0) {
+                pierce.remaining -= 1;
+                projectile.damage *= PIERCE_DAMAGE_FRACTION;
+                break;
+            }
+            let next = ricochet
+                .filter(|ricochet| ricochet.remaining > 0)
+                .and_then(|ricochet| {
+                    ricochet_target(enemy_entity, enemy_transform, &enemies)
+                        .map(|target| (ricochet, target))
+                });
+            if let Some((mut ricochet, target)) = next {
+                ricochet.remaining -= 1;
+                projectile.heading = (target - enemy_transform.translation())
+                    .try_normalize()
+                    .unwrap_or(projectile.heading);
+                break;
+            }
+
+            commands.entity(projectile_entity).despawn_recursive();
+            break;
+        }
+    }
+}
+
+// The nearest other beet in range, if there is one
+fn ricochet_target(
+    hit: Entity,
+    impact: &GlobalTransform,
+    enemies: &Query<(Entity, &GlobalTransform), With<Enemy>>,
+) -> Option<Vec3> {
+    let centre = impact.translation();
+    let candidates = enemies
+        .iter()
+        .filter(|(entity, _)| *entity != hit)
+        .map(|(_, transform)| transform.translation())
+        .filter(|position| position.distance(centre) <= RICOCHET_RANGE)
+        .collect::<Vec<_>>();
+    nearest(centre, &candidates)
+}
+
+// Everything else within the blast radius of a hit gets caught in it, pushed away from the middle
+fn splash(
+    damage: &mut EventWriter<DamageEvent>,
+    projectile: &Projectile,
+    falloff: f32,
+    hit: Entity,
+    impact: &GlobalTransform,
+    enemies: &Query<(Entity, &GlobalTransform), With<Enemy>>,
+) {
+    if projectile.blast_radius <= 0. {
+        return;
+    }
+
+    let centre = impact.translation();
+    for (entity, transform) in enemies.iter() {
+        let offset = transform.translation() - centre;
+        if entity == hit || offset.length() > projectile.blast_radius {
+            continue;
+        }
+        damage.send(DamageEvent {
+            source: None,
+            target: entity,
+            amount: projectile.damage * falloff * BLAST_DAMAGE_FRACTION,
+            direction: Vec3::new(offset.x, 0., offset.z).normalize_or_zero(),
+            critical: false,
+            knockback: PROJECTILE_KNOCKBACK,
+        });
+    }
+}
+
+// Compare world positions, so it doesn't matter what either entity is parented to
+pub fn is_hit(projectile: &GlobalTransform, target: &GlobalTransform) -> bool {
+    projectile.translation().distance(target.translation()) <= HIT_THRESHOLD
+}
+
+fn nearest(position: Vec3, candidates: &[Vec3]) -> Option<Vec3> {
+    candidates.iter().copied().min_by(|a, b| {
+        a.distance_squared(position)
+            .partial_cmp(&b.distance_squared(position))
+            .unwrap()
+    })
+}
+
+fn spawn_enemy(
+    game: Res<Game>,
+    mut timer: ResMut<EnemySpawnTimer>,
+    mut waves: ResMut<WaveManager>,
+    mut rng: ResMut<GameRng>,
+    chunks: Res<EnvironmentChunks>,
+    config: Res<GameConfig>,
+    difficulty: Res<Difficulty>,
+    rig: Res<CameraRig>,
+    time: Res<Time>,
+    mut commands: Commands,
+    transforms: Query<&GlobalTransform>,
+    enemies: Query<(&EnemyKind, &GlobalTransform), With<Enemy>>,
+    players: Query<&GlobalTransform, With<Player>>,
+    obstacles: Query<(&GlobalTransform, &Obstacle)>,
+    telegraphs: Query<(&SpawnTelegraph, &GlobalTransform)>,
+    telegraph_assets: Res<TelegraphAssets>,
+) {
+    // Measured from the scroll alone, so co-op framing doesn't make it any harder
+    let pressure = config
+        .spawning
+        .pressure_at(rig.travelled());
+    let interval = Duration::from_secs_f32(
+        ENEMY_SPAWN_SECONDS * difficulty.scaling().spawn_interval * pressure.interval,
+    );
+    if timer.0.duration() != interval {
+        timer.0.set_duration(interval);
+    }
+    if !timer.0.tick(time.delta()).finished() {
+        return;
+    };
+    // Only the frames something's actually spawned on
+    let _span = info_span!("spawn_enemy", enemies = enemies.iter().count()).entered();
+
+    let camera_z = transforms.get(game.camera).unwrap().translation().z;
+    let players = players
+        .iter()
+        .map(GlobalTransform::translation)
+        .collect::<Vec<_>>();
+    let obstacles = obstacles
+        .iter()
+        .map(|(transform, obstacle)| (transform.translation(), obstacle.radius))
+        .collect::<Vec<_>>();
+    let spawn_z = camera_z - ENEMY_SPAWN_DISTANCE;
+    let half_width = pressure.half_width.max(0.1);
+
+    // Beets that are on their way up count as much as the ones already here
+    let mut enemies = enemies
+        .iter()
+        .map(|(kind, transform)| (*kind, transform.translation()))
+        .chain(
+            telegraphs
+                .iter()
+                .map(|(telegraph, transform)| (telegraph.kind, transform.translation())),
+        )
+        .collect::<Vec<_>>();
+
+    // A wave with a formation comes in groups laid out around the middle of the lane, and the
+    // spawn pressure only sets how often
+    let group = waves.next_group(half_width);
+    let count = group
+        .as_ref()
+        .map_or((pressure.count as u32).max(1), |group| group.len() as u32);
+
+    for index in 0..count {
+        if !waves.can_spawn() {
+            return;
+        }
+
+        // Pick the kind of enemy to spawn
+        let kind = EnemyKind::Beet;
+        if let Err(violation) = spawn_rules::check_kind(&config.spawning, kind, &enemies) {
+            spawn_rules::report(violation);
+            return;
+        }
+
+        // Use one of the level's spawn points around that distance if there's a good one,
+        // otherwise anywhere across the lane. Formations keep to their own spots.
+        let candidates = match &group {
+            Some(group) => {
+                let spot = group[index as usize];
+                vec![Vec3::new(spot.x, 0., spawn_z - spot.y)]
+            }
+            None => {
+                let mut candidates = chunks.spawn_points_between(spawn_z + 1., spawn_z - 1.);
+                candidates.shuffle(&mut rng.0);
+                candidates.push(Vec3::new(rng.0.gen_range(-half_width..half_width), 0., spawn_z));
+                candidates
+            }
+        };
+
+        // Failing all of those, wherever's closest to the last one that'll do. That's the
+        // formation spot or the random point across the lane, rather than a spawn point.
+        let fallback = candidates.last().copied();
+        let translation = candidates
+            .into_iter()
+            .find(|candidate| {
+                spawn_rules::check_position(
+                    &config.spawning,
+                    *candidate,
+                    &players,
+                    &enemies,
+                    &obstacles,
+                )
+                .map_err(spawn_rules::report)
+                .is_ok()
+            })
+            .or_else(|| {
+                spawn_rules::nearest_valid(
+                    &config.spawning,
+                    fallback?,
+                    &players,
+                    &enemies,
+                    &obstacles,
+                )
+            });
+        // Try again next time round. A formation spot with nowhere good near it is left out, and
+        // whoever would have stood there comes with the next group.
+        let Some(translation) = translation else {
+            if group.is_some() {
+                continue;
+            }
+            return;
+        };
+        waves.enemy_spawned();
+
+        // Later ones in the same batch keep their distance from this one too
+        enemies.push((kind, translation));
+        spawn_telegraph(&mut commands, &telegraph_assets, kind, translation);
+    }
+}
+
+// Beets the camera has scrolled past would otherwise trudge along behind it forever, holding up the
+// end of the wave. They're put back out in front, as if they'd only just arrived.
+fn leash_enemies(
+    game: Res<Game>,
+    mut enemies: Query<(Entity, &mut Transform, &GlobalTransform), With<Enemy>>,
+    mut aims: Query<&mut Aim>,
+    transforms: Query<&GlobalTransform, Without<Enemy>>,
+) {
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let camera_z = camera.translation().z;
+
+    for (entity, mut transform, global_transform) in &mut enemies {
+        if global_transform.translation().z < camera_z + ENEMY_LEASH_DISTANCE {
+            continue;
+        }
+        transform.translation.z = camera_z - ENEMY_SPAWN_DISTANCE;
+
+        // Nobody should stay locked on to something that's just jumped up the lane
+        for mut aim in &mut aims {
+            if aim.target == Some(entity) {
+                aim.target = None;
+            }
+        }
+    }
+}
+
+fn track_velocity(mut enemies: Query<(&GlobalTransform, &mut Velocity), With<Enemy>>) {
+    for (transform, mut velocity) in &mut enemies {
+        let position = transform.translation();
+        let step = velocity.last.map_or(Vec3::ZERO, |last| position - last);
+        velocity.per_frame = if step.length() > MAX_TRACKED_STEP {
+            Vec3::ZERO
+        } else {
+            step
+        };
+        velocity.last = Some(position);
+    }
+}
+
+// The models face +Z. Being shoved back doesn't turn a beet round, it just goes backwards.
+fn face_heading(
+    time: Res<Time>,
+    mut enemies: Query<(&Velocity, &Knockback, &mut Transform), With<Enemy>>,
+) {
+    let max_turn = ENEMY_TURN_SPEED * time.delta_seconds();
+    for (velocity, knockback, mut transform) in &mut enemies {
+        if knockback.velocity != Vec3::ZERO {
+            continue;
+        }
+        let Some(heading) = velocity.heading() else { continue };
+
+        let facing = Quat::from_rotation_y(heading.x.atan2(heading.z));
+        let angle = transform.rotation.angle_between(facing);
+        if angle <= f32::EPSILON {
+            continue;
+        }
+        transform.rotation = transform.rotation.slerp(facing, (max_turn / angle).min(1.));
+    }
+}
+
+// Everything an enemy is made of. The spawner decides when and where, but so can a cheat.
+pub fn enemy_bundle(
+    game: &Game,
+    difficulty: Difficulty,
+    kind: EnemyKind,
+    translation: Vec3,
+) -> impl Bundle {
+    let index = match kind {
+        EnemyKind::Beet => 0,
+    };
+
+    (
+        SceneBundle {
+            scene: game.enemies[index].clone(),
+            transform: Transform {
+                translation,
+                ..default()
+            },
+            ..default()
+        },
+        Enemy,
+        kind,
+        Health::new(ENEMY_HEALTH * difficulty.scaling().enemy_health),
+        Knockback::default(),
+        Velocity::default(),
+        AttackState::default(),
+        Collider {
+            radius: ENEMY_RADIUS,
+        },
+        Animated::new(game.enemy_animations[index].clone(), AnimationState::Walk),
+        DespawnOnReset,
+    )
+}
+
+// Stop next to a player, wind up, strike through the damage pipeline, then wait a moment before
+// trying again
+fn enemy_attack(
+    time: Res<Time>,
+    mut enemies: Query<
+        (Entity, &GlobalTransform, &mut AttackState, Option<&mut Animated>),
+        (With<Enemy>, Without<Stunned>, Without<Staggered>),
+    >,
+    // Anything they're after, crops and tractors included
+    players: Query<(Entity, &GlobalTransform), Or<(With<Player>, With<Protected>, With<Crop>)>>,
+    // Only players have these
+    dodges: Query<&Dodge>,
+    mut damage: EventWriter<DamageEvent>,
+    mut grazed: EventWriter<Grazed>,
+) {
+    let delta = time.delta_seconds();
+    let players = players
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation()))
+        .collect::<Vec<_>>();
+
+    for (entity, transform, mut attack, animated) in &mut enemies {
+        let position = transform.translation();
+        let closest = players.iter().copied().min_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .partial_cmp(&b.distance_squared(position))
+                .unwrap()
+        });
+        let distance = closest.map_or(f32::MAX, |(_, player)| player.distance(position));
+
+        let (next, animation) = match *attack {
+            AttackState::Chasing if distance <= ENEMY_ATTACK_RANGE => (
+                AttackState::WindingUp {
+                    remaining: ENEMY_WINDUP_SECONDS,
+                },
+                Some(AnimationState::WindUp),
+            ),
+            AttackState::Chasing => continue,
+            AttackState::WindingUp { remaining } if remaining > delta => {
+                (AttackState::WindingUp { remaining: remaining - delta }, None)
+            }
+            AttackState::WindingUp { .. } => {
+                if let Some((player, player_position)) = closest {
+                    // Rolling through it still sends the damage, which the dodge then shrugs off
+                    let rolling = dodges.get(player).map_or(false, Dodge::is_invulnerable);
+                    let near_miss = dodges.contains(player)
+                        && distance > ENEMY_ATTACK_REACH
+                        && distance <= ENEMY_ATTACK_REACH + GRAZE_MARGIN;
+                    if near_miss || (rolling && distance <= ENEMY_ATTACK_REACH) {
+                        grazed.send(Grazed {
+                            player,
+                            enemy: entity,
+                            position: player_position,
+                        });
+                    }
+                    if distance <= ENEMY_ATTACK_REACH {
+                        let offset = player_position - position;
+                        damage.send(DamageEvent {
+                            source: Some(entity),
+                            target: player,
+                            amount: ENEMY_ATTACK_DAMAGE,
+                            direction: Vec3::new(offset.x, 0., offset.z).normalize_or_zero(),
+                            critical: false,
+                            knockback: 0.,
+                        });
+                    }
+                }
+                (
+                    AttackState::Recovering {
+                        remaining: ENEMY_ATTACK_COOLDOWN_SECONDS,
+                    },
+                    Some(AnimationState::Attack),
+                )
+            }
+            AttackState::Recovering { remaining } if remaining > delta => {
+                (AttackState::Recovering { remaining: remaining - delta }, None)
+            }
+            AttackState::Recovering { .. } => (AttackState::Chasing, Some(AnimationState::Walk)),
+        };
+
+        *attack = next;
+        if let (Some(mut animated), Some(animation)) = (animated, animation) {
+            animated.restart(animation);
+        }
+    }
+}
+
+fn enemy_movement(
+    mut enemy_transforms: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            &Collider,
+            Option<&mut Knockback>,
+            Option<&AttackState>,
+            Option<&Slowed>,
+            Option<&Stunned>,
+            Option<&Staggered>,
+            Option<&Pace>,
+        ),
+        With<Enemy>,
+    >,
+    player_transforms: Query<
+        &GlobalTransform,
+        (Without<Enemy>, Or<(With<Player>, With<Protected>)>),
+    >,
+    crops: Query<&GlobalTransform, (With<Crop>, Without<Enemy>)>,
+    obstacles: Query<(&GlobalTransform, &Obstacle)>,
+    difficulty: Res<Difficulty>,
+    mutators: Res<Mutators>,
+    time: Res<Time>,
+) {
+    // Steering round each other is every beet against every other one
+    let _span = info_span!("enemy_movement", enemies = enemy_transforms.iter().count()).entered();
+    let speed = ENEMY_SPEED
+        * difficulty.scaling().enemy_speed
+        * mutators.enemy_speed()
+        * time.relative_speed();
+    let players = player_transforms
+        .iter()
+        .map(|transform| transform.translation())
+        .collect::<Vec<_>>();
+    let crops = crops
+        .iter()
+        .map(|transform| transform.translation())
+        .collect::<Vec<_>>();
+    let obstacles = obstacles
+        .iter()
+        .map(|(transform, obstacle)| (transform.translation(), obstacle.radius))
+        .collect::<Vec<_>>();
+    let enemies = enemy_transforms
+        .iter()
+        .map(|(entity, _, global_transform, ..)| (entity, global_transform.translation()))
+        .collect::<Vec<_>>();
+
+    for (
+        entity,
+        mut transform,
+        global_transform,
+        collider,
+        mut knockback,
+        attack,
+        slowed,
+        stunned,
+        staggered,
+        pace,
+    ) in enemy_transforms.iter_mut()
+    {
+        // Being shoved back doesn't stop them trying to come forward
+        if let Some(knockback) = knockback.as_mut() {
+            transform.translation += knockback.step();
+        }
+        // ..but being stunned or staggered does
+        if stunned.is_some() || staggered.is_some() {
+            continue;
+        }
+
+        let position = global_transform.translation();
+        // Whoever's closest gets chased, or whatever they're protecting
+        let Some(player_position) = nearest(position, &players) else { return };
+        // ..unless there's a crop going spare nearer to hand
+        let player_position = nearest(position, &crops)
+            .filter(|crop| {
+                let distance = crop.distance(position);
+                distance <= CROP_DIVERT_RANGE && distance < player_position.distance(position)
+            })
+            .unwrap_or(player_position);
+        // ..up to arm's length, rather than sliding on into them. Winding up is done standing
+        // still, so there's a moment to get out of the way.
+        let winding_up = matches!(attack, Some(AttackState::WindingUp { .. }));
+        if winding_up || position.distance(player_position) <= ENEMY_ATTACK_RANGE {
+            continue;
+        }
+
+        // Head for the player, but go around anything in the way rather than pushing into it
+        let heading = steering::steer(
+            position,
+            player_position,
+            collider.radius,
+            obstacles.iter().copied(),
+        );
+        // ..and spread out, so they surround the player instead of piling into one beet
+        let neighbours = enemies
+            .iter()
+            .filter(|(other, _)| *other != entity)
+            .map(|(_, other_position)| *other_position);
+        let separation = steering::separation(position, neighbours, ENEMY_SEPARATION);
+
+        let velocity = (heading + separation * ENEMY_SEPARATION_WEIGHT).clamp_length_max(1.);
+        let speed = speed * pace.map_or(1., |pace| pace.0) * slowed.map_or(1., Slowed::multiplier);
+        transform.translation += velocity * speed;
+    }
+}
+
+fn weapon_fire(
+    mut commands: Commands,
+    game: Res<Game>,
+    time: Res<Time>,
+    mut players: Query<(
+        &Player,
+        &Aim,
+        &PlayerInput,
+        &mut ChargeShot,
+        &mut FireBuffer,
+        &mut BurstFire,
+        &mut Barrage,
+        &mut Spread,
+    )>,
+    enemies: Query<(), With<Enemy>>,
+    transforms: Query<&GlobalTransform>,
+    velocities: Query<&Velocity>,
+    mut weapons: Query<(&mut Ammo, &mut FireCooldown)>,
+    loadout: Res<Loadout>,
+    upgrades: Res<Upgrades>,
+    modifier: Res<WaveModifier>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    mutators: Res<Mutators>,
+    mut rng: ResMut<GameRng>,
+    mut fired: EventWriter<WeaponFired>,
+) {
+    let Some(projectile_asset) = &game.projectile else { return };
+    let stats = modifier.apply(upgrades.apply(loadout.equipped_stats(&config)));
+
+    let full_auto = stats.fire_mode == FireMode::FullAuto;
+    let burst_fire = match stats.fire_mode {
+        FireMode::Burst { shots, delay } => Some((shots, delay)),
+        _ => None,
+    };
+
+    for (player, aim, input, mut charge, mut buffer, mut burst, mut barrage, mut spread) in
+        &mut players
+    {
+        // Pressing fire shoots straight away as usual. Holding it on from there charges a shot up,
+        // which goes off when it's let go, whether the gun's ready again or not. A full-auto gun
+        // just keeps firing instead.
+        let held = charge.held;
+        if input.fire_held && !full_auto {
+            charge.held += time.delta_seconds();
+        } else if held > 0. {
+            charge.held = 0.;
+        }
+        // Assisted play pulls the trigger whenever there's something locked on, which is only
+        // ever something in range
+        let auto_fire = settings.assisted_play.fires() && aim.target.is_some();
+
+        // A barrage weapon paints whatever's locked on while fire's held, rather than charging,
+        // and lets go of the lot at once. Assisted play fires it like any other gun.
+        let volley = stats.locks > 0 && !auto_fire;
+        barrage.painted.retain(|enemy| enemies.contains(*enemy));
+        if volley {
+            if input.fire_held {
+                if let Some(target) = aim.target {
+                    let room = barrage.painted.len() < stats.locks as usize;
+                    if room && !barrage.painted.contains(&target) {
+                        barrage.painted.push(target);
+                    }
+                }
+                continue;
+            }
+            if held == 0. {
+                continue;
+            }
+        }
+        // Nothing painted goes off at whatever it's pointing at, like any other gun
+        let targets: Vec<Option<Entity>> = if barrage.painted.is_empty() {
+            vec![aim.target]
+        } else {
+            barrage.painted.drain(..).map(Some).collect()
+        };
+
+        let charged = !volley && !input.fire_held && held >= CHARGE_SECONDS;
+        if input.fire {
+            buffer.pressed = Some(0.);
+        }
+        // Swapping to something else mid-burst drops the rest of it
+        if burst_fire.is_none() {
+            burst.remaining = 0;
+        }
+        let bursting = burst.remaining > 0;
+        let spraying = full_auto && input.fire_held;
+        if buffer.pressed.is_none() && !charged && !auto_fire && !volley && !bursting && !spraying {
+            continue;
+        }
+
+        // Locked on, there's nothing to shoot at without a target. Otherwise the gun goes off
+        // wherever it's pointing.
+        let locked_on = settings.effective_aim_assist() == AimAssist::LockOn;
+        if locked_on && targets.iter().all(Option::is_none) {
+            if let Some(waited) = buffer.pressed {
+                let waited = waited + time.delta_seconds();
+                buffer.pressed = (waited <= FIRE_BUFFER_SECONDS).then_some(waited);
+            }
+            continue;
+        }
+        let Ok((mut ammo, mut cooldown)) = weapons.get_mut(player.weapon) else { continue };
+        if ammo.current == 0 {
+            buffer.pressed = None;
+            burst.remaining = 0;
+            continue;
+        }
+        // Anything pressed while it's still cooling down goes off as soon as it's ready. The rest
+        // of a burst goes off on its own, a shot at a time, and a press during one waits for after.
+        if !(cooldown.ready() || charged || volley) {
+            continue;
+        }
+        match burst_fire {
+            Some((shots, delay)) if !charged && !volley => {
+                if bursting {
+                    burst.remaining -= 1;
+                } else {
+                    buffer.pressed = None;
+                    burst.remaining = shots.saturating_sub(1);
+                }
+                if burst.remaining > 0 {
+                    cooldown.start_burst(delay);
+                } else {
+                    cooldown.start(stats);
+                }
+            }
+            _ => {
+                buffer.pressed = None;
+                cooldown.start(stats);
+            }
+        }
+
+        let weapon = transforms.get(player.weapon).unwrap();
+        let origin = weapon.translation();
+        // A pumpkin for each, for as long as the ammo lasts
+        for target in targets {
+            if ammo.current == 0 {
+                break;
+            }
+            if !mutators.is_active(Mutator::InfiniteAmmo) {
+                ammo.current -= 1;
+            }
+
+            let heading = match target.filter(|_| locked_on || volley) {
+                Some(enemy) => {
+                    let target = transforms.get(enemy).unwrap().translation();
+                    // Where it'll be by the time the pumpkin gets there, if it can get there at all
+                    let velocity = velocities
+                        .get(enemy)
+                        .map_or(Vec3::ZERO, |velocity| velocity.per_frame);
+                    let speed = PROJECTILE_SPEED * time.relative_speed();
+                    let aim_at = if settings.lead_targets {
+                        intercept(origin, target, velocity, speed).unwrap_or(target)
+                    } else {
+                        target
+                    };
+                    (aim_at - origin).normalize()
+                }
+                None => weapon.forward(),
+            };
+            // A steady shot from standing still goes exactly where it's aimed, without a roll
+            let heading = if spread.angle > 0. {
+                Quat::from_rotation_y(rng.0.gen_range(-spread.angle..=spread.angle)) * heading
+            } else {
+                heading
+            };
+            spread.bloom = (spread.bloom + stats.spread_per_shot).min(stats.max_spread);
+            spread.since_shot = 0.;
+            let critical = rng.0.gen_bool(stats.crit_chance as f64);
+            let mut damage = if critical {
+                stats.damage * stats.crit_multiplier
+            } else {
+                stats.damage
+            };
+            if charged {
+                damage *= CHARGED_DAMAGE_MULTIPLIER;
+            }
+
+            let mut projectile = commands.spawn(SceneBundle {
+                scene: projectile_asset.clone(),
+                transform: Transform {
+                    translation: origin,
+                    scale: Vec3::splat(if charged { CHARGED_SCALE } else { 1. }),
+                    ..default()
+                },
+                ..default()
+            });
+            projectile.insert(Projectile {
+                heading,
+                damage,
+                critical,
+                blast_radius: stats.blast_radius,
+                last_hit: None,
+            });
+            projectile.insert(DespawnOnReset);
+            if stats.pierce > 0 {
+                projectile.insert(Pierce {
+                    remaining: stats.pierce,
+                });
+            }
+            if stats.ricochet > 0 {
+                projectile.insert(Ricochet {
+                    remaining: stats.ricochet,
+                });
+            }
+            if let Some(status) = stats.status {
+                projectile.insert(Inflicts(status));
+            }
+            if charged {
+                projectile.insert(Charged);
+            }
+            projectile.insert(Falloff {
+                range: stats.range,
+                max_range: stats.max_range,
+                travelled: 0.,
+            });
+
+            fired.send(WeaponFired {
+                weapon: player.weapon,
+            });
+        }
+    }
+}
+
+fn update_spread(
+    time: Res<Time>,
+    loadout: Res<Loadout>,
+    upgrades: Res<Upgrades>,
+    modifier: Res<WaveModifier>,
+    config: Res<GameConfig>,
+    mut players: Query<(&PlayerInput, &mut Spread)>,
+) {
+    let stats = modifier.apply(upgrades.apply(loadout.equipped_stats(&config)));
+    for (input, mut spread) in &mut players {
+        spread.since_shot += time.delta_seconds();
+        if spread.since_shot >= SPREAD_RECOVERY_DELAY {
+            spread.bloom = (spread.bloom - SPREAD_RECOVERY * time.delta_seconds()).max(0.);
+        }
+        let moving = input.movement.length().min(1.) * MOVING_SPREAD;
+        spread.angle = (spread.bloom + moving).min(stats.max_spread);
+    }
+}
+
+// Where a shot from `origin` going `speed` meets something at `target` moving at `velocity`, if it
+// ever can. Both are per frame, like everything else that moves.
+fn intercept(origin: Vec3, target: Vec3, velocity: Vec3, speed: f32) -> Option<Vec3> {
+    let offset = target - origin;
+    // How long it takes is where |offset + velocity * t| = speed * t
+    let a = velocity.length_squared() - speed * speed;
+    let b = 2. * offset.dot(velocity);
+    let c = offset.length_squared();
+    let time = if a.abs() < f32::EPSILON {
+        -c / b
+    } else {
+        let discriminant = b * b - 4. * a * c;
+        if discriminant < 0. {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (first, second) = ((-b - root) / (2. * a), (-b + root) / (2. * a));
+        if first > 0. && (first < second || second <= 0.) {
+            first
+        } else {
+            second
+        }
+    };
+    (time.is_finite() && time > 0.).then(|| target + velocity * time)
+}
+
+// How far away something can be locked on to: by assisted play, and at all with the weapon in hand
+#[derive(Clone, Copy)]
+struct AimReach {
+    assisted: f32,
+    max: f32,
+}
+
+// Anything that can be locked on to: the beets, and the spawners sending them out
+type Lockable = Or<(With<Enemy>, With<Spawner>)>;
+
+enum AimDirection {
+    Left,
+    Right
+}
+
+fn player_aim(
+    settings: Res<Settings>,
+    difficulty: Res<Difficulty>,
+    loadout: Res<Loadout>,
+    config: Res<GameConfig>,
+    enemy_transforms: Query<(Entity, &GlobalTransform), Lockable>,
+    mut players: Query<(&GlobalTransform, &PlayerInput, &mut Aim), With<Player>>,
+    // Kept between frames so flicking along the line doesn't allocate every time
+    mut ordered_enemies: Local<Vec<(Entity, f32)>>,
+) {
+    // Nothing to aim at. All that's left to do is let go of whatever's locked, and keep track of
+    // the stick so a flick that's already held doesn't count once something turns up.
+    if enemy_transforms.is_empty() {
+        for (_, input, mut aim) in &mut players {
+            if aim.target.is_some() {
+                aim.target = None;
+            }
+            let flicking = settings.effective_aim_assist() == AimAssist::LockOn
+                && settings.aim_mode == AimMode::Flick;
+            let is_aiming = input.aim.abs() >= settings.right_stick.dead_zone;
+            if flicking && aim.is_aiming != is_aiming {
+                aim.is_aiming = is_aiming;
+            }
+        }
+        return;
+    }
+
+    let _span = info_span!("player_aim", enemies = enemy_transforms.iter().count()).entered();
+    let reach = AimReach {
+        assisted: ASSISTED_RANGE * difficulty.scaling().assist_range,
+        // Everyone's got the same loadout
+        max: loadout.equipped_stats(&config).max_range,
+    };
+    for (player, input, mut aim) in &mut players {
+        aim_player(
+            &settings,
+            &enemy_transforms,
+            player,
+            input,
+            &mut aim,
+            &mut ordered_enemies,
+            reach,
+        );
+    }
+}
+
+fn aim_player(
+    settings: &Settings,
+    enemy_transforms: &Query<(Entity, &GlobalTransform), Lockable>,
+    player: &GlobalTransform,
+    input: &PlayerInput,
+    aim: &mut Mut<Aim>,
+    ordered_enemy_list: &mut Vec<(Entity, f32)>,
+    reach: AimReach,
+) {
+    // Only written when it actually changes, so anything watching for a new target can tell
+    let set_target = |aim: &mut Mut<Aim>, target: Option<Entity>| {
+        if aim.target != target {
+            aim.target = target;
+        }
+    };
+    // Nothing the gun can't reach can be locked on to, and a lock on a beet that's got out of
+    // reach is let go
+    let in_range = |transform: &GlobalTransform| {
+        transform.translation().distance(player.translation()) <= reach.max
+    };
+    let out_of_range = aim
+        .target
+        .and_then(|target| enemy_transforms.get(target).ok())
+        .map_or(false, |(_, transform)| !in_range(transform));
+    if out_of_range {
+        set_target(aim, None);
+    }
+
+    // Assisted play keeps on whichever beet is the nearest threat, if there's one in reach
+    if settings.assisted_play.aims() {
+        let range = reach.assisted.min(reach.max);
+        let target = nearest_enemy(player.translation(), enemy_transforms, range);
+        set_target(aim, target);
+        return;
+    }
+
+    match settings.aim_assist {
+        AimAssist::Off => {
+            set_target(aim, None);
+            return;
+        }
+        AimAssist::Magnetism => {
+            // Whichever beet is closest to the line the gun's on, as long as it's close enough
+            let player_position = player.translation();
+            let direction = free_aim_direction(settings, input);
+            let target = enemy_transforms
+                .iter()
+                .filter(|(_, transform)| in_range(transform))
+                .filter_map(|(entity, transform)| {
+                    let to_enemy = transform.translation() - player_position;
+                    let angle = direction.angle_between(Vec3::new(to_enemy.x, 0., to_enemy.z));
+                    (angle <= MAGNETISM_CONE).then_some((entity, angle))
+                })
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(entity, _)| entity);
+            set_target(aim, target);
+            return;
+        }
+        AimAssist::LockOn => {}
+    }
+
+    if settings.aim_mode == AimMode::Nearest {
+        let target = nearest_enemy(player.translation(), enemy_transforms, reach.max);
+        set_target(aim, target);
+        return;
+    }
+
+    let right_stick_x = input.aim;
+
+
+
+    // We only want to change the aim once the stick has left the dead zone
+    if right_stick_x.abs() < settings.right_stick.dead_zone {
+        if aim.is_aiming {
+            aim.is_aiming = false;
+        }
+        return;
+    }
+
+    // But if we've already left the dead zone, we want to wait until the stick is back
+    if aim.is_aiming { return };
+
+    // Okay, now we're aiming
+    aim.is_aiming = true;
+
+    let aim_direction = if right_stick_x > 0.0 {
+        AimDirection::Right
+    } else { AimDirection::Left };
+
+    // First, get a list of enemies in order from left to right
+    ordered_enemy_list.clear();
+    ordered_enemy_list.extend(
+        enemy_transforms
+            .iter()
+            .filter(|(_, transform)| in_range(transform))
+            .map(|(entity, transform)| (entity, transform.translation().x)),
+    );
+    if ordered_enemy_list.is_empty() {
+        return;
+    };
+
+    ordered_enemy_list.sort_by(|(_, x_a), (_, x_b)| x_a.partial_cmp(x_b).unwrap());
+
+    // If the player isn't currently aiming at an enemy, then take the first one from the left
+    let Some(enemy) = aim.target else { 
+        let enemy = match aim_direction {
+            AimDirection::Left => ordered_enemy_list.first().unwrap().0,
+            AimDirection::Right => ordered_enemy_list.last().unwrap().0
+        };
+        aim.target = Some(enemy);
+        return 
+    };
+    
+    // If the player *is* currently aiming at an enemy, find its index in the sort order
+    let Some(index) = ordered_enemy_list.iter().position(|(entity, _)| *entity == enemy) else {
+        println!("Player is aiming at an entity that does not exist");
+        aim.target = None;
+        return;
+    };
+
+    // If the player is aiming in a direction, and the enemy is already the one that is most in that direction, do nothing
+    match aim_direction {
+        AimDirection::Left => if index == 0 { return },
+        AimDirection::Right => if index == ordered_enemy_list.len()- 1 { return},
+    };
+
+    // Otherwise, aim at the next enemy along in the direction the player is aiming
+    let index_increment: i32 = match aim_direction {
+        AimDirection::Left => -1,
+        AimDirection::Right => 1
+    };
+
+    let next_enemy_index = (index as i32 + index_increment) as usize % (ordered_enemy_list.len());
+    aim.target = Some(ordered_enemy_list[next_enemy_index].0);
+}
+
+// The closest beet no further away than `range`. Turrets pick their targets this way too.
+pub fn nearest_enemy<F: ReadOnlyWorldQuery>(
+    position: Vec3,
+    enemy_transforms: &Query<(Entity, &GlobalTransform), F>,
+    range: f32,
+) -> Option<Entity> {
+    enemy_transforms
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation().distance_squared(position)))
+        .filter(|(_, distance)| *distance <= range * range)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(entity, _)| entity)
+}
+
+// Where the right stick points the gun when nothing's locked on, in world space
+fn free_aim_direction(settings: &Settings, input: &PlayerInput) -> Vec3 {
+    let stick = if input.aim.abs() < settings.right_stick.dead_zone {
+        0.
+    } else {
+        input.aim.clamp(-1., 1.)
+    };
+    let angle = stick * FREE_AIM_ANGLE;
+    Vec3::new(angle.sin(), 0., -angle.cos())
+}
+
+fn weapon_movement(
+    settings: Res<Settings>,
+    players: Query<(&GlobalTransform, &Player, &Aim, &PlayerInput)>,
+    mut transforms: Query<&mut Transform>,
+    global_transforms: Query<&GlobalTransform>,
+) {
+    for (player, Player { weapon, .. }, aim, input) in &players {
+        let player_position = player.translation();
+        let target_direction = aim.target.map(|enemy| {
+            let enemy_position = global_transforms.get(enemy).unwrap().translation();
+            (enemy_position - player_position).normalize_or_zero()
+        });
+
+        // Locked on, the gun points right at the target. Otherwise the stick points it, with
+        // magnetism bending it part of the way towards the nearest beet.
+        let direction = match (settings.effective_aim_assist(), target_direction) {
+            (AimAssist::LockOn, Some(target)) => target,
+            (AimAssist::LockOn, None) => player.forward(),
+            (_, target) => {
+                let stick = free_aim_direction(&settings, input);
+                match target {
+                    Some(target) => stick
+                        .lerp(target, settings.aim_assist_strength.clamp(0., 1.))
+                        .normalize_or_zero(),
+                    None => stick,
+                }
+            }
+        };
+
+        // The gun is a child of the player, so look_at needs the direction in the player's space
+        let local_direction = player.affine().inverse().transform_vector3(direction);
+        let mut gun = transforms.get_mut(*weapon).unwrap();
+        let translation = gun.translation;
+        gun.look_at(translation + local_direction, Vec3::Y);
+    }
+}
@@ -0,0 +1,342 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    config::GameConfig, reset::DespawnOnReset, state::GameState, video::VideoSettings,
+    weather::Weather, Game, WeaponFired,
+};
+
+// Where the run is in its day: 0 is dawn, 1 is the dead of night
+const SUN_COLORS: [(f32, Color); 5] = [
+    (0.0, Color::rgb(1.0, 0.6, 0.35)),
+    (0.3, Color::WHITE),
+    (0.65, Color::rgb(1.0, 0.55, 0.3)),
+    (0.8, Color::rgb(0.6, 0.4, 0.7)),
+    (1.0, Color::rgb(0.4, 0.5, 0.9)),
+];
+const SUN_BRIGHTNESS: [(f32, f32); 5] = [
+    (0.0, 0.4),
+    (0.3, 1.0),
+    (0.65, 0.7),
+    (0.8, 0.15),
+    (1.0, 0.08),
+];
+const AMBIENT_BRIGHTNESS: [(f32, f32); 4] = [(0.0, 0.6), (0.3, 1.0), (0.8, 0.4), (1.0, 0.25)];
+// The sun sets at this point, after which the light is the moon's
+const SUNSET: f32 = 0.8;
+const MAX_SUN_ELEVATION: f32 = 1.2;
+const MOON_ELEVATION: f32 = 0.9;
+// How much a downpour dims the sun, and how much brighter a lightning strike makes everything
+const OVERCAST_DIMMING: f32 = 0.5;
+const LIGHTNING_SUN_BOOST: f32 = 3.;
+const LIGHTNING_AMBIENT_BOOST: f32 = 1.5;
+// The sun's shadows are drawn around this point in front of the camera, however far down the lane
+// it's got
+const SHADOW_CENTRE_AHEAD: f32 = 4.;
+const FLASH_RANGE: f32 = 3.;
+// Out in front of the gun, where the pumpkin leaves it
+const MUZZLE_OFFSET: f32 = 0.3;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>()
+            .add_startup_system(setup_lights)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(advance_time_of_day),
+            )
+            .add_system(apply_time_of_day.after(advance_time_of_day))
+            .add_system(keep_shadows_in_view)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(muzzle_flashes))
+            // Left to die away whatever's going on, so none are left hanging about lit
+            .add_system(fade_flashes)
+            .add_system(enforce_light_budget.after(fade_flashes))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(reset_time_of_day),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TimeOfDay {
+    pub progress: f32,
+}
+
+#[derive(Component)]
+pub struct Sun;
+
+#[derive(Component)]
+pub struct FillLight;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlashKind {
+    Muzzle,
+    // Grenades going off, and the ultimate's pumpkins landing
+    Blast,
+}
+
+impl FlashKind {
+    fn intensity(self) -> f32 {
+        match self {
+            FlashKind::Muzzle => 400.,
+            FlashKind::Blast => 2000.,
+        }
+    }
+
+    fn seconds(self) -> f32 {
+        match self {
+            FlashKind::Muzzle => 0.06,
+            FlashKind::Blast => 0.25,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            FlashKind::Muzzle => Color::rgb(1., 0.85, 0.5),
+            FlashKind::Blast => Color::rgb(1., 0.6, 0.25),
+        }
+    }
+}
+
+// A point light that dies away almost as soon as it's lit. A horde's worth of them at once would
+// bring the frame rate down with it, so only the nearest few, up to the light budget in the video
+// settings, are ever lit.
+#[derive(Component)]
+struct Flash {
+    kind: FlashKind,
+    lifetime: Timer,
+}
+
+fn rotation(euler: Vec3) -> Quat {
+    Quat::from_euler(EulerRot::XYZ, euler.x, euler.y, euler.z)
+}
+
+fn setup_lights(mut commands: Commands, config: Res<GameConfig>) {
+    let lighting = &config.lighting;
+
+    commands.insert_resource(AmbientLight {
+        color: lighting.ambient_color,
+        brightness: lighting.ambient_brightness,
+    });
+
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                color: lighting.sun.color,
+                shadows_enabled: lighting.sun.shadows,
+                illuminance: lighting.sun.illuminance,
+                ..default()
+            },
+            transform: Transform {
+                rotation: rotation(lighting.sun.rotation),
+                ..default()
+            },
+            ..default()
+        },
+        Sun,
+    ));
+
+    // Fill lights never cast shadows - one shadowed light is plenty
+    for fill in &lighting.fill_lights {
+        commands.spawn((
+            DirectionalLightBundle {
+                directional_light: DirectionalLight {
+                    color: fill.color,
+                    illuminance: fill.illuminance,
+                    shadows_enabled: false,
+                    ..default()
+                },
+                transform: Transform {
+                    rotation: rotation(fill.rotation),
+                    ..default()
+                },
+                ..default()
+            },
+            FillLight,
+        ));
+    }
+}
+
+fn advance_time_of_day(
+    mut time_of_day: ResMut<TimeOfDay>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let day_length = config.lighting.day_length_seconds.max(f32::EPSILON);
+    time_of_day.progress = (time_of_day.progress + time.delta_seconds() / day_length).min(1.);
+}
+
+// The weather has its say too, clouding the sun over in the rain and lighting everything up when
+// lightning strikes
+fn apply_time_of_day(
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<Weather>,
+    config: Res<GameConfig>,
+    mut ambient: ResMut<AmbientLight>,
+    mut suns: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+) {
+    if !time_of_day.is_changed() && !weather.is_changed() {
+        return;
+    }
+
+    let lighting = &config.lighting;
+    let t = time_of_day.progress;
+    let overcast = 1. - weather.conditions.rain * OVERCAST_DIMMING;
+
+    // The sun arcs up and back down before sunset, then the moon hangs at a fixed height
+    let elevation = if t < SUNSET {
+        (t / SUNSET * PI).sin() * MAX_SUN_ELEVATION
+    } else {
+        MOON_ELEVATION
+    };
+
+    for (mut light, mut transform) in &mut suns {
+        let tint = sample_color(&SUN_COLORS, t).as_rgba_f32();
+        let base = lighting.sun.color.as_rgba_f32();
+        light.color = Color::rgb(base[0] * tint[0], base[1] * tint[1], base[2] * tint[2]);
+        light.illuminance = lighting.sun.illuminance
+            * sample(&SUN_BRIGHTNESS, t)
+            * overcast
+            * (1. + weather.flash * LIGHTNING_SUN_BOOST);
+        transform.rotation = Quat::from_euler(
+            EulerRot::XYZ,
+            -elevation.max(0.05),
+            lighting.sun.rotation.y,
+            lighting.sun.rotation.z,
+        );
+    }
+
+    ambient.brightness = lighting.ambient_brightness
+        * sample(&AMBIENT_BRIGHTNESS, t)
+        * (1. + weather.flash * LIGHTNING_AMBIENT_BOOST);
+}
+
+// Starts out unlit, and is lit from the next frame if there's room in the budget
+pub fn spawn_flash(commands: &mut Commands, kind: FlashKind, position: Vec3) {
+    commands.spawn((
+        PointLightBundle {
+            point_light: PointLight {
+                color: kind.color(),
+                intensity: kind.intensity(),
+                range: FLASH_RANGE,
+                shadows_enabled: false,
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            visibility: Visibility::INVISIBLE,
+            ..default()
+        },
+        Flash {
+            kind,
+            lifetime: Timer::from_seconds(kind.seconds(), TimerMode::Once),
+        },
+        DespawnOnReset,
+    ));
+}
+
+fn muzzle_flashes(
+    mut commands: Commands,
+    mut fired: EventReader<WeaponFired>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for event in fired.iter() {
+        let Ok(weapon) = transforms.get(event.weapon) else { continue };
+        let position = weapon.translation() + weapon.forward() * MUZZLE_OFFSET;
+        spawn_flash(&mut commands, FlashKind::Muzzle, position);
+    }
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut Flash, &mut PointLight)>,
+) {
+    for (entity, mut flash, mut light) in &mut flashes {
+        if flash.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        light.intensity = flash.kind.intensity() * (1. - flash.lifetime.percent());
+    }
+}
+
+// Whichever point lights are nearest the camera get lit, and the rest wait their turn. Anything
+// that adds point lights goes through this, flashes or not.
+fn enforce_light_budget(
+    game: Res<Game>,
+    video: Res<VideoSettings>,
+    transforms: Query<&GlobalTransform>,
+    mut lights: Query<(Entity, &GlobalTransform, &mut Visibility), With<PointLight>>,
+    // Kept between frames so sorting them doesn't allocate every time
+    mut nearest: Local<Vec<(Entity, f32)>>,
+) {
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let camera = camera.translation();
+
+    nearest.clear();
+    nearest.extend(
+        lights.iter().map(|(entity, transform, _)| {
+            (entity, transform.translation().distance_squared(camera))
+        }),
+    );
+    nearest.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+    nearest.truncate(video.light_budget as usize);
+
+    for (entity, _, mut visibility) in &mut lights {
+        let lit = nearest.iter().any(|(lit, _)| *lit == entity);
+        if visibility.is_visible != lit {
+            visibility.is_visible = lit;
+        }
+    }
+}
+
+// The sun's shadows only cover so much of the lane, so they're kept centred on where the camera's
+// looking as it scrolls along
+fn keep_shadows_in_view(
+    game: Res<Game>,
+    cameras: Query<&GlobalTransform>,
+    mut suns: Query<&mut Transform, With<Sun>>,
+) {
+    let Ok(camera) = cameras.get(game.camera) else { return };
+    let translation = camera.translation();
+    let centre = Vec3::new(translation.x, 0., translation.z - SHADOW_CENTRE_AHEAD);
+    for mut transform in &mut suns {
+        if transform.translation != centre {
+            transform.translation = centre;
+        }
+    }
+}
+
+fn keyframe_span<T: Copy>(keys: &[(f32, T)], t: f32) -> (T, T, f32) {
+    let next = keys
+        .iter()
+        .position(|(key, _)| *key >= t)
+        .unwrap_or(keys.len() - 1);
+    if next == 0 {
+        return (keys[0].1, keys[0].1, 0.);
+    }
+
+    let (from_t, from) = keys[next - 1];
+    let (to_t, to) = keys[next];
+    let blend = ((t - from_t) / (to_t - from_t)).clamp(0., 1.);
+    (from, to, blend)
+}
+
+fn sample(keys: &[(f32, f32)], t: f32) -> f32 {
+    let (from, to, blend) = keyframe_span(keys, t);
+    from + (to - from) * blend
+}
+
+pub fn sample_color(keys: &[(f32, Color)], t: f32) -> Color {
+    let (from, to, blend) = keyframe_span(keys, t);
+    let from = Vec4::from(from.as_rgba_f32());
+    let to = Vec4::from(to.as_rgba_f32());
+    let color = from.lerp(to, blend);
+    Color::rgba(color.x, color.y, color.z, color.w)
+}
+
+fn reset_time_of_day(mut time_of_day: ResMut<TimeOfDay>) {
+    *time_of_day = TimeOfDay::default();
+}
@@ -0,0 +1,121 @@
+use std::{fmt::Debug, fmt::Display, path::PathBuf};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{config::read_data_file, settings::Settings};
+
+const LOCALE_DIR: &str = "assets/locale";
+// Has every string, so anything missing from another language comes from here
+const FALLBACK_LANGUAGE: &str = "en";
+
+// Language codes, and what each language calls itself
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("fr", "Français")];
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Localization>()
+            .add_system_to_stage(CoreStage::PreUpdate, switch_language);
+    }
+}
+
+// Every bit of text the player reads, by key. Strings can have `{name}` placeholders in them, which
+// `format` fills in.
+#[derive(Resource)]
+pub struct Localization {
+    pub language: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        let fallback = load_language(FALLBACK_LANGUAGE);
+        Self {
+            language: FALLBACK_LANGUAGE.into(),
+            strings: fallback.clone(),
+            fallback,
+        }
+    }
+}
+
+impl Localization {
+    // The key itself if nobody's written it yet, so it's easy to spot
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    pub fn format(&self, key: &str, arguments: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in arguments {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    // For settings and the like that are shown by name. Looks up `<prefix>-<variant>`, lowercased,
+    // and falls back to the variant's own name.
+    pub fn variant(&self, prefix: &str, value: &impl Debug) -> String {
+        let name = format!("{value:?}");
+        let key = format!("{prefix}-{}", name.to_lowercase());
+        match self.strings.get(&key).or_else(|| self.fallback.get(&key)) {
+            Some(text) => text.clone(),
+            None => name,
+        }
+    }
+
+    pub fn on_off(&self, value: bool) -> &str {
+        self.get(if value { "on" } else { "off" })
+    }
+}
+
+pub fn language_name(code: &str) -> &str {
+    LANGUAGES
+        .iter()
+        .find(|(language, _)| *language == code)
+        .map_or(code, |(_, name)| name)
+}
+
+fn load_language(code: &str) -> HashMap<String, String> {
+    let path = PathBuf::from(LOCALE_DIR).join(format!("{code}.ron"));
+    let contents = match read_data_file(&path, baked_language(code)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to read {path:?}, its strings will be missing: {e}");
+            return HashMap::default();
+        }
+    };
+
+    ron::from_str(&contents).unwrap_or_else(|e| {
+        error!("{path:?} is invalid, its strings will be missing: {e}");
+        HashMap::default()
+    })
+}
+
+// Every language in `LANGUAGES` is baked in for web builds. Anything else has no strings of its
+// own, the same as a missing file.
+fn baked_language(code: &str) -> &'static str {
+    match code {
+        "en" => include_str!("../assets/locale/en.ron"),
+        "fr" => include_str!("../assets/locale/fr.ron"),
+        _ => "{}",
+    }
+}
+
+// Before anything gets drawn, so a change in the settings shows up the same frame
+fn switch_language(settings: Res<Settings>, mut localization: ResMut<Localization>) {
+    if localization.language == settings.language {
+        return;
+    }
+
+    localization.strings = if settings.language == FALLBACK_LANGUAGE {
+        localization.fallback.clone()
+    } else {
+        load_language(&settings.language)
+    };
+    localization.language = settings.language.clone();
+}
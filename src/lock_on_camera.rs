@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{camera_movement, settings::Settings, state::GameState, Aim, CameraRig, Player};
+
+// How much of the way across to the middle of the player and their target the camera slides,
+// and how far it'll go at most
+const LOCK_ON_SHIFT: f32 = 0.5;
+const MAX_LOCK_ON_SHIFT: f32 = 1.;
+// How much of what's left it turns to face, and how far it'll turn at most, in radians
+const LOCK_ON_TURN: f32 = 0.5;
+const MAX_LOCK_ON_YAW: f32 = 0.25;
+// Slower than the co-op framing, so switching targets doesn't jerk the camera about
+const LOCK_ON_FRAMING_RATE: f32 = 2.;
+
+pub struct LockOnCameraPlugin;
+
+impl Plugin for LockOnCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(frame_lock_on.before(camera_movement)),
+        );
+    }
+}
+
+// Leans the camera towards whatever the players are locked on to, so they and their targets stay
+// in shot. Only ever across the lane and round, never along it, so how far the lane's scrolled and
+// where beets turn up aren't affected.
+fn frame_lock_on(
+    mut rig: ResMut<CameraRig>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    players: Query<(&Aim, &GlobalTransform), With<Player>>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let midpoints = players
+        .iter()
+        .filter_map(|(aim, player)| {
+            let target = transforms.get(aim.target?).ok()?;
+            Some((player.translation() + target.translation()) / 2.)
+        })
+        .collect::<Vec<_>>();
+
+    // With nothing locked, or the setting off, it eases back to straight down the lane
+    let (shift, yaw) = if settings.lock_on_framing && !midpoints.is_empty() {
+        let midpoint = midpoints.iter().sum::<Vec3>() / midpoints.len() as f32;
+        // From where the camera would be without any lean
+        let base = rig.scroll + rig.framing;
+        let across = midpoint.x - base.x;
+        let shift = (across * LOCK_ON_SHIFT).clamp(-MAX_LOCK_ON_SHIFT, MAX_LOCK_ON_SHIFT);
+        let ahead = (base.z - midpoint.z).max(1.);
+        // Turning left is positive, and anything to the right needs turning towards
+        let yaw = (-((across - shift) / ahead).atan() * LOCK_ON_TURN)
+            .clamp(-MAX_LOCK_ON_YAW, MAX_LOCK_ON_YAW);
+        (Vec3::new(shift, 0., 0.), yaw)
+    } else {
+        (Vec3::ZERO, 0.)
+    };
+
+    let catch_up = (time.delta_seconds() * LOCK_ON_FRAMING_RATE).min(1.);
+    rig.lock_on = rig.lock_on.lerp(shift, catch_up);
+    rig.yaw += (yaw - rig.yaw) * catch_up;
+}
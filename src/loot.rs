@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    config::read_data_file,
+    pickups::{spawn_pickup, PickupAssets, PickupKind},
+    state::GameState,
+    EnemyKilled, EnemyKind, GameRng,
+};
+
+const LOOT_PATH: &str = "assets/loot.ron";
+// Kept dim and short, since a field full of drops would otherwise eat the light budget
+const GLOW_INTENSITY: f32 = 60.;
+const GLOW_RANGE: f32 = 1.;
+const GLOW_HEIGHT: f32 = 0.2;
+// Straight up out of a rare drop, tall enough to be seen over a crowd of beets
+const BEAM_HEIGHT: f32 = 3.;
+const BEAM_RADIUS: f32 = 0.03;
+const BEAM_COLOR: Color = Color::rgba(0.75, 0.45, 1., 0.35);
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LootTables::load())
+            .add_startup_system(setup_loot_assets)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(drop_loot));
+    }
+}
+
+// Only uncommon and rarer drops are made to stand out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Rarity {
+    fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::WHITE,
+            Rarity::Uncommon => Color::rgb(0.35, 0.95, 0.45),
+            Rarity::Rare => Color::rgb(0.75, 0.45, 1.),
+        }
+    }
+}
+
+// One of a tier's drops, as written in loot.ron
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct LootDrop {
+    pub pickup: PickupKind,
+    #[serde(default = "one")]
+    pub weight: f32,
+}
+
+fn one() -> f32 {
+    1.
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootTier {
+    pub rarity: Rarity,
+    pub chance: f32,
+    pub drops: Vec<LootDrop>,
+}
+
+// Everything one kind of beet can leave behind
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootTable {
+    pub kind: EnemyKind,
+    #[serde(default)]
+    pub always: Vec<PickupKind>,
+    #[serde(default)]
+    pub tiers: Vec<LootTier>,
+}
+
+impl LootTable {
+    // What always drops, then at most one thing out of the tiers. They're rolled in order and the
+    // first that comes up is the one that drops.
+    fn roll(&self, rng: &mut impl Rng) -> Vec<(PickupKind, Rarity)> {
+        let mut loot = self
+            .always
+            .iter()
+            .map(|pickup| (*pickup, Rarity::Common))
+            .collect::<Vec<_>>();
+        let tier = self
+            .tiers
+            .iter()
+            .find(|tier| rng.gen_bool(tier.chance.clamp(0., 1.) as f64));
+        if let Some(tier) = tier {
+            if let Ok(drop) = tier.drops.choose_weighted(rng, |drop| drop.weight) {
+                loot.push((drop.pickup, tier.rarity));
+            }
+        }
+        loot
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct LootTables(pub Vec<LootTable>);
+
+// Without loot.ron, beets still drop their coin so the shop has something to sell for
+impl Default for LootTables {
+    fn default() -> Self {
+        Self(vec![LootTable {
+            kind: EnemyKind::Beet,
+            always: vec![PickupKind::Coin],
+            tiers: Vec::new(),
+        }])
+    }
+}
+
+impl LootTables {
+    fn load() -> Self {
+        let path = Path::new(LOOT_PATH);
+        let contents = match read_data_file(path, include_str!("../assets/loot.ron")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Unable to read {path:?}, beets will only drop coins: {e}");
+                return Self::default();
+            }
+        };
+
+        match ron::from_str::<Vec<LootTable>>(&contents) {
+            Ok(tables) => Self(tables),
+            Err(e) => {
+                error!("{path:?} is invalid, beets will only drop coins: {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LootAssets {
+    beam_mesh: Handle<Mesh>,
+    beam_material: Handle<StandardMaterial>,
+}
+
+fn setup_loot_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(LootAssets {
+        beam_mesh: meshes.add(Mesh::from(shape::Capsule {
+            radius: BEAM_RADIUS,
+            depth: BEAM_HEIGHT,
+            ..default()
+        })),
+        beam_material: materials.add(StandardMaterial {
+            base_color: BEAM_COLOR,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+// Rolled with the game's own dice, so a recording drops the same things every time it's played
+fn drop_loot(
+    mut commands: Commands,
+    mut killed: EventReader<EnemyKilled>,
+    tables: Res<LootTables>,
+    pickup_assets: Res<PickupAssets>,
+    assets: Res<LootAssets>,
+    mut rng: ResMut<GameRng>,
+) {
+    for enemy in killed.iter() {
+        let Some(table) = tables.0.iter().find(|table| table.kind == enemy.kind) else { continue };
+        for (pickup, rarity) in table.roll(&mut rng.0) {
+            let entity = spawn_pickup(&mut commands, &pickup_assets, pickup, enemy.position);
+            if rarity != Rarity::Common {
+                commands.entity(entity).with_children(|parent| {
+                    glow(parent, &assets, rarity);
+                });
+            }
+        }
+    }
+}
+
+// A little light of the rarity's colour for anything uncommon, and a beam on top for anything
+// rare, so they can be picked out from the other end of the lane. The light goes through the
+// budget like any other, so it may be the beam that does the work.
+fn glow(parent: &mut ChildBuilder, assets: &LootAssets, rarity: Rarity) {
+    parent.spawn(PointLightBundle {
+        point_light: PointLight {
+            color: rarity.color(),
+            intensity: GLOW_INTENSITY,
+            range: GLOW_RANGE,
+            shadows_enabled: false,
+            ..default()
+        },
+        transform: Transform::from_xyz(0., GLOW_HEIGHT, 0.),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    });
+    if rarity == Rarity::Rare {
+        parent.spawn((
+            PbrBundle {
+                mesh: assets.beam_mesh.clone(),
+                material: assets.beam_material.clone(),
+                transform: Transform::from_xyz(0., BEAM_HEIGHT / 2., 0.),
+                ..default()
+            },
+            NotShadowCaster,
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::Health,
+    screen_effects::{ScreenEffect, ScreenEffects},
+    settings::Settings,
+    state::GameState,
+    Player,
+};
+
+const HEARTBEAT: &str = "audio/heartbeat.ogg";
+// Below this much of their health it starts, and it's at its worst on none
+const LOW_HEALTH: f32 = 0.35;
+// Per second, so a heal fades it out rather than snapping it off
+const URGENCY_EASE: f32 = 1.5;
+const VIGNETTE_COLOR: Color = Color::rgb(0.55, 0., 0.);
+const VIGNETTE_ALPHA: f32 = 0.75;
+// A dull grey film over everything, so the world looks dimmer and flatter. Not true
+// desaturation, which would need a post-process bevy doesn't have yet.
+const TINT_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const TINT_ALPHA: f32 = 0.3;
+const HEARTBEAT_VOLUME: f32 = 0.8;
+// How much quicker the heart's going at death's door than when it first kicks in
+const HEARTBEAT_QUICKENS: f32 = 0.6;
+
+pub struct LowHealthPlugin;
+
+impl Plugin for LowHealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LowHealth>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(measure_urgency))
+            .add_system(show_urgency.after(measure_urgency))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_low_health));
+    }
+}
+
+// How close to death the worst-off player is, from 0 when nobody's below `LOW_HEALTH` to 1 on
+// no health at all. Eased towards, like the music intensity.
+#[derive(Resource, Default)]
+pub struct LowHealth {
+    pub urgency: f32,
+    heartbeat: Option<Handle<AudioSink>>,
+}
+
+fn measure_urgency(
+    mut low_health: ResMut<LowHealth>,
+    time: Res<Time>,
+    players: Query<&Health, With<Player>>,
+) {
+    let target = players
+        .iter()
+        .filter(|health| health.max > 0.)
+        .map(|health| 1. - health.current / health.max / LOW_HEALTH)
+        .fold(0_f32, f32::max)
+        .clamp(0., 1.);
+
+    let step = URGENCY_EASE * time.delta_seconds();
+    let urgency = low_health.urgency;
+    low_health.urgency = if target > urgency {
+        (urgency + step).min(target)
+    } else {
+        (urgency - step).max(target)
+    };
+}
+
+// Only while playing: the shop, the choice between waves and the cinematic all get a clear screen
+// and a quiet heart. Pausing suspends the heartbeat along with every other sound.
+fn show_urgency(
+    mut low_health: ResMut<LowHealth>,
+    mut effects: ResMut<ScreenEffects>,
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let urgency = match state.current() {
+        GameState::Playing => low_health.urgency,
+        _ => 0.,
+    };
+
+    if urgency > 0. {
+        effects.set(
+            "low_health",
+            ScreenEffect {
+                vignette: VIGNETTE_COLOR.with_a(VIGNETTE_ALPHA * urgency),
+                tint: TINT_COLOR.with_a(TINT_ALPHA * urgency),
+            },
+        );
+    } else {
+        effects.clear("low_health");
+    }
+
+    // Started the first time it's needed and left looping quietly after that
+    if urgency > 0. && low_health.heartbeat.is_none() {
+        let sink = audio.play_with_settings(
+            asset_server.load(HEARTBEAT),
+            PlaybackSettings::LOOP.with_volume(0.),
+        );
+        low_health.heartbeat = Some(audio_sinks.get_handle(sink));
+    }
+    let Some(heartbeat) = &low_health.heartbeat else { return };
+    let Some(sink) = audio_sinks.get(heartbeat) else { return };
+    sink.set_volume(urgency * HEARTBEAT_VOLUME * settings.effects());
+    sink.set_speed(1. + urgency * HEARTBEAT_QUICKENS);
+}
+
+// Every run starts with a calm heart
+fn reset_low_health(mut low_health: ResMut<LowHealth>) {
+    low_health.urgency = 0.;
+}
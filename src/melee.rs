@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::{
+    animation::{AnimationState, Animated},
+    damage::DamageEvent,
+    dodge::Dodge,
+    input::PlayerInput,
+    state::GameState,
+    weapon_movement, Enemy, Player,
+};
+
+const MELEE_SECONDS: f32 = 0.2;
+// Counted from the start of the swipe
+const MELEE_COOLDOWN_SECONDS: f32 = 0.8;
+const MELEE_RANGE: f32 = 0.5;
+// Either side of straight ahead, in radians
+const MELEE_HALF_ARC: f32 = 1.;
+const MELEE_DAMAGE: f32 = 1.;
+// A good deal harder than a pumpkin, to buy some room
+const MELEE_KNOCKBACK: f32 = 0.15;
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(start_swipes)
+                .with_system(swing.after(start_swipes).after(weapon_movement)),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_melee));
+    }
+}
+
+// Every player has one. Both timers count down to zero and stay there.
+#[derive(Component, Default)]
+pub struct Melee {
+    swinging: f32,
+    cooldown: f32,
+}
+
+impl Melee {
+    pub fn is_swinging(&self) -> bool {
+        self.swinging > 0.
+    }
+}
+
+// The damage all lands at once, on everything close enough in the direction the gun's pointing
+fn start_swipes(
+    mut players: Query<(&Player, &PlayerInput, &mut Melee, Option<&Dodge>, &mut Animated)>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    transforms: Query<&GlobalTransform>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (player, input, mut melee, dodge, mut animated) in &mut players {
+        if !input.melee || melee.is_swinging() || melee.cooldown > 0. {
+            continue;
+        }
+        // Mid-roll the carrot's in no state to swing at anything
+        if dodge.map_or(false, Dodge::is_rolling) {
+            continue;
+        }
+        let Ok(weapon) = transforms.get(player.weapon) else { continue };
+
+        melee.swinging = MELEE_SECONDS;
+        melee.cooldown = MELEE_COOLDOWN_SECONDS;
+        animated.restart(AnimationState::Melee);
+
+        let origin = weapon.translation();
+        let forward = weapon.forward();
+        let facing = Vec3::new(forward.x, 0., forward.z).normalize_or_zero();
+        for (enemy, transform) in &enemies {
+            let offset = transform.translation() - origin;
+            let flat = Vec3::new(offset.x, 0., offset.z);
+            if flat.length() > MELEE_RANGE || facing.angle_between(flat) > MELEE_HALF_ARC {
+                continue;
+            }
+            damage.send(DamageEvent {
+                source: Some(player.weapon),
+                target: enemy,
+                amount: MELEE_DAMAGE,
+                direction: flat.normalize_or_zero(),
+                critical: false,
+                knockback: MELEE_KNOCKBACK,
+            });
+        }
+    }
+}
+
+// Sweeps the gun from one side of the arc to the other. The aiming sets it straight again every
+// frame, so there's nothing to put back afterwards.
+fn swing(
+    time: Res<Time>,
+    mut players: Query<(&Player, &mut Melee)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let delta = time.delta_seconds();
+    for (player, mut melee) in &mut players {
+        melee.cooldown = (melee.cooldown - delta).max(0.);
+        if !melee.is_swinging() {
+            continue;
+        }
+        melee.swinging = (melee.swinging - delta).max(0.);
+
+        let progress = 1. - melee.swinging / MELEE_SECONDS;
+        let sweep = MELEE_HALF_ARC * (1. - 2. * progress);
+        if let Ok(mut weapon) = transforms.get_mut(player.weapon) {
+            weapon.rotation = Quat::from_rotation_y(sweep) * weapon.rotation;
+        }
+    }
+}
+
+fn reset_melee(mut players: Query<&mut Melee>) {
+    for mut melee in &mut players {
+        *melee = Melee::default();
+    }
+}
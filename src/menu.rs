@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+use crate::{
+    campaign::ActiveLevel, difficulty::Difficulty, input::PlayerInput, localization::Localization,
+    navigation::MenuNavigation, state::GameState, suspend::SavedRun,
+};
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(spawn_main_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(start_run)
+                    .with_system(choose_difficulty)
+                    .with_system(update_difficulty_text.after(choose_difficulty)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu).with_system(despawn_main_menu),
+            )
+            // The leaderboard, level select and character select cover the menu, so take it down and
+            // put it back
+            .add_system_set(
+                SystemSet::on_pause(GameState::MainMenu).with_system(despawn_main_menu),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::MainMenu).with_system(spawn_main_menu),
+            );
+    }
+}
+
+#[derive(Component)]
+struct MainMenuUi;
+
+#[derive(Component)]
+struct DifficultyText;
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    saved: Res<SavedRun>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            MainMenuUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.get("menu-title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 80.,
+                    color: Color::WHITE,
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 36.,
+                        color: Color::YELLOW,
+                    },
+                ),
+                DifficultyText,
+            ));
+            if let Some(run) = &saved.0 {
+                parent.spawn(TextBundle::from_section(
+                    localization.format("menu-continue", &[("wave", &run.wave)]),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.,
+                        color: Color::YELLOW,
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                localization.get("menu-help"),
+                TextStyle {
+                    font,
+                    font_size: 30.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn despawn_main_menu(mut commands: Commands, ui: Query<Entity, With<MainMenuUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// The endless lane, once a hero's been picked. Campaign levels start from the level select instead.
+fn start_run(
+    input: Res<PlayerInput>,
+    mut level: ResMut<ActiveLevel>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !input.confirm {
+        return;
+    }
+    level.0 = None;
+    if let Err(e) = state.push(GameState::CharacterSelect) {
+        warn!("Unable to open the character select: {e:?}");
+    }
+}
+
+fn choose_difficulty(navigation: Res<MenuNavigation>, mut difficulty: ResMut<Difficulty>) {
+    if navigation.step.x == 0 {
+        return;
+    }
+
+    let next = difficulty.step(navigation.step.x);
+    if next != *difficulty {
+        *difficulty = next;
+    }
+}
+
+fn update_difficulty_text(
+    difficulty: Res<Difficulty>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<DifficultyText>>,
+) {
+    let name = localization.variant("difficulty", &*difficulty);
+    for mut text in &mut texts {
+        text.sections[0].value = format!("< {name} >");
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::{DamageAppExt, DamageContext, DamageStage, Health},
+    input::{InputSource, PlayerInput},
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    netplay::Netplay,
+    state::GameState,
+    Enemy, Player,
+};
+
+// How much bigger a vegetable's model is drawn with big heads on. Only the model: it's no easier
+// to hit and takes up no more room.
+const BIG_HEAD_SCALE: f32 = 1.8;
+
+pub struct MutatorPlugin;
+
+impl Plugin for MutatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mutators>()
+            .init_resource::<MutatorSelect>()
+            .add_damage_modifier(DamageStage::Clamp, one_hit_point)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Mutators).with_system(spawn_mutator_select),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Mutators)
+                    .with_system(navigate_mutator_select)
+                    .with_system(update_mutator_select_text.after(navigate_mutator_select)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Mutators).with_system(despawn_mutator_select),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(begin_mutators))
+            .add_system(big_heads)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(end_mutators));
+    }
+}
+
+// Twists on a run, picked before it starts. Each one makes the score worth more or less, and
+// they stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutator {
+    DoubleSpeed,
+    OneHitPoint,
+    InfiniteAmmo,
+    BigHeads,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 4] = [
+        Mutator::DoubleSpeed,
+        Mutator::OneHitPoint,
+        Mutator::InfiniteAmmo,
+        Mutator::BigHeads,
+    ];
+
+    pub fn score_multiplier(&self) -> f32 {
+        match self {
+            Mutator::DoubleSpeed => 1.5,
+            Mutator::OneHitPoint => 2.,
+            // Makes it easier, so it's worth less
+            Mutator::InfiniteAmmo => 0.5,
+            Mutator::BigHeads => 1.,
+        }
+    }
+}
+
+// What's been picked in the menu, and what the run that's going is actually being played with.
+// Recordings and online games are always played without any, the same as they're always played
+// as the first hero.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Mutators {
+    chosen: [bool; Mutator::ALL.len()],
+    active: [bool; Mutator::ALL.len()],
+}
+
+impl Mutators {
+    pub fn is_chosen(&self, mutator: Mutator) -> bool {
+        self.chosen[mutator as usize]
+    }
+
+    pub fn is_active(&self, mutator: Mutator) -> bool {
+        self.active[mutator as usize]
+    }
+
+    // Of whatever's been picked, for the menus
+    pub fn chosen_multiplier(&self) -> f32 {
+        Self::multiplier(self.chosen)
+    }
+
+    // Points as they're scored, rounded to the nearest whole one
+    pub fn score(&self, points: u32) -> u32 {
+        (points as f32 * Self::multiplier(self.active)).round() as u32
+    }
+
+    // Exactly these for the run that's starting, whatever's been picked in the menu
+    pub fn play_with(&mut self, mutators: &[Mutator]) {
+        self.active = default();
+        for mutator in mutators {
+            self.active[*mutator as usize] = true;
+        }
+    }
+
+    pub fn enemy_speed(&self) -> f32 {
+        if self.is_active(Mutator::DoubleSpeed) {
+            2.
+        } else {
+            1.
+        }
+    }
+
+    fn multiplier(on: [bool; Mutator::ALL.len()]) -> f32 {
+        Mutator::ALL
+            .iter()
+            .filter(|mutator| on[**mutator as usize])
+            .map(Mutator::score_multiplier)
+            .product()
+    }
+}
+
+#[derive(Resource, Default)]
+struct MutatorSelect {
+    row: usize,
+}
+
+#[derive(Component)]
+struct MutatorSelectUi;
+
+#[derive(Component)]
+struct MutatorSelectText;
+
+// On a model that's been blown up, with the size it was before
+#[derive(Component)]
+struct BigHead(Vec3);
+
+// Any hit at all on a player takes everything they've got left
+fn one_hit_point(context: &mut DamageContext, world: &World) {
+    let on = world
+        .get_resource::<Mutators>()
+        .map_or(false, |mutators| mutators.is_active(Mutator::OneHitPoint));
+    if !on || context.amount <= 0. || world.get::<Player>(context.target).is_none() {
+        return;
+    }
+    if let Some(health) = world.get::<Health>(context.target) {
+        context.amount = context.amount.max(health.current);
+    }
+}
+
+fn spawn_mutator_select(
+    mut commands: Commands,
+    mut select: ResMut<MutatorSelect>,
+    asset_server: Res<AssetServer>,
+) {
+    select.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            MutatorSelectUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                MutatorSelectText,
+            ));
+        });
+}
+
+// Up and down pick one, confirm turns it on or off, back goes back to the character select
+fn navigate_mutator_select(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut select: ResMut<MutatorSelect>,
+    mut mutators: ResMut<Mutators>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the mutators: {e:?}");
+        }
+        return;
+    }
+
+    if input.confirm {
+        let row = select.row;
+        mutators.chosen[row] = !mutators.chosen[row];
+        return;
+    }
+
+    let count = Mutator::ALL.len();
+    select.row = navigation.row(select.row, count);
+}
+
+fn update_mutator_select_text(
+    select: Res<MutatorSelect>,
+    mutators: Res<Mutators>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<MutatorSelectText>>,
+) {
+    let mut panel = format!("{}\n\n", localization.get("mutators-title"));
+    for (index, mutator) in Mutator::ALL.iter().enumerate() {
+        let cursor = if index == select.row { ">" } else { " " };
+        let check = if mutators.is_chosen(*mutator) {
+            "x"
+        } else {
+            " "
+        };
+        panel += &format!(
+            "{cursor} [{check}] {:<24}{:>6}\n",
+            localization.variant("mutator", mutator),
+            format!("x{:.1}", mutator.score_multiplier()),
+        );
+    }
+    let multiplier = format!("{:.2}", mutators.chosen_multiplier());
+    panel += &format!(
+        "\n{}\n\n{}",
+        localization.format("mutators-score", &[("multiplier", &multiplier)]),
+        localization.get("mutators-help"),
+    );
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn despawn_mutator_select(mut commands: Commands, ui: Query<Entity, With<MutatorSelectUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Every run leaves the main menu behind, so that's where it's settled what it's played with
+pub fn begin_mutators(
+    mut mutators: ResMut<Mutators>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+) {
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    mutators.active = if live { mutators.chosen } else { default() };
+}
+
+fn end_mutators(mut mutators: ResMut<Mutators>) {
+    mutators.active = default();
+}
+
+// A vegetable's model is the top of its scene, straight under the entity itself. Beets drawn as
+// part of a big horde stay their usual size.
+fn big_heads(
+    mut commands: Commands,
+    mutators: Res<Mutators>,
+    vegetables: Query<&Children, Or<(With<Player>, With<Enemy>)>>,
+    mut models: Query<(Entity, &mut Transform, Option<&BigHead>), With<Name>>,
+) {
+    let on = mutators.is_active(Mutator::BigHeads);
+    for children in &vegetables {
+        let mut iter = models.iter_many_mut(children);
+        while let Some((entity, mut transform, big)) = iter.fetch_next() {
+            match (on, big) {
+                (true, None) => {
+                    commands.entity(entity).insert(BigHead(transform.scale));
+                    transform.scale *= BIG_HEAD_SCALE;
+                }
+                (false, Some(big)) => {
+                    transform.scale = big.0;
+                    commands.entity(entity).remove::<BigHead>();
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{
+    input::{gather_input, GamepadAssignments, InputSource, PlayerInput},
+    state::GameState,
+};
+
+// How far the stick has to go to count as a press in the menus
+const NAVIGATE_THRESHOLD: f32 = 0.5;
+// What every menu marks the row it's on with
+const CURSOR: &str = "> ";
+pub const FOCUS_COLOR: Color = Color::YELLOW;
+pub const BUTTON_COLOR: Color = Color::rgb(0.2, 0.35, 0.15);
+const FOCUSED_BUTTON_COLOR: Color = Color::rgb(0.45, 0.6, 0.2);
+
+// Which way player one pushed this frame in the menus. The left stick, the d-pad, WASD and the
+// arrow keys all count, so no screen needs a mouse. Confirm and back come from `PlayerInput`.
+#[derive(Resource, Default)]
+pub struct MenuNavigation {
+    // One axis at a time, with up as +y. Zero unless there's been a fresh push since last frame.
+    pub step: IVec2,
+    held: IVec2,
+}
+
+impl MenuNavigation {
+    // Where a push up or down takes the cursor in a list of `rows`. Up the screen is towards the
+    // first row, and either end wraps round to the other.
+    pub fn row(&self, row: usize, rows: usize) -> usize {
+        match self.step.y {
+            0 => row,
+            step if step > 0 => (row + rows - 1) % rows,
+            _ => (row + 1) % rows,
+        }
+    }
+}
+
+// The button a push of confirm will press. Each screen with buttons moves it about itself.
+#[derive(Component)]
+pub struct Focused;
+
+pub struct MenuNavigationPlugin;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuNavigation>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gather_navigation.after(InputSystem).after(gather_input),
+            )
+            .add_system(highlight_focused_buttons);
+    }
+}
+
+// One step per push, rather than whizzing through the rows while it's held
+fn gather_navigation(
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    assignments: Res<GamepadAssignments>,
+    buttons: Res<Input<GamepadButton>>,
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut navigation: ResMut<MenuNavigation>,
+) {
+    let mut push = input.movement;
+    // Recordings only have what went into `PlayerInput`
+    if matches!(*source, InputSource::Live) {
+        let key = |key_code| if keys.pressed(key_code) { 1. } else { 0. };
+        push += Vec2::new(
+            key(KeyCode::Right) - key(KeyCode::Left),
+            key(KeyCode::Up) - key(KeyCode::Down),
+        );
+        if let Some(gamepad) = assignments.gamepad(0) {
+            let button = |button_type| {
+                if buttons.pressed(GamepadButton::new(gamepad, button_type)) {
+                    1.
+                } else {
+                    0.
+                }
+            };
+            push += Vec2::new(
+                button(GamepadButtonType::DPadRight) - button(GamepadButtonType::DPadLeft),
+                button(GamepadButtonType::DPadUp) - button(GamepadButtonType::DPadDown),
+            );
+        }
+    }
+
+    let direction = if push.x.abs() < NAVIGATE_THRESHOLD && push.y.abs() < NAVIGATE_THRESHOLD {
+        IVec2::ZERO
+    } else if push.y.abs() > push.x.abs() {
+        IVec2::new(0, push.y.signum() as i32)
+    } else {
+        IVec2::new(push.x.signum() as i32, 0)
+    };
+    // A screen that's just opened shouldn't act on a push that was meant for the one before it
+    let fresh = direction != navigation.held && !state.is_changed();
+    navigation.step = if fresh { direction } else { IVec2::ZERO };
+    navigation.held = direction;
+}
+
+// Writes a menu's panel into `text`, with the line the cursor's on picked out in the focus colour
+pub fn set_panel(text: &mut Text, panel: &str) {
+    let style = text.sections[0].style.clone();
+    let start = if panel.starts_with(CURSOR) {
+        Some(0)
+    } else {
+        panel.find(&format!("\n{CURSOR}")).map(|index| index + 1)
+    };
+    let Some(start) = start else {
+        text.sections = vec![TextSection::new(panel, style)];
+        return;
+    };
+
+    let end = panel[start..]
+        .find('\n')
+        .map_or(panel.len(), |index| start + index);
+    let focused = TextStyle {
+        color: FOCUS_COLOR,
+        ..style.clone()
+    };
+    text.sections = vec![
+        TextSection::new(&panel[..start], style.clone()),
+        TextSection::new(&panel[start..end], focused),
+        TextSection::new(&panel[end..], style),
+    ];
+}
+
+fn highlight_focused_buttons(
+    mut buttons: Query<(&mut BackgroundColor, Option<&Focused>), With<Button>>,
+) {
+    for (mut background, focused) in &mut buttons {
+        let color = if focused.is_some() {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
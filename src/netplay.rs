@@ -0,0 +1,262 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    net::UdpSocket,
+    time::{Duration, Instant},
+};
+
+use bevy::{app::AppExit, prelude::*, time::TimeSystem};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    golden,
+    input::{self, PlayerInput},
+    level_gen::LevelSeed,
+    revive,
+    settings::Settings,
+    GameRng, Player,
+};
+
+// How long to wait to hear from the other machine before sending our input again
+const RESEND_INTERVAL: Duration = Duration::from_millis(20);
+// Give up on the other machine after this long without a word from it
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_PACKET: usize = 1024;
+
+pub enum NetRole {
+    Host { port: u16 },
+    Join { address: String },
+}
+
+// Online co-op in lockstep: both machines run the whole game, and neither advances a frame until
+// it has the other's input for it. With the same seed and a fixed frame time the two runs then
+// play out identically, so inputs are all that ever goes over the wire.
+//
+// The host is player one on both machines, and drives the menus for both.
+pub struct NetplayPlugin {
+    pub role: NetRole,
+    pub seed: u64,
+}
+
+impl Plugin for NetplayPlugin {
+    fn build(&self, app: &mut App) {
+        let (socket, seed, host) = match &self.role {
+            NetRole::Host { port } => host(*port, self.seed).map(|socket| (socket, self.seed, true)),
+            NetRole::Join { address } => join(address).map(|(socket, seed)| (socket, seed, false)),
+        }
+        .unwrap_or_else(|e| panic!("Unable to start online co-op: {e}"));
+
+        // Anything the simulation reads has to match on both ends. Volume and the like can stay.
+        let defaults = Settings::default();
+        let mut settings = app.world.resource_mut::<Settings>();
+        settings.aim_mode = defaults.aim_mode;
+        settings.aim_assist = defaults.aim_assist;
+        settings.aim_assist_strength = defaults.aim_assist_strength;
+        settings.lead_targets = defaults.lead_targets;
+        settings.assisted_play = defaults.assisted_play;
+        settings.left_stick.dead_zone = defaults.left_stick.dead_zone;
+        settings.right_stick.dead_zone = defaults.right_stick.dead_zone;
+
+        app.insert_resource(LevelSeed(seed))
+            .insert_resource(GameRng::from_seed(seed))
+            .insert_resource(Netplay {
+                socket,
+                host,
+                seed,
+                frame: 0,
+                previous: PlayerInput::default(),
+                received: BTreeMap::new(),
+                guest: PlayerInput::default(),
+            })
+            .add_system_to_stage(CoreStage::First, golden::fixed_frame_time.after(TimeSystem))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                exchange_inputs
+                    .after(input::gather_input)
+                    .before(input::gather_player_input),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                apply_guest_input
+                    .after(input::gather_player_input)
+                    .before(revive::pin_downed),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum NetMessage {
+    Hello,
+    Welcome {
+        seed: u64,
+    },
+    // The frame before rides along in case its packet went missing
+    Input {
+        frame: u32,
+        input: PlayerInput,
+        previous: PlayerInput,
+    },
+}
+
+#[derive(Resource)]
+pub struct Netplay {
+    socket: UdpSocket,
+    host: bool,
+    seed: u64,
+    frame: u32,
+    previous: PlayerInput,
+    received: BTreeMap<u32, PlayerInput>,
+    // The host's input for this frame goes in the `PlayerInput` resource, the guest's here
+    guest: PlayerInput,
+}
+
+impl Netplay {
+    fn exchange(&mut self, local: PlayerInput) -> io::Result<PlayerInput> {
+        let frame = self.frame;
+        let message = NetMessage::Input {
+            frame,
+            input: local,
+            previous: self.previous,
+        };
+        send(&self.socket, &message)?;
+
+        let mut buffer = [0; MAX_PACKET];
+        let mut last_heard = Instant::now();
+        loop {
+            if let Some(remote) = self.received.remove(&frame) {
+                self.received = self.received.split_off(&(frame + 1));
+                self.previous = local;
+                self.frame += 1;
+                return Ok(remote);
+            }
+
+            match self.socket.recv(&mut buffer) {
+                Ok(length) => {
+                    last_heard = Instant::now();
+                    match decode(&buffer[..length]) {
+                        Some(NetMessage::Input {
+                            frame: theirs,
+                            input,
+                            previous,
+                        }) => {
+                            self.received.insert(theirs, input);
+                            if theirs > 0 {
+                                self.received.entry(theirs - 1).or_insert(previous);
+                            }
+                        }
+                        // They never got our welcome
+                        Some(NetMessage::Hello) if self.host => {
+                            send(&self.socket, &NetMessage::Welcome { seed: self.seed })?
+                        }
+                        _ => {}
+                    }
+                }
+                Err(e) if is_retryable(&e) => send(&self.socket, &message)?,
+                Err(e) => return Err(e),
+            }
+
+            if last_heard.elapsed() > TIMEOUT {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the other player stopped responding",
+                ));
+            }
+        }
+    }
+}
+
+fn host(port: u16, seed: u64) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(("0.0.0.0", port))?;
+    info!("Waiting for another player on port {port}");
+
+    let mut buffer = [0; MAX_PACKET];
+    loop {
+        let (length, peer) = socket.recv_from(&mut buffer)?;
+        if let Some(NetMessage::Hello) = decode(&buffer[..length]) {
+            socket.connect(peer)?;
+            socket.set_read_timeout(Some(RESEND_INTERVAL))?;
+            send(&socket, &NetMessage::Welcome { seed })?;
+            info!("{peer} joined");
+            return Ok(socket);
+        }
+    }
+}
+
+fn join(address: &str) -> io::Result<(UdpSocket, u64)> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    socket.connect(address)?;
+    socket.set_read_timeout(Some(RESEND_INTERVAL))?;
+    info!("Joining {address}");
+
+    let started = Instant::now();
+    let mut buffer = [0; MAX_PACKET];
+    while started.elapsed() < TIMEOUT {
+        send(&socket, &NetMessage::Hello)?;
+        match socket.recv(&mut buffer) {
+            Ok(length) => {
+                if let Some(NetMessage::Welcome { seed }) = decode(&buffer[..length]) {
+                    return Ok((socket, seed));
+                }
+            }
+            Err(e) if is_retryable(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("nobody answered at {address}"),
+    ))
+}
+
+fn send(socket: &UdpSocket, message: &NetMessage) -> io::Result<()> {
+    let encoded =
+        ron::to_string(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    socket.send(encoded.as_bytes()).map(|_| ())
+}
+
+// Anything we can't make sense of is dropped, the same as a lost packet
+fn decode(bytes: &[u8]) -> Option<NetMessage> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    ron::from_str(text).ok()
+}
+
+// Timeouts are how we know to resend, and a refused connection just means the other end isn't
+// listening yet
+fn is_retryable(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::ConnectionRefused
+    )
+}
+
+fn exchange_inputs(
+    mut netplay: ResMut<Netplay>,
+    mut input: ResMut<PlayerInput>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let local = *input;
+    match netplay.exchange(local) {
+        Ok(remote) => {
+            let (host, guest) = if netplay.host {
+                (local, remote)
+            } else {
+                (remote, local)
+            };
+            *input = host;
+            netplay.guest = guest;
+        }
+        Err(e) => {
+            error!("Lost the other player: {e}");
+            exit.send(AppExit);
+        }
+    }
+}
+
+fn apply_guest_input(netplay: Res<Netplay>, mut players: Query<(&Player, &mut PlayerInput)>) {
+    for (player, mut input) in &mut players {
+        if player.index == 1 {
+            *input = netplay.guest;
+        }
+    }
+}
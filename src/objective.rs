@@ -0,0 +1,321 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    camera_start,
+    campaign::ActiveLevel,
+    cinematic::{cut_to_game_over, Cinematic, Shot},
+    config::GameConfig,
+    damage::{DamageDealt, Health},
+    localization::Localization,
+    reset::DespawnOnReset,
+    state::GameState,
+    waves::WaveManager,
+    CameraRig,
+};
+
+// The tractor trundles along this far in front of the camera
+const ESCORT_AHEAD: f32 = 4.;
+const CROP_RADIUS: f32 = 0.2;
+const HUD_MARGIN: f32 = 20.;
+
+pub struct ObjectivePlugin;
+
+impl Plugin for ObjectivePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Objective>()
+            .add_event::<ObjectiveCompleted>()
+            .add_event::<ObjectiveFailed>()
+            .add_startup_system(setup_objective_hud)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_objective))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(escort_tractor)
+                    .with_system(fail_objective)
+                    .with_system(complete_objective.after(fail_objective))
+                    .with_system(end_run.after(complete_objective)),
+            )
+            .add_system(update_objective_hud)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_objective));
+    }
+}
+
+// What a level asks of the player on top of staying alive. Campaign levels bring their own, and
+// the endless lane takes whatever's in config.ron.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum ObjectiveDefinition {
+    // Hold out this long
+    Survive { seconds: f32 },
+    // Keep a crop planted at `position` alive this long. The camera waits with it.
+    Protect {
+        seconds: f32,
+        health: f32,
+        position: Vec3,
+    },
+    // See a tractor this far down the lane. It keeps pace with the camera.
+    Escort { distance: f32, health: f32 },
+    // Beat every wave the level planned
+    ClearWaves,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ObjectiveStatus {
+    #[default]
+    InProgress,
+    Complete,
+    Failed,
+}
+
+#[derive(Resource, Default)]
+pub struct Objective {
+    pub definition: Option<ObjectiveDefinition>,
+    pub status: ObjectiveStatus,
+    pub elapsed: f32,
+    // The crop or the tractor
+    pub protected: Option<Entity>,
+}
+
+impl Objective {
+    // Nothing scrolls past the crop until it's safe
+    pub fn holds_camera(&self) -> bool {
+        matches!(self.definition, Some(ObjectiveDefinition::Protect { .. }))
+            && self.status == ObjectiveStatus::InProgress
+    }
+}
+
+pub struct ObjectiveCompleted;
+
+pub struct ObjectiveFailed;
+
+// Enemies go after these as well as the players
+#[derive(Component)]
+pub struct Protected;
+
+#[derive(Component)]
+struct Tractor;
+
+#[derive(Component)]
+struct ObjectiveText;
+
+fn start_objective(
+    mut commands: Commands,
+    mut objective: ResMut<Objective>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<GameConfig>,
+    level: Res<ActiveLevel>,
+) {
+    let definition = match &level.0 {
+        Some(level) => Some(
+            level
+                .objective
+                .clone()
+                .unwrap_or(ObjectiveDefinition::ClearWaves),
+        ),
+        None => config.objective.clone(),
+    };
+    *objective = Objective {
+        definition,
+        ..default()
+    };
+
+    objective.protected = match objective.definition {
+        Some(ObjectiveDefinition::Protect {
+            health, position, ..
+        }) => Some(
+            commands
+                .spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Icosphere {
+                            radius: CROP_RADIUS,
+                            subdivisions: 3,
+                        })),
+                        material: materials.add(Color::rgb(0.3, 0.7, 0.2).into()),
+                        transform: Transform::from_translation(position),
+                        ..default()
+                    },
+                    Protected,
+                    Health::new(health),
+                    NotShadowCaster,
+                    DespawnOnReset,
+                ))
+                .id(),
+        ),
+        Some(ObjectiveDefinition::Escort { health, .. }) => Some(
+            commands
+                .spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Box::new(0.4, 0.3, 0.6))),
+                        material: materials.add(Color::rgb(0.8, 0.15, 0.1).into()),
+                        transform: Transform::from_translation(tractor_position(
+                            camera_start().translation,
+                        )),
+                        ..default()
+                    },
+                    Protected,
+                    Tractor,
+                    Health::new(health),
+                    DespawnOnReset,
+                ))
+                .id(),
+        ),
+        _ => None,
+    };
+}
+
+fn tractor_position(scroll: Vec3) -> Vec3 {
+    Vec3::new(0., 0.15, scroll.z - ESCORT_AHEAD)
+}
+
+// Follows the scroll rather than the camera itself, so co-op framing doesn't drag it sideways
+fn escort_tractor(rig: Res<CameraRig>, mut tractors: Query<&mut Transform, With<Tractor>>) {
+    for mut transform in &mut tractors {
+        transform.translation = tractor_position(rig.scroll);
+    }
+}
+
+fn fail_objective(
+    mut objective: ResMut<Objective>,
+    mut dealt: EventReader<DamageDealt>,
+    mut failed: EventWriter<ObjectiveFailed>,
+) {
+    let Some(protected) = objective.protected else {
+        dealt.clear();
+        return;
+    };
+    let lost = dealt
+        .iter()
+        .any(|damage| damage.killed && damage.target == protected);
+    if lost && objective.status == ObjectiveStatus::InProgress {
+        objective.status = ObjectiveStatus::Failed;
+        failed.send(ObjectiveFailed);
+    }
+}
+
+pub fn complete_objective(
+    time: Res<Time>,
+    rig: Res<CameraRig>,
+    waves: Res<WaveManager>,
+    mut objective: ResMut<Objective>,
+    mut completed: EventWriter<ObjectiveCompleted>,
+) {
+    if objective.status != ObjectiveStatus::InProgress {
+        return;
+    }
+    objective.elapsed += time.delta_seconds();
+
+    let done = match objective.definition {
+        Some(ObjectiveDefinition::Survive { seconds })
+        | Some(ObjectiveDefinition::Protect { seconds, .. }) => objective.elapsed >= seconds,
+        Some(ObjectiveDefinition::Escort { distance, .. }) => rig.travelled() >= distance,
+        Some(ObjectiveDefinition::ClearWaves) => waves.plan_complete(),
+        None => false,
+    };
+    if done {
+        objective.status = ObjectiveStatus::Complete;
+        completed.send(ObjectiveCompleted);
+    }
+}
+
+// Either way the run's over. The game over screen says which, after a last look at the boss if
+// it's been beaten on the way.
+pub fn end_run(
+    mut completed: EventReader<ObjectiveCompleted>,
+    mut failed: EventReader<ObjectiveFailed>,
+    mut state: ResMut<State<GameState>>,
+    mut cinematic: Option<ResMut<Cinematic>>,
+) {
+    let won = completed.iter().count() > 0;
+    let lost = failed.iter().count() > 0;
+    if !(won || lost) || *state.current() != GameState::Playing {
+        return;
+    }
+    let boss_fell = cinematic
+        .as_ref()
+        .and_then(|cinematic| cinematic.boss_fell());
+    let shot = boss_fell
+        .filter(|_| won)
+        .map(|focus| (Shot::BossDefeated, focus));
+    cut_to_game_over(&mut state, cinematic.as_deref_mut(), shot);
+}
+
+// Top middle, under nothing
+fn setup_objective_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect::top(Val::Px(HUD_MARGIN)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 28.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::CENTER),
+                ObjectiveText,
+            ));
+        });
+}
+
+fn update_objective_hud(
+    objective: Res<Objective>,
+    rig: Res<CameraRig>,
+    waves: Res<WaveManager>,
+    health: Query<&Health>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<ObjectiveText>>,
+) {
+    let countdown = |seconds: f32| {
+        let left = (seconds - objective.elapsed).max(0.).ceil() as u32;
+        format!("{}:{:02}", left / 60, left % 60)
+    };
+    let health = objective
+        .protected
+        .and_then(|entity| health.get(entity).ok())
+        .map(|health| {
+            let current = format!("{:.0}", health.current.max(0.));
+            let max = format!("{:.0}", health.max);
+            localization.format("objective-health", &[("current", &current), ("max", &max)])
+        })
+        .unwrap_or_default();
+
+    let hud = match &objective.definition {
+        None => String::new(),
+        Some(ObjectiveDefinition::Survive { seconds }) => {
+            localization.format("objective-survive", &[("time", &countdown(*seconds))])
+        }
+        Some(ObjectiveDefinition::Protect { seconds, .. }) => localization.format(
+            "objective-protect",
+            &[("time", &countdown(*seconds)), ("health", &health)],
+        ),
+        Some(ObjectiveDefinition::Escort { distance, .. }) => {
+            let left = format!("{:.0}", (distance - rig.travelled()).max(0.));
+            localization.format("objective-escort", &[("left", &left), ("health", &health)])
+        }
+        Some(ObjectiveDefinition::ClearWaves) => localization.format(
+            "objective-clear-waves",
+            &[("wave", &waves.wave), ("waves", &waves.plan.len())],
+        ),
+    };
+
+    for mut text in &mut texts {
+        if text.sections[0].value != hud {
+            text.sections[0].value = hud.clone();
+        }
+    }
+}
+
+fn reset_objective(mut objective: ResMut<Objective>) {
+    *objective = Objective::default();
+}
@@ -0,0 +1,108 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{damage::DamageEvent, state::GameState, Projectile};
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(block_projectiles),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            push_out_of_obstacles.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+// Something solid on the ground. Everything collides as a circle on the XZ plane, which is plenty
+// for a carrot dodging around giant vegetables.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub radius: f32,
+}
+
+// Anything that moves and shouldn't be able to walk through obstacles
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Collider {
+    pub radius: f32,
+}
+
+fn flatten(v: Vec3) -> Vec2 {
+    Vec2::new(v.x, v.z)
+}
+
+// Nudges `position` out of any obstacle it overlaps, leaving its height alone
+pub fn resolve_overlap(
+    position: Vec3,
+    radius: f32,
+    obstacles: impl Iterator<Item = (Vec3, f32)>,
+) -> Vec3 {
+    let mut resolved = flatten(position);
+    for (obstacle_position, obstacle_radius) in obstacles {
+        let obstacle_position = flatten(obstacle_position);
+        let min_distance = radius + obstacle_radius;
+        let offset = resolved - obstacle_position;
+        let distance = offset.length();
+        if distance >= min_distance {
+            continue;
+        }
+
+        // Dead centre gives us no direction to push in, so just pick one
+        let direction = if distance > f32::EPSILON {
+            offset / distance
+        } else {
+            Vec2::X
+        };
+        resolved = obstacle_position + direction * min_distance;
+    }
+
+    Vec3::new(resolved.x, position.y, resolved.y)
+}
+
+// Runs after everything has had a chance to move, so whatever did the moving doesn't need to know
+// about obstacles. Colliders are top-level entities, so their local and world translations match.
+fn push_out_of_obstacles(
+    mut colliders: Query<(&mut Transform, &Collider)>,
+    obstacles: Query<(&GlobalTransform, &Obstacle)>,
+) {
+    for (mut transform, collider) in &mut colliders {
+        let resolved = resolve_overlap(
+            transform.translation,
+            collider.radius,
+            obstacles
+                .iter()
+                .map(|(transform, obstacle)| (transform.translation(), obstacle.radius)),
+        );
+        if resolved != transform.translation {
+            transform.translation = resolved;
+        }
+    }
+}
+
+// Whatever the projectile hit gets the damage too - most obstacles have no `Health` and shrug it off
+fn block_projectiles(
+    mut commands: Commands,
+    projectiles: Query<(Entity, &GlobalTransform, &Projectile)>,
+    obstacles: Query<(Entity, &GlobalTransform, &Obstacle)>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (projectile_entity, projectile_transform, projectile) in &projectiles {
+        let projectile_position = flatten(projectile_transform.translation());
+        let blocked_by = obstacles.iter().find(|(_, transform, obstacle)| {
+            flatten(transform.translation()).distance(projectile_position) < obstacle.radius
+        });
+        let Some((obstacle_entity, _, _)) = blocked_by else { continue };
+
+        commands.entity(projectile_entity).despawn_recursive();
+        damage.send(DamageEvent {
+            source: Some(projectile_entity),
+            target: obstacle_entity,
+            amount: projectile.damage,
+            direction: projectile.heading,
+            critical: false,
+            knockback: 0.,
+        });
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    input::{self, ActiveDevice, InputAction, InputSource, PlayerInput},
+    localization::Localization,
+    netplay::Netplay,
+    photo_mode,
+    settings::Settings,
+    state::GameState,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SuspendedSinks>()
+            .add_system(toggle_pause)
+            .add_system(pause_on_focus_lost.after(toggle_pause))
+            .add_system(update_pause_help)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused)
+                    .with_system(spawn_pause_overlay)
+                    .with_system(suspend_presentation),
+            )
+            // Other screens pushed on top of the pause menu bring their own UI
+            .add_system_set(
+                SystemSet::on_pause(GameState::Paused).with_system(despawn_pause_overlay),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::Paused).with_system(spawn_pause_overlay),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(despawn_pause_overlay)
+                    .with_system(resume_presentation),
+            );
+    }
+}
+
+#[derive(Component)]
+struct PauseOverlay;
+
+// The title, then the help
+#[derive(Component)]
+struct PauseText;
+
+// Animation players we stopped, so resuming doesn't restart ones that were already paused
+#[derive(Component)]
+struct SuspendedByPause;
+
+// Likewise for sounds
+#[derive(Resource, Default)]
+struct SuspendedSinks(Vec<Handle<AudioSink>>);
+
+fn toggle_pause(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    // Back steps out of the pause menu and the armory too. The other screens see to it themselves.
+    let backed_out = input.back && matches!(state.current(), GameState::Paused | GameState::Armory);
+    if !input.pause && !backed_out {
+        return;
+    }
+
+    let result = match state.current() {
+        GameState::Playing => state.push(GameState::Paused),
+        GameState::Paused
+        | GameState::Armory
+        | GameState::Settings
+        | GameState::Video
+        | GameState::Calibration
+        | GameState::Console
+        | GameState::PhotoMode => state.pop(),
+        // The pause button might be the one being rebound, the shop and the choice between waves
+        // have their own way out, and there's no run left to pause under a cinematic
+        GameState::MainMenu
+        | GameState::Cinematic
+        | GameState::GameOver
+        | GameState::Leaderboard
+        | GameState::LevelSelect
+        | GameState::Garden
+        | GameState::CharacterSelect
+        | GameState::Mutators
+        | GameState::Remap
+        | GameState::Shop
+        | GameState::Choice => return,
+    };
+    if let Err(e) = result {
+        warn!("Unable to toggle pause: {e:?}");
+    }
+}
+
+// Alt-tabbing away mid-run stops it, sound and all, and leaves the pause menu up for when they
+// come back
+fn pause_on_focus_lost(
+    mut focus: EventReader<WindowFocused>,
+    mut state: ResMut<State<GameState>>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+) {
+    let lost_focus = focus.iter().any(|event| !event.focused);
+    // Recordings play out the same however the window's doing, and an online game can't stop at
+    // just one end
+    if !lost_focus || !matches!(*source, InputSource::Live) || netplay.is_some() {
+        return;
+    }
+    if *state.current() != GameState::Playing {
+        return;
+    }
+    if let Err(e) = state.push(GameState::Paused) {
+        warn!("Unable to pause when the window lost focus: {e:?}");
+    }
+}
+
+// The simulation stops because its systems only run while playing, but animations and audio are
+// driven by bevy itself and need to be told. Both happen in the same frame, so nothing drifts.
+fn suspend_presentation(
+    mut commands: Commands,
+    mut players: Query<(Entity, &mut AnimationPlayer)>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut suspended_sinks: ResMut<SuspendedSinks>,
+) {
+    for (entity, mut player) in &mut players {
+        if !player.is_paused() {
+            player.pause();
+            commands.entity(entity).insert(SuspendedByPause);
+        }
+    }
+
+    for (id, sink) in audio_sinks.iter() {
+        if !sink.is_paused() {
+            sink.pause();
+            suspended_sinks.0.push(audio_sinks.get_handle(id));
+        }
+    }
+}
+
+fn resume_presentation(
+    mut commands: Commands,
+    mut players: Query<(Entity, &mut AnimationPlayer), With<SuspendedByPause>>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut suspended_sinks: ResMut<SuspendedSinks>,
+) {
+    for (entity, mut player) in &mut players {
+        player.resume();
+        commands.entity(entity).remove::<SuspendedByPause>();
+    }
+
+    for handle in suspended_sinks.0.drain(..) {
+        if let Some(sink) = audio_sinks.get(&handle) {
+            sink.play();
+        }
+    }
+}
+
+fn spawn_pause_overlay(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            PauseOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        format!("{}\n", localization.get("pause-title")),
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 60.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 24.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                ])
+                .with_text_alignment(TextAlignment::CENTER),
+                PauseText,
+            ));
+        });
+}
+
+// Follows whichever of the keyboard or the pad was touched last
+fn update_pause_help(
+    settings: Res<Settings>,
+    device: Res<ActiveDevice>,
+    localization: Res<Localization>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut texts: Query<&mut Text, With<PauseText>>,
+) {
+    let photo = match *device {
+        ActiveDevice::Keyboard => input::key_glyph(photo_mode::OPEN_KEY),
+        ActiveDevice::Gamepad => input::button_glyph(photo_mode::OPEN_BUTTON),
+    };
+    let armory = settings.bindings.prompt(InputAction::Inspect, *device);
+    let mut help = localization.format(
+        "pause-help",
+        &[
+            ("armory", &armory),
+            ("confirm", &input::confirm_glyph(*device)),
+            ("photo", &photo),
+        ],
+    );
+    // Only a run that's being played here and now can be put down for later
+    if matches!(*source, InputSource::Live) && netplay.is_none() {
+        let reload = settings.bindings.prompt(InputAction::Reload, *device);
+        help.push('\n');
+        help.push_str(&localization.format("pause-save", &[("reload", &reload)]));
+    }
+
+    for mut text in &mut texts {
+        if text.sections[1].value != help {
+            text.sections[1].value = help.clone();
+        }
+    }
+}
+
+fn despawn_pause_overlay(mut commands: Commands, overlays: Query<Entity, With<PauseOverlay>>) {
+    for entity in &overlays {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,211 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    input::{GamepadAssignments, PlayerInput},
+    settings::{Settings, StickSettings},
+    state::GameState,
+    Game,
+};
+
+pub const OPEN_KEY: KeyCode = KeyCode::P;
+pub const OPEN_BUTTON: GamepadButtonType = GamepadButtonType::RightThumb;
+// Per second, in world units and radians
+const FLY_SPEED: f32 = 3.;
+const LOOK_SPEED: f32 = 1.5;
+const ROLL_SPEED: f32 = 0.8;
+const FOV_SPEED: f32 = 0.5;
+const MIN_FOV: f32 = 0.35;
+const MAX_FOV: f32 = 1.75;
+// Just short of straight up or down, where yaw stops meaning anything
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.05;
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhotoMode>()
+            .add_system(open_photo_mode)
+            .add_system_set(SystemSet::on_enter(GameState::PhotoMode).with_system(enter_photo_mode))
+            .add_system_set(
+                SystemSet::on_update(GameState::PhotoMode)
+                    .with_system(fly_camera)
+                    .with_system(close_photo_mode),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::PhotoMode).with_system(exit_photo_mode));
+    }
+}
+
+// Where the game camera was before it was taken off to fly about, and the HUD that was hidden
+#[derive(Resource, Default)]
+struct PhotoMode {
+    transform: Transform,
+    projection: Option<Projection>,
+    hidden: Vec<Entity>,
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+}
+
+fn open_photo_mode(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    assignments: Res<GamepadAssignments>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if *state.current() != GameState::Paused {
+        return;
+    }
+    let pressed = keys.just_pressed(OPEN_KEY)
+        || assignments.gamepad(0).map_or(false, |gamepad| {
+            buttons.just_pressed(GamepadButton::new(gamepad, OPEN_BUTTON))
+        });
+    if !pressed {
+        return;
+    }
+    if let Err(e) = state.push(GameState::PhotoMode) {
+        warn!("Unable to open photo mode: {e:?}");
+    }
+}
+
+// The run's already frozen underneath the pause menu, so all that's left is getting the HUD out of
+// the way
+fn enter_photo_mode(
+    mut photo_mode: ResMut<PhotoMode>,
+    game: Res<Game>,
+    cameras: Query<(&Transform, &Projection)>,
+    mut hud: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+) {
+    if let Ok((transform, projection)) = cameras.get(game.camera) {
+        let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+        *photo_mode = PhotoMode {
+            transform: *transform,
+            projection: Some(projection.clone()),
+            hidden: Vec::new(),
+            yaw,
+            pitch,
+            roll,
+        };
+    }
+
+    for (entity, mut visibility) in &mut hud {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+            photo_mode.hidden.push(entity);
+        }
+    }
+}
+
+// Left stick (or WASD) flies about, the bumpers (or Q and E) go down and up, the right stick (or
+// the arrow keys) looks around, the d-pad (or Z, C, minus and equals) rolls and zooms
+fn fly_camera(
+    mut photo_mode: ResMut<PhotoMode>,
+    time: Res<Time>,
+    game: Res<Game>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    assignments: Res<GamepadAssignments>,
+    settings: Res<Settings>,
+    mut cameras: Query<(&mut Transform, &mut Projection)>,
+) {
+    let Ok((mut transform, mut projection)) = cameras.get_mut(game.camera) else { return };
+    // The game clock could be slowed or stopped, but the camera shouldn't be
+    let delta = time.raw_delta_seconds();
+
+    let key = |key_code| if keys.pressed(key_code) { 1. } else { 0. };
+    let gamepad = assignments.gamepad(0);
+    let button = |button_type| {
+        let held = gamepad.map_or(false, |gamepad| {
+            buttons.pressed(GamepadButton::new(gamepad, button_type))
+        });
+        if held {
+            1.
+        } else {
+            0.
+        }
+    };
+    let stick = |x, y, stick_settings: &StickSettings| {
+        let Some(gamepad) = gamepad else { return Vec2::ZERO };
+        let axis = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+        stick_settings.apply_dead_zone(stick_settings.orient(Vec2::new(axis(x), axis(y))))
+    };
+
+    let left_stick = stick(
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+        &settings.left_stick,
+    );
+    let right_stick = stick(
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+        &settings.right_stick,
+    );
+    let movement = (left_stick
+        + Vec2::new(key(KeyCode::D) - key(KeyCode::A), key(KeyCode::W) - key(KeyCode::S)))
+    .clamp(Vec2::NEG_ONE, Vec2::ONE);
+    let look = (right_stick
+        + Vec2::new(
+            key(KeyCode::Right) - key(KeyCode::Left),
+            key(KeyCode::Up) - key(KeyCode::Down),
+        ))
+    .clamp(Vec2::NEG_ONE, Vec2::ONE);
+    let rise = key(KeyCode::E) - key(KeyCode::Q) + button(GamepadButtonType::RightTrigger)
+        - button(GamepadButtonType::LeftTrigger);
+    let roll = key(KeyCode::C) - key(KeyCode::Z) + button(GamepadButtonType::DPadRight)
+        - button(GamepadButtonType::DPadLeft);
+    let zoom = key(KeyCode::Minus) - key(KeyCode::Equals) + button(GamepadButtonType::DPadDown)
+        - button(GamepadButtonType::DPadUp);
+
+    photo_mode.yaw -= look.x * LOOK_SPEED * delta;
+    photo_mode.pitch =
+        (photo_mode.pitch + look.y * LOOK_SPEED * delta).clamp(-MAX_PITCH, MAX_PITCH);
+    photo_mode.roll += roll * ROLL_SPEED * delta;
+    transform.rotation = Quat::from_euler(
+        EulerRot::YXZ,
+        photo_mode.yaw,
+        photo_mode.pitch,
+        photo_mode.roll,
+    );
+
+    // Along the ground the way the camera's facing, so looking down doesn't fly into the dirt
+    let facing = Quat::from_rotation_y(photo_mode.yaw);
+    let flight = facing * Vec3::new(movement.x, 0., -movement.y) + Vec3::Y * rise;
+    transform.translation += flight.clamp_length_max(1.) * FLY_SPEED * delta;
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = (perspective.fov + zoom * FOV_SPEED * delta).clamp(MIN_FOV, MAX_FOV);
+    }
+}
+
+// Back (or the pause button) goes back to the pause menu
+fn close_photo_mode(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if !input.back {
+        return;
+    }
+    if let Err(e) = state.pop() {
+        warn!("Unable to leave photo mode: {e:?}");
+    }
+}
+
+// Everything goes back where it was, so the run carries on exactly as it was paused
+fn exit_photo_mode(
+    mut photo_mode: ResMut<PhotoMode>,
+    game: Res<Game>,
+    mut cameras: Query<(&mut Transform, &mut Projection)>,
+    mut hud: Query<&mut Visibility, With<Node>>,
+) {
+    if let Ok((mut transform, mut projection)) = cameras.get_mut(game.camera) {
+        *transform = photo_mode.transform;
+        if let Some(saved) = photo_mode.projection.take() {
+            *projection = saved;
+        }
+    }
+
+    for entity in photo_mode.hidden.drain(..) {
+        if let Ok(mut visibility) = hud.get_mut(entity) {
+            visibility.is_visible = true;
+        }
+    }
+}
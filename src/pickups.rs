@@ -0,0 +1,323 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    damage::Health,
+    intermission::{Modifier, WaveModifier},
+    localization::Localization,
+    popups::PopupEvent,
+    reset::DespawnOnReset,
+    revive::Downed,
+    settings::Settings,
+    state::GameState,
+    waves::WaveCleared,
+    Ammo, AmmoChanged, Game, Player,
+};
+
+const PICKUP_RADIUS: f32 = 0.3;
+const PICKUP_HEIGHT: f32 = 0.15;
+const SPIN_SPEED: f32 = 3.;
+const BOB_SPEED: f32 = 2.5;
+const BOB_HEIGHT: f32 = 0.05;
+const HEALTH_RESTORED: f32 = 1.;
+const AMMO_RESTORED: u32 = 10;
+// When a wave clears, leftovers fly to the player one after another rather than all at once
+const MAGNET_SECONDS: f32 = 1.;
+const MAGNET_STAGGER_SECONDS: f32 = 0.08;
+const WEAPON_MOD_COLOR: Color = Color::rgb(0.75, 0.45, 1.);
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Currency>()
+            .add_event::<PickupCollected>()
+            .add_startup_system(setup_pickup_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(spin_pickups)
+                    .with_system(magnetize_on_wave_clear)
+                    .with_system(magnet_pickups.after(magnetize_on_wave_clear))
+                    .with_system(collect_pickups.after(magnet_pickups))
+                    .with_system(apply_pickups.after(collect_pickups)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_pickups));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum PickupKind {
+    Coin,
+    Health,
+    Ammo,
+    // Lasts until the wave's cleared, the same as a boost picked between waves, and takes the
+    // place of whichever one's on
+    WeaponMod(Modifier),
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+#[derive(Component)]
+struct Magnetized {
+    delay: Timer,
+    start: Option<Vec3>,
+    progress: f32,
+}
+
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec3,
+    pub player: Entity,
+}
+
+#[derive(Resource, Default)]
+pub struct Currency(pub u32);
+
+#[derive(Resource)]
+pub struct PickupAssets {
+    coin_mesh: Handle<Mesh>,
+    coin_material: Handle<StandardMaterial>,
+    health_mesh: Handle<Mesh>,
+    health_material: Handle<StandardMaterial>,
+    ammo_mesh: Handle<Mesh>,
+    ammo_material: Handle<StandardMaterial>,
+    mod_mesh: Handle<Mesh>,
+    mod_material: Handle<StandardMaterial>,
+    collect_sound: Handle<AudioSource>,
+}
+
+fn setup_pickup_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(PickupAssets {
+        coin_mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 0.06,
+            subdivisions: 2,
+        })),
+        coin_material: materials.add(StandardMaterial {
+            base_color: Color::GOLD,
+            metallic: 0.8,
+            perceptual_roughness: 0.3,
+            ..default()
+        }),
+        health_mesh: meshes.add(Mesh::from(shape::Cube { size: 0.12 })),
+        health_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.9, 0.15, 0.15),
+            emissive: Color::rgb(0.3, 0., 0.),
+            ..default()
+        }),
+        ammo_mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 0.08,
+            subdivisions: 2,
+        })),
+        ammo_material: materials.add(Color::ORANGE.into()),
+        mod_mesh: meshes.add(Mesh::from(shape::Torus {
+            radius: 0.07,
+            ring_radius: 0.025,
+            ..default()
+        })),
+        mod_material: materials.add(StandardMaterial {
+            base_color: WEAPON_MOD_COLOR,
+            emissive: Color::rgb(0.3, 0.1, 0.5),
+            ..default()
+        }),
+        collect_sound: asset_server.load("audio/pickup.ogg"),
+    });
+}
+
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    assets: &PickupAssets,
+    kind: PickupKind,
+    position: Vec3,
+) -> Entity {
+    let (mesh, material, scale) = match kind {
+        // Squashed flat into a disc
+        PickupKind::Coin => (
+            assets.coin_mesh.clone(),
+            assets.coin_material.clone(),
+            Vec3::new(1., 1., 0.25),
+        ),
+        PickupKind::Health => (
+            assets.health_mesh.clone(),
+            assets.health_material.clone(),
+            Vec3::ONE,
+        ),
+        // A pumpkin, near enough
+        PickupKind::Ammo => (
+            assets.ammo_mesh.clone(),
+            assets.ammo_material.clone(),
+            Vec3::new(1., 0.8, 1.),
+        ),
+        PickupKind::WeaponMod(_) => (
+            assets.mod_mesh.clone(),
+            assets.mod_material.clone(),
+            Vec3::ONE,
+        ),
+    };
+
+    commands
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_xyz(position.x, PICKUP_HEIGHT, position.z)
+                    .with_scale(scale),
+                ..default()
+            },
+            Pickup { kind },
+            NotShadowCaster,
+            DespawnOnReset,
+        ))
+        .id()
+}
+
+fn spin_pickups(
+    mut pickups: Query<(&mut Transform, Option<&Magnetized>), With<Pickup>>,
+    time: Res<Time>,
+) {
+    for (mut transform, magnetized) in &mut pickups {
+        transform.rotate_y(SPIN_SPEED * time.delta_seconds());
+
+        // The magnet has its own ideas about height
+        if magnetized.is_none() {
+            // Offset by position so a row of pickups doesn't bob in lockstep
+            let phase = transform.translation.x + transform.translation.z;
+            let bob = (time.elapsed_seconds() * BOB_SPEED + phase).sin();
+            transform.translation.y = PICKUP_HEIGHT + bob * BOB_HEIGHT;
+        }
+    }
+}
+
+fn magnetize_on_wave_clear(
+    mut commands: Commands,
+    mut cleared: EventReader<WaveCleared>,
+    pickups: Query<(Entity, &GlobalTransform), (With<Pickup>, Without<Magnetized>)>,
+    transforms: Query<&GlobalTransform>,
+    game: Res<Game>,
+) {
+    if cleared.iter().count() == 0 {
+        return;
+    }
+    let Ok(player) = transforms.get(game.player) else { return };
+    let player_position = player.translation();
+
+    // Closest first, so the cascade sweeps outwards from the player
+    let mut pickups = pickups.iter().collect::<Vec<_>>();
+    pickups.sort_by(|(_, a), (_, b)| {
+        let a = a.translation().distance_squared(player_position);
+        let b = b.translation().distance_squared(player_position);
+        a.partial_cmp(&b).unwrap()
+    });
+
+    for (index, (entity, _)) in pickups.into_iter().enumerate() {
+        commands.entity(entity).insert(Magnetized {
+            delay: Timer::from_seconds(index as f32 * MAGNET_STAGGER_SECONDS, TimerMode::Once),
+            start: None,
+            progress: 0.,
+        });
+    }
+}
+
+fn magnet_pickups(
+    mut pickups: Query<(&mut Transform, &mut Magnetized), With<Pickup>>,
+    transforms: Query<&GlobalTransform>,
+    game: Res<Game>,
+    time: Res<Time>,
+) {
+    let Ok(player) = transforms.get(game.player) else { return };
+    let target = player.translation() + Vec3::Y * PICKUP_HEIGHT;
+
+    for (mut transform, mut magnet) in &mut pickups {
+        if !magnet.delay.tick(time.delta()).finished() {
+            continue;
+        }
+        let start = *magnet.start.get_or_insert(transform.translation);
+
+        magnet.progress = (magnet.progress + time.delta_seconds() / MAGNET_SECONDS).min(1.);
+        // Ease in, so they accelerate into the player
+        let t = magnet.progress * magnet.progress;
+        transform.translation = start.lerp(target, t);
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    pickups: Query<(Entity, &Pickup, &GlobalTransform)>,
+    players: Query<(Entity, &GlobalTransform), (With<Player>, Without<Downed>)>,
+    mut collected: EventWriter<PickupCollected>,
+) {
+    for (entity, pickup, transform) in &pickups {
+        let position = transform.translation();
+        // First come, first served, and nobody who's down can reach
+        let player = players.iter().find(|(_, player)| {
+            let player_position = player.translation() * Vec3::new(1., 0., 1.);
+            (position * Vec3::new(1., 0., 1.)).distance(player_position) <= PICKUP_RADIUS
+        });
+        let Some((player, _)) = player else { continue };
+
+        commands.entity(entity).despawn_recursive();
+        collected.send(PickupCollected {
+            kind: pickup.kind,
+            position,
+            player,
+        });
+    }
+}
+
+fn apply_pickups(
+    mut collected: EventReader<PickupCollected>,
+    mut currency: ResMut<Currency>,
+    mut health: Query<&mut Health>,
+    mut ammo: Query<&mut Ammo>,
+    players: Query<&Player>,
+    assets: Res<PickupAssets>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    mut ammo_changed: EventWriter<AmmoChanged>,
+    mut modifier: ResMut<WaveModifier>,
+    localization: Res<Localization>,
+    mut popups: EventWriter<PopupEvent>,
+) {
+    for pickup in collected.iter() {
+        match pickup.kind {
+            PickupKind::Coin => currency.0 += 1,
+            PickupKind::Health => {
+                if let Ok(mut health) = health.get_mut(pickup.player) {
+                    health.current = (health.current + HEALTH_RESTORED).min(health.max);
+                }
+            }
+            PickupKind::Ammo => {
+                let Ok(player) = players.get(pickup.player) else { continue };
+                if let Ok(mut ammo) = ammo.get_mut(player.weapon) {
+                    ammo.current = (ammo.current + AMMO_RESTORED).min(ammo.max);
+                    ammo_changed.send(AmmoChanged {
+                        weapon: player.weapon,
+                    });
+                }
+            }
+            PickupKind::WeaponMod(weapon_mod) => {
+                modifier.0 = Some(weapon_mod);
+                popups.send(PopupEvent {
+                    text: weapon_mod.describe(&localization),
+                    position: pickup.position,
+                    color: WEAPON_MOD_COLOR,
+                });
+            }
+        }
+        audio.play_with_settings(
+            assets.collect_sound.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.effects()),
+        );
+    }
+}
+
+fn reset_pickups(mut currency: ResMut<Currency>) {
+    currency.0 = 0;
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::{viewport_to_ui, Palette},
+    damage::{resolve_damage, DamageDealt},
+    reset::DespawnOnReset,
+    Enemy, Game,
+};
+
+const POPUP_SECONDS: f32 = 0.8;
+// How far it drifts up over that time, in world units
+const POPUP_RISE: f32 = 0.6;
+const POPUP_FONT_SIZE: f32 = 28.;
+// Numbers start around the middle of a beet rather than at its feet
+const DAMAGE_NUMBER_HEIGHT: f32 = 0.5;
+
+pub struct PopupPlugin;
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PopupEvent>()
+            // Straight after the damage is done, while anything it killed is still around to be
+            // found
+            .add_system_to_stage(CoreStage::PostUpdate, damage_numbers.after(resolve_damage))
+            .add_system(spawn_popups)
+            .add_system(update_popups.after(spawn_popups));
+    }
+}
+
+// A bit of text that appears at a point in the world, floats up and fades away. Damage numbers
+// are one, but anything can send these.
+pub struct PopupEvent {
+    pub text: String,
+    pub position: Vec3,
+    pub color: Color,
+}
+
+// Drawn as UI and moved to wherever `anchor` is on screen each frame, so it always faces the
+// camera and stays the same size however far away it is
+#[derive(Component)]
+struct Popup {
+    anchor: Vec3,
+    age: f32,
+}
+
+fn damage_numbers(
+    mut dealt: EventReader<DamageDealt>,
+    mut popups: EventWriter<PopupEvent>,
+    palette: Res<Palette>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+) {
+    for damage in dealt.iter() {
+        if damage.amount <= 0. {
+            continue;
+        }
+        let Ok(transform) = enemies.get(damage.target) else { continue };
+
+        // A decimal place is plenty once upgrades make it fractional
+        let amount = (damage.amount * 10.).round() / 10.;
+        let (text, color) = if damage.critical {
+            (format!("{amount}!"), palette.critical)
+        } else {
+            (format!("{amount}"), palette.damage)
+        };
+        popups.send(PopupEvent {
+            text,
+            position: transform.translation() + Vec3::Y * DAMAGE_NUMBER_HEIGHT,
+            color,
+        });
+    }
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    mut events: EventReader<PopupEvent>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.iter() {
+        let mut text = TextBundle::from_section(
+            event.text.clone(),
+            TextStyle {
+                font: asset_server.load("FiraSans-Bold.ttf"),
+                font_size: POPUP_FONT_SIZE,
+                color: event.color,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        });
+        // Hidden until it's been put in the right place
+        text.visibility.is_visible = false;
+
+        commands.spawn((
+            text,
+            Popup {
+                anchor: event.position,
+                age: 0.,
+            },
+            DespawnOnReset,
+        ));
+    }
+}
+
+fn update_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut popups: Query<(Entity, &mut Popup, &Node, &mut Style, &mut Text, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get(game.camera) else { return };
+
+    for (entity, mut popup, node, mut style, mut text, mut visibility) in &mut popups {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_SECONDS {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let progress = popup.age / POPUP_SECONDS;
+        let position = popup.anchor + Vec3::Y * POPUP_RISE * progress;
+        // Behind the camera
+        let Some(screen) = camera.world_to_viewport(camera_transform, position) else {
+            visibility.is_visible = false;
+            continue;
+        };
+
+        // Centred on the spot, which is bottom-up just like the viewport
+        let screen = viewport_to_ui(&ui_scale, screen);
+        let size = node.size();
+        style.position = UiRect {
+            left: Val::Px(screen.x - size.x / 2.),
+            bottom: Val::Px(screen.y - size.y / 2.),
+            ..default()
+        };
+        visibility.is_visible = true;
+
+        // Solid for the first half, then fading out
+        let alpha = (2. * (1. - progress)).min(1.);
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// What a custom `App` is most likely to need: the plugins that make up the game, and the
+// components, events, resources and config they share
+//
+//     use vegetable_funeral::prelude::*;
+
+pub use crate::{
+    accessibility::{AccessibilityPlugin, ColourPalette, Palette},
+    ambience::{AmbiencePlugin, MusicIntensity},
+    animation::CharacterAnimationPlugin,
+    armory::ArmoryPlugin,
+    asset_manifest::AssetManifestPlugin,
+    atmosphere::AtmospherePlugin,
+    attract::AttractPlugin,
+    behaviours::{BehaviourPlugin, Behaviours, Pace},
+    calibration::CalibrationPlugin,
+    campaign::{Boss, CampaignPlugin},
+    characters::CharacterPlugin,
+    cinematic::{CinematicPlugin, Shot},
+    config::{ConfigPlugin, GameConfig},
+    console::ConsolePlugin,
+    coop::CoopPlugin,
+    crops::{Crop, CropDefinition, CropPlugin, Crops},
+    daily::{Daily, DailyChallenge, DailyPlugin},
+    damage::{
+        DamageAppExt, DamageContext, DamageDealt, DamageEvent, DamagePlugin, DamageStage, Health,
+        Knockback, Resistance,
+    },
+    destructibles::DestructiblePlugin,
+    difficulty::{Difficulty, DifficultyPlugin},
+    dodge::DodgePlugin,
+    drone::DronePlugin,
+    endless::EndlessPlugin,
+    enemy_bundle,
+    game_over::GameOverPlugin,
+    garden::{Garden, GardenPlugin, Structure},
+    graze::{GrazePlugin, Grazed},
+    grenade::GrenadePlugin,
+    ground::{Ground, GroundPlugin, ProjectileMissed},
+    hazards::HazardPlugin,
+    health_bars::HealthBarPlugin,
+    high_scores::HighScorePlugin,
+    highlight::TargetHighlightPlugin,
+    horde::HordeRenderingPlugin,
+    hotplug::HotplugPlugin,
+    hurt::{HurtPlugin, Invulnerable},
+    input::{ActiveDevice, InputSource, PlayerInput, PlayerInputPlugin},
+    intermission::{IntermissionEvent, IntermissionPlugin, Modifier, WaveModifier},
+    kill_cam::KillCamPlugin,
+    level_gen::{LevelGenPlugin, LevelSeed},
+    lighting::LightingPlugin,
+    localization::{Localization, LocalizationPlugin},
+    lock_on_camera::LockOnCameraPlugin,
+    loot::{LootPlugin, LootTables, Rarity},
+    low_health::{LowHealth, LowHealthPlugin},
+    melee::MeleePlugin,
+    menu::MainMenuPlugin,
+    mutators::{Mutator, MutatorPlugin, Mutators},
+    navigation::{Focused, MenuNavigation, MenuNavigationPlugin},
+    objective::ObjectivePlugin,
+    obstacles::{Collider, Obstacle, ObstaclePlugin},
+    pause::PausePlugin,
+    photo_mode::PhotoModePlugin,
+    pickups::PickupPlugin,
+    popups::{PopupEvent, PopupPlugin},
+    radar::RadarPlugin,
+    remap::RemapPlugin,
+    reset::{DespawnOnReset, ResetPlugin},
+    results::{LevelResults, Rank, ResultsPlugin},
+    revive::{Downed, RevivePlugin},
+    score::ScorePlugin,
+    screen_effects::{ScreenEffect, ScreenEffects, ScreenEffectsPlugin},
+    settings::{AimAssist, AimMode, Settings, SettingsPlugin},
+    shield::{Shield, ShieldPlugin, ShieldStruck},
+    shop::{ShopPlugin, Upgrades},
+    spawners::{Spawner, SpawnerDefinition, SpawnerKind, SpawnerPlugin},
+    sprint::SprintPlugin,
+    stagger::{StaggerPlugin, Staggered},
+    state::{GameState, GameStatePlugin},
+    stats::RunStatsPlugin,
+    status::{ApplyStatus, StatusEffect, StatusKind, StatusPlugin},
+    streaming::EnvironmentStreamingPlugin,
+    suspend::{SavedRun, SuspendPlugin},
+    telegraph::{SpawnTelegraph, TelegraphPlugin},
+    threat_camera::ThreatCameraPlugin,
+    time_scale::TimeScalePlugin,
+    timeline::RunTimelinePlugin,
+    turret::TurretPlugin,
+    tutorial::TutorialPlugin,
+    ultimate::{UltimateMeter, UltimatePlugin, UltimateUnleashed},
+    video::{VideoPlugin, VideoSettings},
+    waves::{WaveCleared, WaveManager, WavePlugin, WaveStarted},
+    weapon_hud::WeaponHudPlugin,
+    weapons::{Crosshair, Loadout, WeaponEquipped, WeaponPlugin},
+    weather::{Weather, WeatherKind, WeatherPlugin},
+    Aim, Ammo, AmmoChanged, AttackState, Barrage, CameraPacing, Enemy, EnemyKilled, EnemyKind,
+    Game, GamePlugins, GameRng, GameplayPlugin, Player, ProjectileHit, Spread, Velocity, Weapon,
+    WeaponFired,
+};
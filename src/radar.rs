@@ -0,0 +1,143 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{accessibility::Palette, spawners::Spawner, Aim, Enemy, Game, Player};
+
+// On screen, in pixels
+const RADAR_SIZE: f32 = 160.;
+const RADAR_MARGIN: f32 = 20.;
+const BLIP_SIZE: f32 = 6.;
+const PLAYER_BLIP_SIZE: f32 = 8.;
+const SPAWNER_BLIP_SIZE: f32 = 10.;
+// How much of the lane it covers, either side of the middle and in front of and behind it
+const RADAR_RANGE: f32 = 12.;
+// The camera sits back from the action, so the middle of the radar is this far in front of it
+const RADAR_CENTRE_AHEAD: f32 = 4.;
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_radar).add_system(update_radar);
+    }
+}
+
+#[derive(Component)]
+struct Radar;
+
+// Stands in for a player, an enemy or a spawner
+#[derive(Component)]
+struct Blip {
+    entity: Entity,
+}
+
+// Bottom right, where nothing else lives
+fn setup_radar(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(RADAR_SIZE), Val::Px(RADAR_SIZE)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(RADAR_MARGIN),
+                    bottom: Val::Px(RADAR_MARGIN),
+                    ..default()
+                },
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        },
+        Radar,
+    ));
+}
+
+// Up the radar is up the lane. Anything out of range isn't shown at all, apart from spawners,
+// which sit on the edge nearest them so there's always a way to tell where they are.
+fn update_radar(
+    mut commands: Commands,
+    game: Res<Game>,
+    palette: Res<Palette>,
+    radars: Query<Entity, With<Radar>>,
+    mut blips: Query<(Entity, &Blip, &mut Style, &mut BackgroundColor)>,
+    players: Query<(Entity, &GlobalTransform), With<Player>>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    spawners: Query<(Entity, &GlobalTransform), With<Spawner>>,
+    aims: Query<&Aim>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Ok(radar) = radars.get_single() else { return };
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let centre = camera.translation() - Vec3::Z * RADAR_CENTRE_AHEAD;
+    let targets = aims.iter().filter_map(|aim| aim.target).collect::<Vec<_>>();
+
+    let mut wanted = HashMap::default();
+    let enemies = enemies.iter().map(|(entity, transform)| {
+        let color = if targets.contains(&entity) {
+            palette.target
+        } else {
+            palette.enemy
+        };
+        (entity, transform, color, BLIP_SIZE, false)
+    });
+    let spawners = spawners.iter().map(|(entity, transform)| {
+        let color = if targets.contains(&entity) {
+            palette.target
+        } else {
+            palette.spawner
+        };
+        (entity, transform, color, SPAWNER_BLIP_SIZE, true)
+    });
+    let players = players
+        .iter()
+        .map(|(entity, transform)| (entity, transform, palette.player, PLAYER_BLIP_SIZE, false));
+    for (entity, transform, color, size, pinned) in enemies.chain(spawners).chain(players) {
+        let mut offset = (transform.translation() - centre) / RADAR_RANGE;
+        if offset.x.abs() > 1. || offset.z.abs() > 1. {
+            if !pinned {
+                continue;
+            }
+            offset = offset.clamp(-Vec3::ONE, Vec3::ONE);
+        }
+        let left = (offset.x * 0.5 + 0.5) * RADAR_SIZE - size / 2.;
+        let top = (offset.z * 0.5 + 0.5) * RADAR_SIZE - size / 2.;
+        wanted.insert(entity, (left, top, color, size));
+    }
+
+    for (blip_entity, blip, mut style, mut background) in &mut blips {
+        let Some((left, top, color, size)) = wanted.remove(&blip.entity) else {
+            commands.entity(blip_entity).despawn_recursive();
+            continue;
+        };
+        style.position = UiRect {
+            left: Val::Px(left),
+            top: Val::Px(top),
+            ..default()
+        };
+        style.size = Size::new(Val::Px(size), Val::Px(size));
+        *background = color.into();
+    }
+
+    // Whatever's left has just come into range
+    for (entity, (left, top, color, size)) in wanted {
+        let blip = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(size), Val::Px(size)),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(left),
+                            top: Val::Px(top),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                },
+                Blip { entity },
+            ))
+            .id();
+        commands.entity(radar).add_child(blip);
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{button_glyph, key_glyph, GamepadAssignments, InputAction, InputBindings, PlayerInput},
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    settings::Settings,
+    state::GameState,
+};
+
+
+pub struct RemapPlugin;
+
+impl Plugin for RemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemapMenu>()
+            .add_system_set(SystemSet::on_enter(GameState::Remap).with_system(spawn_remap))
+            .add_system_set(
+                SystemSet::on_update(GameState::Remap)
+                    .with_system(navigate_remap)
+                    .with_system(update_remap_text.after(navigate_remap)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Remap).with_system(close_remap));
+    }
+}
+
+// A row for each action, then one to put everything back how it was
+fn row_count() -> usize {
+    InputAction::ALL.len() + 1
+}
+
+#[derive(Resource, Default)]
+struct RemapMenu {
+    row: usize,
+    // The confirm that opened this screen is still down on the first frame
+    settled: bool,
+    // Waiting for a key or button to bind to this
+    listening: Option<InputAction>,
+}
+
+#[derive(Component)]
+struct RemapUi;
+
+#[derive(Component)]
+struct RemapText;
+
+fn spawn_remap(mut commands: Commands, mut menu: ResMut<RemapMenu>, asset_server: Res<AssetServer>) {
+    *menu = RemapMenu::default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            RemapUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                RemapText,
+            ));
+        });
+}
+
+// Up and down pick an action and confirm starts listening. The next key binds the keyboard, the
+// next button on player one's pad binds the gamepad, and back gives up. Back is never bindable, so
+// there's always a way out.
+fn navigate_remap(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    assignments: Res<GamepadAssignments>,
+    mut menu: ResMut<RemapMenu>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !menu.settled {
+        menu.settled = true;
+        return;
+    }
+
+    if let Some(action) = menu.listening {
+        if input.back {
+            menu.listening = None;
+        } else if let Some(key) = keys.get_just_pressed().next() {
+            settings.bindings.bind_key(action, *key);
+            menu.listening = None;
+        } else if let Some(button) = buttons
+            .get_just_pressed()
+            .find(|button| Some(button.gamepad) == assignments.gamepad(0))
+        {
+            settings.bindings.bind_button(action, button.button_type);
+            menu.listening = None;
+        }
+        return;
+    }
+
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the controls: {e:?}");
+        }
+        return;
+    }
+    if input.confirm {
+        match InputAction::ALL.get(menu.row) {
+            Some(action) => menu.listening = Some(*action),
+            None => settings.bindings = InputBindings::default(),
+        }
+        return;
+    }
+
+    menu.row = navigation.row(menu.row, row_count());
+}
+
+fn update_remap_text(
+    menu: Res<RemapMenu>,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<RemapText>>,
+) {
+    let mut panel = format!(
+        "{}\n\n  {:<20}{:>12}{:>16}\n",
+        localization.get("controls-title"),
+        "",
+        localization.get("controls-keyboard"),
+        localization.get("controls-gamepad")
+    );
+    for (index, action) in InputAction::ALL.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        let binding = settings.bindings.get(*action);
+        let (key, button) = if menu.listening == Some(*action) {
+            ("...".to_string(), "...".to_string())
+        } else {
+            (
+                binding.key.map_or("-".into(), key_glyph),
+                binding.button.map_or("-".into(), button_glyph),
+            )
+        };
+        panel += &format!(
+            "{cursor} {:<20}{key:>12}{button:>16}\n",
+            localization.get(action.label())
+        );
+    }
+    let cursor = if menu.row == InputAction::ALL.len() { ">" } else { " " };
+    panel += &format!("{cursor} {}\n", localization.get("controls-reset"));
+
+    panel += "\n";
+    panel += localization.get(if menu.listening.is_some() {
+        "controls-listening"
+    } else {
+        "controls-help"
+    });
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn close_remap(mut commands: Commands, ui: Query<Entity, With<RemapUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use crate::{state::GameState, Aim};
+
+pub struct ResetPlugin;
+
+impl Plugin for ResetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(despawn_on_reset));
+    }
+}
+
+// Anything spawned during a run that shouldn't outlive it: beets, pumpkins, pickups, effects and
+// the like. Every way out of a run (retrying, quitting to the menu, saving and quitting) goes
+// through the game over screen, so leaving that is when they all go, whatever spawned them.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DespawnOnReset;
+
+// Nobody's left locked on to something that's gone, whatever order the rest of the reset runs in
+fn despawn_on_reset(
+    mut commands: Commands,
+    doomed: Query<Entity, With<DespawnOnReset>>,
+    mut aims: Query<&mut Aim>,
+) {
+    for mut aim in &mut aims {
+        if aim.target.map_or(false, |target| doomed.contains(target)) {
+            aim.target = None;
+        }
+    }
+    for entity in &doomed {
+        commands.entity(entity).despawn_recursive();
+    }
+}
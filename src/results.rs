@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{ActiveLevel, CampaignProgress, LevelDefinition, Levels},
+    crops::Crops,
+    localization::Localization,
+    objective::{complete_objective, end_run, ObjectiveCompleted},
+    score::Score,
+    state::GameState,
+    stats::RunStats,
+};
+
+// For every crop still standing at the end of a level
+const POINTS_PER_CROP: u32 = 50;
+// Each of these is a step up from a seed: hitting this much of what was fired at, keeping every
+// crop, and taking no more than this much damage all told
+const GOOD_ACCURACY: f32 = 0.5;
+const LIGHT_DAMAGE: f32 = 3.;
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelResults>()
+            // The run's over as soon as `end_run` has seen the objective done, so this has to get in
+            // between the two
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(tally_results.after(complete_objective).before(end_run)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_results));
+    }
+}
+
+// How well a campaign level was won, from scraping through to a clean sweep
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rank {
+    Seed,
+    Sprout,
+    Harvest,
+}
+
+// Filled in the moment a campaign level's won, for the game over screen to show as the results.
+// Nothing for a run that's lost or endless.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LevelResults(pub Option<LevelResult>);
+
+#[derive(Clone, Debug)]
+pub struct LevelResult {
+    pub score: u32,
+    pub crops_kept: u32,
+    pub crop_bonus: u32,
+    pub accuracy: f32,
+    pub time: f32,
+    pub rank: Rank,
+    // The one after it in levels.ron, if there is one
+    pub next_level: Option<LevelDefinition>,
+}
+
+impl LevelResult {
+    pub fn total(&self) -> u32 {
+        self.score + self.crop_bonus
+    }
+
+    // The breakdown, a row at a time
+    pub fn panel(&self, localization: &Localization) -> String {
+        let seconds = self.time as u32;
+        [
+            ("results-score", self.score.to_string()),
+            ("results-crop-bonus", self.crop_bonus.to_string()),
+            ("results-total", self.total().to_string()),
+            ("stats-accuracy", format!("{:.0}%", self.accuracy * 100.)),
+            ("results-time", format!("{}:{:02}", seconds / 60, seconds % 60)),
+            ("results-rank", localization.variant("rank", &self.rank)),
+        ]
+        .iter()
+        .map(|(key, value)| {
+            let label = localization.format(
+                key,
+                &[("crops", &self.crops_kept), ("points", &POINTS_PER_CROP)],
+            );
+            format!("{label:<24}{value:>8}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
+
+// Worked out while the stats are still exactly as the level ended, and saved straight away so
+// quitting from the results screen doesn't lose the win. The crop bonus goes on the score, so the
+// total shown is what the run's worth everywhere else too.
+fn tally_results(
+    mut completed: EventReader<ObjectiveCompleted>,
+    level: Res<ActiveLevel>,
+    levels: Res<Levels>,
+    mut score: ResMut<Score>,
+    stats: Res<RunStats>,
+    crops: Res<Crops>,
+    mut results: ResMut<LevelResults>,
+    mut progress: ResMut<CampaignProgress>,
+) {
+    if completed.iter().count() == 0 {
+        return;
+    }
+    let Some(level) = &level.0 else { return };
+
+    let crops_kept = crops
+        .definition
+        .as_ref()
+        .map_or(0, |definition| definition.count.saturating_sub(crops.lost));
+    let steps = [
+        stats.accuracy() >= GOOD_ACCURACY,
+        crops.lost == 0,
+        stats.damage_taken <= LIGHT_DAMAGE,
+    ]
+    .into_iter()
+    .filter(|step| *step)
+    .count();
+    let rank = match steps {
+        0 | 1 => Rank::Seed,
+        2 => Rank::Sprout,
+        _ => Rank::Harvest,
+    };
+    let next_level = levels
+        .0
+        .iter()
+        .position(|candidate| candidate.name == level.name)
+        .and_then(|index| levels.0.get(index + 1))
+        .cloned();
+
+    let crop_bonus = crops_kept * POINTS_PER_CROP;
+    results.0 = Some(LevelResult {
+        score: score.points,
+        crops_kept,
+        crop_bonus,
+        accuracy: stats.accuracy(),
+        time: stats.time_survived,
+        rank,
+        next_level,
+    });
+    score.points += crop_bonus;
+    progress.complete(level, rank);
+    progress.save();
+}
+
+fn reset_results(mut results: ResMut<LevelResults>) {
+    results.0 = None;
+}
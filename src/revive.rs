@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+
+use crate::{
+    cinematic::{cut_to_game_over, Cinematic, Shot},
+    damage::Health,
+    input::{self, ActiveDevice, InputAction, PlayerInput},
+    localization::Localization,
+    popups::PopupEvent,
+    settings::Settings,
+    state::GameState,
+    Player,
+};
+
+// How long a downed player lasts without help, and how long their partner has to hold the button
+// to get them back up. The countdown stops while they're being helped.
+const DOWNED_SECONDS: f32 = 15.;
+const REVIVE_SECONDS: f32 = 3.;
+// From the middle of one carrot to the middle of the other
+const REVIVE_REACH: f32 = 1.;
+// Of normal speed, while crawling about
+const CRAWL_SPEED: f32 = 0.3;
+// Of their health, once they're back up
+const REVIVE_HEALTH: f32 = 0.5;
+const POPUP_HEIGHT: f32 = 0.6;
+const DOWNED_COLOR: Color = Color::rgb(0.9, 0.3, 0.2);
+const REVIVED_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+// Above the middle of the screen, out of the way of the objective
+const HUD_TOP: f32 = 70.;
+
+pub struct RevivePlugin;
+
+impl Plugin for RevivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_revive_hud)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                pin_downed.after(input::gather_player_input),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(announce_downed)
+                    .with_system(revive_players)
+                    .with_system(bleed_out.after(revive_players)),
+            )
+            .add_system(update_revive_hud)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_revives));
+    }
+}
+
+// Down but not out, in co-op. Going down with nobody else left standing still ends the run.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Downed {
+    remaining: f32,
+    revived: f32,
+}
+
+impl Default for Downed {
+    fn default() -> Self {
+        Self {
+            remaining: DOWNED_SECONDS,
+            revived: 0.,
+        }
+    }
+}
+
+impl Downed {
+    pub fn speed_multiplier(&self) -> f32 {
+        CRAWL_SPEED
+    }
+}
+
+#[derive(Component)]
+struct ReviveText;
+
+// Crawling is all they can do, so everything but the stick's dropped before anything reads it
+pub fn pin_downed(mut players: Query<&mut PlayerInput, With<Downed>>) {
+    for mut input in &mut players {
+        *input = PlayerInput {
+            movement: input.movement,
+            ..default()
+        };
+    }
+}
+
+fn announce_downed(
+    localization: Res<Localization>,
+    downed: Query<&GlobalTransform, Added<Downed>>,
+    mut popups: EventWriter<PopupEvent>,
+) {
+    for transform in &downed {
+        popups.send(PopupEvent {
+            text: localization.get("revive-down").to_string(),
+            position: transform.translation() + Vec3::Y * POPUP_HEIGHT,
+            color: DOWNED_COLOR,
+        });
+    }
+}
+
+fn revive_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    localization: Res<Localization>,
+    mut downed: Query<(Entity, &GlobalTransform, &mut Downed, &mut Health)>,
+    helpers: Query<(&GlobalTransform, &PlayerInput), (With<Player>, Without<Downed>)>,
+    mut popups: EventWriter<PopupEvent>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, transform, mut down, mut health) in &mut downed {
+        let position = transform.translation();
+        let helped = helpers.iter().any(|(helper, input)| {
+            input.revive && helper.translation().distance(position) <= REVIVE_REACH
+        });
+        // Letting go starts it over
+        if !helped {
+            down.revived = 0.;
+            down.remaining -= delta;
+            continue;
+        }
+
+        down.revived += delta;
+        if down.revived < REVIVE_SECONDS {
+            continue;
+        }
+        health.current = health.max * REVIVE_HEALTH;
+        commands.entity(entity).remove::<Downed>();
+        popups.send(PopupEvent {
+            text: localization.get("revive-up").to_string(),
+            position: position + Vec3::Y * POPUP_HEIGHT,
+            color: REVIVED_COLOR,
+        });
+    }
+}
+
+// Nobody got there in time, and that's the run over, the same as going down on your own
+fn bleed_out(
+    downed: Query<(&GlobalTransform, &Downed)>,
+    mut state: ResMut<State<GameState>>,
+    mut cinematic: Option<ResMut<Cinematic>>,
+) {
+    let Some((transform, _)) = downed.iter().find(|(_, down)| down.remaining <= 0.) else {
+        return;
+    };
+    if *state.current() != GameState::Playing {
+        return;
+    }
+    let shot = Some((Shot::Death, transform.translation()));
+    cut_to_game_over(&mut state, cinematic.as_deref_mut(), shot);
+}
+
+// Top middle, just under the objective
+fn setup_revive_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect::top(Val::Px(HUD_TOP)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 24.,
+                        color: DOWNED_COLOR,
+                    },
+                )
+                .with_text_alignment(TextAlignment::CENTER),
+                ReviveText,
+            ));
+        });
+}
+
+// One line for everyone who's down
+fn update_revive_hud(
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    device: Res<ActiveDevice>,
+    downed: Query<(&Player, &Downed)>,
+    mut texts: Query<&mut Text, With<ReviveText>>,
+) {
+    let revive = settings.bindings.prompt(InputAction::Revive, *device);
+    let hud = downed
+        .iter()
+        .map(|(player, down)| {
+            let player = player.index + 1;
+            if down.revived > 0. {
+                let percent = (down.revived / REVIVE_SECONDS * 100.).min(100.) as u32;
+                localization.format(
+                    "revive-progress",
+                    &[("player", &player), ("percent", &percent)],
+                )
+            } else {
+                let time = down.remaining.max(0.).ceil() as u32;
+                localization.format(
+                    "revive-downed",
+                    &[("player", &player), ("revive", &revive), ("time", &time)],
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    for mut text in &mut texts {
+        if text.sections[0].value != hud {
+            text.sections[0].value = hud.clone();
+        }
+    }
+}
+
+fn reset_revives(mut commands: Commands, downed: Query<Entity, With<Downed>>) {
+    for entity in &downed {
+        commands.entity(entity).remove::<Downed>();
+    }
+}
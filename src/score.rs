@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::DamageDealt, graze::Grazed, localization::Localization, mutators::Mutators,
+    state::GameState, waves::WaveCleared, EnemyKilled, Player,
+};
+
+const POINTS_PER_KILL: u32 = 10;
+// Multiplied by the wave number, so later waves are worth more
+const POINTS_PER_WAVE: u32 = 100;
+// For a beet's swing only just missing. Left out of the combo, so it's a bonus on top of the
+// shooting rather than a way round it.
+const POINTS_PER_GRAZE: u32 = 5;
+// Time allowed after a kill to make the next one and keep the combo going
+const COMBO_WINDOW_SECONDS: f32 = 2.;
+const MAX_MULTIPLIER: u32 = 8;
+const HUD_MARGIN: f32 = 20.;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .init_resource::<Combo>()
+            .add_startup_system(setup_score_hud)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(break_combo)
+                    .with_system(add_points.after(break_combo)),
+            )
+            .add_system(update_score_hud)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_score));
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Score {
+    pub points: u32,
+    pub kills: u32,
+}
+
+// Kills in quick succession. Each one is worth more than the last, up to a point.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Combo {
+    pub kills: u32,
+    // Until the combo runs out
+    pub remaining: f32,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        self.kills.clamp(1, MAX_MULTIPLIER)
+    }
+}
+
+#[derive(Component)]
+struct ScoreText;
+
+// Taking a hit or letting the window lapse both end it
+fn break_combo(
+    time: Res<Time>,
+    mut combo: ResMut<Combo>,
+    mut dealt: EventReader<DamageDealt>,
+    players: Query<(), With<Player>>,
+) {
+    if combo.kills == 0 {
+        dealt.clear();
+        return;
+    }
+
+    combo.remaining -= time.delta_seconds();
+    let hurt = dealt
+        .iter()
+        .any(|damage| damage.amount > 0. && players.contains(damage.target));
+    if hurt || combo.remaining <= 0. {
+        *combo = Combo::default();
+    }
+}
+
+// Wave bonuses aren't multiplied - the combo's only about kills. The mutators count for
+// everything, though.
+fn add_points(
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mutators: Res<Mutators>,
+    mut killed: EventReader<EnemyKilled>,
+    mut cleared: EventReader<WaveCleared>,
+    mut grazed: EventReader<Grazed>,
+) {
+    for _ in killed.iter() {
+        combo.kills += 1;
+        combo.remaining = COMBO_WINDOW_SECONDS;
+        score.kills += 1;
+        score.points += mutators.score(POINTS_PER_KILL * combo.multiplier());
+    }
+    for wave in cleared.iter() {
+        score.points += mutators.score(POINTS_PER_WAVE * wave.wave);
+    }
+    for _ in grazed.iter() {
+        score.points += mutators.score(POINTS_PER_GRAZE);
+    }
+}
+
+// Top right, out of the way of the stamina bars
+fn setup_score_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("FiraSans-Bold.ttf"),
+                font_size: 30.,
+                color: Color::WHITE,
+            },
+        )
+        .with_text_alignment(TextAlignment::TOP_RIGHT)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(HUD_MARGIN),
+                right: Val::Px(HUD_MARGIN),
+                ..default()
+            },
+            ..default()
+        }),
+        ScoreText,
+    ));
+}
+
+fn update_score_hud(
+    score: Res<Score>,
+    combo: Res<Combo>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<ScoreText>>,
+) {
+    if !score.is_changed() && !combo.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let mut hud = format!("{}", score.points);
+    if combo.multiplier() > 1 {
+        let combo = localization.format("score-combo", &[("multiplier", &combo.multiplier())]);
+        hud += &format!("\n{combo}");
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = hud.clone();
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>, mut combo: ResMut<Combo>) {
+    *score = Score::default();
+    *combo = Combo::default();
+}
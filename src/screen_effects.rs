@@ -0,0 +1,157 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    ui::FocusPolicy,
+    utils::HashMap,
+};
+
+// The vignette is worked out once into a small texture and stretched over the screen
+const VIGNETTE_SIZE: u32 = 64;
+// How far out from the middle it starts to darken, where the corners are 1
+const VIGNETTE_INNER: f32 = 0.45;
+
+// Whatever wants to tint the whole screen says so here under its own name, and they're all mixed
+// together every frame. Bevy has no post-processing to hook into yet, so it's drawn as a layer of
+// UI over the game and under the HUD.
+#[derive(Resource, Default)]
+pub struct ScreenEffects {
+    effects: HashMap<&'static str, ScreenEffect>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenEffect {
+    // Darkening in from the edges of the screen, as strongly as its alpha
+    pub vignette: Color,
+    // A flat colour over everything. It can dim or tint the screen but it can't take the colour
+    // out of it, as that needs a proper post-process.
+    pub tint: Color,
+}
+
+// Nothing at all, rather than bevy's default of solid white
+impl Default for ScreenEffect {
+    fn default() -> Self {
+        Self {
+            vignette: Color::NONE,
+            tint: Color::NONE,
+        }
+    }
+}
+
+impl ScreenEffects {
+    pub fn set(&mut self, name: &'static str, effect: ScreenEffect) {
+        self.effects.insert(name, effect);
+    }
+
+    pub fn clear(&mut self, name: &'static str) {
+        self.effects.remove(name);
+    }
+
+    // Stronger effects get more of a say in the colour, and they build up without ever going
+    // past fully opaque
+    fn mix(&self, layer: impl Fn(&ScreenEffect) -> Color) -> Color {
+        let mut colour = Vec3::ZERO;
+        let mut weight = 0.;
+        let mut clear = 1.;
+        for effect in self.effects.values() {
+            let [r, g, b, a] = layer(effect).as_rgba_f32();
+            colour += Vec3::new(r, g, b) * a;
+            weight += a;
+            clear *= 1. - a.clamp(0., 1.);
+        }
+        if weight <= 0. {
+            return Color::NONE;
+        }
+        let colour = colour / weight;
+        Color::rgba(colour.x, colour.y, colour.z, 1. - clear)
+    }
+}
+
+#[derive(Component)]
+struct VignetteLayer;
+
+#[derive(Component)]
+struct TintLayer;
+
+pub struct ScreenEffectsPlugin;
+
+impl Plugin for ScreenEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenEffects>()
+            .add_startup_system(spawn_layers)
+            .add_system_to_stage(CoreStage::PostUpdate, apply_effects);
+    }
+}
+
+fn spawn_layers(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let style = Style {
+        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+        position_type: PositionType::Absolute,
+        ..default()
+    };
+    // Clicks go straight through to whatever's underneath, and both sit under the HUD and the
+    // menus
+    commands.spawn((
+        NodeBundle {
+            style: style.clone(),
+            background_color: Color::NONE.into(),
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        TintLayer,
+    ));
+    commands.spawn((
+        ImageBundle {
+            style,
+            image: UiImage(images.add(vignette_image())),
+            background_color: Color::NONE.into(),
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        VignetteLayer,
+    ));
+}
+
+// White, and clear in the middle, so the layer's colour decides what it looks like
+fn vignette_image() -> Image {
+    let half = VIGNETTE_SIZE as f32 / 2.;
+    let mut data = Vec::with_capacity((VIGNETTE_SIZE * VIGNETTE_SIZE * 4) as usize);
+    for y in 0..VIGNETTE_SIZE {
+        for x in 0..VIGNETTE_SIZE {
+            let offset = (Vec2::new(x as f32, y as f32) + 0.5 - half) / half;
+            let distance = offset.length() / std::f32::consts::SQRT_2;
+            let blend = ((distance - VIGNETTE_INNER) / (1. - VIGNETTE_INNER)).clamp(0., 1.);
+            let alpha = blend * blend * (3. - 2. * blend);
+            data.extend([255, 255, 255, (alpha * 255.) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn apply_effects(
+    effects: Res<ScreenEffects>,
+    mut vignettes: Query<&mut BackgroundColor, (With<VignetteLayer>, Without<TintLayer>)>,
+    mut tints: Query<&mut BackgroundColor, (With<TintLayer>, Without<VignetteLayer>)>,
+) {
+    if !effects.is_changed() {
+        return;
+    }
+    let vignette = effects.mix(|effect| effect.vignette);
+    for mut background in &mut vignettes {
+        background.0 = vignette;
+    }
+    let tint = effects.mix(|effect| effect.tint);
+    for mut background in &mut tints {
+        background.0 = tint;
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    num::NonZeroU32,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+            ImageDataLayout, MapMode, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureUsages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::GpuImage,
+        RenderApp, RenderStage,
+    },
+};
+
+use crate::{storage, Game};
+
+// Keyboard only: every pad button's already spoken for, select by the debug render toggle
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+// The window can't be read back in this version of bevy, so the frame is drawn again by a second
+// camera into an image that can. Its first frame can go out before the image is ready, so the
+// second is the one that's kept.
+const CAPTURE_FRAME: u32 = 1;
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        // Nothing to take a picture of without a renderer
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app.add_system_to_stage(RenderStage::Cleanup, save_screenshot);
+
+        app.init_resource::<Screenshot>()
+            .add_plugin(ExtractResourcePlugin::<Screenshot>::default())
+            .add_system_to_stage(CoreStage::First, finish_screenshot)
+            .add_system(take_screenshot);
+    }
+}
+
+// The one being taken, if any
+#[derive(Resource, Clone, Default)]
+struct Screenshot {
+    pending: Option<PendingScreenshot>,
+}
+
+#[derive(Clone)]
+struct PendingScreenshot {
+    image: Handle<Image>,
+    camera: Entity,
+    path: PathBuf,
+    // Frames since the camera went in
+    age: u32,
+}
+
+impl ExtractResource for Screenshot {
+    type Source = Self;
+
+    fn extract_resource(source: &Self) -> Self {
+        source.clone()
+    }
+}
+
+// Something the GPU can draw into and be read back from, like the window but offscreen
+pub fn capture_target(label: &'static str, width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width,
+        height,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some(label),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+// Copies a `capture_target` back from the GPU as RGBA, a row at a time from the top. Waits for the
+// GPU to finish, which is fine for the odd frame.
+pub fn read_back(
+    gpu_image: &GpuImage,
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+) -> (u32, u32, Vec<u8>) {
+    let width = gpu_image.size.x as u32;
+    let height = gpu_image.size.y as u32;
+    let row_bytes = width * 4;
+    let padded_row_bytes = (row_bytes + 255) / 256 * 256;
+
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("capture_read_back"),
+        size: (padded_row_bytes * height) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("capture_read_back"),
+    });
+    encoder.copy_texture_to_buffer(
+        gpu_image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row_bytes),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    render_queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    slice.map_async(MapMode::Read, |_| ());
+    render_device.wgpu_device().poll(wgpu::Maintain::Wait);
+
+    // Strip the row padding and swizzle BGRA into RGBA
+    let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_row_bytes as usize) {
+            for bgra in row[..row_bytes as usize].chunks(4) {
+                pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+            }
+        }
+    }
+    buffer.unmap();
+
+    (width, height, pixels)
+}
+
+// Sees the same as the game camera, at the window's size
+fn take_screenshot(
+    mut commands: Commands,
+    mut screenshot: ResMut<Screenshot>,
+    mut images: ResMut<Assets<Image>>,
+    keys: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    game: Res<Game>,
+    cameras: Query<(&Transform, &Projection)>,
+) {
+    if !keys.just_pressed(SCREENSHOT_KEY) || screenshot.pending.is_some() {
+        return;
+    }
+    let Some(window) = windows.get_primary() else { return };
+    let Ok((transform, projection)) = cameras.get(game.camera) else { return };
+
+    let taken = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    let Some(path) = storage::data_file(&format!("screenshots/{taken}.png")) else {
+        warn!("Nowhere to save screenshots on this platform");
+        return;
+    };
+
+    let image = images.add(capture_target(
+        "screenshot_target",
+        window.physical_width(),
+        window.physical_height(),
+    ));
+    let camera = commands
+        .spawn(Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                // Before the game camera, so the two don't argue about who goes first
+                priority: -1,
+                ..default()
+            },
+            projection: projection.clone(),
+            transform: *transform,
+            ..default()
+        })
+        .id();
+    screenshot.pending = Some(PendingScreenshot {
+        image,
+        camera,
+        path,
+        age: 0,
+    });
+}
+
+// Once the frame's been kept, the camera and its image can go
+fn finish_screenshot(mut commands: Commands, mut screenshot: ResMut<Screenshot>) {
+    let Some(pending) = &mut screenshot.pending else { return };
+    pending.age += 1;
+    if pending.age <= CAPTURE_FRAME {
+        return;
+    }
+    commands.entity(pending.camera).despawn_recursive();
+    screenshot.pending = None;
+}
+
+fn save_screenshot(
+    screenshot: Res<Screenshot>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let Some(pending) = &screenshot.pending else { return };
+    if pending.age != CAPTURE_FRAME {
+        return;
+    }
+    let Some(gpu_image) = images.get(&pending.image) else { return };
+
+    let (width, height, pixels) = read_back(gpu_image, &render_device, &render_queue);
+    if let Some(dir) = pending.path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            error!("Unable to create {dir:?}: {e}");
+            return;
+        }
+    }
+    match image::save_buffer(&pending.path, &pixels, width, height, image::ColorType::Rgba8) {
+        Ok(()) => info!("Saved a screenshot to {:?}", pending.path),
+        Err(e) => error!("Unable to write {:?}: {e}", pending.path),
+    }
+}
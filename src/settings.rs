@@ -0,0 +1,547 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::{ColourPalette, MAX_UI_SCALE, MIN_UI_SCALE},
+    input::{InputBindings, PlayerInput},
+    localization::{language_name, Localization, LANGUAGES},
+    navigation::{self, MenuNavigation},
+    state::GameState,
+    storage,
+    time_scale::MIN_GAME_SPEED,
+    tutorial::TutorialPrompt,
+};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .init_resource::<SettingsMenu>()
+            .add_system(open_settings)
+            .add_system_set(SystemSet::on_enter(GameState::Settings).with_system(spawn_settings))
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                    .with_system(navigate_settings)
+                    .with_system(update_settings_text.after(navigate_settings)),
+            )
+            // The controls screen opens over the top of this one
+            .add_system_set(SystemSet::on_pause(GameState::Settings).with_system(hide_settings))
+            .add_system_set(SystemSet::on_resume(GameState::Settings).with_system(show_settings))
+            .add_system_set(SystemSet::on_exit(GameState::Settings).with_system(close_settings));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimMode {
+    // Flick the right stick to move the lock from one enemy to the next
+    #[default]
+    Flick,
+    // Always locked onto whatever's closest
+    Nearest,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimAssist {
+    // The gun points wherever the right stick puts it
+    Off,
+    // Still aimed with the stick, but pulled towards the beet nearest to where it's pointing
+    Magnetism,
+    // Locked on to one beet at a time, picked by the aim mode
+    #[default]
+    LockOn,
+}
+
+// For anyone who can't aim and fire at the same time as moving about, the game can do one or both
+// of them. Either way it's locked on to the nearest beet in range, whatever the aim settings say.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssistedPlay {
+    #[default]
+    Off,
+    AutoAim,
+    // ..and fires whenever there's something locked on
+    AutoFire,
+}
+
+impl AssistedPlay {
+    pub fn aims(self) -> bool {
+        self != AssistedPlay::Off
+    }
+
+    pub fn fires(self) -> bool {
+        self == AssistedPlay::AutoFire
+    }
+}
+
+// What the companion drone picks out for a quick lock-on, if there's a drone at all
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DroneMode {
+    #[default]
+    Off,
+    Nearest,
+    // Whichever has the most health left
+    Toughest,
+}
+
+// Calibration for one stick on the gamepad
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickSettings {
+    // Anything closer to the middle than this counts as the stick being left alone
+    pub dead_zone: f32,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl StickSettings {
+    fn with_dead_zone(dead_zone: f32) -> Self {
+        Self {
+            dead_zone,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+
+    // Inversion is applied as the pad's read, before the dead zone gets a look
+    pub fn orient(&self, stick: Vec2) -> Vec2 {
+        let flip = |inverted: bool| if inverted { -1. } else { 1. };
+        Vec2::new(stick.x * flip(self.invert_x), stick.y * flip(self.invert_y))
+    }
+
+    pub fn apply_dead_zone(&self, stick: Vec2) -> Vec2 {
+        if stick.length() <= self.dead_zone {
+            Vec2::ZERO
+        } else {
+            stick
+        }
+    }
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self::with_dead_zone(0.1)
+    }
+}
+
+// Player preferences, kept between sessions. Unlike `GameConfig` these are the player's to change.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub aim_mode: AimMode,
+    pub aim_assist: AimAssist,
+    // How hard magnetism pulls, from not at all to straight on target
+    pub aim_assist_strength: f32,
+    // Whether locked-on shots are fired where the beet's going to be, rather than where it is
+    pub lead_targets: bool,
+    pub assisted_play: AssistedPlay,
+    // Whether the camera leans towards whatever's locked on
+    pub lock_on_framing: bool,
+    pub drone: DroneMode,
+    // Slows the whole game down for anyone who finds it too quick, from `MIN_GAME_SPEED` to 1
+    pub game_speed: f32,
+    // The target, damage numbers and HUD bars
+    pub palette: ColourPalette,
+    // How big the whole UI is drawn, from `MIN_UI_SCALE` to `MAX_UI_SCALE`
+    pub ui_scale: f32,
+    // Movement
+    pub left_stick: StickSettings,
+    // Aiming
+    pub right_stick: StickSettings,
+    pub bindings: InputBindings,
+    // Tutorial prompts that have been done, and so aren't shown again
+    pub tutorial_seen: Vec<TutorialPrompt>,
+    // One of the codes in `LANGUAGES`
+    pub language: String,
+    // By name, from the character select
+    pub character: String,
+    // Set by `--mute` for this session only, so it's never saved
+    #[serde(skip)]
+    pub muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 0.8,
+            effects_volume: 1.,
+            aim_mode: AimMode::default(),
+            aim_assist: AimAssist::default(),
+            aim_assist_strength: 0.5,
+            lead_targets: true,
+            assisted_play: AssistedPlay::default(),
+            lock_on_framing: true,
+            drone: DroneMode::default(),
+            game_speed: 1.,
+            palette: ColourPalette::default(),
+            ui_scale: 1.,
+            left_stick: StickSettings::with_dead_zone(0.01),
+            right_stick: StickSettings::with_dead_zone(0.1),
+            bindings: InputBindings::default(),
+            tutorial_seen: Vec::new(),
+            language: "en".into(),
+            character: "Carrot".into(),
+            muted: false,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        let mut settings: Self = storage::data_file(SETTINGS_FILE)
+            .map(|path| storage::load(&path))
+            .unwrap_or_default();
+        settings.bindings.migrate();
+        settings
+    }
+
+    pub fn save(&self) {
+        match storage::data_file(SETTINGS_FILE) {
+            Some(path) => storage::save(&path, self),
+            None => warn!("Nowhere to save settings on this platform"),
+        }
+    }
+
+    pub fn music(&self) -> f32 {
+        self.master() * self.music_volume
+    }
+
+    pub fn effects(&self) -> f32 {
+        self.master() * self.effects_volume
+    }
+
+    fn master(&self) -> f32 {
+        if self.muted {
+            0.
+        } else {
+            self.master_volume
+        }
+    }
+    // Assisted play always locks on, whatever aim assist is set to
+    pub fn effective_aim_assist(&self) -> AimAssist {
+        if self.assisted_play.aims() {
+            AimAssist::LockOn
+        } else {
+            self.aim_assist
+        }
+    }
+}
+
+// One line in the settings menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingsRow {
+    Language,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    AimMode,
+    AimAssist,
+    AimAssistStrength,
+    LeadTargets,
+    AssistedPlay,
+    LockOnFraming,
+    Drone,
+    GameSpeed,
+    Palette,
+    UiScale,
+    // These open their own screens rather than changing anything here
+    Video,
+    Controls,
+    Sticks,
+}
+
+const ROWS: [SettingsRow; 17] = [
+    SettingsRow::Language,
+    SettingsRow::MasterVolume,
+    SettingsRow::MusicVolume,
+    SettingsRow::EffectsVolume,
+    SettingsRow::AimMode,
+    SettingsRow::AimAssist,
+    SettingsRow::AimAssistStrength,
+    SettingsRow::LeadTargets,
+    SettingsRow::AssistedPlay,
+    SettingsRow::LockOnFraming,
+    SettingsRow::Drone,
+    SettingsRow::GameSpeed,
+    SettingsRow::Palette,
+    SettingsRow::UiScale,
+    SettingsRow::Video,
+    SettingsRow::Controls,
+    SettingsRow::Sticks,
+];
+
+impl SettingsRow {
+    // Into the localization
+    fn label(&self) -> &'static str {
+        match self {
+            SettingsRow::Language => "settings-language",
+            SettingsRow::MasterVolume => "settings-master-volume",
+            SettingsRow::MusicVolume => "settings-music-volume",
+            SettingsRow::EffectsVolume => "settings-effects-volume",
+            SettingsRow::AimMode => "settings-aim-mode",
+            SettingsRow::AimAssist => "settings-aim-assist",
+            SettingsRow::AimAssistStrength => "settings-assist-strength",
+            SettingsRow::LeadTargets => "settings-lead-targets",
+            SettingsRow::AssistedPlay => "settings-assisted-play",
+            SettingsRow::LockOnFraming => "settings-lock-on-framing",
+            SettingsRow::Drone => "settings-drone",
+            SettingsRow::GameSpeed => "settings-game-speed",
+            SettingsRow::Palette => "settings-palette",
+            SettingsRow::UiScale => "settings-ui-scale",
+            SettingsRow::Video => "settings-video",
+            SettingsRow::Controls => "settings-controls",
+            SettingsRow::Sticks => "settings-sticks",
+        }
+    }
+
+    fn value(&self, settings: &Settings, localization: &Localization) -> String {
+        let percent = |value: f32| format!("{:.0}%", value * 100.);
+        match self {
+            SettingsRow::Language => language_name(&settings.language).to_string(),
+            SettingsRow::MasterVolume => percent(settings.master_volume),
+            SettingsRow::MusicVolume => percent(settings.music_volume),
+            SettingsRow::EffectsVolume => percent(settings.effects_volume),
+            SettingsRow::AimMode => localization.variant("aim-mode", &settings.aim_mode),
+            SettingsRow::AimAssist => localization.variant("aim-assist", &settings.aim_assist),
+            SettingsRow::AimAssistStrength => percent(settings.aim_assist_strength),
+            SettingsRow::LeadTargets => localization.on_off(settings.lead_targets).to_string(),
+            SettingsRow::AssistedPlay => {
+                localization.variant("assisted-play", &settings.assisted_play)
+            }
+            SettingsRow::LockOnFraming => localization.on_off(settings.lock_on_framing).to_string(),
+            SettingsRow::Drone => localization.variant("drone", &settings.drone),
+            SettingsRow::GameSpeed => percent(settings.game_speed),
+            SettingsRow::Palette => localization.variant("palette", &settings.palette),
+            SettingsRow::UiScale => percent(settings.ui_scale),
+            SettingsRow::Video | SettingsRow::Controls | SettingsRow::Sticks => {
+                localization.get("settings-open").to_string()
+            }
+        }
+    }
+
+    // `direction` is -1 or 1
+    fn adjust(&self, settings: &mut Settings, direction: f32) {
+        let step = |value: &mut f32, by: f32, max: f32| {
+            *value = ((*value + by * direction) / by).round() * by;
+            *value = value.clamp(0., max);
+        };
+        match self {
+            SettingsRow::Language => {
+                let current = LANGUAGES
+                    .iter()
+                    .position(|(code, _)| *code == settings.language)
+                    .unwrap_or_default();
+                let next = (current as i32 + direction as i32).rem_euclid(LANGUAGES.len() as i32);
+                settings.language = LANGUAGES[next as usize].0.to_string();
+            }
+            SettingsRow::MasterVolume => step(&mut settings.master_volume, 0.1, 1.),
+            SettingsRow::MusicVolume => step(&mut settings.music_volume, 0.1, 1.),
+            SettingsRow::EffectsVolume => step(&mut settings.effects_volume, 0.1, 1.),
+            SettingsRow::AimMode => {
+                settings.aim_mode = match settings.aim_mode {
+                    AimMode::Flick => AimMode::Nearest,
+                    AimMode::Nearest => AimMode::Flick,
+                }
+            }
+            SettingsRow::AimAssist => {
+                const ASSISTS: [AimAssist; 3] =
+                    [AimAssist::Off, AimAssist::Magnetism, AimAssist::LockOn];
+                let current = ASSISTS
+                    .iter()
+                    .position(|assist| *assist == settings.aim_assist)
+                    .unwrap_or_default();
+                let next = (current as i32 + direction as i32).rem_euclid(ASSISTS.len() as i32);
+                settings.aim_assist = ASSISTS[next as usize];
+            }
+            SettingsRow::AimAssistStrength => step(&mut settings.aim_assist_strength, 0.1, 1.),
+            SettingsRow::LeadTargets => settings.lead_targets = !settings.lead_targets,
+            SettingsRow::AssistedPlay => {
+                const ASSISTS: [AssistedPlay; 3] =
+                    [AssistedPlay::Off, AssistedPlay::AutoAim, AssistedPlay::AutoFire];
+                let current = ASSISTS
+                    .iter()
+                    .position(|assist| *assist == settings.assisted_play)
+                    .unwrap_or_default();
+                let next = (current as i32 + direction as i32).rem_euclid(ASSISTS.len() as i32);
+                settings.assisted_play = ASSISTS[next as usize];
+            }
+            SettingsRow::LockOnFraming => settings.lock_on_framing = !settings.lock_on_framing,
+            SettingsRow::Drone => {
+                const MODES: [DroneMode; 3] =
+                    [DroneMode::Off, DroneMode::Nearest, DroneMode::Toughest];
+                let current = MODES
+                    .iter()
+                    .position(|mode| *mode == settings.drone)
+                    .unwrap_or_default();
+                let next = (current as i32 + direction as i32).rem_euclid(MODES.len() as i32);
+                settings.drone = MODES[next as usize];
+            }
+            SettingsRow::GameSpeed => {
+                step(&mut settings.game_speed, 0.05, 1.);
+                settings.game_speed = settings.game_speed.max(MIN_GAME_SPEED);
+            }
+            SettingsRow::Palette => {
+                let palettes = ColourPalette::ALL;
+                let current = palettes
+                    .iter()
+                    .position(|palette| *palette == settings.palette)
+                    .unwrap_or_default();
+                let next = (current as i32 + direction as i32).rem_euclid(palettes.len() as i32);
+                settings.palette = palettes[next as usize];
+            }
+            SettingsRow::UiScale => {
+                step(&mut settings.ui_scale, 0.05, MAX_UI_SCALE);
+                settings.ui_scale = settings.ui_scale.max(MIN_UI_SCALE);
+            }
+            SettingsRow::Video | SettingsRow::Controls | SettingsRow::Sticks => {}
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct SettingsMenu {
+    row: usize,
+    // Whatever opened or returned to this screen is still held down on the first frame
+    settled: bool,
+}
+
+#[derive(Component)]
+struct SettingsUi;
+
+#[derive(Component)]
+struct SettingsText;
+
+fn open_settings(input: Res<PlayerInput>, mut state: ResMut<State<GameState>>) {
+    if input.confirm && *state.current() == GameState::Paused {
+        if let Err(e) = state.push(GameState::Settings) {
+            warn!("Unable to open the settings: {e:?}");
+        }
+    }
+}
+
+fn spawn_settings(
+    mut commands: Commands,
+    mut menu: ResMut<SettingsMenu>,
+    asset_server: Res<AssetServer>,
+) {
+    menu.row = 0;
+    menu.settled = false;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            SettingsUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                SettingsText,
+            ));
+        });
+}
+
+// Up and down pick a row, left and right change it, back saves and leaves
+fn navigate_settings(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !menu.settled {
+        menu.settled = true;
+        return;
+    }
+
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the settings: {e:?}");
+        }
+        return;
+    }
+    if input.confirm {
+        let screen = match ROWS[menu.row] {
+            SettingsRow::Video => GameState::Video,
+            SettingsRow::Controls => GameState::Remap,
+            SettingsRow::Sticks => GameState::Calibration,
+            _ => return,
+        };
+        if let Err(e) = state.push(screen) {
+            warn!("Unable to open {screen:?}: {e:?}");
+        }
+        return;
+    }
+
+    menu.row = navigation.row(menu.row, ROWS.len());
+    if navigation.step.x != 0 {
+        ROWS[menu.row].adjust(&mut settings, navigation.step.x as f32);
+    }
+}
+
+fn update_settings_text(
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<SettingsText>>,
+) {
+    let mut panel = format!("{}\n\n", localization.get("settings-title"));
+    for (index, row) in ROWS.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        panel += &format!(
+            "{cursor} {:<20}{:>10}\n",
+            localization.get(row.label()),
+            row.value(&settings, &localization)
+        );
+    }
+    panel += &format!("\n{}", localization.get("settings-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn hide_settings(mut ui: Query<&mut Visibility, With<SettingsUi>>) {
+    for mut visibility in &mut ui {
+        visibility.is_visible = false;
+    }
+}
+
+fn show_settings(mut menu: ResMut<SettingsMenu>, mut ui: Query<&mut Visibility, With<SettingsUi>>) {
+    menu.settled = false;
+    for mut visibility in &mut ui {
+        visibility.is_visible = true;
+    }
+}
+
+fn close_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    ui: Query<Entity, With<SettingsUi>>,
+) {
+    settings.save();
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+
+use crate::{
+    enemy_movement, objective::Protected, reset::DespawnOnReset, state::GameState, Enemy, GameRng,
+    Player,
+};
+
+// The plate stands this far out in front of its beet, and this high off the ground
+const PLATE_DISTANCE: f32 = 0.2;
+const PLATE_HEIGHT: f32 = 0.2;
+const SPARKS_PER_DEFLECT: usize = 5;
+const SPARKS_PER_BREAK: usize = 12;
+const SPARK_SPEED: f32 = 2.5;
+const SPARK_SECONDS: f32 = 0.3;
+
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShieldStruck>()
+            .add_startup_system(setup_shield_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(raise_shields)
+                    .with_system(face_threats.after(raise_shields).after(enemy_movement))
+                    .with_system(strike_shields)
+                    .with_system(fly_sparks),
+            );
+    }
+}
+
+// Held out towards whoever the beet's after. Anything coming at it from inside the arc glances
+// off, and anything from the side or behind gets through as usual. Beets don't turn to face where
+// they're going, so which way it's pointing is worked out here rather than read off the beet.
+#[derive(Component)]
+pub struct Shield {
+    // Radians either side of straight ahead
+    pub half_arc: f32,
+    // Flat, along the ground
+    pub facing: Vec3,
+    plate: Option<Entity>,
+}
+
+impl Shield {
+    pub fn new(arc_degrees: f32) -> Self {
+        Self {
+            half_arc: arc_degrees.to_radians(),
+            facing: Vec3::Z,
+            plate: None,
+        }
+    }
+
+    // Whether something travelling along `heading` would hit the front of it
+    pub fn blocks(&self, heading: Vec3) -> bool {
+        let incoming = Vec3::new(-heading.x, 0., -heading.z);
+        incoming.length_squared() > 0. && incoming.angle_between(self.facing) <= self.half_arc
+    }
+
+    // Bounced off the face of the plate, keeping whatever height it had
+    pub fn deflect(&self, heading: Vec3) -> Vec3 {
+        heading - 2. * heading.dot(self.facing) * self.facing
+    }
+}
+
+// Something hit a shield head on. Only a charged shot breaks it.
+pub struct ShieldStruck {
+    pub target: Entity,
+    pub position: Vec3,
+    pub broken: bool,
+}
+
+#[derive(Component)]
+struct ShieldPlate;
+
+#[derive(Component)]
+struct Spark {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+struct ShieldAssets {
+    plate_mesh: Handle<Mesh>,
+    plate_material: Handle<StandardMaterial>,
+    spark_mesh: Handle<Mesh>,
+    spark_material: Handle<StandardMaterial>,
+}
+
+fn setup_shield_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ShieldAssets {
+        plate_mesh: meshes.add(Mesh::from(shape::Box::new(0.3, 0.35, 0.03))),
+        plate_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.55, 0.5, 0.45),
+            metallic: 0.6,
+            perceptual_roughness: 0.4,
+            ..default()
+        }),
+        spark_mesh: meshes.add(Mesh::from(shape::Cube { size: 0.03 })),
+        spark_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.85, 0.4),
+            emissive: Color::rgb(1.0, 0.7, 0.2),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn raise_shields(
+    mut commands: Commands,
+    shield_assets: Res<ShieldAssets>,
+    mut shields: Query<(Entity, &mut Shield), Added<Shield>>,
+) {
+    for (entity, mut shield) in &mut shields {
+        let plate = commands
+            .spawn((
+                PbrBundle {
+                    mesh: shield_assets.plate_mesh.clone(),
+                    material: shield_assets.plate_material.clone(),
+                    ..default()
+                },
+                ShieldPlate,
+            ))
+            .id();
+        commands.entity(entity).add_child(plate);
+        shield.plate = Some(plate);
+    }
+}
+
+// Towards the nearest player, or whatever they're protecting if that's nearer
+fn face_threats(
+    targets: Query<&GlobalTransform, Or<(With<Player>, With<Protected>)>>,
+    mut shields: Query<(&GlobalTransform, &mut Shield), With<Enemy>>,
+    mut plates: Query<&mut Transform, With<ShieldPlate>>,
+) {
+    for (transform, mut shield) in &mut shields {
+        let position = transform.translation();
+        let nearest = targets
+            .iter()
+            .map(|target| target.translation() - position)
+            .map(|offset| Vec3::new(offset.x, 0., offset.z))
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        if let Some(facing) = nearest.and_then(Vec3::try_normalize) {
+            shield.facing = facing;
+        }
+
+        let Some(plate) = shield.plate else { continue };
+        let Ok(mut plate) = plates.get_mut(plate) else { continue };
+        // The plate's a child of the beet, which turns whichever way it's walking
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        let facing = rotation.inverse() * shield.facing;
+        plate.translation = facing * PLATE_DISTANCE + Vec3::Y * PLATE_HEIGHT;
+        plate.look_at(plate.translation + facing, Vec3::Y);
+    }
+}
+
+// A shower of sparks either way, and a bigger one when it gives
+fn strike_shields(
+    mut commands: Commands,
+    shield_assets: Res<ShieldAssets>,
+    mut rng: ResMut<GameRng>,
+    mut struck: EventReader<ShieldStruck>,
+    shields: Query<&Shield>,
+) {
+    for strike in struck.iter() {
+        let sparks = if strike.broken {
+            SPARKS_PER_BREAK
+        } else {
+            SPARKS_PER_DEFLECT
+        };
+        for _ in 0..sparks {
+            let velocity = Vec3::new(
+                rng.0.gen_range(-1.0..1.0),
+                rng.0.gen_range(0.5..1.5),
+                rng.0.gen_range(-1.0..1.0),
+            ) * SPARK_SPEED;
+            commands.spawn((
+                PbrBundle {
+                    mesh: shield_assets.spark_mesh.clone(),
+                    material: shield_assets.spark_material.clone(),
+                    transform: Transform::from_translation(strike.position),
+                    ..default()
+                },
+                Spark {
+                    velocity,
+                    lifetime: Timer::from_seconds(SPARK_SECONDS, TimerMode::Once),
+                },
+                NotShadowCaster,
+                DespawnOnReset,
+            ));
+        }
+
+        if !strike.broken {
+            continue;
+        }
+        let Ok(shield) = shields.get(strike.target) else { continue };
+        if let Some(plate) = shield.plate {
+            commands.entity(plate).despawn_recursive();
+        }
+        commands.entity(strike.target).remove::<Shield>();
+    }
+}
+
+fn fly_sparks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sparks: Query<(Entity, &mut Transform, &mut Spark)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut transform, mut spark) in &mut sparks {
+        if spark.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation += spark.velocity * dt;
+        transform.scale = Vec3::splat(spark.lifetime.percent_left());
+    }
+}
@@ -0,0 +1,310 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    damage::Health,
+    input::{InputSource, PlayerInput},
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    netplay::Netplay,
+    pickups::Currency,
+    state::GameState,
+    waves::{WaveCleared, WaveManager, WavePhase},
+    weapons::WeaponStats,
+    Player,
+};
+
+// Long enough for the coins left lying about to fly in before it opens
+const SHOP_DELAY_SECONDS: f32 = 1.5;
+const MAX_LEVEL: u32 = 5;
+// What each level adds
+const DAMAGE_PER_LEVEL: f32 = 0.2;
+const FIRE_RATE_PER_LEVEL: f32 = 0.15;
+const BLAST_RADIUS_PER_LEVEL: f32 = 0.25;
+const HEALTH_PER_LEVEL: f32 = 1.;
+const SPEED_PER_LEVEL: f32 = 0.1;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Upgrades>()
+            .init_resource::<ShopMenu>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(open_shop))
+            .add_system_set(SystemSet::on_enter(GameState::Shop).with_system(spawn_shop))
+            .add_system_set(
+                SystemSet::on_update(GameState::Shop)
+                    .with_system(navigate_shop)
+                    .with_system(update_shop_text.after(navigate_shop)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Shop).with_system(close_shop))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_upgrades));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upgrade {
+    Damage,
+    FireRate,
+    BlastRadius,
+    MaxHealth,
+    MoveSpeed,
+    Pierce,
+    Ricochet,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 7] = [
+        Upgrade::Damage,
+        Upgrade::FireRate,
+        Upgrade::BlastRadius,
+        Upgrade::MaxHealth,
+        Upgrade::MoveSpeed,
+        Upgrade::Pierce,
+        Upgrade::Ricochet,
+    ];
+
+    // Into the localization
+    pub fn label(&self) -> &'static str {
+        match self {
+            Upgrade::Damage => "upgrade-damage",
+            Upgrade::FireRate => "upgrade-fire-rate",
+            Upgrade::BlastRadius => "upgrade-blast-radius",
+            Upgrade::MaxHealth => "upgrade-max-health",
+            Upgrade::MoveSpeed => "upgrade-move-speed",
+            Upgrade::Pierce => "upgrade-pierce",
+            Upgrade::Ricochet => "upgrade-ricochet",
+        }
+    }
+
+    // In coins. Each level costs more than the last.
+    pub fn cost(&self, level: u32) -> u32 {
+        let base = match self {
+            Upgrade::Damage | Upgrade::FireRate => 4,
+            Upgrade::BlastRadius => 6,
+            Upgrade::MaxHealth => 5,
+            Upgrade::MoveSpeed => 3,
+            Upgrade::Pierce | Upgrade::Ricochet => 8,
+        };
+        base * (level + 1)
+    }
+}
+
+// Everything bought so far this run
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Upgrades {
+    levels: [u32; Upgrade::ALL.len()],
+    // Upgrades owed for nothing, spent before any coins are
+    pub free_picks: u32,
+}
+
+impl Upgrades {
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        self.levels[upgrade as usize]
+    }
+
+    // Goes on top of the weapon's own upgrade tier, whichever weapon's equipped
+    pub fn apply(&self, stats: WeaponStats) -> WeaponStats {
+        WeaponStats {
+            damage: stats.damage * (1. + self.level(Upgrade::Damage) as f32 * DAMAGE_PER_LEVEL),
+            fire_rate: stats.fire_rate
+                * (1. + self.level(Upgrade::FireRate) as f32 * FIRE_RATE_PER_LEVEL),
+            blast_radius: stats.blast_radius
+                + self.level(Upgrade::BlastRadius) as f32 * BLAST_RADIUS_PER_LEVEL,
+            // A beet further each level
+            pierce: stats.pierce + self.level(Upgrade::Pierce),
+            ricochet: stats.ricochet + self.level(Upgrade::Ricochet),
+            ..stats
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        1. + self.level(Upgrade::MoveSpeed) as f32 * SPEED_PER_LEVEL
+    }
+
+    fn extra_health(&self) -> f32 {
+        self.level(Upgrade::MaxHealth) as f32 * HEALTH_PER_LEVEL
+    }
+}
+
+// The upgrades, then a last row to carry on
+#[derive(Resource, Default)]
+struct ShopMenu {
+    row: usize,
+}
+
+#[derive(Component)]
+struct ShopUi;
+
+#[derive(Component)]
+struct ShopText;
+
+// Recordings and online games have nobody at the keyboard to shop for them, so they go straight
+// on to the next wave
+fn open_shop(
+    mut cleared: EventReader<WaveCleared>,
+    mut countdown: Local<Option<Timer>>,
+    time: Res<Time>,
+    waves: Res<WaveManager>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if cleared.iter().count() > 0 && matches!(*source, InputSource::Live) && netplay.is_none() {
+        *countdown = Some(Timer::from_seconds(SHOP_DELAY_SECONDS, TimerMode::Once));
+    }
+    let Some(timer) = countdown.as_mut() else { return };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    *countdown = None;
+    // The run might have moved on without us
+    if waves.phase != WavePhase::Intermission {
+        return;
+    }
+    if let Err(e) = state.push(GameState::Shop) {
+        warn!("Unable to open the shop: {e:?}");
+    }
+}
+
+fn spawn_shop(mut commands: Commands, mut menu: ResMut<ShopMenu>, asset_server: Res<AssetServer>) {
+    menu.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            ShopUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                ShopText,
+            ));
+        });
+}
+
+// Up and down pick a row, confirm buys, back (or confirming the last row) starts the next wave
+fn navigate_shop(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut menu: ResMut<ShopMenu>,
+    mut upgrades: ResMut<Upgrades>,
+    mut currency: ResMut<Currency>,
+    mut state: ResMut<State<GameState>>,
+    mut players: Query<&mut Health, With<Player>>,
+) {
+    let rows = Upgrade::ALL.len() + 1;
+    if input.back || (input.confirm && menu.row == Upgrade::ALL.len()) {
+        if let Err(e) = state.pop() {
+            warn!("Unable to leave the shop: {e:?}");
+        }
+        return;
+    }
+
+    if input.confirm {
+        let upgrade = Upgrade::ALL[menu.row];
+        let level = upgrades.level(upgrade);
+        if level >= MAX_LEVEL {
+            return;
+        }
+        if upgrades.free_picks > 0 {
+            upgrades.free_picks -= 1;
+        } else {
+            let cost = upgrade.cost(level);
+            if currency.0 < cost {
+                return;
+            }
+            currency.0 -= cost;
+        }
+
+        upgrades.levels[upgrade as usize] += 1;
+        if upgrade == Upgrade::MaxHealth {
+            for mut health in &mut players {
+                health.max += HEALTH_PER_LEVEL;
+                health.current += HEALTH_PER_LEVEL;
+            }
+        }
+        return;
+    }
+
+    menu.row = navigation.row(menu.row, rows);
+}
+
+fn update_shop_text(
+    menu: Res<ShopMenu>,
+    upgrades: Res<Upgrades>,
+    currency: Res<Currency>,
+    waves: Res<WaveManager>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<ShopText>>,
+) {
+    let wallet = match upgrades.free_picks {
+        0 => localization.format("shop-coins", &[("coins", &currency.0)]),
+        1 => localization.get("shop-free-pick").to_string(),
+        picks => localization.format("shop-free-picks", &[("picks", &picks)]),
+    };
+    let mut panel = format!("{}{wallet:>40}\n\n", localization.get("shop-title"));
+    for (index, upgrade) in Upgrade::ALL.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        let level = upgrades.level(*upgrade);
+        let cost = if level >= MAX_LEVEL {
+            localization.get("shop-max").to_string()
+        } else if upgrades.free_picks > 0 {
+            localization.get("shop-free").to_string()
+        } else {
+            upgrade.cost(level).to_string()
+        };
+        panel += &format!(
+            "{cursor} {:<18}{:>12}{cost:>14}\n",
+            localization.get(upgrade.label()),
+            localization.format("shop-level", &[("level", &level), ("max", &MAX_LEVEL)]),
+        );
+    }
+    let cursor = if menu.row == Upgrade::ALL.len() { ">" } else { " " };
+    // A free pick can turn up mid-wave
+    let carry_on = if waves.phase == WavePhase::Intermission {
+        "shop-next-wave"
+    } else {
+        "shop-back-to-it"
+    };
+    panel += &format!("\n{cursor} {}\n", localization.get(carry_on));
+    panel += &format!("\n{}", localization.get("shop-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn close_shop(mut commands: Commands, ui: Query<Entity, With<ShopUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Whatever order this and the world reset run in, everyone ends up on full health at the old max
+fn reset_upgrades(mut upgrades: ResMut<Upgrades>, mut players: Query<&mut Health, With<Player>>) {
+    let extra = upgrades.extra_health();
+    for mut health in &mut players {
+        health.max -= extra;
+        health.current = health.current.min(health.max);
+    }
+    *upgrades = Upgrades::default();
+}
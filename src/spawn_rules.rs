@@ -0,0 +1,199 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{EnemyKind, ENEMY_RADIUS};
+
+// When nothing on offer will do, rings this far apart are tried around it, this many of them with
+// this many points on each
+const NUDGE_STEP: f32 = 0.25;
+const NUDGE_RINGS: u32 = 8;
+const NUDGE_DIRECTIONS: u32 = 8;
+
+// Limits `spawn_enemy` checks before putting anything into the world, so the player doesn't get
+// a beet dropped on their head or buried under one kind of enemy.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SpawnConfig {
+    // Nothing spawns closer to the player than this
+    pub min_player_distance: f32,
+    // ..or on top of another enemy
+    pub min_enemy_spacing: f32,
+    // ..or further than this either side of the middle of the lane
+    pub lane_half_width: f32,
+    // How many of each kind can be alive at once. Kinds left out aren't capped.
+    pub max_alive: HashMap<EnemyKind, u32>,
+    // How spawning ramps up the further the camera gets, whatever wave it is. In order of
+    // distance; anywhere between two points is a blend of them, and past the last it stays put.
+    pub pressure: Vec<Pressure>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Pressure {
+    // How far the camera has scrolled down the lane
+    pub distance: f32,
+    // Multiplies the time between spawns
+    pub interval: f32,
+    // Enemies without a spawn point to use land anywhere this far either side of the lane's middle
+    pub half_width: f32,
+    // How many spawn each time. Blended like the rest, then rounded down.
+    pub count: f32,
+}
+
+impl Default for Pressure {
+    fn default() -> Self {
+        Self {
+            distance: 0.,
+            interval: 1.,
+            half_width: 2.,
+            count: 1.,
+        }
+    }
+}
+
+impl SpawnConfig {
+    pub fn pressure_at(&self, distance: f32) -> Pressure {
+        let (Some(first), Some(last)) = (self.pressure.first(), self.pressure.last()) else {
+            return Pressure::default();
+        };
+        if distance <= first.distance {
+            return Pressure { distance, ..*first };
+        }
+
+        for pair in self.pressure.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if distance > to.distance {
+                continue;
+            }
+            let t = (distance - from.distance) / (to.distance - from.distance).max(f32::EPSILON);
+            let blend = |a: f32, b: f32| a + (b - a) * t;
+            return Pressure {
+                distance,
+                interval: blend(from.interval, to.interval),
+                half_width: blend(from.half_width, to.half_width),
+                count: blend(from.count, to.count),
+            };
+        }
+        Pressure { distance, ..*last }
+    }
+}
+
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        Self {
+            min_player_distance: 4.,
+            min_enemy_spacing: 0.6,
+            lane_half_width: 3.5,
+            max_alive: HashMap::from_iter([(EnemyKind::Beet, 30)]),
+            pressure: vec![
+                Pressure::default(),
+                Pressure {
+                    distance: 100.,
+                    interval: 0.75,
+                    half_width: 2.5,
+                    count: 2.,
+                },
+                Pressure {
+                    distance: 250.,
+                    interval: 0.5,
+                    half_width: 3.,
+                    count: 3.,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SpawnViolation {
+    TooMany { kind: EnemyKind, alive: u32 },
+    TooCloseToPlayer { distance: f32 },
+    TooCloseToEnemy { distance: f32 },
+    OffLane { offset: f32 },
+    InsideObstacle { distance: f32 },
+}
+
+pub fn check_kind(
+    config: &SpawnConfig,
+    kind: EnemyKind,
+    enemies: &[(EnemyKind, Vec3)],
+) -> Result<(), SpawnViolation> {
+    let Some(&max) = config.max_alive.get(&kind) else { return Ok(()) };
+    let alive = enemies.iter().filter(|(other, _)| *other == kind).count() as u32;
+    if alive >= max {
+        return Err(SpawnViolation::TooMany { kind, alive });
+    }
+    Ok(())
+}
+
+// Obstacles are their position and radius
+pub fn check_position(
+    config: &SpawnConfig,
+    position: Vec3,
+    players: &[Vec3],
+    enemies: &[(EnemyKind, Vec3)],
+    obstacles: &[(Vec3, f32)],
+) -> Result<(), SpawnViolation> {
+    if position.x.abs() > config.lane_half_width {
+        return Err(SpawnViolation::OffLane { offset: position.x });
+    }
+
+    // Across the ground, whatever height they're at
+    let flat = |v: Vec3| Vec2::new(v.x, v.z);
+    for (obstacle, radius) in obstacles {
+        let distance = flat(position).distance(flat(*obstacle));
+        if distance < radius + ENEMY_RADIUS {
+            return Err(SpawnViolation::InsideObstacle { distance });
+        }
+    }
+
+    let distance = players
+        .iter()
+        .map(|player| position.distance(*player))
+        .fold(f32::MAX, f32::min);
+    if distance < config.min_player_distance {
+        return Err(SpawnViolation::TooCloseToPlayer { distance });
+    }
+
+    let closest = enemies
+        .iter()
+        .map(|(_, enemy)| position.distance(*enemy))
+        .fold(f32::MAX, f32::min);
+    if closest < config.min_enemy_spacing {
+        return Err(SpawnViolation::TooCloseToEnemy { distance: closest });
+    }
+
+    Ok(())
+}
+
+// The closest point to `around` that passes, if there's one near enough. Nothing's rolled for it,
+// so the same start always ends up in the same place.
+pub fn nearest_valid(
+    config: &SpawnConfig,
+    around: Vec3,
+    players: &[Vec3],
+    enemies: &[(EnemyKind, Vec3)],
+    obstacles: &[(Vec3, f32)],
+) -> Option<Vec3> {
+    (1..=NUDGE_RINGS).find_map(|ring| {
+        let radius = ring as f32 * NUDGE_STEP;
+        (0..NUDGE_DIRECTIONS)
+            .map(|direction| {
+                let angle = direction as f32 / NUDGE_DIRECTIONS as f32 * TAU;
+                around + Vec3::new(angle.cos(), 0., angle.sin()) * radius
+            })
+            .find(|candidate| {
+                check_position(config, *candidate, players, enemies, obstacles).is_ok()
+            })
+    })
+}
+
+// Handy when tuning the numbers above, just noise otherwise
+pub fn report(violation: SpawnViolation) {
+    #[cfg(debug_assertions)]
+    info!("Spawn rejected: {violation:?}");
+    #[cfg(not(debug_assertions))]
+    let _ = violation;
+}
@@ -0,0 +1,264 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    camera_start,
+    campaign::ActiveLevel,
+    config::GameConfig,
+    damage::{DamageDealt, Health},
+    destructibles::Breakable,
+    obstacles::Obstacle,
+    reset::DespawnOnReset,
+    spawn_rules,
+    state::GameState,
+    telegraph::{spawn_telegraph, SpawnTelegraph, TelegraphAssets},
+    Aim, Enemy, EnemyKind, Game, GameRng,
+};
+
+// Only the ones this close to the camera, in front or behind, send anything out. The rest wait
+// for the lane to get to them rather than flooding it from miles away.
+const SPAWNER_ACTIVE_RANGE: f32 = 14.;
+// Beets come up this far out in front of it, towards the players, give or take a little either
+// side
+const EMERGE_GAP: f32 = 0.25;
+const EMERGE_SPREAD: f32 = 0.4;
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_spawner_assets)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(place_spawners))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(emit_enemies)
+                    .with_system(destroy_spawners),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum SpawnerKind {
+    // Quick to knock in but never stops for long
+    #[default]
+    GopherHole,
+    // Takes some shifting, and sends out a beet every so often until it is
+    CompostHeap,
+}
+
+impl SpawnerKind {
+    fn health(self) -> f32 {
+        match self {
+            SpawnerKind::GopherHole => 6.,
+            SpawnerKind::CompostHeap => 15.,
+        }
+    }
+
+    // Seconds between beets
+    fn interval(self) -> f32 {
+        match self {
+            SpawnerKind::GopherHole => 3.,
+            SpawnerKind::CompostHeap => 5.,
+        }
+    }
+
+    fn radius(self) -> f32 {
+        match self {
+            SpawnerKind::GopherHole => 0.35,
+            SpawnerKind::CompostHeap => 0.5,
+        }
+    }
+}
+
+// Somewhere down the lane that keeps sending beets out until it's destroyed, as written in
+// levels.ron
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SpawnerDefinition {
+    pub kind: SpawnerKind,
+    // Across the lane, with 0 the middle
+    pub x: f32,
+    // How far down the lane from where the camera starts
+    pub distance: f32,
+}
+
+impl Default for SpawnerDefinition {
+    fn default() -> Self {
+        Self {
+            kind: SpawnerKind::default(),
+            x: 0.,
+            distance: 20.,
+        }
+    }
+}
+
+// Shootable and lockable like a beet, and shown on the radar wherever it is
+#[derive(Component)]
+pub struct Spawner {
+    pub kind: SpawnerKind,
+    timer: Timer,
+}
+
+#[derive(Resource)]
+struct SpawnerAssets {
+    hole_mesh: Handle<Mesh>,
+    hole_material: Handle<StandardMaterial>,
+    mound_mesh: Handle<Mesh>,
+    heap_mesh: Handle<Mesh>,
+    dirt_material: Handle<StandardMaterial>,
+    heap_material: Handle<StandardMaterial>,
+}
+
+fn setup_spawner_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let radius = SpawnerKind::GopherHole.radius();
+    commands.insert_resource(SpawnerAssets {
+        hole_mesh: meshes.add(Mesh::from(shape::Plane { size: radius * 1.4 })),
+        hole_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.08, 0.05, 0.03),
+            unlit: true,
+            ..default()
+        }),
+        mound_mesh: meshes.add(Mesh::from(shape::Torus {
+            radius,
+            ring_radius: 0.1,
+            ..default()
+        })),
+        heap_mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: SpawnerKind::CompostHeap.radius(),
+            subdivisions: 2,
+        })),
+        dirt_material: materials.add(Color::rgb(0.4, 0.28, 0.16).into()),
+        heap_material: materials.add(Color::rgb(0.3, 0.24, 0.12).into()),
+    });
+}
+
+// Nothing random about where they go, so a level always has them in the same places
+fn place_spawners(mut commands: Commands, level: Res<ActiveLevel>, assets: Res<SpawnerAssets>) {
+    let Some(level) = &level.0 else { return };
+    let start = camera_start().translation.z;
+    for definition in &level.spawners {
+        let kind = definition.kind;
+        let position = Vec3::new(definition.x, 0., start - definition.distance);
+        let (mesh, material, transform) = match kind {
+            SpawnerKind::GopherHole => (
+                assets.mound_mesh.clone(),
+                assets.dirt_material.clone(),
+                Transform::from_translation(position),
+            ),
+            // Half sunk into the ground and squashed down
+            SpawnerKind::CompostHeap => (
+                assets.heap_mesh.clone(),
+                assets.heap_material.clone(),
+                Transform::from_translation(position).with_scale(Vec3::new(1., 0.6, 1.)),
+            ),
+        };
+        let mut spawner = commands.spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform,
+                ..default()
+            },
+            Spawner {
+                kind,
+                timer: Timer::from_seconds(kind.interval(), TimerMode::Repeating),
+            },
+            Obstacle {
+                radius: kind.radius(),
+            },
+            Breakable,
+            Health::new(kind.health()),
+            NotShadowCaster,
+            DespawnOnReset,
+        ));
+        if kind == SpawnerKind::GopherHole {
+            spawner.with_children(|hole| {
+                // Just off the ground, so it doesn't flicker against it
+                hole.spawn((
+                    PbrBundle {
+                        mesh: assets.hole_mesh.clone(),
+                        material: assets.hole_material.clone(),
+                        transform: Transform::from_xyz(0., 0.01, 0.),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                ));
+            });
+        }
+    }
+}
+
+// Through the same churned-up ground as every other beet, and held to the same limits on how many
+// can be about at once. They're on top of the wave, not part of it.
+fn emit_enemies(
+    mut commands: Commands,
+    game: Res<Game>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    telegraph_assets: Res<TelegraphAssets>,
+    mut spawners: Query<(&GlobalTransform, &mut Spawner)>,
+    enemies: Query<(&EnemyKind, &GlobalTransform), With<Enemy>>,
+    telegraphs: Query<(&SpawnTelegraph, &GlobalTransform)>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let camera_z = camera.translation().z;
+
+    let mut alive = enemies
+        .iter()
+        .map(|(kind, transform)| (*kind, transform.translation()))
+        .chain(
+            telegraphs
+                .iter()
+                .map(|(telegraph, transform)| (telegraph.kind, transform.translation())),
+        )
+        .collect::<Vec<_>>();
+
+    for (transform, mut spawner) in &mut spawners {
+        let position = transform.translation();
+        if (position.z - camera_z).abs() > SPAWNER_ACTIVE_RANGE {
+            continue;
+        }
+        if !spawner.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let kind = EnemyKind::Beet;
+        if let Err(violation) = spawn_rules::check_kind(&config.spawning, kind, &alive) {
+            spawn_rules::report(violation);
+            continue;
+        }
+        let offset = Vec3::new(
+            rng.0.gen_range(-EMERGE_SPREAD..EMERGE_SPREAD),
+            0.,
+            spawner.kind.radius() + EMERGE_GAP,
+        );
+        alive.push((kind, position + offset));
+        spawn_telegraph(&mut commands, &telegraph_assets, kind, position + offset);
+    }
+}
+
+// `Breakable` takes care of the debris. Whoever had it locked is let go first, so nothing's left
+// pointing at it.
+fn destroy_spawners(
+    mut dealt: EventReader<DamageDealt>,
+    spawners: Query<(), With<Spawner>>,
+    mut aims: Query<&mut Aim>,
+) {
+    for damage in dealt.iter().filter(|damage| damage.killed) {
+        if !spawners.contains(damage.target) {
+            continue;
+        }
+        for mut aim in &mut aims {
+            if aim.target == Some(damage.target) {
+                aim.target = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::Palette, input::PlayerInput, player_movement, state::GameState, Player,
+};
+
+const SPRINT_MULTIPLIER: f32 = 1.8;
+// A full bar lasts this long flat out
+const SPRINT_SECONDS: f32 = 2.5;
+// ..and takes this long to fill back up from empty
+const RECOVERY_SECONDS: f32 = 4.;
+// Run it dry and there's no sprinting again until it's back to this
+const EXHAUSTION_THRESHOLD: f32 = 0.3;
+const BAR_WIDTH: f32 = 160.;
+const BAR_HEIGHT: f32 = 10.;
+const BAR_MARGIN: f32 = 20.;
+
+pub struct SprintPlugin;
+
+impl Plugin for SprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(sprint.before(player_movement)),
+        )
+        .add_system(spawn_stamina_bars)
+        .add_system(update_stamina_bars.after(spawn_stamina_bars))
+        .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_stamina));
+    }
+}
+
+// From 0 to 1
+#[derive(Component)]
+pub struct Stamina {
+    pub current: f32,
+    sprinting: bool,
+    exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: 1.,
+            sprinting: false,
+            exhausted: false,
+        }
+    }
+}
+
+impl Stamina {
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.sprinting {
+            SPRINT_MULTIPLIER
+        } else {
+            1.
+        }
+    }
+}
+
+#[derive(Component)]
+struct StaminaBar {
+    player: Entity,
+}
+
+#[derive(Component)]
+struct StaminaFill;
+
+// Holding sprint only counts while actually going somewhere. It's the walking (or standing about)
+// that gets the breath back.
+fn sprint(time: Res<Time>, mut players: Query<(&PlayerInput, &mut Stamina)>) {
+    let delta = time.delta_seconds();
+    for (input, mut stamina) in &mut players {
+        stamina.sprinting = input.sprint && input.movement != Vec2::ZERO && !stamina.exhausted;
+
+        if stamina.sprinting {
+            stamina.current = (stamina.current - delta / SPRINT_SECONDS).max(0.);
+            if stamina.current == 0. {
+                stamina.exhausted = true;
+                stamina.sprinting = false;
+            }
+        } else {
+            stamina.current = (stamina.current + delta / RECOVERY_SECONDS).min(1.);
+            if stamina.current >= EXHAUSTION_THRESHOLD {
+                stamina.exhausted = false;
+            }
+        }
+    }
+}
+
+// A bar in the bottom left for each player, stacked upwards in player order
+fn spawn_stamina_bars(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    players: Query<(Entity, &Player), Added<Stamina>>,
+) {
+    for (entity, player) in &players {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(BAR_MARGIN),
+                            bottom: Val::Px(BAR_MARGIN + player.index as f32 * BAR_HEIGHT * 2.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                },
+                StaminaBar { player: entity },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                            ..default()
+                        },
+                        background_color: palette.stamina.into(),
+                        ..default()
+                    },
+                    StaminaFill,
+                ));
+            });
+    }
+}
+
+fn update_stamina_bars(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    bars: Query<(Entity, &StaminaBar, &Children)>,
+    stamina: Query<&Stamina>,
+    mut fills: Query<(&mut Style, &mut BackgroundColor), With<StaminaFill>>,
+) {
+    for (entity, bar, children) in &bars {
+        // Players who've dropped out take their bar with them
+        let Ok(stamina) = stamina.get(bar.player) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        for child in children {
+            let Ok((mut style, mut color)) = fills.get_mut(*child) else { continue };
+            style.size.width = Val::Percent(stamina.current * 100.);
+            // Grey while it's too low to sprint on
+            *color = if stamina.exhausted {
+                Color::rgb(0.5, 0.5, 0.5).into()
+            } else {
+                palette.stamina.into()
+            };
+        }
+    }
+}
+
+fn reset_stamina(mut players: Query<&mut Stamina>) {
+    for mut stamina in &mut players {
+        *stamina = Stamina::default();
+    }
+}
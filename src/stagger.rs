@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+
+use crate::{
+    animation::{Animated, AnimationState},
+    campaign::Boss,
+    projectile_hit,
+    state::GameState,
+    AttackState, Enemy, ProjectileHit,
+};
+
+// How long a beet reels from a pumpkin, and how long after that before another can make it
+const STAGGER_SECONDS: f32 = 0.25;
+const STEADY_SECONDS: f32 = 0.4;
+// Metres per second it's sent back at to begin with, slowing to a stop by the end
+const STAGGER_SPEED: f32 = 1.5;
+
+pub struct StaggerPlugin;
+
+impl Plugin for StaggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(stagger_enemies.after(projectile_hit))
+                .with_system(reel.after(stagger_enemies))
+                .with_system(steady.after(reel)),
+        );
+    }
+}
+
+// Knocked off its stride by a pumpkin, stumbling back the way the pumpkin was going. It doesn't
+// move or attack of its own accord until it's found its feet.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Staggered {
+    pub remaining: f32,
+    // Flat along the ground
+    pub direction: Vec3,
+}
+
+// Just found its feet again, and can't be staggered until this runs out. Otherwise anything
+// quick enough would keep a beet stumbling about forever.
+#[derive(Component, Clone, Copy, Debug)]
+struct Steadied {
+    remaining: f32,
+}
+
+// A wind-up that's interrupted has to be started again from the top. Bosses are too big to be
+// bothered.
+fn stagger_enemies(
+    mut commands: Commands,
+    mut hits: EventReader<ProjectileHit>,
+    mut enemies: Query<
+        (&mut AttackState, Option<&mut Animated>),
+        (
+            With<Enemy>,
+            Without<Boss>,
+            Without<Staggered>,
+            Without<Steadied>,
+        ),
+    >,
+) {
+    for hit in hits.iter() {
+        let Ok((mut attack, animated)) = enemies.get_mut(hit.target) else { continue };
+        let direction = Vec3::new(hit.direction.x, 0., hit.direction.z).normalize_or_zero();
+        commands.entity(hit.target).insert(Staggered {
+            remaining: STAGGER_SECONDS,
+            direction,
+        });
+        if matches!(*attack, AttackState::WindingUp { .. }) {
+            *attack = AttackState::Chasing;
+        }
+        if let Some(mut animated) = animated {
+            animated.restart(AnimationState::Idle);
+        }
+    }
+}
+
+// Eases off as it goes, so it reads as a stumble rather than a shove
+fn reel(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemies: Query<(
+        Entity,
+        &mut Transform,
+        &mut Staggered,
+        &AttackState,
+        Option<&mut Animated>,
+    )>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut staggered, attack, animated) in &mut enemies {
+        let speed = STAGGER_SPEED * (staggered.remaining / STAGGER_SECONDS).clamp(0., 1.);
+        transform.translation += staggered.direction * speed * delta;
+
+        staggered.remaining -= delta;
+        if staggered.remaining > 0. {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .remove::<Staggered>()
+            .insert(Steadied {
+                remaining: STEADY_SECONDS,
+            });
+        // Back after whoever it was chasing. Anything else picks its own animation up again.
+        if let (Some(mut animated), AttackState::Chasing) = (animated, attack) {
+            animated.restart(AnimationState::Walk);
+        }
+    }
+}
+
+fn steady(mut commands: Commands, time: Res<Time>, mut enemies: Query<(Entity, &mut Steadied)>) {
+    for (entity, mut steadied) in &mut enemies {
+        steadied.remaining -= time.delta_seconds();
+        if steadied.remaining <= 0. {
+            commands.entity(entity).remove::<Steadied>();
+        }
+    }
+}
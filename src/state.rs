@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+
+// Gameplay systems only run while `Playing` is the active state. Anything pushed on top of it
+// (pause, for now) freezes the run underneath until it's popped off again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameState {
+    MainMenu,
+    Playing,
+    Paused,
+    // Looking over the weapons, from the pause menu
+    Armory,
+    // Also from the pause menu
+    Settings,
+    // Window, vsync and shadows, from the settings
+    Video,
+    // Rebinding the controls, also from the settings
+    Remap,
+    // Stick dead zones and inversion, from the settings too
+    Calibration,
+    // Spending coins between waves
+    Shop,
+    // Taking one of two modifiers for the next wave, also between waves
+    Choice,
+    // The run's just ended, and the camera's having a last look round before the game over screen
+    Cinematic,
+    // The player is dead, looking back over the run
+    GameOver,
+    // From the main menu
+    Leaderboard,
+    // Picking a campaign level, also from the main menu
+    LevelSelect,
+    // Building up the garden with coins saved from the campaign, from the level select
+    Garden,
+    // Picking who to play as, on the way into a run from either of those
+    CharacterSelect,
+    // Twists on the run, from the character select
+    Mutators,
+    // The developer console, dropped down over the run
+    Console,
+    // Flying the camera round the frozen run, from the pause menu
+    PhotoMode,
+}
+
+pub struct GameStatePlugin {
+    pub initial: GameState,
+}
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(self.initial);
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    damage::{resolve_damage, DamageDealt},
+    state::GameState,
+    Enemy, EnemyKilled, EnemyKind, Player, ProjectileHit, WeaponFired,
+};
+
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            // Not tied to the state, so the blow that ends the run still gets counted
+            .add_system(count_shots)
+            .add_system(count_kills)
+            .add_system_to_stage(CoreStage::PostUpdate, count_damage.after(resolve_damage))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(count_time))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_stats));
+    }
+}
+
+// How the run went, for the game over screen. Every player's shots and hits count together.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RunStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    // To beets, from anything
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub kills: HashMap<EnemyKind, u32>,
+    // In seconds, not counting time spent paused or in the shop
+    pub time_survived: f32,
+}
+
+impl RunStats {
+    // From 0 to 1
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.;
+        }
+        (self.hits as f32 / self.shots_fired as f32).min(1.)
+    }
+}
+
+fn count_shots(
+    mut stats: ResMut<RunStats>,
+    mut fired: EventReader<WeaponFired>,
+    mut hits: EventReader<ProjectileHit>,
+) {
+    stats.shots_fired += fired.iter().count() as u32;
+    stats.hits += hits.iter().count() as u32;
+}
+
+fn count_kills(mut stats: ResMut<RunStats>, mut killed: EventReader<EnemyKilled>) {
+    for kill in killed.iter() {
+        *stats.kills.entry(kill.kind).or_default() += 1;
+    }
+}
+
+// Straight after the damage is worked out, while anything it killed is still about to check
+fn count_damage(
+    mut stats: ResMut<RunStats>,
+    mut dealt: EventReader<DamageDealt>,
+    players: Query<(), With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    for damage in dealt.iter() {
+        if players.contains(damage.target) {
+            stats.damage_taken += damage.amount;
+        } else if enemies.contains(damage.target) {
+            stats.damage_dealt += damage.amount;
+        }
+    }
+}
+
+fn count_time(mut stats: ResMut<RunStats>, time: Res<Time>) {
+    stats.time_survived += time.delta_seconds();
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    damage::{DamageEvent, Health},
+    state::GameState,
+};
+
+// Burning hurts in pulses, like the hazards do
+const BURN_TICK_SECONDS: f32 = 0.5;
+// However strong a slow is, nothing's stopped in its tracks by one
+const MAX_SLOW: f32 = 0.9;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatus>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(apply_statuses)
+                .with_system(burn.after(apply_statuses))
+                .with_system(expire_statuses::<Slowed>.after(apply_statuses))
+                .with_system(expire_statuses::<Burning>.after(burn))
+                .with_system(expire_statuses::<Stunned>.after(apply_statuses)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum StatusKind {
+    // Moves at `1 - strength` of its usual speed
+    Slow,
+    // Takes `strength` damage a second
+    Burn,
+    // Beets can't move or attack. Strength doesn't come into it.
+    Stun,
+}
+
+// Something a weapon or a hazard leaves behind on whatever it hits, as written in the config
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub seconds: f32,
+    #[serde(default)]
+    pub strength: f32,
+}
+
+// On a projectile, for whatever it hits
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Inflicts(pub StatusEffect);
+
+// Anything can send one of these. Hitting something that's already affected tops it back up,
+// keeping whichever is longer and whichever is stronger.
+#[derive(Clone, Copy, Debug)]
+pub struct ApplyStatus {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+// Every status wears off the same way, by counting down
+pub trait Status: Component {
+    fn remaining(&mut self) -> &mut f32;
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Slowed {
+    pub remaining: f32,
+    pub strength: f32,
+}
+
+impl Slowed {
+    // What to multiply speed by while it lasts
+    pub fn multiplier(&self) -> f32 {
+        1. - self.strength.clamp(0., MAX_SLOW)
+    }
+}
+
+impl Status for Slowed {
+    fn remaining(&mut self) -> &mut f32 {
+        &mut self.remaining
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Burning {
+    pub remaining: f32,
+    pub damage_per_second: f32,
+    tick: Timer,
+}
+
+impl Status for Burning {
+    fn remaining(&mut self) -> &mut f32 {
+        &mut self.remaining
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Stunned {
+    pub remaining: f32,
+}
+
+impl Status for Stunned {
+    fn remaining(&mut self) -> &mut f32 {
+        &mut self.remaining
+    }
+}
+
+// Only living things are affected. Whatever died on the hit that inflicted it is left alone.
+fn apply_statuses(
+    mut commands: Commands,
+    mut applied: EventReader<ApplyStatus>,
+    mut targets: Query<(Option<&mut Slowed>, Option<&mut Burning>, Option<&mut Stunned>, &Health)>,
+) {
+    for ApplyStatus { target, effect } in applied.iter().copied() {
+        let Ok((slowed, burning, stunned, health)) = targets.get_mut(target) else { continue };
+        if health.is_dead() || effect.seconds <= 0. {
+            continue;
+        }
+
+        match effect.kind {
+            StatusKind::Slow => match slowed {
+                Some(mut slowed) => {
+                    slowed.remaining = slowed.remaining.max(effect.seconds);
+                    slowed.strength = slowed.strength.max(effect.strength);
+                }
+                None => {
+                    commands.entity(target).insert(Slowed {
+                        remaining: effect.seconds,
+                        strength: effect.strength,
+                    });
+                }
+            },
+            StatusKind::Burn => match burning {
+                Some(mut burning) => {
+                    burning.remaining = burning.remaining.max(effect.seconds);
+                    burning.damage_per_second = burning.damage_per_second.max(effect.strength);
+                }
+                None => {
+                    commands.entity(target).insert(Burning {
+                        remaining: effect.seconds,
+                        damage_per_second: effect.strength,
+                        tick: Timer::from_seconds(BURN_TICK_SECONDS, TimerMode::Repeating),
+                    });
+                }
+            },
+            StatusKind::Stun => match stunned {
+                Some(mut stunned) => stunned.remaining = stunned.remaining.max(effect.seconds),
+                None => {
+                    commands.entity(target).insert(Stunned {
+                        remaining: effect.seconds,
+                    });
+                }
+            },
+        }
+    }
+}
+
+// Through the damage pipeline like anything else, so resistances still count
+fn burn(
+    time: Res<Time>,
+    mut burning: Query<(Entity, &mut Burning)>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (entity, mut burning) in &mut burning {
+        if !burning.tick.tick(time.delta()).just_finished() {
+            continue;
+        }
+        damage.send(DamageEvent {
+            source: None,
+            target: entity,
+            amount: burning.damage_per_second * BURN_TICK_SECONDS,
+            direction: Vec3::ZERO,
+            critical: false,
+            knockback: 0.,
+        });
+    }
+}
+
+fn expire_statuses<T: Status>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut statuses: Query<(Entity, &mut T)>,
+) {
+    for (entity, mut status) in &mut statuses {
+        let remaining = status.remaining();
+        *remaining -= time.delta_seconds();
+        if *remaining <= 0. {
+            commands.entity(entity).remove::<T>();
+        }
+    }
+}
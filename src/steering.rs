@@ -0,0 +1,93 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+// Context steering: score a ring of candidate directions by how much they head towards the target,
+// knock out the ones that run into something, and take the best of what's left. Cheap enough to do
+// for every enemy every frame, and it slides enemies around obstacles instead of into them.
+const DIRECTIONS: usize = 16;
+// How far ahead an enemy looks for obstacles
+const LOOKAHEAD: f32 = 1.;
+
+pub fn steer(
+    position: Vec3,
+    target: Vec3,
+    radius: f32,
+    obstacles: impl Iterator<Item = (Vec3, f32)> + Clone,
+) -> Vec3 {
+    let position = Vec2::new(position.x, position.z);
+    let to_target = Vec2::new(target.x, target.z) - position;
+    let Some(desired) = to_target.try_normalize() else { return Vec3::ZERO };
+    // Don't worry about anything beyond the target
+    let lookahead = LOOKAHEAD.min(to_target.length());
+
+    let mut best = None;
+    let mut best_score = f32::MIN;
+    for i in 0..DIRECTIONS {
+        let angle = i as f32 / DIRECTIONS as f32 * TAU;
+        let direction = Vec2::new(angle.cos(), angle.sin());
+        let interest = direction.dot(desired);
+
+        let danger = obstacles
+            .clone()
+            .map(|(obstacle, obstacle_radius)| {
+                danger_along(
+                    position,
+                    direction,
+                    lookahead,
+                    Vec2::new(obstacle.x, obstacle.z),
+                    radius + obstacle_radius,
+                )
+            })
+            .fold(0f32, f32::max);
+
+        let score = interest - danger * 2.;
+        if score > best_score {
+            best_score = score;
+            best = Some(direction);
+        }
+    }
+
+    let direction = best.unwrap_or(desired);
+    Vec3::new(direction.x, 0., direction.y)
+}
+
+// 0 if walking `lookahead` along `direction` misses the obstacle, rising to 1 the sooner it's hit
+fn danger_along(
+    position: Vec2,
+    direction: Vec2,
+    lookahead: f32,
+    obstacle: Vec2,
+    clearance: f32,
+) -> f32 {
+    let to_obstacle = obstacle - position;
+    let along = to_obstacle.dot(direction);
+    if along < 0. || along > lookahead + clearance {
+        return 0.;
+    }
+
+    let miss_distance = (to_obstacle - direction * along).length();
+    if miss_distance >= clearance {
+        return 0.;
+    }
+
+    1. - (along / (lookahead + clearance)).clamp(0., 1.)
+}
+
+// How much `position` wants to get away from its neighbours - strongest when they're right on top
+// of it, and nothing at all once they're `range` away
+pub fn separation(position: Vec3, neighbours: impl Iterator<Item = Vec3>, range: f32) -> Vec3 {
+    let mut push = Vec3::ZERO;
+    for neighbour in neighbours {
+        let away = Vec3::new(position.x - neighbour.x, 0., position.z - neighbour.z);
+        let distance = away.length();
+        if distance >= range {
+            continue;
+        }
+
+        // Two enemies spawned on the same spot still need to split up somehow
+        let direction = away.try_normalize().unwrap_or(Vec3::X);
+        push += direction * (1. - distance / range);
+    }
+    push
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Somewhere per-user that survives the game being reinstalled, e.g.
+// ~/.local/share/vegetable-funeral on Linux or %APPDATA%\Vegetable Funeral on Windows
+#[cfg(not(target_arch = "wasm32"))]
+pub fn data_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("games", "Vegetable Funeral", "Vegetable Funeral")
+        .map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+// Browsers don't give us a filesystem, so "files" are keys in the page's local storage instead
+#[cfg(target_arch = "wasm32")]
+pub fn data_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("vegetable-funeral"))
+}
+
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(name))
+}
+
+// Missing files aren't worth complaining about - it's just the first run
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let Some(contents) = read(path) else { return T::default() };
+    ron::from_str(&contents).unwrap_or_else(|e| {
+        error!("{path:?} is invalid, starting afresh: {e}");
+        T::default()
+    })
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) {
+    match ron::ser::to_string_pretty(value, default()) {
+        Ok(contents) => {
+            if let Err(e) = write(path, &contents) {
+                error!("Unable to write {path:?}: {e}");
+            }
+        }
+        Err(e) => error!("Unable to serialize {path:?}: {e}"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("can't create {dir:?}: {e}"))?;
+    }
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(path: &Path) -> Option<String> {
+    local_storage()?
+        .get_item(&path.to_string_lossy())
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    let storage = local_storage().ok_or("local storage isn't available")?;
+    storage
+        .set_item(&path.to_string_lossy(), contents)
+        .map_err(|e| format!("{e:?}"))
+}
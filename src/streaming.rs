@@ -0,0 +1,277 @@
+use bevy::{
+    gltf::{Gltf, GltfNode},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{
+    ambience::{Biome, CurrentBiome},
+    asset_manifest::AssetManifest,
+    campaign::ActiveLevel,
+    config::GameConfig,
+    damage::Health,
+    destructibles::Breakable,
+    hazards::{spawn_hazard, HazardAssets},
+    pickups::{spawn_pickup, PickupAssets},
+    level_gen::{generate_chunk, ChunkLayout, LevelAssets, LevelSeed, PROP_SCENES},
+    obstacles::Obstacle,
+    state::GameState,
+    weather::Foliage,
+    Game,
+};
+
+// Breakable props take a couple of pumpkins
+const PROP_HEALTH: f32 = 2.;
+// Empties in environment.glb named like this mark where enemies can spawn
+const SPAWN_NODE_PREFIX: &str = "spawn_";
+
+pub struct EnvironmentStreamingPlugin;
+
+impl Plugin for EnvironmentStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnvironmentChunks>()
+            .add_system(load_environment)
+            .add_system(read_authored_spawn_points.after(load_environment))
+            .add_system(stream_environment)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(unload_chunks));
+    }
+}
+
+// Belongs to a chunk without being parented to it, and goes when the chunk does
+#[derive(Component)]
+pub struct ChunkMember(pub i32);
+
+pub struct LoadedChunk {
+    pub entity: Entity,
+    pub layout: ChunkLayout,
+    // Where the environment scene was put, variant and all
+    pub scene_transform: Transform,
+}
+
+#[derive(Resource, Default)]
+pub struct EnvironmentChunks {
+    pub scene: Handle<Scene>,
+    // Chunk 0 sits where the environment was authored, and each one after it is another
+    // `chunk_length` further down -Z
+    pub loaded: HashMap<i32, LoadedChunk>,
+    pub gltf: Handle<Gltf>,
+    // Relative to the environment scene, once it's loaded. Empty if the artists haven't placed
+    // any, in which case each chunk's generated ones are used instead.
+    pub authored_spawn_points: Option<Vec<Vec3>>,
+    // The file `scene` and `gltf` came from
+    pub environment: String,
+}
+
+impl EnvironmentChunks {
+    pub fn spawn_points_between(&self, near_z: f32, far_z: f32) -> Vec<Vec3> {
+        let authored = self
+            .authored_spawn_points
+            .as_ref()
+            .filter(|points| !points.is_empty());
+        self.loaded
+            .values()
+            .flat_map(|chunk| match authored {
+                Some(points) => points
+                    .iter()
+                    .map(|point| chunk.scene_transform.transform_point(*point))
+                    .collect(),
+                None => chunk.layout.spawn_points.clone(),
+            })
+            .filter(|point| point.z <= near_z && point.z >= far_z)
+            .collect()
+    }
+}
+
+// Campaign levels can bring their own environment. Switching throws away whatever was built out
+// of the old one, and the lane gets streamed back in from the new one.
+fn load_environment(
+    mut commands: Commands,
+    mut chunks: ResMut<EnvironmentChunks>,
+    level: Res<ActiveLevel>,
+    asset_server: Res<AssetServer>,
+    members: Query<Entity, With<ChunkMember>>,
+) {
+    let environment = level.environment();
+    if chunks.environment == environment {
+        return;
+    }
+
+    for (_, loaded) in chunks.loaded.drain() {
+        commands.entity(loaded.entity).despawn_recursive();
+    }
+    for entity in &members {
+        commands.entity(entity).despawn_recursive();
+    }
+    chunks.scene = asset_server.load(format!("{environment}#Scene0"));
+    chunks.gltf = asset_server.load(environment);
+    chunks.authored_spawn_points = None;
+    chunks.environment = environment.to_string();
+}
+
+// A node's transform is relative to its parent and it doesn't know what that is, so markers need
+// to sit at the top level. They're dropped onto the ground wherever the artist left them.
+fn read_authored_spawn_points(
+    mut chunks: ResMut<EnvironmentChunks>,
+    gltfs: Res<Assets<Gltf>>,
+    nodes: Res<Assets<GltfNode>>,
+) {
+    if chunks.authored_spawn_points.is_some() {
+        return;
+    }
+    let Some(gltf) = gltfs.get(&chunks.gltf) else { return };
+
+    let mut points = gltf
+        .named_nodes
+        .iter()
+        .filter(|(name, _)| name.starts_with(SPAWN_NODE_PREFIX))
+        .filter_map(|(name, node)| Some((name, nodes.get(node)?.transform.translation)))
+        .map(|(name, translation)| (name.clone(), Vec3::new(translation.x, 0., translation.z)))
+        .collect::<Vec<_>>();
+    // The map's order isn't stable, and spawning shuffles these with the game's RNG
+    points.sort_by(|(a, _), (b, _)| a.cmp(b));
+    info!("{} has {} authored spawn points", chunks.environment, points.len());
+
+    chunks.authored_spawn_points = Some(points.into_iter().map(|(_, point)| point).collect());
+}
+
+pub fn chunk_at(z: f32, chunk_length: f32) -> i32 {
+    (-z / chunk_length).floor() as i32
+}
+
+pub fn biome_for_chunk(chunk: i32, config: &GameConfig) -> Biome {
+    let environment = &config.environment;
+    if environment.biomes.is_empty() {
+        return Biome::default();
+    }
+
+    let stretch = chunk.max(0) as usize / environment.chunks_per_biome.max(1) as usize;
+    environment.biomes[stretch % environment.biomes.len()]
+}
+
+fn stream_environment(
+    mut commands: Commands,
+    mut chunks: ResMut<EnvironmentChunks>,
+    mut current_biome: ResMut<CurrentBiome>,
+    config: Res<GameConfig>,
+    seed: Res<LevelSeed>,
+    level_assets: Res<LevelAssets>,
+    manifest: Res<AssetManifest>,
+    hazard_assets: Res<HazardAssets>,
+    pickup_assets: Res<PickupAssets>,
+    members: Query<(Entity, &ChunkMember)>,
+    game: Res<Game>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Ok(camera) = transforms.get(game.camera) else { return };
+    let environment = &config.environment;
+    let chunk_length = environment.chunk_length.max(1.);
+
+    let current = chunk_at(camera.translation().z, chunk_length);
+    let first = current - environment.chunks_behind as i32;
+    let last = current + environment.chunks_ahead as i32;
+
+    let biome = biome_for_chunk(current, &config);
+    if current_biome.0 != biome {
+        current_biome.0 = biome;
+    }
+
+    chunks.loaded.retain(|chunk, loaded| {
+        let keep = (first..=last).contains(chunk);
+        if !keep {
+            commands.entity(loaded.entity).despawn_recursive();
+        }
+        keep
+    });
+    for (entity, member) in &members {
+        if !(first..=last).contains(&member.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for chunk in first.max(0)..=last {
+        if chunks.loaded.contains_key(&chunk) {
+            continue;
+        }
+
+        let layout = generate_chunk(*seed, chunk, chunk_length);
+        let environment_scene = chunks.scene.clone();
+        let chunk_transform = Transform::from_xyz(0., 0., -chunk as f32 * chunk_length);
+        let scene_transform = chunk_transform.mul_transform(layout.variant.transform());
+        let entity = commands
+            .spawn(SpatialBundle::from_transform(chunk_transform))
+            .with_children(|parent| {
+                parent.spawn(SceneBundle {
+                    scene: environment_scene,
+                    transform: layout.variant.transform(),
+                    ..default()
+                });
+
+                for prop in &layout.props {
+                    let radius = manifest
+                        .scene(PROP_SCENES[prop.scene])
+                        .map_or(prop.radius, |scene| scene.collider_radius);
+                    let rotation = Quat::from_rotation_y(prop.rotation);
+                    let mut entity = parent.spawn((
+                        SceneBundle {
+                            scene: level_assets.props[prop.scene].clone(),
+                            transform: Transform::from_translation(prop.position)
+                                .with_rotation(rotation),
+                            ..default()
+                        },
+                        Obstacle { radius },
+                        // Further down the lane sways a little later, so gusts roll along it
+                        Foliage {
+                            rotation,
+                            phase: prop.position.z,
+                        },
+                    ));
+                    if prop.breakable {
+                        entity.insert((Breakable, Health::new(PROP_HEALTH)));
+                    }
+                }
+
+                for hazard in &layout.hazards {
+                    spawn_hazard(parent, &hazard_assets, hazard.kind, hazard.position);
+                }
+            })
+            .id();
+
+        for pickup in &layout.pickups {
+            let entity = spawn_pickup(&mut commands, &pickup_assets, pickup.kind, pickup.position);
+            commands.entity(entity).insert(ChunkMember(chunk));
+        }
+
+        chunks.loaded.insert(
+            chunk,
+            LoadedChunk {
+                entity,
+                layout,
+                scene_transform,
+            },
+        );
+    }
+}
+
+// A fresh run gets fresh chunks, with everything that was broken or picked up put back
+fn unload_chunks(
+    mut commands: Commands,
+    mut chunks: ResMut<EnvironmentChunks>,
+    members: Query<Entity, With<ChunkMember>>,
+) {
+    clear_chunks(&mut commands, &mut chunks, &members);
+}
+
+// Everything streamed in so far, for when the lane's been changed underneath it. Whatever's in view
+// streams straight back in.
+pub fn clear_chunks(
+    commands: &mut Commands,
+    chunks: &mut EnvironmentChunks,
+    members: &Query<Entity, With<ChunkMember>>,
+) {
+    for (_, loaded) in chunks.loaded.drain() {
+        commands.entity(loaded.entity).despawn_recursive();
+    }
+    for entity in members {
+        commands.entity(entity).despawn_recursive();
+    }
+}
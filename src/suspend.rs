@@ -0,0 +1,324 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    campaign::{ActiveLevel, Levels},
+    daily::Daily,
+    damage::Health,
+    difficulty::Difficulty,
+    input::{InputSource, PlayerInput},
+    level_gen::LevelSeed,
+    netplay::Netplay,
+    objective::Objective,
+    pickups::Currency,
+    score::{Combo, Score},
+    settings::Settings,
+    shop::Upgrades,
+    state::GameState,
+    storage,
+    waves::{advance_waves, WaveManager},
+    weapons::{Loadout, WeaponEquipped},
+    Ammo, AmmoChanged, CameraPacing, CameraRig, Game, GameRng, Player,
+};
+
+const SUSPENDED_RUN_FILE: &str = "run.ron";
+
+pub struct SuspendPlugin;
+
+impl Plugin for SuspendPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedRun::load())
+            .init_resource::<Suspending>()
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(save_and_quit))
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver).with_system(leave_suspended_run),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(finish_suspending)
+                    .with_system(forget_resuming),
+            )
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(continue_run))
+            // Before the wave it's picking up is announced as started
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(resume_run.before(advance_waves)),
+            );
+    }
+}
+
+// Just enough to pick a run back up where it was left. The beets, pickups and anything else
+// lying about aren't kept: the wave it was on starts again from the top.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuspendedRun {
+    pub seed: u64,
+    // The campaign level, by name, or none for the endless lane
+    pub level: Option<String>,
+    pub difficulty: Difficulty,
+    pub character: String,
+    pub wave: u32,
+    pub points: u32,
+    pub kills: u32,
+    pub coins: u32,
+    pub players: Vec<SuspendedPlayer>,
+    pub upgrades: Upgrades,
+    pub loadout: Loadout,
+    pub objective_elapsed: f32,
+    // How far down the lane the camera was
+    pub camera_z: f32,
+    // A fresh seed drawn from the run's randomness, so a resumed run still plays out the same
+    // every time it's resumed
+    pub rng: u64,
+}
+
+// Everyone who was in the run, by their player index
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuspendedPlayer {
+    pub index: usize,
+    pub health: f32,
+    pub max_health: f32,
+    pub ammo: u32,
+    pub position: Vec3,
+}
+
+// What's waiting to be carried on from the main menu, if anything
+#[derive(Resource, Default)]
+pub struct SavedRun(pub Option<SuspendedRun>);
+
+impl SavedRun {
+    fn load() -> Self {
+        Self(storage::data_file(SUSPENDED_RUN_FILE).and_then(|path| storage::load(&path)))
+    }
+
+    fn save(&self) {
+        match storage::data_file(SUSPENDED_RUN_FILE) {
+            Some(path) => storage::save(&path, &self.0),
+            None => warn!("Nowhere to save the run on this platform"),
+        }
+    }
+}
+
+// On the way out to the main menu through the game over screen, which is where everything gets
+// put back for the next run. There's nothing to look back over, so it's skipped.
+#[derive(Resource, Default)]
+pub struct Suspending(pub bool);
+
+// Picked up on the first frame of the run, once everything that sets up a new run has had its go.
+// Player two only turns up once their pad's been seen, so each player's kept until they're back
+// in, or the run's over.
+#[derive(Resource)]
+struct Resuming {
+    run: SuspendedRun,
+    started: bool,
+}
+
+// Reload on the pause menu. Online games can't be put down halfway, recordings aren't anyone's
+// to save and a daily challenge is one go, start to finish.
+fn save_and_quit(
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+    daily: Res<Daily>,
+    mut saved: ResMut<SavedRun>,
+    mut suspending: ResMut<Suspending>,
+    mut state: ResMut<State<GameState>>,
+    run: RunState,
+) {
+    let live = matches!(*source, InputSource::Live) && netplay.is_none();
+    if !input.reload || !live || daily.is_playing() {
+        return;
+    }
+    let Some(suspended) = run.capture() else { return };
+
+    saved.0 = Some(suspended);
+    saved.save();
+    suspending.0 = true;
+    // Leaving the run underneath behind too
+    if let Err(e) = state.replace(GameState::GameOver) {
+        warn!("Unable to save and quit: {e:?}");
+    }
+}
+
+fn leave_suspended_run(suspending: Res<Suspending>, mut state: ResMut<State<GameState>>) {
+    if !suspending.0 {
+        return;
+    }
+    if let Err(e) = state.set(GameState::MainMenu) {
+        warn!("Unable to get back to the main menu: {e:?}");
+    }
+}
+
+fn finish_suspending(mut suspending: ResMut<Suspending>) {
+    suspending.0 = false;
+}
+
+// Anyone who never came back in doesn't get dropped into the next run
+fn forget_resuming(mut commands: Commands) {
+    commands.remove_resource::<Resuming>();
+}
+
+// Pause on the main menu. The save's used up as soon as it's carried on, so a run can't be played
+// from the same spot twice.
+fn continue_run(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    levels: Res<Levels>,
+    mut saved: ResMut<SavedRun>,
+    mut active: ResMut<ActiveLevel>,
+    mut seed: ResMut<LevelSeed>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !input.pause {
+        return;
+    }
+    let Some(run) = &saved.0 else { return };
+
+    // Left where it is if its level's gone, rather than thrown away
+    let level = match &run.level {
+        Some(name) => {
+            let Some(level) = levels.0.iter().find(|level| &level.name == name) else {
+                warn!("The saved run was on {name:?}, which isn't in levels.ron any more");
+                return;
+            };
+            Some(level.clone())
+        }
+        None => None,
+    };
+    let Some(run) = saved.0.take() else { return };
+    saved.save();
+
+    active.0 = level;
+    seed.0 = run.seed;
+    *difficulty = run.difficulty;
+    if settings.character != run.character {
+        settings.character = run.character.clone();
+        settings.save();
+    }
+    commands.insert_resource(Resuming {
+        run,
+        started: false,
+    });
+    if let Err(e) = state.replace(GameState::Playing) {
+        warn!("Unable to carry on the run: {e:?}");
+    }
+}
+
+fn resume_run(
+    mut commands: Commands,
+    resuming: Option<ResMut<Resuming>>,
+    mut run: RunState,
+    mut equipped: EventWriter<WeaponEquipped>,
+    mut ammo_changed: EventWriter<AmmoChanged>,
+) {
+    let Some(mut resuming) = resuming else { return };
+    if !resuming.started {
+        run.restore(&resuming.run);
+        equipped.send(WeaponEquipped {
+            weapon: run.loadout.equipped,
+        });
+        resuming.started = true;
+    }
+
+    resuming.run.players.retain(|saved| {
+        let Some(weapon) = run.restore_player(saved) else { return true };
+        ammo_changed.send(AmmoChanged { weapon });
+        false
+    });
+    if resuming.run.players.is_empty() {
+        commands.remove_resource::<Resuming>();
+    }
+}
+
+// Everything a suspended run is made from, for saving it and putting it back
+#[derive(SystemParam)]
+struct RunState<'w, 's> {
+    game: Res<'w, Game>,
+    seed: Res<'w, LevelSeed>,
+    level: Res<'w, ActiveLevel>,
+    difficulty: Res<'w, Difficulty>,
+    settings: Res<'w, Settings>,
+    waves: ResMut<'w, WaveManager>,
+    score: ResMut<'w, Score>,
+    combo: ResMut<'w, Combo>,
+    currency: ResMut<'w, Currency>,
+    upgrades: ResMut<'w, Upgrades>,
+    loadout: ResMut<'w, Loadout>,
+    objective: ResMut<'w, Objective>,
+    rig: ResMut<'w, CameraRig>,
+    pacing: ResMut<'w, CameraPacing>,
+    rng: ResMut<'w, GameRng>,
+    players: Query<'w, 's, (&'static Player, &'static mut Transform, &'static mut Health)>,
+    weapons: Query<'w, 's, &'static mut Ammo>,
+}
+
+impl<'w, 's> RunState<'w, 's> {
+    fn capture(mut self) -> Option<SuspendedRun> {
+        // Nobody to carry on as without player one
+        self.players.get(self.game.player).ok()?;
+        let players = self
+            .players
+            .iter()
+            .map(|(player, transform, health)| SuspendedPlayer {
+                index: player.index,
+                health: health.current,
+                max_health: health.max,
+                ammo: self
+                    .weapons
+                    .get(player.weapon)
+                    .map_or(0, |ammo| ammo.current),
+                position: transform.translation,
+            })
+            .collect();
+        Some(SuspendedRun {
+            seed: self.seed.0,
+            level: self.level.0.as_ref().map(|level| level.name.clone()),
+            difficulty: *self.difficulty,
+            character: self.settings.character.clone(),
+            wave: self.waves.wave,
+            points: self.score.points,
+            kills: self.score.kills,
+            coins: self.currency.0,
+            players,
+            upgrades: self.upgrades.clone(),
+            loadout: self.loadout.clone(),
+            objective_elapsed: self.objective.elapsed,
+            camera_z: self.rig.scroll.z,
+            rng: self.rng.0.gen(),
+        })
+    }
+
+    fn restore(&mut self, run: &SuspendedRun) {
+        self.waves.resume_at(run.wave);
+        *self.score = Score {
+            points: run.points,
+            kills: run.kills,
+        };
+        *self.combo = Combo::default();
+        self.currency.0 = run.coins;
+        *self.upgrades = run.upgrades.clone();
+        *self.loadout = run.loadout.clone();
+        self.objective.elapsed = run.objective_elapsed;
+        self.rig.scroll.z = run.camera_z;
+        *self.pacing = CameraPacing::default();
+        *self.rng = GameRng::from_seed(run.rng);
+    }
+
+    // Puts one player back as they were, and hands back their weapon. None if they haven't
+    // turned up yet.
+    fn restore_player(&mut self, saved: &SuspendedPlayer) -> Option<Entity> {
+        let (player, mut transform, mut health) = self
+            .players
+            .iter_mut()
+            .find(|(player, _, _)| player.index == saved.index)?;
+        transform.translation = saved.position;
+        health.max = saved.max_health;
+        health.current = saved.health.min(saved.max_health);
+        if let Ok(mut ammo) = self.weapons.get_mut(player.weapon) {
+            ammo.current = saved.ammo.min(ammo.max);
+        }
+        Some(player.weapon)
+    }
+}
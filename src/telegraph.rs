@@ -0,0 +1,173 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use crate::{
+    difficulty::Difficulty, enemy_bundle, reset::DespawnOnReset, state::GameState, EnemyKind, Game,
+};
+
+// How long the ground churns before a beet comes up out of it
+const TELEGRAPH_SECONDS: f32 = 1.;
+// ..and how long it takes to climb all the way out, from this far down
+const EMERGE_SECONDS: f32 = 0.4;
+const EMERGE_DEPTH: f32 = 0.4;
+// How quickly the mound heaves while it's waiting, in radians per second, and by how much
+const HEAVE_SPEED: f32 = 18.;
+const HEAVE_HEIGHT: f32 = 0.15;
+
+pub struct TelegraphPlugin;
+
+impl Plugin for TelegraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_telegraph_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(churn_ground)
+                    .with_system(emerge.after(churn_ground))
+                    .with_system(settle_mounds.after(churn_ground)),
+            );
+    }
+}
+
+// A mound of dirt where a beet's about to come up. Counts as a beet as far as the waves and the
+// spawn rules are concerned, so a wave isn't over while there's still one of these about.
+#[derive(Component)]
+pub struct SpawnTelegraph {
+    pub kind: EnemyKind,
+    timer: Timer,
+}
+
+// Left behind once the beet's up, sinking back into the ground
+#[derive(Component)]
+struct Settling(Timer);
+
+// Still climbing out, and down in the ground where most shots pass over it
+#[derive(Component)]
+pub struct Emerging(Timer);
+
+// Left empty by default, for apps like the test harness that don't draw anything
+#[derive(Resource, Default)]
+pub struct TelegraphAssets {
+    mound_mesh: Handle<Mesh>,
+    mound_material: Handle<StandardMaterial>,
+    decal_mesh: Handle<Mesh>,
+    decal_material: Handle<StandardMaterial>,
+}
+
+fn setup_telegraph_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(TelegraphAssets {
+        mound_mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.18,
+            ..default()
+        })),
+        mound_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.35, 0.24, 0.15),
+            perceptual_roughness: 1.,
+            ..default()
+        }),
+        decal_mesh: meshes.add(Mesh::from(shape::Plane { size: 0.7 })),
+        decal_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.2, 0.13, 0.08, 0.7),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+// In place of spawning the beet straight away
+pub fn spawn_telegraph(
+    commands: &mut Commands,
+    assets: &TelegraphAssets,
+    kind: EnemyKind,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: assets.mound_mesh.clone(),
+                material: assets.mound_material.clone(),
+                transform: Transform::from_translation(position).with_scale(Vec3::ZERO),
+                ..default()
+            },
+            SpawnTelegraph {
+                kind,
+                timer: Timer::from_seconds(TELEGRAPH_SECONDS, TimerMode::Once),
+            },
+            NotShadowCaster,
+            DespawnOnReset,
+        ))
+        .with_children(|mound| {
+            // Just off the ground, so it doesn't flicker against it
+            mound.spawn((
+                PbrBundle {
+                    mesh: assets.decal_mesh.clone(),
+                    material: assets.decal_material.clone(),
+                    transform: Transform::from_xyz(0., 0.01, 0.),
+                    ..default()
+                },
+                NotShadowCaster,
+            ));
+        })
+        .id()
+}
+
+// The mound grows and heaves until it's time, then the beet comes up through it
+fn churn_ground(
+    mut commands: Commands,
+    game: Res<Game>,
+    difficulty: Res<Difficulty>,
+    time: Res<Time>,
+    mut telegraphs: Query<(Entity, &mut Transform, &mut SpawnTelegraph)>,
+) {
+    for (entity, mut transform, mut telegraph) in &mut telegraphs {
+        let heave = (telegraph.timer.elapsed_secs() * HEAVE_SPEED).sin() * HEAVE_HEIGHT;
+        let grown = telegraph.timer.percent();
+        transform.scale = Vec3::new(1., 0.4 + heave, 1.) * grown;
+        if !telegraph.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let translation = transform.translation - Vec3::Y * EMERGE_DEPTH;
+        commands.spawn((
+            enemy_bundle(&game, *difficulty, telegraph.kind, translation),
+            Emerging(Timer::from_seconds(EMERGE_SECONDS, TimerMode::Once)),
+        ));
+        let settling = Settling(Timer::from_seconds(EMERGE_SECONDS, TimerMode::Once));
+        commands
+            .entity(entity)
+            .remove::<SpawnTelegraph>()
+            .insert(settling);
+    }
+}
+
+// Only the height's touched, so the chasing can get going while it climbs
+fn emerge(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut beets: Query<(Entity, &mut Transform, &mut Emerging)>,
+) {
+    for (entity, mut transform, mut emerging) in &mut beets {
+        emerging.0.tick(time.delta());
+        transform.translation.y = -EMERGE_DEPTH * emerging.0.percent_left();
+        if emerging.0.finished() {
+            commands.entity(entity).remove::<Emerging>();
+        }
+    }
+}
+
+fn settle_mounds(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut mounds: Query<(Entity, &mut Transform, &mut Settling)>,
+) {
+    for (entity, mut transform, mut settling) in &mut mounds {
+        if settling.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.scale = Vec3::new(1., 0.4, 1.) * settling.0.percent_left();
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    damage::DamageDealt,
+    difficulty::Difficulty,
+    level_gen::LevelSeed,
+    state::GameState,
+    waves::{WaveCleared, WaveManager, WaveStarted},
+    Enemy, EnemyKilled, EnemyKind, Player,
+};
+
+// Where each player is gets written down this often, in seconds of play
+const POSITION_SAMPLE_SECONDS: f32 = 0.5;
+
+// Writes what happens in each run to its own file in `dir`, one JSON object per line, for working
+// out balance problems from lots of runs rather than a hunch
+pub struct TelemetryPlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Telemetry {
+            dir: self.dir.clone(),
+            file: None,
+            runs: 0,
+            elapsed: 0.,
+            since_sample: 0.,
+        })
+        .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_log))
+        .add_system_set(SystemSet::on_update(GameState::Playing).with_system(sample_positions))
+        // Outside of `Playing`, so the hit that ends the run is still written down
+        .add_system(log_events)
+        .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(end_log))
+        .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(close_log));
+    }
+}
+
+#[derive(Resource)]
+struct Telemetry {
+    dir: PathBuf,
+    // Open from the start of a run until it's been retried or left
+    file: Option<BufWriter<File>>,
+    runs: u32,
+    // Seconds of play since the run started, not counting pauses
+    elapsed: f32,
+    since_sample: f32,
+}
+
+impl Telemetry {
+    fn write(&mut self, event: TelemetryEvent) {
+        let Some(file) = &mut self.file else { return };
+        let line = TelemetryLine {
+            time: self.elapsed,
+            event,
+        };
+        let result = serde_json::to_writer(&mut *file, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|_| file.write_all(b"\n"));
+        if let Err(e) = result {
+            warn!("Unable to write telemetry, giving up on this run: {e}");
+            self.file = None;
+        }
+    }
+
+    fn flush(&mut self) {
+        let Some(file) = &mut self.file else { return };
+        if let Err(e) = file.flush() {
+            warn!("Unable to write telemetry, giving up on this run: {e}");
+            self.file = None;
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TelemetryLine {
+    time: f32,
+    #[serde(flatten)]
+    event: TelemetryEvent,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum TelemetryEvent {
+    RunStarted {
+        seed: u64,
+        difficulty: String,
+        wave: u32,
+    },
+    WaveStarted {
+        wave: u32,
+    },
+    WaveCleared {
+        wave: u32,
+    },
+    Spawn {
+        enemy: u32,
+        kind: EnemyKind,
+        position: [f32; 3],
+    },
+    // Anything hurting anything, players included
+    Damage {
+        source: Option<u32>,
+        target: u32,
+        player: bool,
+        amount: f32,
+        critical: bool,
+        killed: bool,
+    },
+    Death {
+        enemy: u32,
+        kind: EnemyKind,
+        position: [f32; 3],
+    },
+    PlayerPosition {
+        player: usize,
+        position: [f32; 3],
+    },
+    RunEnded {
+        wave: u32,
+    },
+}
+
+// Every run gets a fresh file, named for when it started so they sort in order
+fn start_log(
+    mut telemetry: ResMut<Telemetry>,
+    seed: Res<LevelSeed>,
+    difficulty: Res<Difficulty>,
+    waves: Res<WaveManager>,
+) {
+    if telemetry.file.is_some() {
+        return;
+    }
+
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = telemetry
+        .dir
+        .join(format!("run-{started}-{}.jsonl", telemetry.runs));
+    let file = std::fs::create_dir_all(&telemetry.dir).and_then(|_| File::create(&path));
+    match file {
+        Ok(file) => {
+            info!("Logging this run to {path:?}");
+            telemetry.file = Some(BufWriter::new(file));
+        }
+        Err(e) => {
+            warn!("Unable to create {path:?}, this run won't be logged: {e}");
+            return;
+        }
+    }
+    telemetry.runs += 1;
+    telemetry.elapsed = 0.;
+    telemetry.since_sample = 0.;
+    telemetry.write(TelemetryEvent::RunStarted {
+        seed: seed.0,
+        difficulty: format!("{:?}", *difficulty),
+        wave: waves.wave,
+    });
+}
+
+// Also keeps the clock, which stops while paused. Flushes as it goes, since closing the window
+// doesn't give anything a chance to tidy up.
+fn sample_positions(
+    mut telemetry: ResMut<Telemetry>,
+    time: Res<Time>,
+    players: Query<(&Player, &GlobalTransform)>,
+) {
+    telemetry.elapsed += time.delta_seconds();
+    telemetry.since_sample += time.delta_seconds();
+    if telemetry.since_sample < POSITION_SAMPLE_SECONDS {
+        return;
+    }
+    telemetry.since_sample = 0.;
+
+    for (player, transform) in &players {
+        telemetry.write(TelemetryEvent::PlayerPosition {
+            player: player.index,
+            position: transform.translation().to_array(),
+        });
+    }
+    telemetry.flush();
+}
+
+fn log_events(
+    mut telemetry: ResMut<Telemetry>,
+    mut started: EventReader<WaveStarted>,
+    mut cleared: EventReader<WaveCleared>,
+    mut dealt: EventReader<DamageDealt>,
+    mut killed: EventReader<EnemyKilled>,
+    spawned: Query<(Entity, &EnemyKind, &Transform), Added<Enemy>>,
+    players: Query<(), With<Player>>,
+) {
+    if telemetry.file.is_none() {
+        return;
+    }
+
+    for wave in started.iter() {
+        telemetry.write(TelemetryEvent::WaveStarted { wave: wave.wave });
+    }
+    for (entity, kind, transform) in &spawned {
+        telemetry.write(TelemetryEvent::Spawn {
+            enemy: entity.index(),
+            kind: *kind,
+            position: transform.translation.to_array(),
+        });
+    }
+    for damage in dealt.iter() {
+        telemetry.write(TelemetryEvent::Damage {
+            source: damage.source.map(Entity::index),
+            target: damage.target.index(),
+            player: players.contains(damage.target),
+            amount: damage.amount,
+            critical: damage.critical,
+            killed: damage.killed,
+        });
+    }
+    for kill in killed.iter() {
+        telemetry.write(TelemetryEvent::Death {
+            enemy: kill.entity.index(),
+            kind: kill.kind,
+            position: kill.position.to_array(),
+        });
+    }
+    for wave in cleared.iter() {
+        telemetry.write(TelemetryEvent::WaveCleared { wave: wave.wave });
+    }
+}
+
+fn end_log(mut telemetry: ResMut<Telemetry>, waves: Res<WaveManager>) {
+    telemetry.write(TelemetryEvent::RunEnded { wave: waves.wave });
+    telemetry.flush();
+}
+
+fn close_log(mut telemetry: ResMut<Telemetry>) {
+    telemetry.flush();
+    telemetry.file = None;
+}
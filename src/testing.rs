@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use bevy::{ecs::event::Event, prelude::*, time::TimeSystem};
+
+use crate::{
+    accessibility::Palette,
+    campaign::ActiveLevel,
+    characters::Characters,
+    config::GameConfig,
+    damage::DamagePlugin,
+    difficulty::{Difficulty, DifficultyPlugin},
+    dodge::DodgePlugin,
+    enemy_bundle, golden,
+    graze::Grazed,
+    hurt::HurtPlugin,
+    input::{InputSource, PlayerInput},
+    intermission::WaveModifier,
+    level_gen::LevelSeed,
+    mutators::Mutators,
+    objective::Objective,
+    obstacles::ObstaclePlugin,
+    settings::Settings,
+    shield::ShieldStruck,
+    shop::Upgrades,
+    sprint::SprintPlugin,
+    state::{GameState, GameStatePlugin},
+    status::ApplyStatus,
+    streaming::EnvironmentChunks,
+    telegraph::TelegraphAssets,
+    waves::WavePlugin,
+    weapons::WeaponPlugin,
+    Aim, EnemyKind, EnemySpawnTimer, Game, GameRng, GameplayPlugin, Projectile,
+};
+
+// Just the gameplay: no window, no rendering, no level streaming and no menus, with the clock
+// stepped by a fixed amount each tick. Tests spawn what they need exactly where they need it, set
+// player one's input directly and tick until something should have happened.
+//
+//     let mut test = GameplayTest::new();
+//     let enemy = test.spawn_enemy(Vec3::new(0., 0., -1.));
+//     test.spawn_projectile(Vec3::new(0., 0., -0.5), Vec3::NEG_Z, 1.);
+//     test.ticks(20);
+//     assert!(!test.exists(enemy));
+pub struct GameplayTest {
+    pub app: App,
+}
+
+impl GameplayTest {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .insert_resource(GameConfig::default())
+            .insert_resource(Settings::default())
+            .insert_resource(LevelSeed(seed))
+            .insert_resource(GameRng::from_seed(seed))
+            .init_resource::<PlayerInput>()
+            .init_resource::<InputSource>()
+            .init_resource::<Characters>()
+            .init_resource::<EnvironmentChunks>()
+            .init_resource::<Upgrades>()
+            .init_resource::<WaveModifier>()
+            .init_resource::<Objective>()
+            .init_resource::<ActiveLevel>()
+            .init_resource::<TelegraphAssets>()
+            .init_resource::<Mutators>()
+            // The HUD bars and damage indicators go by these
+            .init_resource::<Palette>()
+            .init_resource::<UiScale>()
+            // Shots and swings send these whether or not anything's listening
+            .add_event::<ApplyStatus>()
+            .add_event::<ShieldStruck>()
+            .add_event::<Grazed>()
+            .add_plugin(GameStatePlugin {
+                initial: GameState::Playing,
+            })
+            .add_plugin(DamagePlugin)
+            .add_plugin(DifficultyPlugin)
+            .add_plugin(DodgePlugin)
+            .add_plugin(HurtPlugin)
+            .add_plugin(SprintPlugin)
+            .add_plugin(ObstaclePlugin)
+            .add_plugin(WavePlugin)
+            .add_plugin(WeaponPlugin)
+            .add_plugin(GameplayPlugin)
+            .add_system_to_stage(CoreStage::First, golden::fixed_frame_time.after(TimeSystem));
+
+        // Startup systems spawn the camera and player one
+        app.update();
+        Self { app }
+    }
+
+    // Nothing spawns on its own unless a test asks for it
+    pub fn without_enemy_spawns(mut self) -> Self {
+        self.app.world.resource_mut::<EnemySpawnTimer>().0.pause();
+        self
+    }
+
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.app.insert_resource(difficulty);
+        self
+    }
+
+    pub fn tick(&mut self) {
+        self.app.update();
+    }
+
+    pub fn ticks(&mut self, count: u32) {
+        for _ in 0..count {
+            self.tick();
+        }
+    }
+
+    // Ticks until `done` says so, giving up after `limit` ticks. Returns whether it happened.
+    pub fn tick_until(&mut self, limit: u32, mut done: impl FnMut(&mut World) -> bool) -> bool {
+        for _ in 0..limit {
+            self.tick();
+            if done(&mut self.app.world) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.app.world.resource::<Time>().elapsed()
+    }
+
+    pub fn world(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    pub fn player(&self) -> Entity {
+        self.app.world.resource::<Game>().player
+    }
+
+    pub fn spud_gun(&self) -> Entity {
+        self.app.world.resource::<Game>().spud_gun
+    }
+
+    // Stays set until it's changed, as if the stick were held there
+    pub fn set_input(&mut self, input: PlayerInput) {
+        let player = self.player();
+        self.app.world.entity_mut(player).insert(input);
+        *self.app.world.resource_mut::<PlayerInput>() = input;
+    }
+
+    pub fn place_player(&mut self, translation: Vec3) {
+        let player = self.player();
+        if let Some(mut transform) = self.app.world.get_mut::<Transform>(player) {
+            transform.translation = translation;
+        }
+    }
+
+    pub fn aim_target(&self) -> Option<Entity> {
+        self.app.world.get::<Aim>(self.player())?.target
+    }
+
+    pub fn spawn_enemy(&mut self, translation: Vec3) -> Entity {
+        let game = self.app.world.resource::<Game>();
+        let difficulty = *self.app.world.resource::<Difficulty>();
+        let bundle = enemy_bundle(game, difficulty, EnemyKind::Beet, translation);
+        self.app.world.spawn(bundle).id()
+    }
+
+    pub fn spawn_projectile(&mut self, translation: Vec3, heading: Vec3, damage: f32) -> Entity {
+        self.app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_translation(translation)),
+                Projectile {
+                    heading: heading.normalize_or_zero(),
+                    damage,
+                    critical: false,
+                    blast_radius: 0.,
+                    last_hit: None,
+                },
+            ))
+            .id()
+    }
+
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world.get_entity(entity).is_some()
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world.get::<T>(entity)
+    }
+
+    pub fn count<T: Component>(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<T>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world.resource::<State<GameState>>().current()
+    }
+
+    // Picked up by whatever reads it on the next tick
+    pub fn send_event<E: Event>(&mut self, event: E) {
+        self.app.world.resource_mut::<Events<E>>().send(event);
+    }
+
+    // Every event of this kind that's still around. Events only last a couple of updates, so drain
+    // them straight after the tick that should have sent them.
+    pub fn drain_events<E: Event>(&mut self) -> Vec<E> {
+        self.app
+            .world
+            .resource_mut::<Events<E>>()
+            .drain()
+            .collect()
+    }
+}
+
+impl Default for GameplayTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
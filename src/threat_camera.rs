@@ -0,0 +1,63 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::prelude::*;
+
+use crate::{camera_movement, state::GameState, CameraRig, Enemy, Game};
+
+// Up to this many beets about and the camera stays where it's always been. It's all the way out by
+// the time there are this many.
+const CALM_ENEMIES: usize = 4;
+const CROWDED_ENEMIES: usize = 20;
+// All the way out, it's this much further back and up, and sees this much more round the sides
+const MAX_PULL_BACK: f32 = 1.2;
+const MAX_RAISE: f32 = 0.8;
+const MAX_EXTRA_FOV: f32 = 0.15;
+// Bevy's own, which is what the camera starts with
+const BASE_FOV: f32 = FRAC_PI_4;
+// Slower than the co-op framing, so a beet or two coming and going doesn't have it bobbing about
+const THREAT_FRAMING_RATE: f32 = 0.8;
+
+pub struct ThreatCameraPlugin;
+
+impl Plugin for ThreatCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(frame_threat.before(camera_movement)),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_threat_fov));
+    }
+}
+
+// Pulls the camera back, up and wider the more beets there are, so a big wave's all in shot, and
+// eases it back in once they've been dealt with. Everything that's about is part of the wave
+// being faced, since anything that falls too far behind gets sent back up the lane.
+fn frame_threat(
+    mut rig: ResMut<CameraRig>,
+    time: Res<Time>,
+    game: Res<Game>,
+    enemies: Query<(), With<Enemy>>,
+    mut cameras: Query<(&Transform, &mut Projection)>,
+) {
+    let Ok((camera, mut projection)) = cameras.get_mut(game.camera) else { return };
+    let crowd = enemies.iter().count().saturating_sub(CALM_ENEMIES) as f32;
+    let threat = (crowd / (CROWDED_ENEMIES - CALM_ENEMIES) as f32).min(1.);
+    let eased = threat * threat * (3. - 2. * threat);
+
+    let target = camera.back() * MAX_PULL_BACK * eased + Vec3::Y * MAX_RAISE * eased;
+    let catch_up = (time.delta_seconds() * THREAT_FRAMING_RATE).min(1.);
+    rig.threat = rig.threat.lerp(target, catch_up);
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        let fov = BASE_FOV + MAX_EXTRA_FOV * eased;
+        perspective.fov += (fov - perspective.fov) * catch_up;
+    }
+}
+
+// The rig goes back with the rest of the world, and the lens has to go with it
+fn reset_threat_fov(game: Res<Game>, mut cameras: Query<&mut Projection>) {
+    let Ok(mut projection) = cameras.get_mut(game.camera) else { return };
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = BASE_FOV;
+    }
+}
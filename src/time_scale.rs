@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{input::InputSource, netplay::Netplay, settings::Settings, state::GameState};
+
+// The slowest the game speed setting goes
+pub const MIN_GAME_SPEED: f32 = 0.7;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .add_system_to_stage(CoreStage::PreUpdate, apply_time_scale)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_time_scale));
+    }
+}
+
+// How fast the game runs, fed into `Time::relative_speed` every frame along with the game speed
+// setting. Anything driven by frame counts rather than the clock should multiply its step by
+// `Time::relative_speed` to keep up.
+#[derive(Resource)]
+pub struct TimeScale {
+    // Whatever the console's set it to
+    pub base: f32,
+    dilation: Option<Dilation>,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self {
+            base: 1.,
+            dilation: None,
+        }
+    }
+}
+
+// A slowdown that holds for a while then eases back to normal, timed in real seconds so it isn't
+// stretched out by itself
+struct Dilation {
+    scale: f32,
+    hold: f32,
+    ramp: f32,
+    elapsed: f32,
+}
+
+impl Dilation {
+    fn current(&self) -> f32 {
+        let ramped = (self.elapsed - self.hold) / self.ramp.max(f32::EPSILON);
+        self.scale + (1. - self.scale) * ramped.clamp(0., 1.)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.hold + self.ramp
+    }
+}
+
+impl TimeScale {
+    // Drop to `scale` straight away, stay there for `hold` seconds and take `ramp` more to get
+    // back up to speed. Replaces any slowdown that's already going.
+    pub fn dilate(&mut self, scale: f32, hold: f32, ramp: f32) {
+        self.dilation = Some(Dilation {
+            scale: scale.max(0.),
+            hold,
+            ramp,
+            elapsed: 0.,
+        });
+    }
+
+    // From 0 back at normal speed to 1 at the bottom of the slowdown
+    pub fn dilation_weight(&self) -> f32 {
+        self.dilation.as_ref().map_or(0., |dilation| {
+            (1. - dilation.current()) / (1. - dilation.scale).max(f32::EPSILON)
+        })
+    }
+
+    pub fn current(&self) -> f32 {
+        self.base * self.dilation.as_ref().map_or(1., Dilation::current)
+    }
+}
+
+fn apply_time_scale(
+    mut scale: ResMut<TimeScale>,
+    mut time: ResMut<Time>,
+    settings: Res<Settings>,
+    source: Res<InputSource>,
+    netplay: Option<Res<Netplay>>,
+) {
+    if let Some(dilation) = scale.dilation.as_mut() {
+        dilation.elapsed += time.raw_delta_seconds();
+        if dilation.finished() {
+            scale.dilation = None;
+        }
+    }
+
+    // Recordings and online games keep to full speed, so the run plays out the same everywhere
+    let game_speed = if matches!(*source, InputSource::Live) && netplay.is_none() {
+        settings.game_speed.clamp(MIN_GAME_SPEED, 1.)
+    } else {
+        1.
+    };
+    let current = scale.current() * game_speed;
+    if time.relative_speed() != current {
+        time.set_relative_speed(current);
+    }
+}
+
+// A run that ended in slow motion doesn't start the next one that way. The console's setting stays.
+fn reset_time_scale(mut scale: ResMut<TimeScale>) {
+    scale.dilation = None;
+}
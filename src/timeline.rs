@@ -0,0 +1,352 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::DamageDealt, input::PlayerInput, state::GameState, suspend::Suspending,
+    waves::WaveCleared, Enemy, EnemyKilled, Game,
+};
+
+// Ten snapshots a second is plenty to scrub through and cheap enough to keep for a whole run
+const SAMPLE_SECONDS: f32 = 0.1;
+// This many kills inside the window counts as a highlight
+const BIG_COMBO_KILLS: usize = 3;
+const BIG_COMBO_SECONDS: f32 = 1.;
+// Snapshots per second of holding the stick all the way over
+const SCRUB_SPEED: f32 = 30.;
+
+pub struct RunTimelinePlugin;
+
+impl Plugin for RunTimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunTimeline>()
+            .init_resource::<Scrubber>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(record_frames)
+                    .with_system(record_moments),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(start_review)
+                    .with_system(spawn_timeline_ui),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(scrub)
+                    .with_system(show_frame.after(scrub))
+                    .with_system(update_timeline_ui.after(scrub)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(end_review));
+    }
+}
+
+// Where everything was at one point in the run
+pub struct TimelineFrame {
+    pub time: f32,
+    pub camera: Transform,
+    pub player: Transform,
+    pub enemies: Vec<Vec3>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MomentKind {
+    WaveCleared(u32),
+    PlayerHurt,
+    PlayerDied,
+    BigCombo(usize),
+}
+
+impl MomentKind {
+    fn color(&self) -> Color {
+        match self {
+            MomentKind::WaveCleared(_) => Color::GREEN,
+            MomentKind::PlayerHurt => Color::ORANGE,
+            MomentKind::PlayerDied => Color::RED,
+            MomentKind::BigCombo(_) => Color::GOLD,
+        }
+    }
+}
+
+pub struct KeyMoment {
+    // Index into `RunTimeline::frames`
+    pub frame: usize,
+    pub kind: MomentKind,
+}
+
+// A rough recording of the run for looking back over once it's finished. Unlike an input recording
+// it can be jumped around in freely, since nothing has to be re-simulated.
+#[derive(Resource, Default)]
+pub struct RunTimeline {
+    pub frames: Vec<TimelineFrame>,
+    pub moments: Vec<KeyMoment>,
+    elapsed: f32,
+    since_sample: f32,
+    recent_kills: Vec<f32>,
+}
+
+impl RunTimeline {
+    fn mark(&mut self, kind: MomentKind) {
+        let frame = self.frames.len().saturating_sub(1);
+        self.moments.push(KeyMoment { frame, kind });
+    }
+}
+
+#[derive(Resource, Default)]
+struct Scrubber {
+    position: f32,
+    ghosts: Vec<Entity>,
+}
+
+#[derive(Component)]
+struct TimelineUi;
+
+#[derive(Component)]
+struct TimelineCursor;
+
+fn record_frames(
+    mut timeline: ResMut<RunTimeline>,
+    time: Res<Time>,
+    game: Res<Game>,
+    transforms: Query<&Transform>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+) {
+    timeline.elapsed += time.delta_seconds();
+    timeline.since_sample += time.delta_seconds();
+    if !timeline.frames.is_empty() && timeline.since_sample < SAMPLE_SECONDS {
+        return;
+    }
+    timeline.since_sample = 0.;
+
+    let (Ok(camera), Ok(player)) = (transforms.get(game.camera), transforms.get(game.player)) else {
+        return;
+    };
+    let frame = TimelineFrame {
+        time: timeline.elapsed,
+        camera: *camera,
+        player: *player,
+        enemies: enemies.iter().map(|t| t.translation()).collect(),
+    };
+    timeline.frames.push(frame);
+}
+
+fn record_moments(
+    mut timeline: ResMut<RunTimeline>,
+    mut cleared: EventReader<WaveCleared>,
+    mut killed: EventReader<EnemyKilled>,
+    mut dealt: EventReader<DamageDealt>,
+    game: Res<Game>,
+) {
+    for wave in cleared.iter() {
+        timeline.mark(MomentKind::WaveCleared(wave.wave));
+    }
+
+    for damage in dealt.iter().filter(|damage| damage.target == game.player) {
+        let kind = if damage.killed {
+            MomentKind::PlayerDied
+        } else {
+            MomentKind::PlayerHurt
+        };
+        timeline.mark(kind);
+    }
+
+    let now = timeline.elapsed;
+    let kills = killed.iter().count();
+    if kills == 0 {
+        return;
+    }
+    timeline.recent_kills.extend(std::iter::repeat(now).take(kills));
+    timeline
+        .recent_kills
+        .retain(|kill| now - kill <= BIG_COMBO_SECONDS);
+    if timeline.recent_kills.len() >= BIG_COMBO_KILLS {
+        let combo = timeline.recent_kills.len();
+        timeline.recent_kills.clear();
+        timeline.mark(MomentKind::BigCombo(combo));
+    }
+}
+
+// The live enemies are frozen where they were, so hide them and let ghosts stand in
+fn start_review(
+    timeline: Res<RunTimeline>,
+    mut scrubber: ResMut<Scrubber>,
+    mut enemies: Query<&mut Visibility, With<Enemy>>,
+    suspending: Res<Suspending>,
+) {
+    if suspending.0 {
+        return;
+    }
+    scrubber.position = timeline.frames.len().saturating_sub(1) as f32;
+    for mut visibility in &mut enemies {
+        visibility.is_visible = false;
+    }
+}
+
+// The stick scrubs, fire jumps to the next key moment
+fn scrub(
+    input: Res<PlayerInput>,
+    timeline: Res<RunTimeline>,
+    mut scrubber: ResMut<Scrubber>,
+    time: Res<Time>,
+) {
+    let last = timeline.frames.len().saturating_sub(1) as f32;
+    let current = scrubber.position.round() as usize;
+
+    if input.fire {
+        let next = timeline
+            .moments
+            .iter()
+            .find(|moment| moment.frame > current)
+            .or_else(|| timeline.moments.first());
+        if let Some(moment) = next {
+            scrubber.position = moment.frame as f32;
+        }
+    }
+
+    let scrub = input.movement.x + input.aim;
+    scrubber.position =
+        (scrubber.position + scrub * SCRUB_SPEED * time.delta_seconds()).clamp(0., last);
+}
+
+fn show_frame(
+    mut commands: Commands,
+    timeline: Res<RunTimeline>,
+    mut scrubber: ResMut<Scrubber>,
+    game: Res<Game>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let Some(frame) = timeline.frames.get(scrubber.position.round() as usize) else { return };
+
+    if let Ok(mut camera) = transforms.get_mut(game.camera) {
+        *camera = frame.camera;
+    }
+    if let Ok(mut player) = transforms.get_mut(game.player) {
+        *player = frame.player;
+    }
+
+    while scrubber.ghosts.len() < frame.enemies.len() {
+        let ghost = commands
+            .spawn(SceneBundle {
+                scene: game.enemies[0].clone(),
+                ..default()
+            })
+            .id();
+        scrubber.ghosts.push(ghost);
+    }
+
+    for (i, ghost) in scrubber.ghosts.iter().enumerate() {
+        let Ok(mut transform) = transforms.get_mut(*ghost) else { continue };
+        match frame.enemies.get(i) {
+            Some(position) => transform.translation = *position,
+            // Spare ghosts wait out of sight
+            None => transform.translation = Vec3::NEG_Y * 100.,
+        }
+    }
+}
+
+fn spawn_timeline_ui(
+    mut commands: Commands,
+    timeline: Res<RunTimeline>,
+    asset_server: Res<AssetServer>,
+    suspending: Res<Suspending>,
+) {
+    if suspending.0 {
+        return;
+    }
+    let frame_count = timeline.frames.len().max(1) as f32;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(10.),
+                        bottom: Val::Px(40.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(80.), Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(1., 1., 1., 0.3).into(),
+                ..default()
+            },
+            TimelineUi,
+        ))
+        .with_children(|bar| {
+            for moment in &timeline.moments {
+                bar.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Percent(moment.frame as f32 / frame_count * 100.),
+                            bottom: Val::Px(-4.),
+                            ..default()
+                        },
+                        size: Size::new(Val::Px(4.), Val::Px(18.)),
+                        ..default()
+                    },
+                    background_color: moment.kind.color().into(),
+                    ..default()
+                });
+            }
+
+            bar.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Px(2.), Val::Px(26.)),
+                        position: UiRect {
+                            bottom: Val::Px(-8.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    ..default()
+                },
+                TimelineCursor,
+            ));
+
+            bar.spawn(TextBundle {
+                text: Text::from_section(
+                    "Move to scrub through the run, fire to jump to the next highlight",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 20.,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(20.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            });
+        });
+}
+
+fn update_timeline_ui(
+    timeline: Res<RunTimeline>,
+    scrubber: Res<Scrubber>,
+    mut cursors: Query<&mut Style, With<TimelineCursor>>,
+) {
+    let frame_count = timeline.frames.len().max(1) as f32;
+    for mut style in &mut cursors {
+        style.position.left = Val::Percent(scrubber.position / frame_count * 100.);
+    }
+}
+
+fn end_review(
+    mut commands: Commands,
+    mut timeline: ResMut<RunTimeline>,
+    mut scrubber: ResMut<Scrubber>,
+    ui: Query<Entity, With<TimelineUi>>,
+) {
+    for entity in scrubber.ghosts.drain(..).chain(ui.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    *timeline = RunTimeline::default();
+    scrubber.position = 0.;
+}
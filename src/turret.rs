@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::PlayerInput, nearest_enemy, reset::DespawnOnReset, state::GameState, Enemy, Game,
+    Player, Projectile, WeaponFired,
+};
+
+// How many each player gets per run
+const TURRET_CHARGES: u32 = 2;
+const TURRET_SECONDS: f32 = 12.;
+const TURRET_FIRE_SECONDS: f32 = 0.6;
+// It won't waste pumpkins on anything further off than this
+const TURRET_RANGE: f32 = 3.;
+const TURRET_DAMAGE: f32 = 0.5;
+// Planted just ahead of whoever put it down, up at about gun height
+const TURRET_OFFSET: Vec3 = Vec3::new(0., 0.25, -0.3);
+const TURRET_SCALE: f32 = 1.5;
+
+pub struct TurretPlugin;
+
+impl Plugin for TurretPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(deploy_turrets)
+                .with_system(fire_turrets)
+                .with_system(expire_turrets),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_turrets));
+    }
+}
+
+// Every player has one, filled up at the start of each run
+#[derive(Component)]
+pub struct TurretCharges(pub u32);
+
+impl Default for TurretCharges {
+    fn default() -> Self {
+        Self(TURRET_CHARGES)
+    }
+}
+
+// A scarecrow with a spud gun of its own. It picks its targets the same way nearest-enemy aiming
+// does, and packs up when its time's up.
+#[derive(Component)]
+struct Turret {
+    remaining: f32,
+    cooldown: Timer,
+}
+
+fn deploy_turrets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut players: Query<(&PlayerInput, &GlobalTransform, &mut TurretCharges), With<Player>>,
+) {
+    for (input, transform, mut charges) in &mut players {
+        if !input.deploy || charges.0 == 0 {
+            continue;
+        }
+        charges.0 -= 1;
+
+        commands.spawn((
+            SceneBundle {
+                scene: asset_server.load("launcher.glb#Scene0"),
+                transform: Transform::from_translation(transform.translation() + TURRET_OFFSET)
+                    .with_scale(Vec3::splat(TURRET_SCALE)),
+                ..default()
+            },
+            Turret {
+                remaining: TURRET_SECONDS,
+                cooldown: Timer::from_seconds(TURRET_FIRE_SECONDS, TimerMode::Repeating),
+            },
+            DespawnOnReset,
+        ));
+    }
+}
+
+// Its shots go through the same hit detection and damage as everyone else's
+fn fire_turrets(
+    mut commands: Commands,
+    game: Res<Game>,
+    time: Res<Time>,
+    mut turrets: Query<(Entity, &mut Transform, &mut Turret)>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    mut fired: EventWriter<WeaponFired>,
+) {
+    let Some(projectile_asset) = &game.projectile else { return };
+
+    for (entity, mut transform, mut turret) in &mut turrets {
+        let origin = transform.translation;
+        let Some(target) = nearest_enemy(origin, &enemies, TURRET_RANGE) else { continue };
+        let Ok((_, target)) = enemies.get(target) else { continue };
+        let heading = (target.translation() - origin).normalize_or_zero();
+        transform.look_at(origin + Vec3::new(heading.x, 0., heading.z), Vec3::Y);
+
+        if !turret.cooldown.tick(time.delta()).just_finished() {
+            continue;
+        }
+        commands.spawn((
+            SceneBundle {
+                scene: projectile_asset.clone(),
+                transform: Transform::from_translation(origin),
+                ..default()
+            },
+            Projectile {
+                heading,
+                damage: TURRET_DAMAGE,
+                critical: false,
+                blast_radius: 0.,
+                last_hit: None,
+            },
+            DespawnOnReset,
+        ));
+        fired.send(WeaponFired { weapon: entity });
+    }
+}
+
+fn expire_turrets(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut turrets: Query<(Entity, &mut Turret)>,
+) {
+    for (entity, mut turret) in &mut turrets {
+        turret.remaining -= time.delta_seconds();
+        if turret.remaining <= 0. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn reset_turrets(mut players: Query<&mut TurretCharges>) {
+    for mut charges in &mut players {
+        *charges = TurretCharges::default();
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{ActiveDevice, InputAction, InputSource, PlayerInput},
+    localization::Localization,
+    settings::Settings,
+    state::GameState,
+    Aim, Enemy, Game,
+};
+
+const PROMPT_MARGIN: f32 = 120.;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_tutorial_prompt)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(dismiss_prompts),
+            )
+            .add_system(update_tutorial_prompt.after(dismiss_prompts));
+    }
+}
+
+// Each one's shown once it's relevant and stays up until it's been done, then never again
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TutorialPrompt {
+    Move,
+    // Flicking the right stick to lock on to a beet
+    Aim,
+    Fire,
+}
+
+impl TutorialPrompt {
+    pub const ALL: [TutorialPrompt; 3] =
+        [TutorialPrompt::Move, TutorialPrompt::Aim, TutorialPrompt::Fire];
+
+    // Only ever for whatever the player's holding
+    fn text(
+        &self,
+        settings: &Settings,
+        device: ActiveDevice,
+        localization: &Localization,
+    ) -> String {
+        let prompt = |action| settings.bindings.prompt(action, device);
+        match self {
+            TutorialPrompt::Move => localization.variant("tutorial-move", &device),
+            TutorialPrompt::Aim => localization
+                .variant("tutorial-aim", &device)
+                .replace("{left}", &prompt(InputAction::AimLeft))
+                .replace("{right}", &prompt(InputAction::AimRight)),
+            TutorialPrompt::Fire => {
+                localization.format("tutorial-fire", &[("button", &prompt(InputAction::Fire))])
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct TutorialText;
+
+// The first prompt that hasn't been done yet, if there's anything for it to be about
+fn current_prompt(settings: &Settings, enemies_about: bool) -> Option<TutorialPrompt> {
+    let prompt = TutorialPrompt::ALL
+        .into_iter()
+        .find(|prompt| !settings.tutorial_seen.contains(prompt))?;
+    match prompt {
+        TutorialPrompt::Move => Some(prompt),
+        // Nothing to aim at or shoot until the beets turn up
+        TutorialPrompt::Aim | TutorialPrompt::Fire => enemies_about.then_some(prompt),
+    }
+}
+
+// Recordings are played by someone else, so they don't tick anything off
+fn dismiss_prompts(
+    mut settings: ResMut<Settings>,
+    input: Res<PlayerInput>,
+    source: Res<InputSource>,
+    game: Res<Game>,
+    aims: Query<&Aim>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    if !matches!(*source, InputSource::Live) {
+        return;
+    }
+    let Some(prompt) = current_prompt(&settings, !enemies.is_empty()) else { return };
+
+    let done = match prompt {
+        TutorialPrompt::Move => input.movement != Vec2::ZERO,
+        TutorialPrompt::Aim => {
+            input.aim != 0.
+                || aims
+                    .get(game.player)
+                    .map_or(false, |aim| aim.target.is_some())
+        }
+        TutorialPrompt::Fire => input.fire,
+    };
+    if done {
+        settings.tutorial_seen.push(prompt);
+        settings.save();
+    }
+}
+
+// Bottom middle, above the HUD along the bottom
+fn setup_tutorial_prompt(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect::bottom(Val::Px(PROMPT_MARGIN)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 32.,
+                        color: Color::YELLOW,
+                    },
+                )
+                .with_text_alignment(TextAlignment::CENTER),
+                TutorialText,
+            ));
+        });
+}
+
+fn update_tutorial_prompt(
+    settings: Res<Settings>,
+    device: Res<ActiveDevice>,
+    source: Res<InputSource>,
+    state: Res<State<GameState>>,
+    enemies: Query<(), With<Enemy>>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<TutorialText>>,
+) {
+    let prompt = if matches!(*source, InputSource::Live) && *state.current() == GameState::Playing
+    {
+        current_prompt(&settings, !enemies.is_empty())
+    } else {
+        None
+    };
+    let hud = prompt.map_or(String::new(), |prompt| {
+        prompt.text(&settings, *device, &localization)
+    });
+
+    for mut text in &mut texts {
+        if text.sections[0].value != hud {
+            text.sections[0].value = hud.clone();
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::Palette,
+    damage::DamageEvent,
+    graze::Grazed,
+    ground::Ground,
+    input::PlayerInput,
+    lighting::{spawn_flash, FlashKind},
+    reset::DespawnOnReset,
+    state::GameState,
+    Enemy, EnemyKilled, Game, Player,
+};
+
+// How much of the meter each one fills, from empty to full at 1
+const CHARGE_PER_KILL: f32 = 0.04;
+const CHARGE_PER_GRAZE: f32 = 0.1;
+const ULTIMATE_DAMAGE: f32 = 5.;
+// Pumpkins come down out of the sky one after another rather than all at once, from this high
+const SHELL_STAGGER_SECONDS: f32 = 0.05;
+const SHELL_HEIGHT: f32 = 8.;
+// Metres per second
+const SHELL_FALL_SPEED: f32 = 14.;
+const SHELL_SCALE: f32 = 1.4;
+// The screen goes white and fades back over this long
+const FLASH_SECONDS: f32 = 0.4;
+const FLASH_ALPHA: f32 = 0.8;
+// Just to the right of the grenade bar
+const BAR_WIDTH: f32 = 120.;
+const BAR_HEIGHT: f32 = 10.;
+const BAR_LEFT: f32 = 260.;
+const BAR_MARGIN: f32 = 20.;
+const CHARGING_COLOUR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+pub struct UltimatePlugin;
+
+impl Plugin for UltimatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UltimateMeter>()
+            .add_event::<UltimateUnleashed>()
+            .add_startup_system(spawn_ultimate_bar)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(charge_ultimate)
+                    .with_system(unleash_ultimate.after(charge_ultimate))
+                    .with_system(drop_shells.after(unleash_ultimate)),
+            )
+            .add_system(fade_flash)
+            .add_system(update_ultimate_bar)
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_ultimate));
+    }
+}
+
+// Shared between everyone playing, like the score, since kills aren't put down to anyone in
+// particular. Whoever presses the button first gets to let it go.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct UltimateMeter {
+    pub charge: f32,
+}
+
+impl UltimateMeter {
+    pub fn is_full(&self) -> bool {
+        self.charge >= 1.
+    }
+}
+
+pub struct UltimateUnleashed {
+    pub player: Entity,
+}
+
+// On its way down on to one beet, following it about until it lands
+#[derive(Component)]
+struct Shell {
+    target: Entity,
+    delay: f32,
+}
+
+#[derive(Component)]
+struct UltimateFlash {
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct UltimateFill;
+
+// The ultimate's own kills don't count, or it'd fill itself straight back up
+fn charge_ultimate(
+    mut meter: ResMut<UltimateMeter>,
+    mut killed: EventReader<EnemyKilled>,
+    mut grazed: EventReader<Grazed>,
+    shells: Query<(), With<Shell>>,
+) {
+    let charge = killed.iter().count() as f32 * CHARGE_PER_KILL
+        + grazed.iter().count() as f32 * CHARGE_PER_GRAZE;
+    if charge == 0. || !shells.is_empty() || meter.is_full() {
+        return;
+    }
+    meter.charge = (meter.charge + charge).min(1.);
+}
+
+// A pumpkin for every beet there is, wherever it is
+fn unleash_ultimate(
+    mut commands: Commands,
+    game: Res<Game>,
+    mut meter: ResMut<UltimateMeter>,
+    players: Query<(Entity, &PlayerInput), With<Player>>,
+    enemies: Query<(Entity, &GlobalTransform), With<Enemy>>,
+    mut unleashed: EventWriter<UltimateUnleashed>,
+) {
+    if !meter.is_full() {
+        return;
+    }
+    let Some(projectile_asset) = &game.projectile else { return };
+    let Some((player, _)) = players.iter().find(|(_, input)| input.ultimate) else { return };
+
+    meter.charge = 0.;
+    unleashed.send(UltimateUnleashed { player });
+    for (index, (enemy, transform)) in enemies.iter().enumerate() {
+        let position = transform.translation();
+        commands.spawn((
+            SceneBundle {
+                scene: projectile_asset.clone(),
+                transform: Transform::from_xyz(position.x, SHELL_HEIGHT, position.z)
+                    .with_scale(Vec3::splat(SHELL_SCALE)),
+                ..default()
+            },
+            Shell {
+                target: enemy,
+                delay: index as f32 * SHELL_STAGGER_SECONDS,
+            },
+            DespawnOnReset,
+        ));
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::rgba(1., 1., 1., FLASH_ALPHA).into(),
+            ..default()
+        },
+        UltimateFlash {
+            remaining: FLASH_SECONDS,
+        },
+        DespawnOnReset,
+    ));
+}
+
+// Anything whose beet has gone already lands where it last was, harmlessly
+fn drop_shells(
+    mut commands: Commands,
+    time: Res<Time>,
+    ground: Res<Ground>,
+    mut shells: Query<(Entity, &mut Transform, &mut Shell)>,
+    transforms: Query<&GlobalTransform>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut shell) in &mut shells {
+        if shell.delay > 0. {
+            shell.delay -= delta;
+            continue;
+        }
+
+        let target = transforms
+            .get(shell.target)
+            .ok()
+            .map(GlobalTransform::translation);
+        if let Some(target) = target {
+            transform.translation.x = target.x;
+            transform.translation.z = target.z;
+        }
+        transform.translation.y -= SHELL_FALL_SPEED * delta;
+        let floor = match target {
+            Some(target) => target.y,
+            None => ground.height_at(transform.translation),
+        };
+        if transform.translation.y > floor {
+            continue;
+        }
+
+        if target.is_some() {
+            damage.send(DamageEvent {
+                source: Some(entity),
+                target: shell.target,
+                amount: ULTIMATE_DAMAGE,
+                direction: Vec3::NEG_Y,
+                critical: true,
+                knockback: 0.,
+            });
+        }
+        let centre = transform.translation;
+        spawn_flash(&mut commands, FlashKind::Blast, centre + Vec3::Y * 0.2);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn fade_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut UltimateFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut color) in &mut flashes {
+        flash.remaining -= time.delta_seconds();
+        if flash.remaining <= 0. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        color.0.set_a(FLASH_ALPHA * flash.remaining / FLASH_SECONDS);
+    }
+}
+
+// One bar for everyone, alongside player one's stamina and grenade bars
+fn spawn_ultimate_bar(mut commands: Commands, palette: Res<Palette>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(BAR_LEFT),
+                    bottom: Val::Px(BAR_MARGIN),
+                    ..default()
+                },
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    background_color: palette.ultimate.into(),
+                    ..default()
+                },
+                UltimateFill,
+            ));
+        });
+}
+
+fn update_ultimate_bar(
+    meter: Res<UltimateMeter>,
+    palette: Res<Palette>,
+    mut fills: Query<(&mut Style, &mut BackgroundColor), With<UltimateFill>>,
+) {
+    if !meter.is_changed() && !palette.is_changed() {
+        return;
+    }
+    for (mut style, mut color) in &mut fills {
+        style.size.width = Val::Percent(meter.charge * 100.);
+        *color = if meter.is_full() {
+            palette.ultimate.into()
+        } else {
+            CHARGING_COLOUR.into()
+        };
+    }
+}
+
+fn reset_ultimate(mut meter: ResMut<UltimateMeter>) {
+    *meter = UltimateMeter::default();
+}
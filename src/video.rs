@@ -0,0 +1,365 @@
+use bevy::{
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    window::{PresentMode, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    input::PlayerInput,
+    lighting::Sun,
+    localization::Localization,
+    navigation::{self, MenuNavigation},
+    state::GameState,
+    storage,
+};
+
+const VIDEO_FILE: &str = "video.ron";
+// How many point lights (muzzle flashes, blasts) can be lit at once
+const LIGHT_BUDGETS: [u32; 5] = [0, 2, 4, 8, 16];
+const RESOLUTIONS: [(u32, u32); 5] = [
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+pub struct VideoPlugin;
+
+impl Plugin for VideoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(VideoSettings::load())
+            .init_resource::<VideoMenu>()
+            .add_system(apply_window_settings)
+            .add_system(apply_shadow_quality)
+            .add_system_set(SystemSet::on_enter(GameState::Video).with_system(spawn_video))
+            .add_system_set(
+                SystemSet::on_update(GameState::Video)
+                    .with_system(navigate_video)
+                    .with_system(update_video_text.after(navigate_video)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Video).with_system(close_video));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    // Covers the screen at the desktop's resolution
+    Borderless,
+    // Takes the screen over at the chosen resolution
+    Fullscreen,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    fn map_size(self) -> usize {
+        match self {
+            ShadowQuality::Off | ShadowQuality::Low => 1024,
+            ShadowQuality::Medium => 2048,
+            ShadowQuality::High => 4096,
+        }
+    }
+
+    // How far the sun's shadows reach either side of the middle of the view. Sharper shadows
+    // reach further too.
+    fn distance(self) -> f32 {
+        match self {
+            ShadowQuality::Off | ShadowQuality::Low => 12.,
+            ShadowQuality::Medium => 20.,
+            ShadowQuality::High => 30.,
+        }
+    }
+}
+
+// Kept apart from `Settings` since it's about the machine rather than the player. A low-end laptop
+// shouldn't drag someone's aim preferences down with it.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub display_mode: DisplayMode,
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    pub shadows: ShadowQuality,
+    // One of `LIGHT_BUDGETS`
+    pub light_budget: u32,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            display_mode: DisplayMode::default(),
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            shadows: ShadowQuality::default(),
+            light_budget: 8,
+        }
+    }
+}
+
+impl VideoSettings {
+    fn load() -> Self {
+        storage::data_file(VIDEO_FILE)
+            .map(|path| storage::load(&path))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        match storage::data_file(VIDEO_FILE) {
+            Some(path) => storage::save(&path, self),
+            None => warn!("Nowhere to save video settings on this platform"),
+        }
+    }
+}
+
+// One line in the video menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum VideoRow {
+    DisplayMode,
+    Resolution,
+    Vsync,
+    Shadows,
+    Lights,
+}
+
+const ROWS: [VideoRow; 5] = [
+    VideoRow::DisplayMode,
+    VideoRow::Resolution,
+    VideoRow::Vsync,
+    VideoRow::Shadows,
+    VideoRow::Lights,
+];
+
+// Steps through `options` from `current`, wrapping round at either end
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, direction: i32) -> T {
+    let index = options.iter().position(|option| *option == current).unwrap_or_default();
+    let next = (index as i32 + direction).rem_euclid(options.len() as i32);
+    options[next as usize]
+}
+
+impl VideoRow {
+    // Into the localization
+    fn label(&self) -> &'static str {
+        match self {
+            VideoRow::DisplayMode => "video-display",
+            VideoRow::Resolution => "video-resolution",
+            VideoRow::Vsync => "video-vsync",
+            VideoRow::Shadows => "video-shadows",
+            VideoRow::Lights => "video-lights",
+        }
+    }
+
+    fn value(&self, video: &VideoSettings, localization: &Localization) -> String {
+        match self {
+            VideoRow::DisplayMode => localization.variant("display-mode", &video.display_mode),
+            VideoRow::Resolution => format!("{}x{}", video.resolution.0, video.resolution.1),
+            VideoRow::Vsync => localization.on_off(video.vsync).to_string(),
+            VideoRow::Shadows => localization.variant("shadows", &video.shadows),
+            VideoRow::Lights => video.light_budget.to_string(),
+        }
+    }
+
+    // `direction` is -1 or 1
+    fn adjust(&self, video: &mut VideoSettings, direction: i32) {
+        match self {
+            VideoRow::DisplayMode => {
+                video.display_mode = cycle(
+                    &[DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen],
+                    video.display_mode,
+                    direction,
+                )
+            }
+            VideoRow::Resolution => {
+                video.resolution = cycle(&RESOLUTIONS, video.resolution, direction)
+            }
+            VideoRow::Vsync => video.vsync = !video.vsync,
+            VideoRow::Shadows => {
+                video.shadows = cycle(
+                    &[
+                        ShadowQuality::Off,
+                        ShadowQuality::Low,
+                        ShadowQuality::Medium,
+                        ShadowQuality::High,
+                    ],
+                    video.shadows,
+                    direction,
+                )
+            }
+            VideoRow::Lights => {
+                video.light_budget = cycle(&LIGHT_BUDGETS, video.light_budget, direction)
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct VideoMenu {
+    row: usize,
+}
+
+#[derive(Component)]
+struct VideoUi;
+
+#[derive(Component)]
+struct VideoText;
+
+fn apply_window_settings(video: Res<VideoSettings>, mut windows: ResMut<Windows>) {
+    if !video.is_changed() {
+        return;
+    }
+
+    let Some(window) = windows.get_primary_mut() else { return };
+    let mode = match video.display_mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+        DisplayMode::Fullscreen => WindowMode::SizedFullscreen,
+    };
+    if window.mode() != mode {
+        window.set_mode(mode);
+    }
+
+    let (width, height) = video.resolution;
+    if window.requested_width() != width as f32 || window.requested_height() != height as f32 {
+        window.set_resolution(width as f32, height as f32);
+    }
+
+    let present_mode = if video.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode() != present_mode {
+        window.set_present_mode(present_mode);
+    }
+}
+
+// Off leaves the sun's shadows off whatever the config says; otherwise the config decides whether
+// there are any and this decides how sharp they are and how far they reach
+fn apply_shadow_quality(
+    video: Res<VideoSettings>,
+    config: Res<GameConfig>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut suns: Query<&mut DirectionalLight, With<Sun>>,
+    added: Query<(), Added<Sun>>,
+) {
+    if !video.is_changed() && added.is_empty() {
+        return;
+    }
+
+    if shadow_map.size != video.shadows.map_size() {
+        shadow_map.size = video.shadows.map_size();
+    }
+    let enabled = config.lighting.sun.shadows && video.shadows != ShadowQuality::Off;
+    let distance = video.shadows.distance();
+    for mut light in &mut suns {
+        if light.shadows_enabled != enabled {
+            light.shadows_enabled = enabled;
+        }
+        // Deep enough to take in everything between the sun and the ground
+        if light.shadow_projection.right != distance {
+            light.shadow_projection = OrthographicProjection {
+                left: -distance,
+                right: distance,
+                bottom: -distance,
+                top: distance,
+                near: -10. * distance,
+                far: 10. * distance,
+                ..default()
+            };
+        }
+    }
+}
+
+fn spawn_video(mut commands: Commands, mut menu: ResMut<VideoMenu>, asset_server: Res<AssetServer>) {
+    menu.row = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                ..default()
+            },
+            VideoUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font_size: 26.,
+                        color: Color::WHITE,
+                    },
+                ),
+                VideoText,
+            ));
+        });
+}
+
+// Up and down pick a row, left and right change it, back saves and leaves
+fn navigate_video(
+    input: Res<PlayerInput>,
+    navigation: Res<MenuNavigation>,
+    mut menu: ResMut<VideoMenu>,
+    mut video: ResMut<VideoSettings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if input.back {
+        if let Err(e) = state.pop() {
+            warn!("Unable to close the video settings: {e:?}");
+        }
+        return;
+    }
+
+    menu.row = navigation.row(menu.row, ROWS.len());
+    if navigation.step.x != 0 {
+        ROWS[menu.row].adjust(&mut video, navigation.step.x);
+    }
+}
+
+fn update_video_text(
+    menu: Res<VideoMenu>,
+    video: Res<VideoSettings>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<VideoText>>,
+) {
+    let mut panel = format!("{}\n\n", localization.get("video-title"));
+    for (index, row) in ROWS.iter().enumerate() {
+        let cursor = if index == menu.row { ">" } else { " " };
+        panel += &format!(
+            "{cursor} {:<16}{:>12}\n",
+            localization.get(row.label()),
+            row.value(&video, &localization)
+        );
+    }
+    panel += &format!("\n{}", localization.get("settings-help"));
+
+    for mut text in &mut texts {
+        navigation::set_panel(&mut text, &panel);
+    }
+}
+
+fn close_video(mut commands: Commands, video: Res<VideoSettings>, ui: Query<Entity, With<VideoUi>>) {
+    video.save();
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
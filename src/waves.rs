@@ -0,0 +1,256 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    campaign::ActiveLevel, difficulty::Difficulty, state::GameState, telegraph::SpawnTelegraph,
+    Enemy,
+};
+
+const FIRST_WAVE_SIZE: u32 = 4;
+const WAVE_SIZE_GROWTH: u32 = 2;
+// On Normal. The difficulty scales it from there.
+const INTERMISSION_SECONDS: f32 = 5.;
+// How far apart beets in a formation stand, across the lane and down it
+const FORMATION_SPACING: f32 = 0.8;
+// The most that come in one group. Any more follow on the next spawn.
+const MAX_GROUP_SIZE: u32 = 5;
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveManager>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(plan_waves))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(advance_waves))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(reset_waves));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WavePhase {
+    // Enemies are still coming
+    Spawning,
+    // Everything has spawned, waiting for the last of them to die
+    Clearing,
+    // A breather before the next wave
+    Intermission,
+}
+
+// How a wave's beets turn up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Formation {
+    // A few at a time, anywhere across the lane
+    #[default]
+    Scattered,
+    // Side by side across the lane
+    Line,
+    // Point first, with the arms trailing back either side
+    V,
+    // Down both edges of the lane at once
+    Pincer,
+    // Scattered for the first half of the wave, then everything left in one go
+    TrickleThenBurst,
+}
+
+impl Formation {
+    // Where each of a group of `count` stands: across the lane from the middle, and how much
+    // further down it than the usual spawn distance
+    pub fn layout(&self, count: u32, half_width: f32) -> Vec<Vec2> {
+        (0..count)
+            .map(|index| match self {
+                Formation::Scattered => Vec2::ZERO,
+                Formation::Line | Formation::TrickleThenBurst => {
+                    let across = if count > 1 {
+                        index as f32 / (count - 1) as f32 * 2. - 1.
+                    } else {
+                        0.
+                    };
+                    Vec2::new(across * half_width, 0.)
+                }
+                Formation::V => {
+                    let rank = ((index + 1) / 2) as f32;
+                    let side = if index % 2 == 0 { 1. } else { -1. };
+                    let across = (side * rank * FORMATION_SPACING).clamp(-half_width, half_width);
+                    Vec2::new(across, rank * FORMATION_SPACING)
+                }
+                Formation::Pincer => {
+                    let side = if index % 2 == 0 { 1. } else { -1. };
+                    Vec2::new(side * half_width, (index / 2) as f32 * FORMATION_SPACING)
+                }
+            })
+            .collect()
+    }
+}
+
+// One wave of a level, as written in levels.ron
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WaveDefinition {
+    pub enemies: u32,
+    pub formation: Formation,
+    // The name of one of the behaviours in beets.behaviours.ron, for every beet in the wave
+    pub behaviour: Option<String>,
+}
+
+#[derive(Resource)]
+pub struct WaveManager {
+    pub wave: u32,
+    pub phase: WavePhase,
+    pub remaining_to_spawn: u32,
+    // The waves a level sets out. Anything past the end grows like it does endlessly, scattered.
+    pub plan: Vec<WaveDefinition>,
+    // Where every run starts, which is only ever past the first for testing
+    first_wave: u32,
+    intermission: Timer,
+    // Whether `WaveStarted` has gone out for this wave yet
+    started: bool,
+}
+
+impl Default for WaveManager {
+    fn default() -> Self {
+        Self::starting_at(1)
+    }
+}
+
+impl WaveManager {
+    pub fn starting_at(wave: u32) -> Self {
+        let wave = wave.max(1);
+        let mut manager = Self {
+            wave,
+            phase: WavePhase::Spawning,
+            remaining_to_spawn: 0,
+            plan: Vec::new(),
+            first_wave: wave,
+            intermission: Timer::from_seconds(INTERMISSION_SECONDS, TimerMode::Once),
+            started: false,
+        };
+        manager.remaining_to_spawn = manager.wave_size(wave);
+        manager
+    }
+
+    // Picking a saved run back up, from the top of the wave it was on. Where runs start is left
+    // alone, so the next one still starts there.
+    pub fn resume_at(&mut self, wave: u32) {
+        self.wave = wave.max(1);
+        self.phase = WavePhase::Spawning;
+        self.remaining_to_spawn = self.wave_size(self.wave);
+        self.intermission.reset();
+        self.started = false;
+    }
+
+    fn planned(&self, wave: u32) -> Option<&WaveDefinition> {
+        wave.checked_sub(1)
+            .and_then(|index| self.plan.get(index as usize))
+    }
+
+    pub fn wave_size(&self, wave: u32) -> u32 {
+        match self.planned(wave) {
+            Some(planned) => planned.enemies,
+            None => FIRST_WAVE_SIZE + WAVE_SIZE_GROWTH * wave.saturating_sub(1),
+        }
+    }
+
+    // The group the current wave wants spawned next, laid out as `Formation::layout` does, or
+    // none to spawn a few anywhere as usual
+    pub fn next_group(&self, half_width: f32) -> Option<Vec<Vec2>> {
+        let formation = self
+            .planned(self.wave)
+            .map_or(Formation::Scattered, |planned| planned.formation);
+        let remaining = self.remaining_to_spawn;
+        match formation {
+            Formation::Scattered => None,
+            Formation::TrickleThenBurst if remaining * 2 > self.wave_size(self.wave) => None,
+            Formation::TrickleThenBurst => Some(formation.layout(remaining, half_width)),
+            _ => Some(formation.layout(remaining.min(MAX_GROUP_SIZE), half_width)),
+        }
+    }
+
+    pub fn behaviour(&self) -> Option<&str> {
+        self.planned(self.wave)?.behaviour.as_deref()
+    }
+
+    // Every wave the level planned has been beaten
+    pub fn plan_complete(&self) -> bool {
+        !self.plan.is_empty()
+            && self.wave as usize >= self.plan.len()
+            && self.phase == WavePhase::Intermission
+    }
+
+    pub fn can_spawn(&self) -> bool {
+        self.phase == WavePhase::Spawning && self.remaining_to_spawn > 0
+    }
+
+    pub fn enemy_spawned(&mut self) {
+        self.remaining_to_spawn = self.remaining_to_spawn.saturating_sub(1);
+    }
+}
+
+pub struct WaveStarted {
+    pub wave: u32,
+}
+
+pub struct WaveCleared {
+    pub wave: u32,
+}
+
+pub fn advance_waves(
+    mut manager: ResMut<WaveManager>,
+    // Including any still on their way up
+    enemies: Query<(), Or<(With<Enemy>, With<SpawnTelegraph>)>>,
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    mut started: EventWriter<WaveStarted>,
+    mut cleared: EventWriter<WaveCleared>,
+) {
+    match manager.phase {
+        WavePhase::Spawning => {
+            if manager.remaining_to_spawn == 0 {
+                manager.phase = WavePhase::Clearing;
+            }
+        }
+        WavePhase::Clearing => {
+            if enemies.is_empty() {
+                cleared.send(WaveCleared { wave: manager.wave });
+                manager.phase = WavePhase::Intermission;
+                let seconds = INTERMISSION_SECONDS * difficulty.scaling().intermission;
+                manager.intermission.set_duration(Duration::from_secs_f32(seconds));
+                manager.intermission.reset();
+            }
+        }
+        WavePhase::Intermission => {
+            if manager.intermission.tick(time.delta()).finished() {
+                manager.wave += 1;
+                manager.remaining_to_spawn = manager.wave_size(manager.wave);
+                manager.phase = WavePhase::Spawning;
+                manager.started = false;
+            }
+        }
+    }
+
+    // The first wave of a run, or one picked back up, starts without an intermission before it
+    if !manager.started {
+        manager.started = true;
+        started.send(WaveStarted { wave: manager.wave });
+    }
+}
+
+// Every run starts here, whether it's the first or a retry
+fn plan_waves(mut manager: ResMut<WaveManager>, level: Res<ActiveLevel>) {
+    let plan = level
+        .0
+        .as_ref()
+        .map(|level| level.waves.clone())
+        .unwrap_or_default();
+    if manager.plan != plan {
+        manager.plan = plan;
+        manager.remaining_to_spawn = manager.wave_size(manager.wave);
+    }
+}
+
+fn reset_waves(mut manager: ResMut<WaveManager>) {
+    *manager = WaveManager::starting_at(manager.first_wave);
+}
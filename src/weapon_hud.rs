@@ -0,0 +1,530 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::{viewport_to_ui, Palette},
+    config::GameConfig,
+    grenade::{GrenadeCooldown, GrenadeThrown},
+    state::GameState,
+    weapons::{Crosshair, FireCooldown, Loadout, WeaponDefinition, WeaponEquipped},
+    Aim, Ammo, AmmoChanged, Barrage, Game, Player, Spread, WeaponFired,
+};
+
+const PANEL_MARGIN: f32 = 20.;
+// The ring goes round the icon and fills back up as the gun gets ready again
+const RING_SIZE: f32 = 64.;
+const RING_SEGMENTS: usize = 12;
+const SEGMENT_SIZE: f32 = 6.;
+const ICON_SIZE: f32 = 40.;
+const PIP_SIZE: f32 = 10.;
+const SECONDARY_WIDTH: f32 = 60.;
+const SECONDARY_HEIGHT: f32 = 6.;
+// Nothing locked on, the crosshair sits this far out in front of the gun
+const CROSSHAIR_DISTANCE: f32 = 4.;
+const CROSSHAIR_THICKNESS: f32 = 3.;
+// How far out the crosshair's pieces are pushed for each radian of spread
+const SPREAD_PIXELS_PER_RADIAN: f32 = 60.;
+const LIT_COLOUR: Color = Color::WHITE;
+const DIM_COLOUR: Color = Color::rgba(1., 1., 1., 0.2);
+
+pub struct WeaponHudPlugin;
+
+impl Plugin for WeaponHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_weapon_hud)
+            .add_system(rebuild_weapon_hud)
+            .add_system(update_ammo_text.after(rebuild_weapon_hud))
+            .add_system(update_ready_ring.after(rebuild_weapon_hud))
+            .add_system(update_lock_pips.after(rebuild_weapon_hud))
+            .add_system(update_secondary.after(rebuild_weapon_hud))
+            .add_system(move_crosshair.after(rebuild_weapon_hud))
+            .add_system(spread_crosshair.after(rebuild_weapon_hud));
+    }
+}
+
+// Bottom right, laid out for whichever weapon's equipped. It's player one's: everyone else has
+// the stamina and grenade bars to go on.
+#[derive(Component)]
+struct WeaponPanel;
+
+#[derive(Component)]
+struct AmmoText;
+
+#[derive(Component)]
+struct ReadySegment {
+    index: usize,
+}
+
+// One per beet a barrage weapon can paint
+#[derive(Component)]
+struct LockPip {
+    index: usize,
+}
+
+#[derive(Component)]
+struct SecondaryFill;
+
+#[derive(Component)]
+struct CrosshairRoot;
+
+// Where it sits with the spread closed right up
+#[derive(Component)]
+struct CrosshairPiece {
+    centre: Vec2,
+    size: Vec2,
+}
+
+fn setup_weapon_hud(
+    mut commands: Commands,
+    loadout: Res<Loadout>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+    asset_server: Res<AssetServer>,
+) {
+    let definition = config
+        .weapons
+        .get(loadout.equipped)
+        .cloned()
+        .unwrap_or_default();
+    spawn_panel(&mut commands, &definition, &palette, &asset_server);
+    spawn_crosshair(&mut commands, definition.crosshair);
+}
+
+// The barrage weapons get a row of pips for what they've painted, and each gun its own icon and
+// crosshair, so the whole lot's put up again rather than patched. A new palette's picked up the
+// same way.
+fn rebuild_weapon_hud(
+    mut commands: Commands,
+    mut equipped: EventReader<WeaponEquipped>,
+    loadout: Res<Loadout>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+    asset_server: Res<AssetServer>,
+    panels: Query<Entity, Or<(With<WeaponPanel>, With<CrosshairRoot>)>>,
+) {
+    let weapon = match equipped.iter().last() {
+        Some(equipped) => equipped.weapon,
+        None if palette.is_changed() && !palette.is_added() => loadout.equipped,
+        None => return,
+    };
+
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+    let definition = config.weapons.get(weapon).cloned().unwrap_or_default();
+    spawn_panel(&mut commands, &definition, &palette, &asset_server);
+    spawn_crosshair(&mut commands, definition.crosshair);
+}
+
+fn spawn_panel(
+    commands: &mut Commands,
+    definition: &WeaponDefinition,
+    palette: &Palette,
+    asset_server: &AssetServer,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(PANEL_MARGIN),
+                        bottom: Val::Px(PANEL_MARGIN),
+                        ..default()
+                    },
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            WeaponPanel,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(RING_SIZE), Val::Px(RING_SIZE)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|ring| {
+                    match &definition.icon {
+                        Some(icon) => {
+                            ring.spawn(ImageBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(ICON_SIZE), Val::Px(ICON_SIZE)),
+                                    ..default()
+                                },
+                                image: UiImage(asset_server.load(icon.as_str())),
+                                ..default()
+                            });
+                        }
+                        None => {
+                            ring.spawn(
+                                TextBundle::from_section(
+                                    initials(&definition.name),
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 24.,
+                                        color: Color::WHITE,
+                                    },
+                                )
+                                .with_text_alignment(TextAlignment::CENTER),
+                            );
+                        }
+                    }
+
+                    // Clockwise from the top
+                    let radius = (RING_SIZE - SEGMENT_SIZE) / 2.;
+                    for index in 0..RING_SEGMENTS {
+                        let angle = index as f32 / RING_SEGMENTS as f32 * TAU;
+                        let centre = Vec2::new(angle.sin(), angle.cos()) * radius;
+                        ring.spawn((
+                            square(
+                                Vec2::splat(RING_SIZE / 2.) + centre,
+                                Vec2::splat(SEGMENT_SIZE),
+                                LIT_COLOUR,
+                            ),
+                            ReadySegment { index },
+                        ));
+                    }
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        margin: UiRect::left(Val::Px(8.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font,
+                                font_size: 30.,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        AmmoText,
+                    ));
+
+                    if definition.locks > 0 {
+                        column
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    margin: UiRect::top(Val::Px(4.)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                for index in 0..definition.locks as usize {
+                                    row.spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                size: Size::new(
+                                                    Val::Px(PIP_SIZE),
+                                                    Val::Px(PIP_SIZE),
+                                                ),
+                                                margin: UiRect::right(Val::Px(3.)),
+                                                ..default()
+                                            },
+                                            background_color: DIM_COLOUR.into(),
+                                            ..default()
+                                        },
+                                        LockPip { index },
+                                    ));
+                                }
+                            });
+                    }
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(SECONDARY_WIDTH),
+                                    Val::Px(SECONDARY_HEIGHT),
+                                ),
+                                margin: UiRect::top(Val::Px(4.)),
+                                ..default()
+                            },
+                            background_color: DIM_COLOUR.into(),
+                            ..default()
+                        })
+                        .with_children(|bar| {
+                            bar.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                                        ..default()
+                                    },
+                                    background_color: palette.grenade.into(),
+                                    ..default()
+                                },
+                                SecondaryFill,
+                            ));
+                        });
+                });
+        });
+}
+
+// "Spud Launcher" is SL, for want of a picture of one
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .collect()
+}
+
+// A block of colour centred on `centre`, measured from the bottom left of its parent
+fn square(centre: Vec2, size: Vec2, colour: Color) -> NodeBundle {
+    NodeBundle {
+        style: square_style(centre, size),
+        background_color: colour.into(),
+        ..default()
+    }
+}
+
+fn square_style(centre: Vec2, size: Vec2) -> Style {
+    Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            left: Val::Px(centre.x - size.x / 2.),
+            bottom: Val::Px(centre.y - size.y / 2.),
+            ..default()
+        },
+        size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+        ..default()
+    }
+}
+
+// Each shape as blocks around the point being aimed at
+fn crosshair_pieces(crosshair: Crosshair) -> Vec<(Vec2, Vec2)> {
+    let thick = CROSSHAIR_THICKNESS;
+    match crosshair {
+        Crosshair::Dot => vec![(Vec2::ZERO, Vec2::splat(thick * 2.))],
+        Crosshair::Cross => vec![
+            (Vec2::new(-10., 0.), Vec2::new(10., thick)),
+            (Vec2::new(10., 0.), Vec2::new(10., thick)),
+            (Vec2::new(0., -10.), Vec2::new(thick, 10.)),
+            (Vec2::new(0., 10.), Vec2::new(thick, 10.)),
+        ],
+        Crosshair::Ring => (0..RING_SEGMENTS)
+            .map(|index| {
+                let angle = index as f32 / RING_SEGMENTS as f32 * TAU;
+                (
+                    Vec2::new(angle.sin(), angle.cos()) * 14.,
+                    Vec2::splat(thick),
+                )
+            })
+            .collect(),
+        // An L in each corner
+        Crosshair::Brackets => [(-1., -1.), (-1., 1.), (1., -1.), (1., 1.)]
+            .into_iter()
+            .flat_map(|(x, y)| {
+                let corner = Vec2::new(x, y) * 16.;
+                [
+                    (corner - Vec2::new(x * 4., 0.), Vec2::new(8., thick)),
+                    (corner - Vec2::new(0., y * 4.), Vec2::new(thick, 8.)),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn spawn_crosshair(commands: &mut Commands, crosshair: Crosshair) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::INVISIBLE,
+                ..default()
+            },
+            CrosshairRoot,
+        ))
+        .with_children(|parent| {
+            for (centre, size) in crosshair_pieces(crosshair) {
+                parent.spawn((
+                    square(centre, size, LIT_COLOUR),
+                    CrosshairPiece { centre, size },
+                ));
+            }
+        });
+}
+
+fn update_ammo_text(
+    game: Res<Game>,
+    mut fired: EventReader<WeaponFired>,
+    mut changed: EventReader<AmmoChanged>,
+    added: Query<(), Added<AmmoText>>,
+    weapons: Query<&Ammo>,
+    mut texts: Query<&mut Text, With<AmmoText>>,
+) {
+    let ours = |weapon: Entity| weapon == game.spud_gun;
+    let fired = fired.iter().any(|event| ours(event.weapon));
+    let changed = changed.iter().any(|event| ours(event.weapon));
+    if !fired && !changed && added.is_empty() {
+        return;
+    }
+
+    let Ok(ammo) = weapons.get(game.spud_gun) else { return };
+    for mut text in &mut texts {
+        text.sections[0].value = format!("{} / {}", ammo.current, ammo.max);
+    }
+}
+
+// Only moves while the gun's getting ready again after a shot
+fn update_ready_ring(
+    game: Res<Game>,
+    mut fired: EventReader<WeaponFired>,
+    mut equipped: EventReader<WeaponEquipped>,
+    mut cooling: Local<bool>,
+    cooldowns: Query<&FireCooldown>,
+    mut segments: Query<(&ReadySegment, &mut BackgroundColor)>,
+) {
+    let equipped = equipped.iter().count() > 0;
+    if fired.iter().any(|event| event.weapon == game.spud_gun) || equipped {
+        *cooling = true;
+    }
+    if !*cooling {
+        return;
+    }
+
+    let progress = cooldowns
+        .get(game.spud_gun)
+        .map_or(1., |cooldown| cooldown.0.percent());
+    for (segment, mut colour) in &mut segments {
+        let lit = (segment.index as f32 + 1.) / RING_SEGMENTS as f32 <= progress;
+        *colour = if lit { LIT_COLOUR } else { DIM_COLOUR }.into();
+    }
+    if progress >= 1. {
+        *cooling = false;
+    }
+}
+
+fn update_lock_pips(
+    game: Res<Game>,
+    palette: Res<Palette>,
+    barrages: Query<&Barrage, Changed<Barrage>>,
+    mut pips: Query<(&LockPip, &mut BackgroundColor)>,
+) {
+    let Ok(barrage) = barrages.get(game.player) else { return };
+    for (pip, mut colour) in &mut pips {
+        let painted = pip.index < barrage.painted.len();
+        *colour = if painted { palette.painted } else { DIM_COLOUR }.into();
+    }
+}
+
+// Player one's grenade, from being thrown until it's back
+fn update_secondary(
+    game: Res<Game>,
+    mut thrown: EventReader<GrenadeThrown>,
+    mut recharging: Local<bool>,
+    cooldowns: Query<&GrenadeCooldown>,
+    mut fills: Query<&mut Style, With<SecondaryFill>>,
+) {
+    if thrown.iter().any(|event| event.player == game.player) {
+        *recharging = true;
+    }
+    if !*recharging {
+        return;
+    }
+
+    let charge = cooldowns
+        .get(game.player)
+        .map_or(1., GrenadeCooldown::charge);
+    for mut style in &mut fills {
+        style.size.width = Val::Percent(charge * 100.);
+    }
+    if charge >= 1. {
+        *recharging = false;
+    }
+}
+
+// Over whatever player one's locked on to, or out in front of the gun. Only while playing, so it
+// isn't left hanging over the menus.
+fn move_crosshair(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    loadout: Res<Loadout>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+    ui_scale: Res<UiScale>,
+    players: Query<(&Player, &Aim)>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut roots: Query<(&mut Style, &mut Visibility), With<CrosshairRoot>>,
+    mut pieces: Query<&mut BackgroundColor, With<CrosshairPiece>>,
+) {
+    let range = loadout.equipped_stats(&config).range;
+    let target = players.get(game.player).ok().and_then(|(player, aim)| {
+        let weapon = transforms.get(player.weapon).ok()?;
+        let origin = weapon.translation();
+        let locked = aim
+            .target
+            .and_then(|target| transforms.get(target).ok())
+            .map(GlobalTransform::translation);
+        let (point, colour) = match locked {
+            Some(target) if target.distance(origin) > range => (target, palette.falling_off),
+            Some(target) => (target, palette.target),
+            None => (origin + weapon.forward() * CROSSHAIR_DISTANCE, LIT_COLOUR),
+        };
+        Some((point, colour))
+    });
+    let screen = target.zip(cameras.get(game.camera).ok()).and_then(
+        |((point, colour), (camera, camera_transform))| {
+            let screen = camera.world_to_viewport(camera_transform, point)?;
+            Some((viewport_to_ui(&ui_scale, screen), colour))
+        },
+    );
+
+    let shown = screen.filter(|_| *state.current() == GameState::Playing);
+
+    for (mut style, mut visibility) in &mut roots {
+        visibility.is_visible = shown.is_some();
+        let Some((screen, colour)) = shown else { continue };
+        style.position = UiRect {
+            left: Val::Px(screen.x),
+            bottom: Val::Px(screen.y),
+            ..default()
+        };
+        for mut piece in &mut pieces {
+            *piece = colour.into();
+        }
+    }
+}
+
+// Pushes the crosshair's pieces out from the middle as player one's spread opens up. A dot has
+// nowhere to be pushed out to, so it grows instead.
+fn spread_crosshair(
+    game: Res<Game>,
+    spreads: Query<&Spread>,
+    mut pieces: Query<(&CrosshairPiece, &mut Style)>,
+) {
+    let angle = spreads.get(game.player).map_or(0., |spread| spread.angle);
+    let push = angle * SPREAD_PIXELS_PER_RADIAN;
+    for (piece, mut style) in &mut pieces {
+        let (centre, size) = if piece.centre == Vec2::ZERO {
+            (piece.centre, piece.size + Vec2::splat(push))
+        } else {
+            (piece.centre + piece.centre.normalize() * push, piece.size)
+        };
+        let placed = square_style(centre, size);
+        if *style != placed {
+            *style = placed;
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::GameConfig, state::GameState, status::StatusEffect};
+
+// Each upgrade tier adds this much on top of the base damage
+const DAMAGE_PER_TIER: f32 = 0.25;
+// Shots per second a full-auto gun can't go past with fire held, whatever it's upgraded to
+const MAX_AUTO_FIRE_RATE: f32 = 12.;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Loadout>()
+            .add_event::<WeaponEquipped>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(tick_fire_cooldowns),
+            );
+    }
+}
+
+// One entry in the weapon list in config.ron
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct WeaponDefinition {
+    pub name: String,
+    pub scene: String,
+    pub damage: f32,
+    // Shots per second, or bursts per second for a burst-fire gun
+    pub fire_rate: f32,
+    pub fire_mode: FireMode,
+    // From 0 to 1
+    pub crit_chance: f32,
+    // What a crit multiplies the damage by
+    pub crit_multiplier: f32,
+    // Anything else this close to a hit takes splash damage. Nothing does at zero.
+    pub blast_radius: f32,
+    // How many beets a shot carries on through before it stops in one
+    pub pierce: u32,
+    // How many times a shot bounces on to the next beet after a hit
+    pub ricochet: u32,
+    // Left on every beet a shot hits
+    pub status: Option<StatusEffect>,
+    // How many beets it can paint while fire's held, with a pumpkin for each of them when it's let
+    // go. An ordinary gun at zero.
+    pub locks: u32,
+    // Shots do full damage out to `range`, less and less past it, and drop out of the air at
+    // `max_range`. Nothing further away than that can be locked on to either.
+    pub range: f32,
+    pub max_range: f32,
+    // How much wider, in radians either side, each shot leaves the spread, and as wide as it gets
+    pub spread_per_shot: f32,
+    pub max_spread: f32,
+    // An image under assets/ for the HUD. Without one the HUD spells out the name.
+    pub icon: Option<String>,
+    pub crosshair: Crosshair,
+}
+
+// What's drawn over whatever player one's aiming at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Crosshair {
+    #[default]
+    Dot,
+    Cross,
+    Ring,
+    Brackets,
+}
+
+// What a pull of the trigger does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum FireMode {
+    // A shot a press
+    #[default]
+    SemiAuto,
+    // `shots` shots a press, `delay` seconds apart, and the cooldown only once they're all out
+    Burst {
+        shots: u32,
+        delay: f32,
+    },
+    // Keeps going for as long as fire's held, rather than charging up
+    FullAuto,
+}
+
+impl Default for WeaponDefinition {
+    fn default() -> Self {
+        Self {
+            name: "Spud Launcher".into(),
+            scene: "launcher.glb#Scene0".into(),
+            damage: 1.,
+            fire_rate: 3.,
+            fire_mode: FireMode::SemiAuto,
+            crit_chance: 0.1,
+            crit_multiplier: 2.,
+            blast_radius: 0.,
+            pierce: 0,
+            ricochet: 0,
+            status: None,
+            locks: 0,
+            range: 6.,
+            max_range: 10.,
+            spread_per_shot: 0.04,
+            max_spread: 0.25,
+            icon: None,
+            crosshair: Crosshair::Dot,
+        }
+    }
+}
+
+// What a weapon actually does once its upgrades are taken into account
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeaponStats {
+    pub damage: f32,
+    pub fire_rate: f32,
+    pub fire_mode: FireMode,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    pub blast_radius: f32,
+    pub pierce: u32,
+    pub ricochet: u32,
+    pub status: Option<StatusEffect>,
+    pub locks: u32,
+    pub range: f32,
+    pub max_range: f32,
+    pub spread_per_shot: f32,
+    pub max_spread: f32,
+}
+
+impl WeaponStats {
+    // Crits counted at their average
+    pub fn dps(&self) -> f32 {
+        self.damage
+            * self.shots_per_second()
+            * (1. + self.crit_chance * (self.crit_multiplier - 1.))
+    }
+
+    // With the trigger pulled again the moment it's ready
+    pub fn shots_per_second(&self) -> f32 {
+        match self.fire_mode {
+            FireMode::SemiAuto => self.fire_rate,
+            FireMode::Burst { shots, delay } => {
+                let cycle = shots.saturating_sub(1) as f32 * delay + 1. / self.fire_rate.max(0.01);
+                shots.max(1) as f32 / cycle
+            }
+            FireMode::FullAuto => self.fire_rate.min(MAX_AUTO_FIRE_RATE),
+        }
+    }
+}
+
+// The weapons the player has to choose from, as indices into `GameConfig::weapons`
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct Loadout {
+    pub unlocked: Vec<usize>,
+    pub equipped: usize,
+    // Parallel to the weapon list
+    pub tiers: Vec<u32>,
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self {
+            unlocked: vec![0],
+            equipped: 0,
+            tiers: Vec::new(),
+        }
+    }
+}
+
+impl Loadout {
+    pub fn tier(&self, weapon: usize) -> u32 {
+        self.tiers.get(weapon).copied().unwrap_or(0)
+    }
+
+    pub fn stats(&self, config: &GameConfig, weapon: usize) -> WeaponStats {
+        let definition = config.weapons.get(weapon).cloned().unwrap_or_default();
+        let tier = self.tier(weapon) as f32;
+        WeaponStats {
+            damage: definition.damage * (1. + tier * DAMAGE_PER_TIER),
+            fire_rate: definition.fire_rate,
+            fire_mode: definition.fire_mode,
+            crit_chance: definition.crit_chance.clamp(0., 1.),
+            crit_multiplier: definition.crit_multiplier,
+            blast_radius: definition.blast_radius,
+            pierce: definition.pierce,
+            ricochet: definition.ricochet,
+            status: definition.status,
+            locks: definition.locks,
+            range: definition.range,
+            // Never short of where the falloff starts
+            max_range: definition.max_range.max(definition.range),
+            spread_per_shot: definition.spread_per_shot.max(0.),
+            max_spread: definition.max_spread.max(0.),
+        }
+    }
+
+    pub fn equipped_stats(&self, config: &GameConfig) -> WeaponStats {
+        self.stats(config, self.equipped)
+    }
+}
+
+// A different weapon's been equipped, as an index into `GameConfig::weapons`
+pub struct WeaponEquipped {
+    pub weapon: usize,
+}
+
+// Time until the weapon it's on can fire again
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FireCooldown(pub Timer);
+
+impl Default for FireCooldown {
+    // Ready to go straight away
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(0., TimerMode::Once);
+        timer.tick(Duration::ZERO);
+        Self(timer)
+    }
+}
+
+impl FireCooldown {
+    pub fn ready(&self) -> bool {
+        self.0.finished()
+    }
+
+    pub fn start(&mut self, stats: WeaponStats) {
+        let rate = match stats.fire_mode {
+            FireMode::FullAuto => stats.fire_rate.min(MAX_AUTO_FIRE_RATE),
+            _ => stats.fire_rate,
+        };
+        self.0 = Timer::from_seconds(1. / rate.max(0.01), TimerMode::Once);
+    }
+
+    // Between one shot of a burst and the next
+    pub fn start_burst(&mut self, delay: f32) {
+        self.0 = Timer::from_seconds(delay.max(0.), TimerMode::Once);
+    }
+}
+
+fn tick_fire_cooldowns(mut cooldowns: Query<&mut FireCooldown>, time: Res<Time>) {
+    for mut cooldown in &mut cooldowns {
+        cooldown.0.tick(time.delta());
+    }
+}
@@ -0,0 +1,299 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::{
+    campaign::ActiveLevel, config::GameConfig, level_gen::LevelSeed, state::GameState, Game,
+};
+
+// How long one kind of weather takes to turn into the next
+const BLEND_SECONDS: f32 = 8.;
+// Enough to fill the view in a downpour. Lighter rain just shows fewer of them.
+const RAIN_DROPS: usize = 400;
+const RAIN_SPEED: f32 = 9.;
+// Around the spot the camera's looking at, which is all anyone sees
+const RAIN_AHEAD: f32 = 5.;
+const RAIN_HALF_WIDTH: f32 = 6.;
+const RAIN_HEIGHT: f32 = 8.;
+// How far a full gale pushes the rain sideways, and leans the foliage over, in radians
+const WIND_DRIFT: f32 = 4.;
+const RAIN_LEAN: f32 = 0.4;
+const SWAY_ANGLE: f32 = 0.15;
+// Radians per second
+const SWAY_SPEED: f32 = 2.5;
+const FLASH_SECONDS: f32 = 0.35;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .insert_resource(WeatherRng(StdRng::seed_from_u64(0)))
+            .add_startup_system(spawn_rain)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_weather))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(change_weather)
+                    .with_system(fall_rain.after(change_weather))
+                    .with_system(sway_foliage.after(change_weather))
+                    .with_system(strike_lightning.after(change_weather)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(clear_weather));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Windy,
+    Drizzle,
+    Rain,
+    Storm,
+}
+
+impl WeatherKind {
+    fn conditions(&self) -> Conditions {
+        let (rain, wind, lightning) = match self {
+            WeatherKind::Clear => (0., 0.1, 0.),
+            WeatherKind::Windy => (0., 1., 0.),
+            WeatherKind::Drizzle => (0.3, 0.3, 0.),
+            WeatherKind::Rain => (0.8, 0.5, 0.),
+            WeatherKind::Storm => (1., 1., 4.),
+        };
+        Conditions {
+            rain,
+            wind,
+            lightning,
+        }
+    }
+}
+
+// One stretch of weather in a level's forecast, which goes round and round for as long as the run
+// does
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct WeatherSpell {
+    pub kind: WeatherKind,
+    pub seconds: f32,
+}
+
+impl Default for WeatherSpell {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            seconds: 60.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Conditions {
+    // From nothing to a downpour, 0 to 1
+    pub rain: f32,
+    // From still to a gale, 0 to 1
+    pub wind: f32,
+    // Strikes a minute
+    pub lightning: f32,
+}
+
+impl Conditions {
+    fn lerp(&self, to: Conditions, blend: f32) -> Conditions {
+        let lerp = |from: f32, to: f32| from + (to - from) * blend;
+        Conditions {
+            rain: lerp(self.rain, to.rain),
+            wind: lerp(self.wind, to.wind),
+            lightning: lerp(self.lightning, to.lightning),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Weather {
+    forecast: Vec<WeatherSpell>,
+    spell: usize,
+    // Seconds into the current spell
+    elapsed: f32,
+    // Seconds of play, for the foliage to sway along to
+    clock: f32,
+    // Whatever it was like when the current spell took over
+    previous: Conditions,
+    // Partway from `previous` to the current spell's
+    pub conditions: Conditions,
+    // How much brighter a strike of lightning has left everything, fading from 1 to 0
+    pub flash: f32,
+    until_lightning: f32,
+}
+
+// Seeded from the level, so the weather plays out the same way on a replay or a golden test
+// without taking anything from the game's own RNG
+#[derive(Resource)]
+struct WeatherRng(StdRng);
+
+#[derive(Component)]
+struct RainDrop;
+
+// Leans over in the wind, from however it was standing
+#[derive(Component)]
+pub struct Foliage {
+    pub rotation: Quat,
+    // So a field of it doesn't all sway in step
+    pub phase: f32,
+}
+
+fn spawn_rain(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.01, 0.25, 0.01)));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.75, 0.8, 0.9, 0.4),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    for _ in 0..RAIN_DROPS {
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                visibility: Visibility::INVISIBLE,
+                ..default()
+            },
+            RainDrop,
+            NotShadowCaster,
+        ));
+    }
+}
+
+// Campaign levels bring their own forecast, and the endless lane uses the config's. Either way a
+// run starts in whatever the first spell is, rather than blending in from the last run's.
+fn start_weather(
+    mut weather: ResMut<Weather>,
+    mut rng: ResMut<WeatherRng>,
+    config: Res<GameConfig>,
+    level: Res<ActiveLevel>,
+    seed: Res<LevelSeed>,
+) {
+    let forecast = match &level.0 {
+        Some(level) => level.weather.clone(),
+        None => config.weather.clone(),
+    };
+    let conditions = forecast
+        .first()
+        .map_or_else(|| WeatherKind::Clear.conditions(), |spell| spell.kind.conditions());
+    *weather = Weather {
+        forecast,
+        previous: conditions,
+        conditions,
+        ..default()
+    };
+    rng.0 = StdRng::seed_from_u64(seed.0);
+}
+
+fn change_weather(mut weather: ResMut<Weather>, time: Res<Time>) {
+    let delta = time.delta_seconds();
+    weather.clock += delta;
+    if weather.forecast.is_empty() {
+        return;
+    }
+
+    weather.elapsed += delta;
+    let seconds = weather.forecast[weather.spell].seconds.max(BLEND_SECONDS);
+    if weather.elapsed >= seconds {
+        weather.elapsed -= seconds;
+        weather.spell = (weather.spell + 1) % weather.forecast.len();
+        weather.previous = weather.conditions;
+    }
+
+    let target = weather.forecast[weather.spell].kind.conditions();
+    let blend = (weather.elapsed / BLEND_SECONDS).min(1.);
+    weather.conditions = weather.previous.lerp(target, blend);
+}
+
+// Only as many drops as the rain calls for are shown. Each falls through a box in front of the
+// camera and starts again at the top once it's through the ground or blown out of the box.
+fn fall_rain(
+    weather: Res<Weather>,
+    mut rng: ResMut<WeatherRng>,
+    time: Res<Time>,
+    game: Res<Game>,
+    cameras: Query<&GlobalTransform>,
+    mut drops: Query<(&mut Transform, &mut Visibility), With<RainDrop>>,
+) {
+    let Ok(camera) = cameras.get(game.camera) else { return };
+    let forward = camera.forward();
+    let center = camera.translation() + Vec3::new(forward.x, 0., forward.z) * RAIN_AHEAD;
+    let delta = time.delta_seconds();
+    let wind = weather.conditions.wind;
+    let falling = (weather.conditions.rain * RAIN_DROPS as f32).round() as usize;
+    let velocity = Vec3::new(wind * WIND_DRIFT, -RAIN_SPEED, 0.);
+    let lean = Quat::from_rotation_z(wind * RAIN_LEAN);
+
+    for (index, (mut transform, mut visibility)) in drops.iter_mut().enumerate() {
+        let was_falling = visibility.is_visible;
+        visibility.is_visible = index < falling;
+        if !visibility.is_visible {
+            continue;
+        }
+
+        transform.translation += velocity * delta;
+        transform.rotation = lean;
+        let offset = transform.translation - center;
+        let outside = offset.x.abs() > RAIN_HALF_WIDTH || offset.z.abs() > RAIN_HALF_WIDTH;
+        if was_falling && transform.translation.y > 0. && !outside {
+            continue;
+        }
+
+        // Anywhere up the column when it first appears, so a shower doesn't arrive as one sheet
+        let height = if was_falling {
+            RAIN_HEIGHT
+        } else {
+            rng.0.gen_range(0.0..RAIN_HEIGHT)
+        };
+        transform.translation = Vec3::new(
+            center.x + rng.0.gen_range(-RAIN_HALF_WIDTH..RAIN_HALF_WIDTH),
+            height,
+            center.z + rng.0.gen_range(-RAIN_HALF_WIDTH..RAIN_HALF_WIDTH),
+        );
+    }
+}
+
+// The wind always blows across the lane, and gusts come and go on top of it
+fn sway_foliage(weather: Res<Weather>, mut foliage: Query<(&Foliage, &mut Transform)>) {
+    let wind = weather.conditions.wind;
+    for (foliage, mut transform) in &mut foliage {
+        let gust = 0.6 + 0.4 * (weather.clock * SWAY_SPEED + foliage.phase).sin();
+        transform.rotation = Quat::from_rotation_z(-wind * gust * SWAY_ANGLE) * foliage.rotation;
+    }
+}
+
+// The flash itself is the lighting's to show, brightening the sun and the sky by `flash`
+fn strike_lightning(mut weather: ResMut<Weather>, mut rng: ResMut<WeatherRng>, time: Res<Time>) {
+    let delta = time.delta_seconds();
+    weather.flash = (weather.flash - delta / FLASH_SECONDS).max(0.);
+
+    let strikes_per_minute = weather.conditions.lightning;
+    if strikes_per_minute <= 0. {
+        return;
+    }
+    weather.until_lightning -= delta;
+    if weather.until_lightning > 0. {
+        return;
+    }
+
+    weather.flash = 1.;
+    weather.until_lightning = 60. / strikes_per_minute * rng.0.gen_range(0.5..1.5);
+}
+
+// Blue skies for whatever's next, until it starts and says otherwise
+fn clear_weather(
+    mut weather: ResMut<Weather>,
+    mut drops: Query<&mut Visibility, With<RainDrop>>,
+) {
+    *weather = Weather::default();
+    for mut visibility in &mut drops {
+        visibility.is_visible = false;
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use vegetable_funeral::{prelude::*, testing::GameplayTest};
+
+fn hurt(test: &mut GameplayTest, target: Entity, amount: f32) {
+    test.send_event(DamageEvent {
+        source: None,
+        target,
+        amount,
+        direction: Vec3::NEG_Z,
+        critical: false,
+        knockback: 0.,
+    });
+}
+
+// What the pipeline made of the last hit
+fn dealt(test: &mut GameplayTest) -> DamageDealt {
+    let dealt = test.drain_events::<DamageDealt>();
+    assert_eq!(dealt.len(), 1);
+    dealt[0]
+}
+
+fn spawn_target(test: &mut GameplayTest, health: f32) -> Entity {
+    let enemy = test.spawn_enemy(Vec3::new(0., 0., -3.));
+    test.world().entity_mut(enemy).insert(Health::new(health));
+    enemy
+}
+
+#[test]
+fn damage_event_takes_health() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let enemy = test.spawn_enemy(Vec3::new(0., 0., -3.));
+    test.world().entity_mut(enemy).insert(Health::new(5.));
+
+    hurt(&mut test, enemy, 2.);
+    test.tick();
+    assert_eq!(test.get::<Health>(enemy).unwrap().current, 3.);
+    assert!(test.exists(enemy));
+}
+
+#[test]
+fn killed_enemy_is_despawned_and_let_go_of() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    {
+        let mut settings = test.world().resource_mut::<Settings>();
+        settings.aim_assist = AimAssist::LockOn;
+        settings.aim_mode = AimMode::Nearest;
+    }
+    test.place_player(Vec3::ZERO);
+    let enemy = test.spawn_enemy(Vec3::new(0., 0., -3.));
+    test.ticks(2);
+    assert_eq!(test.aim_target(), Some(enemy));
+
+    hurt(&mut test, enemy, 100.);
+    test.tick();
+    assert!(!test.exists(enemy));
+    assert_eq!(test.aim_target(), None);
+    assert_eq!(test.drain_events::<EnemyKilled>().len(), 1);
+}
+
+#[test]
+fn killed_player_ends_the_run() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let player = test.player();
+    assert_eq!(test.state(), GameState::Playing);
+
+    hurt(&mut test, player, 1000.);
+    let over = test.tick_until(5, |world| {
+        *world.resource::<State<GameState>>().current() == GameState::GameOver
+    });
+    assert!(over, "the run carried on with the carrot dead");
+}
+
+#[test]
+fn base_stage_scales_damage_to_players_by_difficulty() {
+    let mut test = GameplayTest::new()
+        .without_enemy_spawns()
+        .with_difficulty(Difficulty::Hard);
+    let player = test.player();
+    test.world().entity_mut(player).insert(Health::new(10.));
+
+    hurt(&mut test, player, 2.);
+    test.tick();
+    assert_eq!(dealt(&mut test).amount, 3.);
+    assert_eq!(test.get::<Health>(player).unwrap().current, 7.);
+}
+
+#[test]
+fn base_stage_leaves_beets_alone() {
+    let mut test = GameplayTest::new()
+        .without_enemy_spawns()
+        .with_difficulty(Difficulty::Hard);
+    let enemy = spawn_target(&mut test, 10.);
+
+    hurt(&mut test, enemy, 2.);
+    test.tick();
+    assert_eq!(dealt(&mut test).amount, 2.);
+}
+
+#[test]
+fn resistance_stage_shrugs_off_a_fraction() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let enemy = spawn_target(&mut test, 10.);
+    test.world().entity_mut(enemy).insert(Resistance(0.25));
+
+    hurt(&mut test, enemy, 4.);
+    test.tick();
+    assert_eq!(dealt(&mut test).amount, 3.);
+    assert_eq!(test.get::<Health>(enemy).unwrap().current, 7.);
+}
+
+#[test]
+fn resistance_stage_never_turns_damage_into_healing() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let enemy = spawn_target(&mut test, 10.);
+    test.world().entity_mut(enemy).insert(Resistance(2.));
+
+    hurt(&mut test, enemy, 4.);
+    test.tick();
+    assert_eq!(dealt(&mut test).amount, 0.);
+    assert_eq!(test.get::<Health>(enemy).unwrap().current, 10.);
+}
+
+#[test]
+fn clamp_stage_stops_damage_going_negative() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    // Something earlier taking off more than there was
+    test.app
+        .add_damage_modifier(DamageStage::Weapon, |context, _| context.amount -= 10.);
+    let enemy = spawn_target(&mut test, 10.);
+
+    hurt(&mut test, enemy, 4.);
+    test.tick();
+    assert_eq!(dealt(&mut test).amount, 0.);
+    assert_eq!(test.get::<Health>(enemy).unwrap().current, 10.);
+}
+
+#[test]
+fn stages_run_in_order() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    // Backwards, so it's the stage that decides and not the order they went in
+    for stage in [
+        DamageStage::Clamp,
+        DamageStage::Critical,
+        DamageStage::Resistance,
+        DamageStage::Relic,
+        DamageStage::Weapon,
+        DamageStage::Base,
+    ] {
+        let seen = seen.clone();
+        test.app.add_damage_modifier(stage, move |_, _| {
+            seen.lock().unwrap().push(stage);
+        });
+    }
+    let enemy = spawn_target(&mut test, 10.);
+
+    hurt(&mut test, enemy, 1.);
+    test.tick();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            DamageStage::Base,
+            DamageStage::Weapon,
+            DamageStage::Relic,
+            DamageStage::Resistance,
+            DamageStage::Critical,
+            DamageStage::Clamp,
+        ]
+    );
+}
+
+// Nothing in the game hooks into the weapon, relic or critical stages yet, so these stand in for
+// what will. Each step only comes out right if it gets the result of the one before.
+#[test]
+fn each_stage_gets_the_one_befores_result() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    test.app
+        .add_damage_modifier(DamageStage::Critical, |context, _| {
+            if context.critical {
+                context.amount *= 3.;
+            }
+        })
+        .add_damage_modifier(DamageStage::Relic, |context, _| context.amount *= 2.)
+        .add_damage_modifier(DamageStage::Weapon, |context, _| {
+            context.amount += 2.;
+            context.critical = true;
+        });
+    let enemy = spawn_target(&mut test, 20.);
+    test.world().entity_mut(enemy).insert(Resistance(0.5));
+
+    // (1 + 2) * 2 * 0.5 * 3
+    hurt(&mut test, enemy, 1.);
+    test.tick();
+    let dealt = dealt(&mut test);
+    assert_eq!(dealt.amount, 9.);
+    assert!(dealt.critical);
+    assert_eq!(test.get::<Health>(enemy).unwrap().current, 11.);
+}
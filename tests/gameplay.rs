@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use vegetable_funeral::{prelude::*, testing::GameplayTest};
+
+// Well over the few seconds between spawns
+const SPAWN_TICKS: u32 = 600;
+
+fn spawned(world: &mut World) -> usize {
+    world
+        .query_filtered::<(), Or<(With<Enemy>, With<SpawnTelegraph>)>>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn projectile_kills_enemy() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    let enemy = test.spawn_enemy(Vec3::new(0., 0., -1.));
+    test.spawn_projectile(Vec3::new(0., 0., -0.5), Vec3::NEG_Z, 100.);
+
+    let killed = test.tick_until(60, |world| world.get_entity(enemy).is_none());
+    assert!(killed, "the pumpkin never finished off the beet");
+    assert_eq!(test.count::<Enemy>(), 0);
+}
+
+#[test]
+fn aim_locks_on_to_nearest_enemy() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    {
+        let mut settings = test.world().resource_mut::<Settings>();
+        settings.aim_assist = AimAssist::LockOn;
+        settings.aim_mode = AimMode::Nearest;
+    }
+    test.place_player(Vec3::ZERO);
+    let far = test.spawn_enemy(Vec3::new(1.5, 0., -4.));
+    let near = test.spawn_enemy(Vec3::new(-0.5, 0., -2.));
+    assert_ne!(far, near);
+
+    test.ticks(2);
+    assert_eq!(test.aim_target(), Some(near));
+}
+
+#[test]
+fn paused_spawn_timer_spawns_nothing() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    test.ticks(SPAWN_TICKS);
+    assert_eq!(spawned(test.world()), 0);
+}
+
+// So the test above is down to the pause, and not to nothing spawning in the harness at all
+#[test]
+fn running_spawn_timer_spawns_enemies() {
+    let mut test = GameplayTest::new();
+    assert!(test.tick_until(SPAWN_TICKS, |world| spawned(world) > 0));
+}
+
+// A beet under a moved parent is only where its global transform says, so that's where it's hit
+fn spawn_parented_enemy(test: &mut GameplayTest, offset: Vec3, local: Vec3) -> Entity {
+    let enemy = test.spawn_enemy(local);
+    let world = test.world();
+    world
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            offset,
+        )))
+        .push_children(&[enemy]);
+    enemy
+}
+
+#[test]
+fn projectile_hits_parented_enemy_where_it_is_in_the_world() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    // Straight down the lane from it, so it walks along the pumpkin's path
+    test.place_player(Vec3::new(3., 0., 5.));
+    let offset = Vec3::new(3., 0., 0.);
+    let enemy = spawn_parented_enemy(&mut test, offset, Vec3::new(0., 0., -1.));
+    // Let the parent's offset reach the beet's global transform
+    test.tick();
+    let global = test.get::<GlobalTransform>(enemy).unwrap().translation();
+    assert!(global.distance(offset + Vec3::new(0., 0., -1.)) < 0.1);
+
+    test.spawn_projectile(global + Vec3::Z * 0.5, Vec3::NEG_Z, 100.);
+    let killed = test.tick_until(60, |world| world.get_entity(enemy).is_none());
+    assert!(killed, "the pumpkin went through the beet's world position");
+}
+
+#[test]
+fn projectile_misses_parented_enemys_local_position() {
+    let mut test = GameplayTest::new().without_enemy_spawns();
+    test.place_player(Vec3::new(3., 0., 5.));
+    let local = Vec3::new(0., 0., -1.);
+    let enemy = spawn_parented_enemy(&mut test, Vec3::new(3., 0., 0.), local);
+    test.tick();
+
+    // Straight through where the beet would be if its local transform were taken as global
+    test.spawn_projectile(local + Vec3::Z * 0.5, Vec3::NEG_Z, 100.);
+    test.ticks(30);
+    assert!(test.exists(enemy));
+    let health = test.get::<Health>(enemy).unwrap();
+    assert_eq!(health.current, health.max);
+}